@@ -0,0 +1,62 @@
+//! Renders a hex dump as styled lines, like a TUI application would do to show
+//! it in a widget. With ratatui, every `StyledSpan` becomes a `Span` with a
+//! `Style` whose colors are mapped from `hexyl::Color`, and every `StyledLine`
+//! a `Line` of a `Paragraph`. This example draws the spans with its own
+//! escape sequences instead, to show the mapping without a TUI library.
+
+use hexyl::{color_from_name, render_to_lines, BorderStyle, Color, PrinterOptions, Style, Theme};
+
+/// Maps a color to the SGR parameter of a foreground color, like a TUI
+/// application would map it to the color type of its library.
+fn sgr_foreground(color: Color) -> String {
+    match color {
+        Color::Black => "30".to_owned(),
+        Color::Red => "31".to_owned(),
+        Color::Green => "32".to_owned(),
+        Color::Yellow => "33".to_owned(),
+        Color::Blue => "34".to_owned(),
+        Color::Magenta => "35".to_owned(),
+        Color::Cyan => "36".to_owned(),
+        Color::White => "37".to_owned(),
+        Color::BrightBlack => "90".to_owned(),
+        Color::BrightRed => "91".to_owned(),
+        Color::BrightGreen => "92".to_owned(),
+        Color::BrightYellow => "93".to_owned(),
+        Color::BrightBlue => "94".to_owned(),
+        Color::BrightMagenta => "95".to_owned(),
+        Color::BrightCyan => "96".to_owned(),
+        Color::BrightWhite => "97".to_owned(),
+        Color::Indexed(i) => format!("38;5;{i}"),
+        Color::Rgb(r, g, b) => format!("38;2;{r};{g};{b}"),
+    }
+}
+
+fn main() {
+    let input = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR\x00\x00\x00\x80\x00\x00\x00D\x08\x02";
+
+    let options = PrinterOptions {
+        border_style: BorderStyle::Ascii,
+        panels: 1,
+        ..PrinterOptions::default()
+    };
+
+    // The spans have the colors of the theme, like the output of a printer.
+    let theme = Theme {
+        nonascii: color_from_name("bright-red").unwrap(),
+        ..Theme::default()
+    };
+
+    for line in render_to_lines(input, &options, theme) {
+        for span in &line.spans {
+            match span.style {
+                Style {
+                    fg: Some(color), ..
+                } => {
+                    print!("\x1b[{}m{}\x1b[39m", sgr_foreground(color), span.text)
+                }
+                _ => print!("{}", span.text),
+            }
+        }
+        println!();
+    }
+}
@@ -0,0 +1,131 @@
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+
+/// A reader that only reads whole blocks of `alignment` bytes at positions
+/// that are multiples of `alignment` from the inner reader, e.g. for raw disk
+/// devices that reject unaligned reads. The bytes in between are returned
+/// like from the inner reader, so reads and seeks may be unaligned.
+///
+/// The blocks are also read into memory at an address that is a multiple of
+/// `alignment`, as files opened for direct I/O (e.g. with `O_DIRECT` on
+/// Linux) require.
+pub struct AlignedReader<R: Read + Seek> {
+    inner: R,
+    alignment: u64,
+    /// The position of this reader in the input.
+    position: u64,
+    /// The memory for the blocks, which is allocated once and has room to
+    /// align its start.
+    storage: Vec<u8>,
+    /// The range of `storage` with the last block that has been read.
+    buf: Range<usize>,
+    /// The position of `buf` in the input.
+    buf_start: u64,
+}
+
+impl<R: Read + Seek> AlignedReader<R> {
+    /// The number of bytes that is read at once, rounded up to a multiple of
+    /// the alignment.
+    const CAPACITY: u64 = 64 * 1024;
+
+    /// Creates a reader that starts at the current position of `inner`. With
+    /// an `alignment` of 1, reads and seeks are passed on unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `alignment` is zero.
+    pub fn new(mut inner: R, alignment: u64) -> io::Result<Self> {
+        assert!(alignment > 0, "the alignment must not be zero");
+        let position = if alignment > 1 {
+            inner.stream_position()?
+        } else {
+            0
+        };
+        Ok(AlignedReader {
+            inner,
+            alignment,
+            position,
+            storage: vec![],
+            buf: 0..0,
+            buf_start: 0,
+        })
+    }
+
+    /// Reads the aligned block that contains the current position into `buf`.
+    fn fill_buf(&mut self) -> io::Result<()> {
+        let start = self.position / self.alignment * self.alignment;
+        let capacity = (Self::CAPACITY.div_ceil(self.alignment) * self.alignment) as usize;
+        let alignment = self.alignment as usize;
+        self.inner.seek(SeekFrom::Start(start))?;
+        if self.storage.is_empty() {
+            self.storage = vec![0; capacity + alignment - 1];
+        }
+        let address = self.storage.as_ptr() as usize;
+        let offset = (alignment - address % alignment) % alignment;
+        self.buf = offset..offset;
+        let n = loop {
+            match self
+                .inner
+                .read(&mut self.storage[offset..offset + capacity])
+            {
+                Ok(n) => break n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        };
+        self.buf = offset..offset + n;
+        self.buf_start = start;
+        Ok(())
+    }
+
+    /// Returns the buffered bytes from the current position on.
+    fn buffered(&self) -> &[u8] {
+        let end = self.buf_start + self.buf.len() as u64;
+        if (self.buf_start..end).contains(&self.position) {
+            &self.storage[self.buf.start + (self.position - self.buf_start) as usize..self.buf.end]
+        } else {
+            &[]
+        }
+    }
+}
+
+impl<R: Read + Seek> Read for AlignedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.alignment == 1 {
+            return self.inner.read(buf);
+        }
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.buffered().is_empty() {
+            self.fill_buf()?;
+        }
+        let available = self.buffered();
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: Read + Seek> Seek for AlignedReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        if self.alignment == 1 {
+            return self.inner.seek(pos);
+        }
+        // Seeking does not read, so it does not have to be aligned. The inner
+        // reader is moved before the next read.
+        let position = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::End(_) => Some(self.inner.seek(pos)?),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        self.position = position.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        Ok(self.position)
+    }
+}
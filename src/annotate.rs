@@ -0,0 +1,347 @@
+//! The header fields of known file formats for `--annotate`, which are
+//! highlighted in the dump in one color per kind of field.
+//!
+//! The format is recognized by its magic number. Only the headers are
+//! annotated, not the data they describe: the ELF header, the length, type
+//! and CRC of the PNG chunks (and the fields of `IHDR`), the local file
+//! headers of a ZIP archive, the headers of a PCAP capture and its packets,
+//! and the numeric fields of the headers of a tar archive.
+
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::str;
+
+use hexyl::{color_from_name, Highlight};
+
+/// The most records (chunks, members or packets) whose headers are
+/// annotated, so that large files do not take up a lot of memory.
+const MAX_RECORDS: usize = 1000;
+
+/// The size of a block of a tar archive.
+const TAR_BLOCK_SIZE: u64 = 512;
+
+/// What the value of a field is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FieldKind {
+    /// A magic number or another tag that identifies the format or a record.
+    Magic,
+    /// The size of the file, a record or a field.
+    Length,
+    /// An offset in the file or an address in memory.
+    Pointer,
+    /// Any other number, like a version, a type or a checksum.
+    Integer,
+}
+
+impl FieldKind {
+    fn color(self) -> &'static [u8] {
+        let name = match self {
+            FieldKind::Magic => "bright-magenta",
+            FieldKind::Length => "bright-yellow",
+            FieldKind::Pointer => "bright-cyan",
+            FieldKind::Integer => "bright-green",
+        };
+        color_from_name(name).expect("known color")
+    }
+}
+
+/// A field of a header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Field {
+    pub name: String,
+    /// The positions of the field in the file.
+    pub range: Range<u64>,
+    pub kind: FieldKind,
+}
+
+/// Recognizes the format of `file` and returns the fields of its headers,
+/// ordered by their position, or `None` if the format is not known.
+pub(crate) fn annotate<R: Read + Seek>(file: &mut R) -> io::Result<Option<Vec<Field>>> {
+    let mut start = [0; TAR_BLOCK_SIZE as usize];
+    let len = read_at(file, 0, &mut start)?;
+    let start = &start[..len];
+    let fields = if start.len() >= 6 && start.starts_with(b"\x7fELF") {
+        elf_fields(start)
+    } else if start.starts_with(b"\x89PNG\r\n\x1a\n") {
+        png_fields(file)?
+    } else if start.starts_with(b"PK\x03\x04") {
+        zip_fields(file)?
+    } else if start.len() >= 4 && pcap_endianness(&start[..4]).is_some() {
+        pcap_fields(file, start)?
+    } else if start.get(257..262) == Some(b"ustar") {
+        tar_fields(file)?
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(fields))
+}
+
+/// Returns the highlights of the `fields`, at their positions in the file,
+/// with their names as labels.
+pub(crate) fn highlights(fields: &[Field]) -> Vec<Highlight> {
+    fields
+        .iter()
+        .map(|field| Highlight {
+            range: field.range.clone(),
+            color: field.kind.color(),
+            label: Some(field.name.clone()),
+        })
+        .collect()
+}
+
+/// Collects the fields of a header, one after another.
+struct Fields<'a> {
+    fields: &'a mut Vec<Field>,
+    position: u64,
+}
+
+impl Fields<'_> {
+    fn push(&mut self, name: impl Into<String>, size: u64, kind: FieldKind) -> &mut Self {
+        self.fields.push(Field {
+            name: name.into(),
+            range: self.position..self.position + size,
+            kind,
+        });
+        self.skip(size)
+    }
+
+    /// Skips the bytes of a field that is not annotated, like a name.
+    fn skip(&mut self, size: u64) -> &mut Self {
+        self.position += size;
+        self
+    }
+}
+
+fn elf_fields(start: &[u8]) -> Vec<Field> {
+    use FieldKind::*;
+
+    // The size of addresses and offsets, depending on `EI_CLASS`.
+    let word = if start[4] == 2 { 8 } else { 4 };
+    let mut fields = vec![];
+    Fields {
+        fields: &mut fields,
+        position: 0,
+    }
+    .push("ELF magic", 4, Magic)
+    .push("EI_CLASS", 1, Integer)
+    .push("EI_DATA", 1, Integer)
+    .push("EI_VERSION", 1, Integer)
+    .push("EI_OSABI", 1, Integer)
+    .push("EI_ABIVERSION", 1, Integer)
+    .skip(7)
+    .push("e_type", 2, Integer)
+    .push("e_machine", 2, Integer)
+    .push("e_version", 4, Integer)
+    .push("e_entry", word, Pointer)
+    .push("e_phoff", word, Pointer)
+    .push("e_shoff", word, Pointer)
+    .push("e_flags", 4, Integer)
+    .push("e_ehsize", 2, Length)
+    .push("e_phentsize", 2, Length)
+    .push("e_phnum", 2, Integer)
+    .push("e_shentsize", 2, Length)
+    .push("e_shnum", 2, Integer)
+    .push("e_shstrndx", 2, Integer);
+    fields
+}
+
+fn png_fields<R: Read + Seek>(file: &mut R) -> io::Result<Vec<Field>> {
+    use FieldKind::*;
+
+    let mut fields = vec![];
+    Fields {
+        fields: &mut fields,
+        position: 0,
+    }
+    .push("PNG signature", 8, Magic);
+    let mut position = 8;
+    for _ in 0..MAX_RECORDS {
+        let mut header = [0; 8];
+        if read_at(file, position, &mut header)? < header.len() {
+            break;
+        }
+        let (length, chunk_type) = header.split_at(4);
+        if !chunk_type.iter().all(u8::is_ascii_alphabetic) {
+            break;
+        }
+        let length = u64::from(u32::from_be_bytes(length.try_into().expect("4 bytes")));
+        let chunk_type = String::from_utf8_lossy(chunk_type).into_owned();
+        let mut chunk = Fields {
+            fields: &mut fields,
+            position,
+        };
+        chunk.push(format!("{chunk_type} length"), 4, Length).push(
+            format!("{chunk_type} type"),
+            4,
+            Magic,
+        );
+        if chunk_type == "IHDR" && length == 13 {
+            chunk
+                .push("width", 4, Integer)
+                .push("height", 4, Integer)
+                .push("bit depth", 1, Integer)
+                .push("color type", 1, Integer)
+                .push("compression", 1, Integer)
+                .push("filter", 1, Integer)
+                .push("interlace", 1, Integer);
+        } else {
+            chunk.skip(length);
+        }
+        chunk.push(format!("{chunk_type} CRC"), 4, Integer);
+        if chunk_type == "IEND" {
+            break;
+        }
+        position = chunk.position;
+    }
+    Ok(fields)
+}
+
+fn zip_fields<R: Read + Seek>(file: &mut R) -> io::Result<Vec<Field>> {
+    use FieldKind::*;
+
+    let mut fields = vec![];
+    let mut position = 0;
+    for _ in 0..MAX_RECORDS {
+        let mut header = [0; 30];
+        if read_at(file, position, &mut header)? < header.len()
+            || !header.starts_with(b"PK\x03\x04")
+        {
+            break;
+        }
+        let u16_at = |i: usize| u64::from(u16::from_le_bytes([header[i], header[i + 1]]));
+        let flags = u16_at(6);
+        let compressed_size = u64::from(u32::from_le_bytes(
+            header[18..22].try_into().expect("4 bytes"),
+        ));
+        let mut member = Fields {
+            fields: &mut fields,
+            position,
+        };
+        member
+            .push("local file header signature", 4, Magic)
+            .push("version needed", 2, Integer)
+            .push("flags", 2, Integer)
+            .push("compression", 2, Integer)
+            .push("modification time", 2, Integer)
+            .push("modification date", 2, Integer)
+            .push("CRC-32", 4, Integer)
+            .push("compressed size", 4, Length)
+            .push("uncompressed size", 4, Length)
+            .push("file name length", 2, Length)
+            .push("extra field length", 2, Length)
+            .skip(u16_at(26) + u16_at(28));
+        // With a data descriptor, the sizes are only known after the data.
+        if flags & 0x08 != 0 {
+            break;
+        }
+        position = member.position + compressed_size;
+    }
+    Ok(fields)
+}
+
+/// Returns whether the numbers of a PCAP capture with the magic number
+/// `magic` are little-endian, or `None` if it is not a PCAP magic number.
+fn pcap_endianness(magic: &[u8]) -> Option<bool> {
+    match magic {
+        b"\xd4\xc3\xb2\xa1" | b"\x4d\x3c\xb2\xa1" => Some(true),
+        b"\xa1\xb2\xc3\xd4" | b"\xa1\xb2\x3c\x4d" => Some(false),
+        _ => None,
+    }
+}
+
+fn pcap_fields<R: Read + Seek>(file: &mut R, start: &[u8]) -> io::Result<Vec<Field>> {
+    use FieldKind::*;
+
+    let little_endian = pcap_endianness(&start[..4]).expect("PCAP magic number");
+    let mut fields = vec![];
+    let mut header = Fields {
+        fields: &mut fields,
+        position: 0,
+    };
+    header
+        .push("PCAP magic", 4, Magic)
+        .push("major version", 2, Integer)
+        .push("minor version", 2, Integer)
+        .push("time zone", 4, Integer)
+        .push("timestamp accuracy", 4, Integer)
+        .push("snapshot length", 4, Length)
+        .push("link type", 4, Integer);
+    let mut position = header.position;
+    for _ in 0..MAX_RECORDS {
+        let mut record = [0; 16];
+        if read_at(file, position, &mut record)? < record.len() {
+            break;
+        }
+        let captured: [u8; 4] = record[8..12].try_into().expect("4 bytes");
+        let captured = u64::from(if little_endian {
+            u32::from_le_bytes(captured)
+        } else {
+            u32::from_be_bytes(captured)
+        });
+        let mut packet = Fields {
+            fields: &mut fields,
+            position,
+        };
+        packet
+            .push("timestamp (seconds)", 4, Integer)
+            .push("timestamp (fraction)", 4, Integer)
+            .push("captured length", 4, Length)
+            .push("original length", 4, Length)
+            .skip(captured);
+        position = packet.position;
+    }
+    Ok(fields)
+}
+
+fn tar_fields<R: Read + Seek>(file: &mut R) -> io::Result<Vec<Field>> {
+    use FieldKind::*;
+
+    let mut fields = vec![];
+    let mut position = 0;
+    for _ in 0..MAX_RECORDS {
+        let mut header = [0; TAR_BLOCK_SIZE as usize];
+        if read_at(file, position, &mut header)? < header.len() || &header[257..262] != b"ustar" {
+            break;
+        }
+        // The size is an octal number, padded with spaces or NULs.
+        let size = str::from_utf8(&header[124..136])
+            .ok()
+            .and_then(|size| {
+                u64::from_str_radix(size.trim_matches(|c| c == ' ' || c == '\0'), 8).ok()
+            })
+            .unwrap_or(0);
+        Fields {
+            fields: &mut fields,
+            position,
+        }
+        .skip(100)
+        .push("mode", 8, Integer)
+        .push("owner", 8, Integer)
+        .push("group", 8, Integer)
+        .push("size", 12, Length)
+        .push("modification time", 12, Integer)
+        .push("checksum", 8, Integer)
+        .push("type", 1, Integer)
+        .skip(100)
+        .push("ustar magic", 6, Magic)
+        .push("ustar version", 2, Integer);
+        position += TAR_BLOCK_SIZE + size.div_ceil(TAR_BLOCK_SIZE) * TAR_BLOCK_SIZE;
+    }
+    Ok(fields)
+}
+
+/// Reads the bytes at `position` into `buf`, and returns how many there were
+/// before the end of the file.
+fn read_at<R: Read + Seek>(file: &mut R, position: u64, buf: &mut [u8]) -> io::Result<usize> {
+    file.seek(SeekFrom::Start(position))?;
+    let mut len = 0;
+    while len < buf.len() {
+        match file.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(len)
+}
@@ -0,0 +1,76 @@
+//! The bytes of the input as a C array, like `xxd -i`, e.g. to embed a
+//! firmware blob in a program.
+//!
+//! The array is followed by a variable with its length, and the bytes are
+//! written in lines of [`BYTES_PER_LINE`] bytes:
+//!
+//! ```text
+//! unsigned char ascii[] = {
+//!   0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x61, 0x62,
+//!   0x63, 0x64, 0x65, 0x0a
+//! };
+//! unsigned int ascii_len = 16;
+//! ```
+
+use std::io::{self, BufReader, Read, Write};
+
+/// The number of bytes in a line of the array, as with `xxd -i`.
+pub const BYTES_PER_LINE: usize = 12;
+
+/// Writes the bytes of `reader` as a C array called `name` to `writer`, see
+/// the [module documentation](self). `name` should be a valid C identifier,
+/// see [`c_identifier`].
+pub fn write_c_include<R: Read, W: Write + ?Sized>(
+    reader: R,
+    writer: &mut W,
+    name: &str,
+) -> io::Result<()> {
+    writeln!(writer, "unsigned char {name}[] = {{")?;
+    let mut reader = BufReader::new(reader);
+    let mut buf = [0; 8 * 1024];
+    let mut len: u64 = 0;
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        for &b in &buf[..n] {
+            // The separator of the previous byte is only written now, as the
+            // last byte has none.
+            if len > 0 {
+                if len % BYTES_PER_LINE as u64 == 0 {
+                    writeln!(writer, ",")?;
+                } else {
+                    write!(writer, ", ")?;
+                }
+            }
+            if len % BYTES_PER_LINE as u64 == 0 {
+                write!(writer, "  ")?;
+            }
+            write!(writer, "0x{b:02x}")?;
+            len += 1;
+        }
+    }
+    if len > 0 {
+        writeln!(writer)?;
+    }
+    writeln!(writer, "}};")?;
+    writeln!(writer, "unsigned int {name}_len = {len};")?;
+    writer.flush()
+}
+
+/// Turns `name` into a valid C identifier like `xxd -i` does for file names:
+/// every character except ASCII letters and digits is replaced by `_`, and a
+/// leading digit is prefixed with `__`.
+pub fn c_identifier(name: &str) -> String {
+    let mut identifier: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if identifier.is_empty() || identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.insert_str(0, "__");
+    }
+    identifier
+}
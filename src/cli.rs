@@ -0,0 +1,1358 @@
+#[cfg(feature = "session")]
+use std::ffi::OsString;
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::path::PathBuf;
+
+use clap::builder::ArgPredicate;
+use clap::{ArgAction, ArgGroup, Args, Parser, Subcommand, ValueEnum, ValueHint};
+
+use clap_complete::Shell;
+
+use const_format::formatcp;
+
+use hexyl::{BorderStyle, CharPanels, CharacterTable, Endianness, LineHash, OnError, Padding};
+
+use crate::layout::Fit;
+use crate::DEFAULT_BLOCK_SIZE;
+
+const LENGTH_HELP_TEXT: &str = "Only read N bytes from the input. The N argument can also include \
+                                a unit with a decimal prefix (kB, MB, ..) or binary prefix (kiB, \
+                                MiB, ..), or can be specified using a hex number. The short \
+                                option '-l' can be used as an alias.
+Examples: --length=64, --length=4KiB, --length=0xff";
+
+const SKIP_HELP_TEXT: &str = "Skip the first N bytes of the input. The N argument can also \
+                              include a unit (see `--length` for details).
+A negative value is valid and will seek from the end of the file.";
+
+const BLOCK_SIZE_HELP_TEXT: &str = "Sets the size of the `block` unit to SIZE.
+Examples: --block-size=1024, --block-size=4kB";
+
+const DISPLAY_OFFSET_HELP_TEXT: &str = "Add N bytes to the displayed file position. The N \
+                                        argument can also include a unit (see `--length` for \
+                                        details).
+A negative value is valid and calculates an offset relative to the end of the file. N can also \
+                                        be relative to 'start', 'end' (the size of the file) or \
+                                        'here' (the '--skip' position, which labels the first \
+                                        displayed byte 'here+N').
+Examples: --display-offset=0x8000, --display-offset=end-0x100, --display-offset=here+1MiB";
+
+const TERMINAL_WIDTH_HELP_TEXT: &str = "Sets the number of terminal columns to be displayed.
+Since the terminal width may not be an evenly divisible by the width per hex data column, this \
+                                        will use the greatest number of hex data panels that can \
+                                        fit in the requested width but still leave some space to \
+                                        the right.
+Cannot be used with other width-setting options.";
+
+#[derive(Debug, Parser)]
+#[command(
+    version,
+    about,
+    max_term_width(90),
+    args_conflicts_with_subcommands(true),
+    disable_help_subcommand(true),
+    after_help(
+        "Running `hexyl [OPTIONS] [FILE]` without a subcommand is the same as \
+         `hexyl dump [OPTIONS] [FILE]`.
+
+Default options can be set in the HEXYL_DEFAULT_ARGS environment variable, \
+         e.g. HEXYL_DEFAULT_ARGS='--border=none --panels=1', and for matching files in \
+         the rules file ~/.config/hexyl/rules, with lines like \
+         '*.wav -> --group-size=2 --endianness=little'. Options given on the command \
+         line take precedence over the rules, which take precedence over the \
+         environment variable.
+
+Exit status: 0 on success, 1 for other errors, 2 for invalid arguments, 3 if \
+         the FILE does not exist, 4 for other errors while reading or writing, 5 if \
+         nothing matched '--filter-byte' or '--find', and 6 if the dump differs from \
+         the '--verify' file or the files of 'hexyl diff' differ."
+    )
+)]
+pub(crate) struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    dump: DumpArgs,
+
+    /// Print a completion script for the given shell to stdout and exit.
+    #[arg(long, value_name("SHELL"))]
+    pub completion: Option<Shell>,
+
+    /// Print the man page in roff format to stdout and exit, e.g. 'hexyl
+    /// --generate-manpage > hexyl.1'. It describes the options of this binary,
+    /// including those of the optional features it was built with.
+    #[arg(long, conflicts_with("completion"))]
+    pub generate_manpage: bool,
+}
+
+impl Cli {
+    /// Returns the subcommand to run, which defaults to `dump`.
+    pub fn into_command(self) -> Command {
+        self.command
+            .unwrap_or_else(|| Command::Dump(Box::new(self.dump)))
+    }
+
+    /// Returns the options of the `dump` subcommand, unless another
+    /// subcommand is run.
+    pub fn dump_args(&self) -> Option<&DumpArgs> {
+        match &self.command {
+            Some(Command::Dump(args)) => Some(args.as_ref()),
+            Some(Command::Diff(_) | Command::Convert(_)) => None,
+            None => Some(&self.dump),
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum Command {
+    /// Display the contents of a file as a hex dump (the default).
+    Dump(Box<DumpArgs>),
+
+    /// Compare two files side by side, with the bytes that differ
+    /// highlighted.
+    #[command(long_flag("diff"))]
+    Diff(DiffArgs),
+
+    /// Convert a file between raw bytes, hex digits, a hex dump of hexyl and
+    /// a C array, e.g. 'hexyl convert --from=hex --to=include'.
+    Convert(ConvertArgs),
+}
+
+#[derive(Debug, Args)]
+pub(crate) struct ConvertArgs {
+    /// The file to convert. If no FILE argument is given, read from STDIN.
+    #[arg(value_name("FILE"), value_hint(ValueHint::FilePath))]
+    pub file: Option<PathBuf>,
+
+    /// The format of the input.
+    #[arg(long, value_enum, default_value_t, value_name("FORMAT"))]
+    pub from: ConvertInput,
+
+    /// The format of the output.
+    #[arg(long, value_enum, value_name("FORMAT"))]
+    pub to: ConvertOutput,
+
+    /// Skip the first N bytes of the converted input, i.e. of the bytes and
+    /// not of the hex digits. The N argument can include a unit (see
+    /// 'hexyl --help' for details).
+    #[arg(short, long, value_name("N"))]
+    pub skip: Option<String>,
+
+    /// Only convert N bytes of the converted input. The N argument can include
+    /// a unit (see 'hexyl --help' for details).
+    #[arg(short('n'), long, visible_short_alias('c'), value_name("N"))]
+    pub length: Option<String>,
+
+    /// The name of the C array for '--to=include'. NAME defaults to the file
+    /// name with all characters except letters and digits replaced by '_', or
+    /// 'buf' for stdin.
+    #[arg(long, value_name("NAME"))]
+    pub name: Option<String>,
+
+    /// Write to FILE instead of stdout.
+    #[arg(long, value_name("FILE"), value_hint(ValueHint::FilePath))]
+    pub output: Option<PathBuf>,
+
+    /// Write the raw bytes of '--to=raw' to stdout even if it is a terminal.
+    #[arg(long)]
+    pub force_tty: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ConvertInput {
+    /// The bytes themselves.
+    #[default]
+    Raw,
+    /// Hex digits like the output of '--plain-hex', whitespace is ignored.
+    Hex,
+    /// A hex dump of hexyl with the default layout, as read by '--reverse'.
+    Dump,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ConvertOutput {
+    /// The bytes themselves, like '--reverse'.
+    Raw,
+    /// Hex digits in lines of 60 columns, like '--plain-hex'.
+    Hex,
+    /// A C array, like '--include'.
+    Include,
+}
+
+#[derive(Debug, Args)]
+pub(crate) struct DiffArgs {
+    /// The file to show on the left.
+    #[arg(value_name("FILE1"), value_hint(ValueHint::FilePath))]
+    pub left: PathBuf,
+
+    /// The file to show on the right.
+    #[arg(value_name("FILE2"), value_hint(ValueHint::FilePath))]
+    pub right: PathBuf,
+
+    /// Show all lines. Otherwise, lines that repeat the preceding line on
+    /// both sides are replaced by a line with an asterisk.
+    #[arg(short('v'), long)]
+    pub no_squeezing: bool,
+
+    /// When to use colors. Without colors, the bytes that differ are not
+    /// highlighted, but lines with differences are still marked.
+    #[arg(long, value_enum, default_value_t, value_name("WHEN"))]
+    pub color: ColorWhen,
+
+    /// Whether to draw a border.
+    #[arg(long, value_enum, default_value_t, value_name("STYLE"))]
+    pub border: BorderStyle,
+
+    /// Sets the number of hex data panels on each side. By default, as many
+    /// panels are shown as fit into the terminal, and one panel if the output
+    /// does not go to a terminal.
+    #[arg(long, value_name("N"))]
+    pub panels: Option<NonZeroU64>,
+
+    /// Defines how bytes are mapped to characters.
+    #[arg(long, value_enum, default_value_t, value_name("FORMAT"))]
+    pub character_table: CharacterTable,
+}
+
+#[derive(Debug, Args)]
+#[command(group(ArgGroup::new("parallel").multiple(true).args(["count_bytes", "find", "find_text"])))]
+pub(crate) struct DumpArgs {
+    /// The file to display. If no FILE argument is given, read from STDIN.
+    #[arg(value_name("FILE"), value_hint(ValueHint::FilePath))]
+    pub file: Option<PathBuf>,
+
+    #[arg(
+        help(LENGTH_HELP_TEXT),
+        short('n'),
+        long,
+        visible_short_alias('c'),
+        visible_alias("bytes"),
+        short_alias('l'),
+        value_name("N")
+    )]
+    pub length: Option<String>,
+
+    /// Check that the input contains at least as many bytes as requested with
+    /// '--length'. By default, a shorter input is an error. With
+    /// '--strict-length=warn', a warning is printed instead.
+    #[arg(
+        long,
+        value_enum,
+        value_name("MODE"),
+        num_args(0..=1),
+        require_equals(true),
+        default_missing_value("error"),
+        requires("length")
+    )]
+    pub strict_length: Option<StrictLength>,
+
+    #[arg(help(SKIP_HELP_TEXT), short, long, value_name("N"))]
+    pub skip: Option<String>,
+
+    /// Stop reading at position N of the input (exclusive) instead of after a
+    /// '--length', e.g. '--skip=0x200 --end=0x400' for the bytes from 0x200
+    /// to 0x3ff. The N argument can also include a unit (see '--length' for
+    /// details).
+    #[arg(
+        long,
+        value_name("N"),
+        conflicts_with_all(["length", "per_file_length", "prompt"])
+    )]
+    pub end: Option<String>,
+
+    /// Stop at the first occurrence of PATTERN in the input, e.g.
+    /// '--until=\x00\x00\xff\xff' for the end marker of a frame in a stream.
+    /// The PATTERN is text in which '\xHH' stands for a byte in hex, and '\\',
+    /// '\0', '\n', '\r' and '\t' for the usual characters. The pattern itself
+    /// is shown, unless '--until-exclusive' is given. '--length' and '--end'
+    /// still limit the input.
+    #[arg(long, value_name("PATTERN"), conflicts_with("prompt"))]
+    pub until: Option<String>,
+
+    /// Stop before the PATTERN of '--until' instead of after it.
+    #[arg(long, requires("until"))]
+    pub until_exclusive: bool,
+
+    #[arg(
+        help(BLOCK_SIZE_HELP_TEXT),
+        long,
+        default_value(formatcp!("{DEFAULT_BLOCK_SIZE}")),
+        value_name("SIZE")
+    )]
+    pub block_size: String,
+
+    /// Displays all input data. Otherwise any number of groups of output lines
+    /// which would be identical to the preceding group of lines, are replaced
+    /// with a line comprised of a single asterisk.
+    #[arg(short('v'), long)]
+    pub no_squeezing: bool,
+
+    /// After the dump, list every squeezed region with its first and last
+    /// position, its length and the repeated byte, e.g. '0x400-0xfff: 3072
+    /// bytes of 0x00', so that readers of a saved dump know what was left
+    /// out.
+    #[arg(long, conflicts_with("no_squeezing"))]
+    pub summary: bool,
+
+    /// The marker of squeezed lines instead of '*', e.g. '…' or '[snip]',
+    /// which can be 1 to 8 columns wide.
+    #[arg(long, value_name("MARKER"), default_value("*"))]
+    pub squeeze_char: String,
+
+    /// When to use colors.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t,
+        value_name("WHEN"),
+        default_value_if("plain", ArgPredicate::IsPresent, Some("never"))
+    )]
+    pub color: ColorWhen,
+
+    /// The color of null bytes, e.g. 'red' or 'bright-blue' (see
+    /// '--color-command'). This takes precedence over the HEXYL_COLOR_NULL
+    /// environment variable, and likewise for the other '--color-*' options.
+    #[arg(long, value_name("COLOR"))]
+    pub color_null: Option<String>,
+
+    /// The color of printable ASCII characters.
+    #[arg(long, value_name("COLOR"), visible_alias("color-ascii"))]
+    pub color_printable: Option<String>,
+
+    /// The color of ASCII whitespace characters.
+    #[arg(long, value_name("COLOR"))]
+    pub color_whitespace: Option<String>,
+
+    /// The color of other ASCII characters.
+    #[arg(long, value_name("COLOR"))]
+    pub color_other: Option<String>,
+
+    /// The color of non-ASCII bytes.
+    #[arg(long, value_name("COLOR"))]
+    pub color_nonascii: Option<String>,
+
+    /// The color of the position panel.
+    #[arg(long, value_name("COLOR"))]
+    pub color_offset: Option<String>,
+
+    /// The color of the marker of squeezed lines (see '--squeeze-char').
+    #[arg(long, value_name("COLOR"))]
+    pub color_squeeze: Option<String>,
+
+    /// Let an external command decide the colors of the bytes. For every
+    /// displayed line, hexyl writes the offset and the bytes of the line in
+    /// hex to the standard input of CMD (e.g. '00000010 de ad be ef').
+    /// CMD must answer with a line containing one entry per byte, which is
+    /// either a color (e.g. 'red', 'bright-blue'), a byte category ('null',
+    /// 'printable', 'whitespace', 'other', 'nonascii') or '-' for the
+    /// default color, and flush its output after every line.
+    #[arg(long, value_name("CMD"))]
+    pub color_command: Option<String>,
+
+    /// Reset all colors at the end of every line, so that no color carries
+    /// over to the next line if lines are cut off (e.g. by a pager or a
+    /// terminal multiplexer).
+    #[arg(long)]
+    pub reset_per_line: bool,
+
+    /// Color every byte on its own, with a reset after it, instead of only
+    /// switching colors where they change. Every cell of a line then carries
+    /// its own color, so that lines can be cut apart (e.g. with 'grep -o' or
+    /// 'cut') without colors leaking into other parts.
+    #[arg(long)]
+    pub stateless_colors: bool,
+
+    /// Whether to draw a border.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t,
+        value_name("STYLE"),
+        default_value_if("plain", ArgPredicate::IsPresent, Some("none")),
+        default_value_if("style", "debug", Some("none")),
+        default_value_if("compact", "true", Some("compact"))
+    )]
+    pub border: BorderStyle,
+
+    /// Show the base, the number of panels, the group size, the endianness
+    /// and the character table in the top border, e.g.
+    /// '[hex|2 panels|g1|BE|default]', so that saved dumps describe
+    /// themselves.
+    #[arg(long)]
+    pub show_settings: bool,
+
+    /// Show the name of the input, the positions of the first and the last
+    /// byte and their number in the bottom border, e.g.
+    /// '[file.bin 0x400-0x7ff (1 KiB)]', for screenshots where the top is
+    /// cropped.
+    #[arg(long)]
+    pub footer_info: bool,
+
+    /// Show a mark in the middle of every panel (between the fourth and the
+    /// fifth byte by default), in the hex panel and in the character panel,
+    /// to make it easier to find the byte of a character. Requires a
+    /// '--group-size' that divides half of the '--bytes-per-panel'.
+    #[arg(long, default_value_if("style", "debug", Some("true")))]
+    pub tick_marks: bool,
+
+    /// Show the hex digits of the bytes and of the positions in uppercase,
+    /// e.g. 'DE AD BE EF'.
+    #[arg(long, default_value_if("style", "debug", Some("true")))]
+    pub uppercase: bool,
+
+    /// Use the layout of another tool. 'debug' shows lines of 16 bytes like
+    /// DOS DEBUG, with 'segment:offset' positions ('--offset-style=segment'),
+    /// a '-' between the eighth and the ninth byte, uppercase hex digits, no
+    /// border, and '.' for the bytes that are not printable ASCII. Other
+    /// options still apply, e.g. '--character-table=codepage-437', and
+    /// override the ones of the style.
+    #[arg(long, value_enum, value_name("STYLE"))]
+    pub style: Option<Style>,
+
+    /// Use the narrow layout of 'hexdump -C' with the colors of hexyl, e.g.
+    /// '00000000: 7f 45 4c 46 ...  |.ELF...|': no border ('--border=compact'),
+    /// a colon after the position, and '.' for the bytes that are not
+    /// printable ASCII. Other options still apply, e.g.
+    /// '--character-table=default', and override the ones of the layout.
+    /// Nothing is printed for an empty input. The dump can not be read by
+    /// '--reverse'.
+    #[arg(long, conflicts_with_all(["style", "plain"]))]
+    pub compact: bool,
+
+    /// Display output with --no-characters, --no-position, --border=none, and
+    /// --color=never.
+    #[arg(short, long)]
+    pub plain: bool,
+
+    /// Do not show the character panel on the right.
+    #[arg(long)]
+    pub no_characters: bool,
+
+    /// Show the character panel on the right. This is the default, unless
+    /// --no-characters has been specified.
+    #[arg(
+        short('C'),
+        long,
+        action(ArgAction::SetTrue),
+        overrides_with("no_characters")
+    )]
+    pub characters: (),
+
+    /// Choose the hex panels whose bytes are shown in a character panel:
+    /// 'all' of them, only the 'first' or the 'last' one, or 'none'. E.g.
+    /// '--panels=4 --char-panels=last' shows a single character panel on
+    /// the right.
+    #[arg(long, value_enum, default_value_t, value_name("WHICH"))]
+    pub char_panels: CharPanels,
+
+    /// Defines how bytes are mapped to characters.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t,
+        value_name("FORMAT"),
+        default_value_if("style", "debug", Some("ascii")),
+        default_value_if("compact", "true", Some("ascii"))
+    )]
+    pub character_table: CharacterTable,
+
+    /// With '--character-table=codepage-437', show blanks instead of the
+    /// graphic symbols for the control characters 0x00-0x1f.
+    #[arg(long)]
+    pub cp437_blank_controls: bool,
+
+    /// Show a column that classifies every line as text (T), zeros (Z),
+    /// high-entropy data (H) or other binary data (B).
+    #[arg(long)]
+    pub classify_lines: bool,
+
+    /// Show a column with a checksum of the bytes of every line, CRC-8
+    /// ('crc8') or CRC-16 ('crc16'), to compare two dumps by eye or with
+    /// 'diff' even when the other columns differ.
+    #[arg(long, value_enum, value_name("HASH"))]
+    pub line_hash: Option<LineHash>,
+
+    /// Mark the position where the input ends with a '▏' in the hex panel,
+    /// to tell apart inputs that only differ in their length. If the input
+    /// ends at the end of a line, an extra line with the final position is
+    /// printed.
+    #[arg(long)]
+    pub mark_eof: bool,
+
+    /// Print a row below the header with the column of every byte within the
+    /// line in hex ('00 01 02 ...'), in the color of the position panel, to
+    /// find the byte at a position like 0x...7 at a glance. With
+    /// '--group-size', there is one column per group.
+    #[arg(long)]
+    pub ruler: bool,
+
+    /// Only display lines that contain at least one of the given bytes, and
+    /// highlight these bytes, e.g. '--filter-byte=0xcc' to find breakpoints.
+    /// Values can be decimal or hex numbers, and several values can be given
+    /// separated by commas. The number of matches is printed to stderr.
+    #[arg(long, value_name("BYTE,.."), value_delimiter(','), num_args(1..))]
+    pub filter_byte: Vec<String>,
+
+    /// Only display lines that contain a match of the given hex string, or a
+    /// part of one, and highlight the matched bytes, e.g. '--find=DEADBEEF'.
+    /// '??' matches any byte ('--find=DE??BE'), and spaces between the bytes
+    /// are ignored. The option can be given several times, and the offsets of
+    /// the matches are printed to stderr.
+    #[arg(long, value_name("HEX"))]
+    pub find: Vec<String>,
+
+    /// Like '--find', but for the bytes of TEXT, e.g. '--find-text=PNG'. The
+    /// escape sequences of '--until' can be used.
+    #[arg(long, value_name("TEXT"))]
+    pub find_text: Vec<String>,
+
+    /// After the dump, print the start, end and length of every region of
+    /// displayed positions to stderr, in hex and decimal, e.g. to pass them on
+    /// to dd. Regions are separated by lines that '--filter-byte' left out.
+    #[arg(long)]
+    pub print_spans: bool,
+
+    /// Write events as JSON lines to the file descriptor N, e.g. '3' with
+    /// '3>status.jsonl' or '2' for stderr, for programs that wrap hexyl: the
+    /// progress after every block of input, the matches of '--find', the
+    /// squeezed regions, and a summary at the end of every input.
+    #[cfg(unix)]
+    #[arg(long, value_name("N"))]
+    pub status_fd: Option<i32>,
+
+    /// Fail instead of keeping more than SIZE bytes of data in memory. Apart
+    /// from the line buffers, only some features keep data in memory: the
+    /// regions of '--print-spans' and '--summary', the files of
+    /// '--csv-ranges', the contents of '--clipboard', the sample of
+    /// '--detect-stride' (which is shortened instead) and the chunks of
+    /// '--prompt' (which are made smaller). SIZE can include a unit (see
+    /// '--length' for details).
+    #[arg(long, value_name("SIZE"))]
+    pub max_memory: Option<String>,
+
+    /// Read the input in blocks of SIZE bytes (default: 64 KiB) instead of
+    /// line by line. Larger blocks can be faster on network file systems.
+    /// SIZE can include a unit (see '--length' for details).
+    #[arg(long, value_name("SIZE"))]
+    pub io_buffer_size: Option<String>,
+
+    /// Make every offset in the position panel a hyperlink to the URL built
+    /// from TEMPLATE, in which '{offset}' is replaced by the offset in hex and
+    /// '{offset_dec}' by the offset in decimal, e.g. to open a disassembler at
+    /// that address. Only used if colors are enabled.
+    #[arg(long, value_name("TEMPLATE"))]
+    pub hyperlink_offsets: Option<String>,
+
+    /// Highlight the regions listed in a CSV file with the columns
+    /// 'offset,length,label,color', e.g. exported from binwalk or an 010
+    /// Editor template. Offsets and lengths are decimal or hex ('0x...') and
+    /// refer to the positions in the position panel. The label and the color
+    /// (see '--color-command') are optional. Labels are shown at the end of
+    /// the line where their region starts.
+    #[arg(long, value_name("FILE"), value_hint(ValueHint::FilePath))]
+    pub csv_ranges: Option<PathBuf>,
+
+    /// Show the comments from a file with one 'OFFSET<TAB>COMMENT' line per
+    /// comment at the end of the line that contains the offset, e.g. to
+    /// regenerate an annotated dump from the raw file. Offsets are decimal or
+    /// hex ('0x...') and refer to the positions in the position panel. Long
+    /// comments are wrapped to the width of the terminal, and continued below
+    /// the line. Empty lines and lines starting with '#' are ignored.
+    #[arg(long, value_name("FILE"), value_hint(ValueHint::FilePath))]
+    pub comments: Option<PathBuf>,
+
+    /// Highlight the bytes from START up to (excluding) END in a color (see
+    /// '--color-command', default: bright magenta), and show the LABEL at the
+    /// end of the line where the region starts, e.g. '--highlight
+    /// 0x40..0x78:yellow:"program header"'. START and END refer to the
+    /// positions in the position panel. Can be given several times, and a
+    /// legend of all labels with their ranges is printed below the dump.
+    #[arg(long, value_name("START..END[:COLOR[:LABEL]]"))]
+    pub highlight: Vec<String>,
+
+    /// Remove terminal escape sequences (e.g. colors, cursor movements or
+    /// window titles) from the labels of '--highlight', '--map' and
+    /// '--dwarf', the comments of '--comments', the name of the input and
+    /// the template of '--hyperlink-offsets', and show their other control
+    /// characters (e.g. the bell) as placeholders, for texts from untrusted
+    /// sources. The character panel never contains control characters.
+    #[arg(long)]
+    pub strict_sanitize: bool,
+
+    /// Print the input as a hex dump, as a run-length summary ('rle') that
+    /// shows long runs of identical bytes in a single line, or as one JSON
+    /// object per line ('json') for scripts.
+    #[arg(
+        long,
+        alias("format"),
+        value_enum,
+        default_value_t,
+        value_name("FORMAT"),
+        conflicts_with_all(["step", "count_bytes"])
+    )]
+    pub output_format: OutputFormat,
+
+    /// Print the input as a continuous string of lowercase hex digits without
+    /// offsets, borders or character panels, like 'xxd -p', wrapped after
+    /// COLS digits (default: 60). '--plain-hex=0' prints a single line.
+    #[arg(
+        long,
+        visible_alias("ps"),
+        value_name("COLS"),
+        num_args(0..=1),
+        require_equals(true),
+        default_missing_value("60"),
+        conflicts_with_all([
+            "output_format",
+            "porcelain",
+            "step",
+            "count_bytes",
+            "auto_text",
+            "verify",
+            "reverse"
+        ])
+    )]
+    pub plain_hex: Option<usize>,
+
+    /// Print the input as a C array with its length, like 'xxd -i', e.g. to
+    /// embed a firmware blob: 'unsigned char NAME[] = {0x7f, 0x45, ...};
+    /// unsigned int NAME_len = 4;'. NAME defaults to the file name with all
+    /// characters except letters and digits replaced by '_', or 'buf' for
+    /// stdin.
+    #[arg(
+        long,
+        value_name("NAME"),
+        num_args(0..=1),
+        require_equals(true),
+        conflicts_with_all([
+            "output_format",
+            "porcelain",
+            "plain_hex",
+            "step",
+            "count_bytes",
+            "auto_text",
+            "verify",
+            "reverse",
+            "watch",
+            "recursive",
+            "prompt",
+            "cat",
+            "map"
+        ])
+    )]
+    pub include: Option<Option<String>>,
+
+    /// Print the input in a format for scripts that stays the same across
+    /// releases: one line per 16 bytes with the offset as 16 hex digits, a
+    /// colon, and the bytes in hex ('0000000000000010: 02 00 3e 00'). No
+    /// lines are squeezed, and all options that change the look of the hex
+    /// dump as well as the HEXYL_DEFAULT_ARGS environment variable and the
+    /// rules file are ignored.
+    #[arg(
+        long,
+        value_enum,
+        value_name("VERSION"),
+        conflicts_with_all([
+            "output_format",
+            "step",
+            "count_bytes",
+            "detect_stride",
+            "filter_byte",
+            "find",
+            "find_text",
+            "prompt",
+            "recursive",
+            "watch",
+        ])
+    )]
+    pub porcelain: Option<Porcelain>,
+
+    /// Instead of a hex dump, print a table with the number of bytes and the
+    /// share of each byte category (NULL, printable ASCII, ASCII whitespace,
+    /// other ASCII, non-ASCII) for every '--range', or for the whole input.
+    #[arg(long)]
+    pub count_bytes: bool,
+
+    /// With '--count-bytes', '--find' or '--find-text', read a FILE in N
+    /// chunks in parallel, which is faster for large images on fast storage.
+    /// Other inputs, and '--find' with '--step', are read by a single thread.
+    #[arg(
+        short('j'),
+        long,
+        value_name("N"),
+        default_value("1"),
+        requires("parallel"),
+        conflicts_with_all(["until", "retries", "on_error"])
+    )]
+    pub jobs: NonZeroUsize,
+
+    /// Instead of printing the dump, compare it with the file EXPECTED, e.g. a
+    /// dump that was saved with the same options. If they differ, a unified
+    /// diff from EXPECTED to the dump is printed and hexyl exits with status 6.
+    #[arg(
+        long,
+        value_name("EXPECTED"),
+        value_hint(ValueHint::FilePath),
+        conflicts_with_all([
+            "watch",
+            "recursive",
+            "prompt",
+            "porcelain",
+            "output_format",
+            "count_bytes",
+            "auto_text"
+        ])
+    )]
+    #[cfg_attr(feature = "decompress", arg(conflicts_with("decompress")))]
+    pub verify: Option<PathBuf>,
+
+    /// Convert a hex dump of hexyl in FILE (or stdin) back into the bytes,
+    /// like 'xxd -r'. The dump may have colors, any '--border' style, and a
+    /// position panel, which is needed to restore squeezed lines. Pass the
+    /// same '--base', '--group-size', '--endianness' and '--interleave' as for
+    /// the dump, and '--offset-style=sector' with its '--sector-size' for
+    /// sector positions. Dumps of '--style=debug' and '--compact' can not be reversed.
+    /// The bytes are written to stdout, or to the file given with '--output'.
+    #[arg(
+        long,
+        conflicts_with_all([
+            "watch",
+            "recursive",
+            "prompt",
+            "verify",
+            "porcelain",
+            "output_format",
+            "count_bytes",
+            "auto_text",
+            "mnemonics",
+            "cat",
+            "map",
+            "style",
+            "compact"
+        ])
+    )]
+    pub reverse: bool,
+
+    /// Write the bytes of '--reverse' to FILE instead of stdout.
+    #[arg(
+        long,
+        value_name("FILE"),
+        value_hint(ValueHint::FilePath),
+        requires("reverse")
+    )]
+    pub output: Option<PathBuf>,
+
+    /// Write raw binary output like the bytes of '--reverse' to stdout even
+    /// if it is a terminal.
+    #[arg(long)]
+    pub force_tty: bool,
+
+    /// A range of input positions 'START:END' (END is exclusive) for
+    /// '--count-bytes'. Both can include a unit (see '--length' for details),
+    /// and either can be omitted to start at the beginning or stop at the end
+    /// of the input. Can be given multiple times.
+    #[arg(long, value_name("START:END"), requires("count_bytes"))]
+    pub range: Vec<String>,
+
+    /// Only display every Nth byte, e.g. '--step=4' to extract one channel of
+    /// interleaved 4-byte samples. The position panel shows the positions of
+    /// the bytes in the input. See also '--phase'.
+    #[arg(
+        long,
+        default_value("1"),
+        value_name("N"),
+        conflicts_with("count_bytes")
+    )]
+    pub step: NonZeroU64,
+
+    /// The index of the first byte that is displayed with '--step', e.g.
+    /// '--step=4 --phase=1' displays the bytes at positions 1, 5, 9, ...
+    #[arg(long, default_value_t, value_name("N"))]
+    pub phase: u64,
+
+    /// Retry failed reads up to N times, e.g. for damaged disks that return
+    /// transient errors.
+    #[arg(long, default_value("0"), value_name("N"))]
+    pub retries: u32,
+
+    /// What to do if reading fails even after all '--retries'. Unreadable
+    /// data is skipped in blocks of '--block-size' bytes, and a summary of the
+    /// unreadable blocks is shown at the end.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t,
+        value_name("MODE"),
+        conflicts_with("step")
+    )]
+    pub on_error: OnError,
+
+    /// Read whole sectors from disk devices, for raw devices that reject reads
+    /// which do not start and end at a multiple of the logical sector size.
+    /// Without this option, a warning is shown if '--skip' or '--length' are
+    /// not multiples of the sector size.
+    #[arg(long)]
+    pub align_device_reads: bool,
+
+    /// Read the FILE with direct I/O (O_DIRECT), bypassing the page cache, so
+    /// that dumping a large file does not evict the cached data of other
+    /// programs. The file is read in aligned blocks. If the file system does
+    /// not support direct I/O, a warning is shown and the file is read
+    /// normally.
+    #[cfg(target_os = "linux")]
+    #[arg(long, requires("file"), conflicts_with_all(["recursive", "prompt"]))]
+    #[cfg_attr(feature = "decompress", arg(conflicts_with("decompress")))]
+    #[cfg_attr(feature = "session", arg(conflicts_with("save_session")))]
+    #[cfg_attr(feature = "tui", arg(conflicts_with("interactive")))]
+    pub direct_io: bool,
+
+    /// Whether to display the position panel on the left.
+    #[arg(short('P'), long)]
+    pub no_position: bool,
+
+    /// The panels to display, as a comma-separated list of 'offset', 'hex'
+    /// and 'chars' in this order, e.g. '--columns=hex,chars' for the same
+    /// output as '--no-position'. The hex panel is always displayed.
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter(','),
+        value_name("COLUMNS"),
+        conflicts_with_all(["no_characters", "characters", "no_position"])
+    )]
+    pub columns: Option<Vec<Column>>,
+
+    #[arg(
+        help(DISPLAY_OFFSET_HELP_TEXT),
+        short('o'),
+        long,
+        default_value("0"),
+        value_name("N")
+    )]
+    pub display_offset: String,
+
+    /// Show the positions relative to OFFSET, e.g. a load address or the
+    /// start of a structure: OFFSET is subtracted from the displayed
+    /// positions (including '--display-offset'), and positions before it
+    /// are shown as the distance to it with a '-' in front, e.g.
+    /// '--relative-to=0x401000'. OFFSET can also include a unit (see
+    /// '--length' for details).
+    #[arg(long, value_name("OFFSET"))]
+    pub relative_to: Option<String>,
+
+    /// How positions are shown in the position panel: 'byte' shows the byte
+    /// offset, 'sector' shows the sector number and the offset within that
+    /// sector (e.g. 'LBA 00000003 +1f0'), see '--sector-size', and 'segment'
+    /// shows a 'segment:offset' address of real mode like DOS DEBUG (e.g.
+    /// '0000:0100'), which wraps around after 1 MiB.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t,
+        value_name("STYLE"),
+        default_value_if("style", "debug", Some("segment"))
+    )]
+    pub offset_style: OffsetStyleArg,
+
+    /// Sets the sector size for '--offset-style=sector'. The SIZE argument
+    /// can also include a unit (see '--length' for details).
+    #[arg(long, default_value("512"), value_name("SIZE"))]
+    pub sector_size: String,
+
+    /// Sets the number of hex data panels to be displayed. `--panels=auto` will
+    /// display the maximum number of hex data panels based on the current
+    /// terminal width, and `--panels=auto-N` at most N of them. By default,
+    /// hexyl will show two panels, unless the terminal is not wide enough for
+    /// that. If the output does not go to a terminal, e.g. into a file or a
+    /// pipe, the default is always two panels.
+    #[arg(long, value_name("N"), default_value_if("style", "debug", Some("1")))]
+    pub panels: Option<String>,
+
+    /// Sets the number of bytes in every panel (default: 8), e.g.
+    /// '--bytes-per-panel=16' for the layout of xxd or 4 for narrow
+    /// terminals. It has to be a multiple of the '--group-size'.
+    #[arg(
+        long,
+        visible_alias("width"),
+        value_name("N"),
+        default_value_if("style", "debug", Some("16"))
+    )]
+    pub bytes_per_panel: Option<NonZeroU64>,
+
+    /// Show at most N panels when the number of panels is chosen for the
+    /// terminal width (e.g. with '--panels=auto' or '--fit=width'), so that
+    /// lines do not get too long on wide terminals.
+    #[arg(long, value_name("N"))]
+    pub max_panels: Option<NonZeroU64>,
+
+    /// Chooses the number of panels: 'width' shows as many panels as fit into
+    /// the terminal, 'width:N' as many as fit into N columns, 'panels:N'
+    /// shows N panels and 'bytes:N' shows N bytes per line (a multiple of
+    /// '--bytes-per-panel').
+    /// '--panels' and '--terminal-width' are shorthands for this option.
+    #[arg(
+        long,
+        value_name("SPEC"),
+        value_parser(clap::value_parser!(Fit)),
+        conflicts_with_all(["panels", "terminal_width"])
+    )]
+    pub fit: Option<Fit>,
+
+    /// Look for a dominant record length in the first 64 KiB of the input
+    /// and choose the number of panels so that every line holds whole
+    /// records, e.g. three panels for 24-byte records. The detected stride is
+    /// printed to stderr.
+    #[arg(long, conflicts_with_all(["panels", "fit", "terminal_width", "step"]))]
+    pub detect_stride: bool,
+
+    /// Print the input as it is instead of a hex dump if the first 4 KiB of
+    /// it look like UTF-8 text, e.g. when a text file is passed by accident.
+    #[arg(
+        long,
+        conflicts_with_all(["watch", "prompt", "porcelain", "output_format", "count_bytes"])
+    )]
+    pub auto_text: bool,
+
+    /// Number of bytes/octets that should be grouped together. You can use the
+    /// '--endianness' option to control the ordering of the bytes within a
+    /// group. '--groupsize' can be used as an alias (xxd-compatibility).
+    #[arg(
+        short('g'),
+        long,
+        value_enum,
+        default_value_t,
+        alias("groupsize"),
+        value_name("N")
+    )]
+    pub group_size: GroupSize,
+
+    /// Whether to print out groups in little-endian or big-endian format. This
+    /// option only has an effect if the '--group-size' is larger than 1. '-e'
+    /// can be used as an alias for '--endianness=little'.
+    #[arg(long, value_enum, default_value_t, value_name("FORMAT"))]
+    pub endianness: Endianness,
+
+    /// Below every line, show a row with the groups in the other endianness,
+    /// labeled 'le' or 'be'. Only useful with a group size larger than one.
+    #[arg(long)]
+    pub show_both_endianness: bool,
+
+    /// An alias for '--endianness=little'.
+    #[arg(short('e'), hide(true), overrides_with("endianness"))]
+    pub little_endian_format: bool,
+
+    /// Sets the base used for the bytes. The possible options are binary,
+    /// octal, decimal, and hexadecimal.
+    #[arg(short('b'), long, default_value("hexadecimal"), value_name("B"))]
+    pub base: String,
+
+    /// Show every line in several bases, one row per base, e.g.
+    /// '--interleave=hex,dec'. The first base replaces the one given by
+    /// '--base'. Accepts the same values as '--base'.
+    #[arg(
+        long,
+        value_name("B,.."),
+        action(ArgAction::Set),
+        value_delimiter(','),
+        num_args(1..),
+        require_equals(true),
+        conflicts_with("base")
+    )]
+    pub interleave: Vec<String>,
+
+    /// How the digits of a byte are padded in the hex panel, e.g. '007'
+    /// (zeros) or '  7' (spaces) for '--base=decimal'.
+    #[arg(long, value_enum, default_value_t, value_name("PADDING"))]
+    pub padding: Padding,
+
+    /// Below every line that contains ASCII control characters, show a row
+    /// with their mnemonics (e.g. 'NUL', 'ESC', 'DEL'). Bytes are shown with
+    /// at least three characters, so that the mnemonics fit.
+    #[arg(long)]
+    pub mnemonics: bool,
+
+    #[arg(
+        help(TERMINAL_WIDTH_HELP_TEXT),
+        long,
+        value_name("N"),
+        conflicts_with("panels")
+    )]
+    pub terminal_width: Option<NonZeroU64>,
+
+    /// Wrap lines that are wider than N columns, instead of limiting the
+    /// number of panels to the terminal width. Wrapped lines end with a
+    /// continuation marker. Use '--panels' to set the number of bytes per
+    /// line.
+    #[arg(
+        long,
+        value_name("N"),
+        value_parser(clap::value_parser!(u16).range(2..)),
+        conflicts_with("terminal_width")
+    )]
+    pub wrap: Option<u16>,
+
+    /// Keep running and re-display the FILE whenever it changes on disk. The
+    /// screen is cleared before every new dump.
+    #[arg(long, requires("file"), conflicts_with("recursive"))]
+    pub watch: bool,
+
+    /// Keep the FILE open after its end and print the bytes that are appended
+    /// to it, like 'tail -f', e.g. for a growing log or a capture from a
+    /// serial port. The output continues until hexyl is interrupted, or until
+    /// the bytes of '--length' have been printed.
+    #[arg(
+        short('f'),
+        long,
+        requires("file"),
+        conflicts_with_all([
+            "watch",
+            "recursive",
+            "prompt",
+            "until",
+            "wrap",
+            "verify",
+            "detect_stride",
+            "count_bytes",
+            "porcelain",
+            "output_format"
+        ])
+    )]
+    pub follow: bool,
+
+    /// If FILE is a directory, dump every file below it in order of their
+    /// paths, each with a header line, followed by a summary on stderr.
+    #[arg(short('r'), long, requires("file"))]
+    pub recursive: bool,
+
+    /// Display the FILES one after another as a single input instead of a
+    /// FILE, e.g. the parts of a split firmware image. '--skip' and
+    /// '--length' apply to the whole input.
+    #[arg(
+        long,
+        num_args(1..),
+        value_name("FILES"),
+        value_hint(ValueHint::FilePath),
+        conflicts_with("file")
+    )]
+    pub cat: Option<Vec<PathBuf>>,
+
+    /// With '--cat', show the name of every file at the end of the line
+    /// where it begins.
+    #[arg(long, requires("cat"))]
+    pub boundaries: bool,
+
+    /// Display the files at the given offsets of a single address space
+    /// instead of a FILE, e.g. '--map base.bin@0 app.bin@0x10000' for a
+    /// firmware that is built from several blobs. The gaps between the files
+    /// are shown as zeros (and squeezed), and the positions of every file are
+    /// shown in its own color, with its name at the line where it begins.
+    #[arg(
+        long,
+        num_args(1..),
+        value_name("FILE@OFFSET"),
+        conflicts_with_all(["file", "cat"])
+    )]
+    pub map: Option<Vec<String>>,
+
+    /// Only read N bytes from every file with '--recursive', e.g. to look at
+    /// the file headers in a directory.
+    #[arg(long, value_name("N"), requires("recursive"), conflicts_with("length"))]
+    pub per_file_length: Option<String>,
+
+    /// Show one screen of the FILE at a time, and read commands from stdin
+    /// to move around: an empty line or '+' for the next screen, '+N' or '-N'
+    /// to move by N bytes, 'g N' to go to position N, '/TEXT' to go to the
+    /// next occurrence of TEXT, and 'q' to quit. This works in any terminal,
+    /// e.g. over a plain SSH session.
+    #[arg(
+        long,
+        requires("file"),
+        conflicts_with_all(["skip", "length", "display_offset", "watch", "recursive", "count_bytes"])
+    )]
+    pub prompt: bool,
+
+    /// Browse the FILE on the whole terminal: the arrow keys or 'j' and 'k'
+    /// scroll by a line, Page Up and Page Down or 'b' and space by a screen,
+    /// Home and End or 'G' go to the start and the end, 'g' to a position,
+    /// '/' searches a text, 'n' goes to its next occurrence, and 'q' quits.
+    /// Only the bytes on the screen are read.
+    #[cfg(all(feature = "tui", unix))]
+    #[arg(
+        short('i'),
+        long,
+        requires("file"),
+        conflicts_with_all([
+            "skip",
+            "length",
+            "display_offset",
+            "watch",
+            "follow",
+            "recursive",
+            "prompt",
+            "count_bytes",
+            "include",
+            "plain_hex",
+            "output_format",
+            "porcelain",
+            "verify",
+            "auto_text"
+        ])
+    )]
+    pub interactive: bool,
+
+    /// Do not print a hint on stderr when reading from an interactive
+    /// terminal because no FILE was given.
+    #[arg(long)]
+    pub stdin_ok: bool,
+
+    /// Do not print a hint on stderr when the input looks like text and the
+    /// output goes to an interactive terminal.
+    #[arg(long)]
+    pub text_ok: bool,
+
+    /// Do not print a line on stderr when the input starts with a byte order
+    /// mark (UTF-8, UTF-16 or UTF-32) or a shebang, e.g. 'hexyl: the input
+    /// starts with a shebang for `/bin/sh`'.
+    #[arg(long)]
+    pub no_sniff: bool,
+
+    /// Display the current text contents of the clipboard (as UTF-8) instead
+    /// of a FILE.
+    #[cfg(feature = "clipboard")]
+    #[arg(long, conflicts_with_all(["file", "cat", "map"]))]
+    pub clipboard: bool,
+
+    /// Decompress gzip or zstd compressed input, and dump every gzip member
+    /// or zstd frame separately after a header line with its position in the
+    /// compressed input. '--skip', '--length' and '--display-offset' apply to
+    /// every member.
+    #[cfg(feature = "decompress")]
+    #[arg(
+        long,
+        conflicts_with_all([
+            "watch",
+            "recursive",
+            "prompt",
+            "porcelain",
+            "count_bytes",
+            "cat",
+            "map"
+        ])
+    )]
+    pub decompress: bool,
+
+    /// Whether the offsets of the decompressed members continue over all
+    /// members ('continue') or start at zero for every member ('reset').
+    #[cfg(feature = "decompress")]
+    #[arg(
+        long,
+        value_enum,
+        default_value_t,
+        value_name("MODE"),
+        requires("decompress")
+    )]
+    pub member_offsets: MemberOffsets,
+
+    /// Recognize the format of FILE by its magic number (ELF, PNG, ZIP, PCAP
+    /// or tar) and highlight the fields of its headers, with their names as
+    /// labels: magic numbers in magenta, lengths in yellow, pointers in cyan
+    /// and other integers in green.
+    #[arg(
+        long,
+        requires("file"),
+        conflicts_with_all(["watch", "recursive", "prompt", "porcelain"])
+    )]
+    pub annotate: bool,
+
+    /// Scan FILE with the YARA rules in the source file RULES, highlight the
+    /// bytes of every string match (in one color per rule) and print a
+    /// summary of the matches with their offsets in FILE after the dump.
+    #[cfg(feature = "yara")]
+    #[arg(
+        long,
+        value_name("RULES"),
+        value_hint(ValueHint::FilePath),
+        requires("file"),
+        conflicts_with_all(["watch", "recursive", "prompt", "porcelain"])
+    )]
+    pub yara: Option<PathBuf>,
+
+    /// Read the layout of the struct '--type' from the DWARF debug information
+    /// of BINARY (or of a separate debug file), and highlight its fields in
+    /// the dump of the struct at '--at', with their names and sizes as labels.
+    /// Nested structs are shown as their fields, e.g. 'origin.x'.
+    #[cfg(feature = "dwarf")]
+    #[arg(
+        long,
+        value_name("BINARY"),
+        value_hint(ValueHint::FilePath),
+        requires("type_name")
+    )]
+    pub dwarf: Option<PathBuf>,
+
+    /// The name of the struct, union or class (or of a typedef of it) for
+    /// '--dwarf'.
+    #[cfg(feature = "dwarf")]
+    #[arg(long = "type", value_name("NAME"), requires("dwarf"))]
+    pub type_name: Option<String>,
+
+    /// The position of the struct of '--dwarf', as shown in the position
+    /// panel (default: 0).
+    #[cfg(feature = "dwarf")]
+    #[arg(long, value_name("ADDRESS"), requires("dwarf"))]
+    pub at: Option<String>,
+
+    /// Save the input and the options of the dump to the session file SESSION
+    /// (zstd compressed), and display the dump as usual. The dump can be
+    /// displayed again with '--load-session', without the original input.
+    #[cfg(feature = "session")]
+    #[arg(
+        long,
+        value_name("SESSION"),
+        value_hint(ValueHint::FilePath),
+        conflicts_with_all(["watch", "recursive", "prompt", "cat", "map"])
+    )]
+    #[cfg_attr(feature = "yara", arg(conflicts_with("yara")))]
+    pub save_session: Option<PathBuf>,
+
+    /// Display the dump that has been saved with '--save-session', with the
+    /// same input and options. Default options from the environment and the
+    /// rules file are not used.
+    #[cfg(feature = "session")]
+    #[arg(
+        long,
+        value_name("SESSION"),
+        value_hint(ValueHint::FilePath),
+        exclusive(true)
+    )]
+    pub load_session: Option<PathBuf>,
+
+    /// The command line including the default options, which is saved with
+    /// '--save-session'.
+    #[cfg(feature = "session")]
+    #[arg(skip)]
+    pub command_line: Vec<OsString>,
+
+    /// The input of a session from '--load-session', which replaces FILE.
+    #[cfg(feature = "session")]
+    #[arg(skip)]
+    pub session_input: Option<Vec<u8>>,
+}
+
+#[derive(Clone, Debug, Default, ValueEnum)]
+pub(crate) enum ColorWhen {
+    /// Always use colorized output.
+    #[default]
+    Always,
+
+    /// Only displays colors if the output goes to an interactive terminal.
+    Auto,
+
+    /// Do not use colorized output.
+    Never,
+
+    /// Override the NO_COLOR environment variable.
+    Force,
+}
+
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub(crate) enum OffsetStyleArg {
+    /// Show byte offsets.
+    #[default]
+    Byte,
+
+    /// Show sector numbers and offsets within the sector.
+    Sector,
+
+    /// Show 'segment:offset' addresses of real mode.
+    Segment,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Style {
+    /// The layout of DOS DEBUG.
+    Debug,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub(crate) enum Column {
+    /// The position panel.
+    Offset,
+    /// The hex panels.
+    Hex,
+    /// The character panels.
+    Chars,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum OutputFormat {
+    /// The usual hex dump.
+    #[default]
+    Hexdump,
+
+    /// One line per run of identical bytes ('OFFSET run LEN BYTE') or per
+    /// up to 16 other bytes ('OFFSET lit LEN BYTES..').
+    Rle,
+
+    /// One JSON object per line, e.g. '{"offset":0,"bytes":[127,69],
+    /// "ascii":".E","squeezed":false}'. The first squeezed line also has the
+    /// number of squeezed bytes as "length".
+    Json,
+}
+
+#[cfg(feature = "decompress")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum MemberOffsets {
+    /// Continue the offsets of the previous members.
+    #[default]
+    Continue,
+    /// Start every member at offset zero.
+    Reset,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Porcelain {
+    /// The first version of the format.
+    V1,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub(crate) enum StrictLength {
+    /// Print a warning to stderr.
+    Warn,
+
+    /// Fail with an error.
+    Error,
+}
+
+#[derive(Clone, Debug, Default, ValueEnum)]
+pub(crate) enum GroupSize {
+    /// Grouped together every byte/octet.
+    #[default]
+    #[value(name = "1")]
+    One,
+
+    /// Grouped together every 2 bytes/octets.
+    #[value(name = "2")]
+    Two,
+
+    /// Grouped together every 4 bytes/octets.
+    #[value(name = "4")]
+    Four,
+
+    /// Grouped together every 8 bytes/octets.
+    #[value(name = "8")]
+    Eight,
+}
+
+impl From<GroupSize> for u8 {
+    fn from(number: GroupSize) -> Self {
+        match number {
+            GroupSize::One => 1,
+            GroupSize::Two => 2,
+            GroupSize::Four => 4,
+            GroupSize::Eight => 8,
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use anyhow::{anyhow, Result};
+
+use arboard::{Clipboard, Error};
+
+/// Returns the text contents of the clipboard as UTF-8, or nothing if the
+/// clipboard does not contain any text.
+pub fn read() -> Result<Vec<u8>> {
+    let mut clipboard =
+        Clipboard::new().map_err(|err| anyhow!("failed to access the clipboard: {err}"))?;
+
+    match clipboard.get_text() {
+        Ok(text) => Ok(text.into_bytes()),
+        Err(Error::ContentNotAvailable) => Ok(vec![]),
+        Err(err) => Err(anyhow!("failed to read the clipboard: {err}")),
+    }
+}
@@ -1,12 +1,92 @@
 use owo_colors::{colors, Color};
 
+use crate::ByteCategory;
+
 pub const COLOR_NULL: &[u8] = colors::BrightBlack::ANSI_FG.as_bytes();
 pub const COLOR_OFFSET: &[u8] = colors::BrightBlack::ANSI_FG.as_bytes();
 pub const COLOR_ASCII_PRINTABLE: &[u8] = colors::Cyan::ANSI_FG.as_bytes();
 pub const COLOR_ASCII_WHITESPACE: &[u8] = colors::Green::ANSI_FG.as_bytes();
 pub const COLOR_ASCII_OTHER: &[u8] = colors::Green::ANSI_FG.as_bytes();
 pub const COLOR_NONASCII: &[u8] = colors::Yellow::ANSI_FG.as_bytes();
+pub const COLOR_EOF: &[u8] = colors::Red::ANSI_FG.as_bytes();
+pub const COLOR_UNREADABLE: &[u8] = colors::BrightRed::ANSI_FG.as_bytes();
+pub const COLOR_HIGHLIGHT: &[u8] = colors::BrightMagenta::ANSI_FG.as_bytes();
 pub const COLOR_RESET: &[u8] = colors::Default::ANSI_FG.as_bytes();
+/// Resets all attributes, not only the foreground color.
+pub const SGR_RESET: &[u8] = b"\x1b[0m";
+
+/// The colors of the byte categories and of the position panel, see
+/// [`PrinterBuilder::theme`](crate::PrinterBuilder::theme).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub null: &'static [u8],
+    pub ascii_printable: &'static [u8],
+    pub ascii_whitespace: &'static [u8],
+    pub ascii_other: &'static [u8],
+    pub nonascii: &'static [u8],
+    pub offset: &'static [u8],
+    /// The color of the marker of squeezed lines.
+    pub squeeze: &'static [u8],
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            null: COLOR_NULL,
+            ascii_printable: COLOR_ASCII_PRINTABLE,
+            ascii_whitespace: COLOR_ASCII_WHITESPACE,
+            ascii_other: COLOR_ASCII_OTHER,
+            nonascii: COLOR_NONASCII,
+            offset: COLOR_OFFSET,
+            squeeze: COLOR_OFFSET,
+        }
+    }
+}
+
+impl Theme {
+    /// Returns the color of the bytes in `category`.
+    pub fn category_color(&self, category: ByteCategory) -> &'static [u8] {
+        match category {
+            ByteCategory::Null => self.null,
+            ByteCategory::AsciiPrintable => self.ascii_printable,
+            ByteCategory::AsciiWhitespace => self.ascii_whitespace,
+            ByteCategory::AsciiOther => self.ascii_other,
+            ByteCategory::NonAscii => self.nonascii,
+        }
+    }
+}
+
+/// Looks up a foreground color by its name (e.g. `red` or `bright-blue`), or
+/// by the name of a byte category (`null`, `printable`, `whitespace`, `other`,
+/// `nonascii` or `unreadable`) to use the color of that category.
+pub fn color_from_name(name: &str) -> Option<&'static [u8]> {
+    let color = match name {
+        "black" => colors::Black::ANSI_FG,
+        "red" => colors::Red::ANSI_FG,
+        "green" => colors::Green::ANSI_FG,
+        "yellow" => colors::Yellow::ANSI_FG,
+        "blue" => colors::Blue::ANSI_FG,
+        "magenta" => colors::Magenta::ANSI_FG,
+        "cyan" => colors::Cyan::ANSI_FG,
+        "white" => colors::White::ANSI_FG,
+        "bright-black" => colors::BrightBlack::ANSI_FG,
+        "bright-red" => colors::BrightRed::ANSI_FG,
+        "bright-green" => colors::BrightGreen::ANSI_FG,
+        "bright-yellow" => colors::BrightYellow::ANSI_FG,
+        "bright-blue" => colors::BrightBlue::ANSI_FG,
+        "bright-magenta" => colors::BrightMagenta::ANSI_FG,
+        "bright-cyan" => colors::BrightCyan::ANSI_FG,
+        "bright-white" => colors::BrightWhite::ANSI_FG,
+        "null" => return Some(COLOR_NULL),
+        "printable" => return Some(COLOR_ASCII_PRINTABLE),
+        "whitespace" => return Some(COLOR_ASCII_WHITESPACE),
+        "other" => return Some(COLOR_ASCII_OTHER),
+        "nonascii" => return Some(COLOR_NONASCII),
+        "unreadable" => return Some(COLOR_UNREADABLE),
+        _ => return None,
+    };
+    Some(color.as_bytes())
+}
 
 #[rustfmt::skip]
 pub const CP437: [char; 256] = [
@@ -47,6 +127,31 @@ pub const CP437: [char; 256] = [
     '≡','±','≥','≤','⌠','⌡','÷','≈','°','∙','·','√','ⁿ','²','■','ﬀ',
 ];
 
+/// ISO-8859-1 (Latin-1), with the placeholders of
+/// [`CharacterTable::Default`](crate::CharacterTable::Default) for the ASCII
+/// control characters and for the C1 control characters 0x80-0x9f. The
+/// no-break space is shown as '_' like other whitespace, and the soft hyphen,
+/// which is usually invisible, as '-'.
+#[rustfmt::skip]
+pub const LATIN1: [char; 256] = [
+    '⋄','•','•','•','•','•','•','•','•','_','_','•','_','_','•','•',
+    '•','•','•','•','•','•','•','•','•','•','•','•','•','•','•','•',
+    ' ','!','"','#','$','%','&','\'','(',')','*','+',',','-','.','/',
+    '0','1','2','3','4','5','6','7','8','9',':',';','<','=','>','?',
+    '@','A','B','C','D','E','F','G','H','I','J','K','L','M','N','O',
+    'P','Q','R','S','T','U','V','W','X','Y','Z','[','\\',']','^','_',
+    '`','a','b','c','d','e','f','g','h','i','j','k','l','m','n','o',
+    'p','q','r','s','t','u','v','w','x','y','z','{','|','}','~','•',
+    '•','•','•','•','•','•','•','•','•','•','•','•','•','•','•','•',
+    '•','•','•','•','•','•','•','•','•','•','•','•','•','•','•','•',
+    '_','¡','¢','£','¤','¥','¦','§','¨','©','ª','«','¬','-','®','¯',
+    '°','±','²','³','´','µ','¶','·','¸','¹','º','»','¼','½','¾','¿',
+    'À','Á','Â','Ã','Ä','Å','Æ','Ç','È','É','Ê','Ë','Ì','Í','Î','Ï',
+    'Ð','Ñ','Ò','Ó','Ô','Õ','Ö','×','Ø','Ù','Ú','Û','Ü','Ý','Þ','ß',
+    'à','á','â','ã','ä','å','æ','ç','è','é','ê','ë','ì','í','î','ï',
+    'ð','ñ','ò','ó','ô','õ','ö','÷','ø','ù','ú','û','ü','ý','þ','ÿ',
+];
+
 #[rustfmt::skip]
 pub const CP1047: [char; 256] = [
      //
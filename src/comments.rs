@@ -0,0 +1,34 @@
+//! Reading the comments for `--comments` from a file with one `offset<TAB>text`
+//! line per comment, e.g. the notes of an earlier analysis.
+
+use anyhow::{anyhow, Context, Result};
+
+use hexyl::Comment;
+
+use crate::csv_ranges::parse_number;
+
+/// Parses the comments of a file. Offsets are decimal or hex (with a `0x`
+/// prefix), and the text is everything after the first tab. Empty lines and
+/// lines starting with `#` are ignored.
+pub(crate) fn parse_comments(text: &str) -> Result<Vec<Comment>> {
+    let mut comments = vec![];
+    for (number, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let comment = parse_line(line).with_context(|| format!("line {}", number + 1))?;
+        comments.push(comment);
+    }
+    Ok(comments)
+}
+
+fn parse_line(line: &str) -> Result<Comment> {
+    let (offset, text) = line
+        .split_once('\t')
+        .ok_or_else(|| anyhow!("expected an offset and a comment separated by a tab"))?;
+    let position = parse_number(offset.trim()).context("invalid offset")?;
+    Ok(Comment {
+        position,
+        text: text.trim().to_owned(),
+    })
+}
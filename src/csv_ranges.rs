@@ -0,0 +1,107 @@
+//! Reading the regions for `--csv-ranges` from a CSV file with the columns
+//! `offset,length,label,color`, e.g. as exported by other analysis tools.
+
+use anyhow::{anyhow, Context, Result};
+
+use hexyl::{color_from_name, Highlight, COLOR_HIGHLIGHT};
+
+/// Parses the regions of a CSV file. Offsets and lengths are decimal or hex
+/// (with a `0x` prefix). The label and the color are optional, the default
+/// color is the one of `--filter-byte`. Empty lines, lines starting with `#`
+/// and a header line starting with `offset` are ignored.
+pub(crate) fn parse_csv_ranges(text: &str) -> Result<Vec<Highlight>> {
+    let mut highlights = vec![];
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields = split_fields(line).with_context(|| format!("line {}", number + 1))?;
+        if highlights.is_empty() && fields[0].eq_ignore_ascii_case("offset") {
+            continue;
+        }
+        let highlight = parse_fields(&fields).with_context(|| format!("line {}", number + 1))?;
+        highlights.push(highlight);
+    }
+    Ok(highlights)
+}
+
+fn parse_fields(fields: &[String]) -> Result<Highlight> {
+    let [offset, length, rest @ ..] = fields else {
+        return Err(anyhow!("expected at least an offset and a length"));
+    };
+    if rest.len() > 2 {
+        return Err(anyhow!(
+            "expected at most four fields (offset, length, label, color)"
+        ));
+    }
+    let start = parse_number(offset).context("invalid offset")?;
+    let length = parse_number(length).context("invalid length")?;
+    let end = start
+        .checked_add(length)
+        .ok_or_else(|| anyhow!("the region ends beyond the largest possible offset"))?;
+    let label = rest.first().filter(|label| !label.is_empty()).cloned();
+    let color = match rest.get(1).map(|color| color.as_str()) {
+        None | Some("") => COLOR_HIGHLIGHT,
+        Some(color) => color_from_name(color).ok_or_else(|| anyhow!("unknown color {color:?}"))?,
+    };
+    Ok(Highlight {
+        range: start..end,
+        color,
+        label,
+    })
+}
+
+pub(crate) fn parse_number(n: &str) -> Result<u64> {
+    let result = match n.strip_prefix("0x").or_else(|| n.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => n.parse(),
+    };
+    result.map_err(|_| anyhow!("{n:?} is not a number"))
+}
+
+/// Splits a line into its fields, which may be quoted with `"` to contain
+/// commas. A quote inside a quoted field is written as `""`.
+fn split_fields(line: &str) -> Result<Vec<String>> {
+    let mut fields = vec![];
+    let mut chars = line.chars().peekable();
+    loop {
+        let mut field = String::new();
+        while chars.peek() == Some(&' ') {
+            chars.next();
+        }
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('"') if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    }
+                    Some('"') => break,
+                    Some(c) => field.push(c),
+                    None => return Err(anyhow!("unterminated quoted field")),
+                }
+            }
+            while chars.peek() == Some(&' ') {
+                chars.next();
+            }
+            if !matches!(chars.peek(), None | Some(',')) {
+                return Err(anyhow!("expected ',' after a quoted field"));
+            }
+        } else {
+            while let Some(&c) = chars.peek() {
+                if c == ',' {
+                    break;
+                }
+                field.push(c);
+                chars.next();
+            }
+            field.truncate(field.trim_end().len());
+        }
+        fields.push(field);
+        if chars.next().is_none() {
+            return Ok(fields);
+        }
+    }
+}
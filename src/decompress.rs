@@ -0,0 +1,210 @@
+//! Decompressing gzip and zstd input for `--decompress`, one member (gzip) or
+//! frame (zstd) at a time.
+
+use std::fmt;
+use std::io::{self, BufRead, Read};
+
+use anyhow::{anyhow, Context, Result};
+
+use flate2::bufread::GzDecoder;
+use ruzstd::StreamingDecoder;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Format {
+    Gzip,
+    Zstd,
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Format::Gzip => "gzip member",
+            Format::Zstd => "zstd frame",
+        })
+    }
+}
+
+/// A gzip member or a zstd frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Member {
+    /// The number of the member, starting at 1.
+    pub number: u64,
+    pub format: Format,
+    /// The position of the member in the compressed input.
+    pub compressed_offset: u64,
+    /// The position of the first decompressed byte of the member, counted
+    /// over all members.
+    pub offset: u64,
+}
+
+/// Calls `dump` with every member of the compressed `reader` and a reader for
+/// its decompressed contents. The rest of a member that `dump` does not read
+/// is skipped. Zstd skippable frames are left out.
+pub(crate) fn for_each_member(
+    reader: impl Read,
+    mut dump: impl FnMut(&Member, &mut dyn Read) -> Result<()>,
+) -> Result<()> {
+    let mut reader = PeekReader::new(reader);
+    let mut number = 0;
+    let mut offset = 0;
+    loop {
+        let compressed_offset = reader.position;
+        let magic = reader.peek(ZSTD_MAGIC.len())?.to_vec();
+        if magic.is_empty() {
+            return Ok(());
+        }
+
+        let format = if magic.starts_with(&GZIP_MAGIC) {
+            Format::Gzip
+        } else if magic == ZSTD_MAGIC {
+            Format::Zstd
+        } else if is_skippable_frame(&magic) {
+            skip_frame(&mut reader)
+                .with_context(|| format!("invalid zstd frame at offset {compressed_offset:#x}"))?;
+            continue;
+        } else {
+            return Err(anyhow!(
+                "the input at offset {compressed_offset:#x} is neither gzip nor zstd compressed"
+            ));
+        };
+
+        number += 1;
+        let member = Member {
+            number,
+            format,
+            compressed_offset,
+            offset,
+        };
+        let context = || format!("failed to decompress {format} {number}");
+        let mut decoder: Box<dyn Read> = match format {
+            Format::Gzip => Box::new(GzDecoder::new(&mut reader)),
+            Format::Zstd => Box::new(
+                StreamingDecoder::new(&mut reader)
+                    .map_err(|err| anyhow!("{err}"))
+                    .with_context(context)?,
+            ),
+        };
+        let mut decoder = CountingReader {
+            inner: &mut decoder,
+            count: 0,
+        };
+        dump(&member, &mut decoder)?;
+        io::copy(&mut decoder, &mut io::sink()).with_context(context)?;
+        offset += decoder.count;
+    }
+}
+
+/// Zstd skippable frames start with 0x184d2a50 to 0x184d2a5f (little endian).
+fn is_skippable_frame(magic: &[u8]) -> bool {
+    matches!(magic, [low, 0x2a, 0x4d, 0x18] if low & 0xf0 == 0x50)
+}
+
+/// Counts the bytes read from the inner reader.
+struct CountingReader<R> {
+    inner: R,
+    count: u64,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+}
+
+/// A buffered reader that can look ahead at the start of the next member.
+struct PeekReader<R> {
+    inner: R,
+    buf: Vec<u8>,
+    /// The start of the unread part of `buf`.
+    start: usize,
+    /// The number of bytes that have been consumed.
+    position: u64,
+}
+
+impl<R: Read> PeekReader<R> {
+    const CAPACITY: usize = 8 * 1024;
+
+    fn new(inner: R) -> Self {
+        PeekReader {
+            inner,
+            buf: vec![],
+            start: 0,
+            position: 0,
+        }
+    }
+
+    /// Returns the next `len` bytes without consuming them, or fewer if the
+    /// input ends before.
+    fn peek(&mut self, len: usize) -> io::Result<&[u8]> {
+        while self.buf.len() - self.start < len {
+            self.buf.drain(..self.start);
+            self.start = 0;
+            let filled = self.buf.len();
+            self.buf.resize(Self::CAPACITY.max(len), 0);
+            let n = loop {
+                match self.inner.read(&mut self.buf[filled..]) {
+                    Ok(n) => break n,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => {
+                        self.buf.truncate(filled);
+                        return Err(e);
+                    }
+                }
+            };
+            self.buf.truncate(filled + n);
+            if n == 0 {
+                break;
+            }
+        }
+        let end = self.buf.len().min(self.start + len);
+        Ok(&self.buf[self.start..end])
+    }
+}
+
+impl<R: Read> Read for PeekReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: Read> BufRead for PeekReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.start == self.buf.len() {
+            self.start = 0;
+            self.buf.clear();
+        }
+        self.peek(1)?;
+        Ok(&self.buf[self.start..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.start += amt;
+        self.position += amt as u64;
+    }
+}
+
+/// Skips a zstd skippable frame, which consists of the magic number, the
+/// length of the contents (32 bits, little endian) and the contents.
+fn skip_frame(reader: &mut impl Read) -> Result<()> {
+    let mut header = [0; 8];
+    reader
+        .read_exact(&mut header)
+        .context("the skippable frame is truncated")?;
+    let length = u64::from(u32::from_le_bytes([
+        header[4], header[5], header[6], header[7],
+    ]));
+    let skipped = io::copy(&mut reader.take(length), &mut io::sink())?;
+    if skipped < length {
+        return Err(anyhow!("the skippable frame is truncated"));
+    }
+    Ok(())
+}
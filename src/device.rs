@@ -0,0 +1,149 @@
+//! The logical sector size of disk devices, for `--align-device-reads`. Raw
+//! devices on some platforms reject reads that do not start and end at a
+//! sector boundary. Files opened for direct I/O with `--direct-io`, which
+//! bypasses the page cache on Linux, have the same restriction.
+
+use std::fs::File;
+#[cfg(target_os = "linux")]
+use std::io::{self, Read, Seek};
+#[cfg(target_os = "linux")]
+use std::path::Path;
+
+#[cfg(target_os = "linux")]
+use hexyl::AlignedReader;
+
+/// The alignment of the reads from files opened for direct I/O, unless their
+/// logical sector size is larger. It is a multiple of the block size of
+/// common devices and file systems.
+pub(crate) const DIRECT_IO_ALIGNMENT: u64 = 4096;
+
+/// Returns the logical sector size of `file` if it is a disk device, or `None`
+/// for other files and on platforms where it is not known.
+#[cfg(target_os = "linux")]
+pub(crate) fn logical_sector_size(file: &File) -> Option<u64> {
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::io::AsRawFd;
+
+    if !file.metadata().ok()?.file_type().is_block_device() {
+        return None;
+    }
+    let mut size: libc::c_int = 0;
+    // SAFETY: `BLKSSZGET` writes an `int` to the given pointer.
+    let result = unsafe { libc::ioctl(file.as_raw_fd(), libc::BLKSSZGET, &mut size) };
+    (result == 0 && size > 0).then_some(size as u64)
+}
+
+/// Returns the logical sector size of `file` if it is a disk device, or `None`
+/// for other files and on platforms where it is not known.
+#[cfg(target_os = "macos")]
+pub(crate) fn logical_sector_size(file: &File) -> Option<u64> {
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::io::AsRawFd;
+
+    /// `_IOR('d', 24, uint32_t)` from `<sys/disk.h>`.
+    const DKIOCGETBLOCKSIZE: libc::c_ulong = 0x4004_6418;
+
+    let file_type = file.metadata().ok()?.file_type();
+    if !file_type.is_block_device() && !file_type.is_char_device() {
+        return None;
+    }
+    let mut size: u32 = 0;
+    // SAFETY: `DKIOCGETBLOCKSIZE` writes a `uint32_t` to the given pointer.
+    let result = unsafe { libc::ioctl(file.as_raw_fd(), DKIOCGETBLOCKSIZE, &mut size) };
+    (result == 0 && size > 0).then_some(u64::from(size))
+}
+
+/// Returns the logical sector size of `file` if it is a disk device, or `None`
+/// for other files and on platforms where it is not known.
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub(crate) fn logical_sector_size(_file: &File) -> Option<u64> {
+    None
+}
+
+/// Returns whether reading `length` bytes (or up to the end) from `position`
+/// has to be aligned to sectors of `sector_size` bytes.
+pub(crate) fn is_unaligned(sector_size: u64, position: u64, length: Option<u64>) -> bool {
+    position % sector_size != 0 || length.is_some_and(|length| length % sector_size != 0)
+}
+
+/// Returns the alignment of the reads from a file opened for direct I/O, with
+/// the logical sector size of the file if it is a disk device.
+pub(crate) fn direct_io_alignment(sector_size: Option<u64>) -> u64 {
+    sector_size.map_or(DIRECT_IO_ALIGNMENT, |sector_size| {
+        sector_size.max(DIRECT_IO_ALIGNMENT)
+    })
+}
+
+/// Opens `path` for reading with `O_DIRECT`, so that the reads bypass the
+/// page cache. If the file system rejects direct I/O, or the file is neither
+/// a regular file nor a disk device, the file is opened for normal reads,
+/// which [`is_direct`] tells.
+#[cfg(target_os = "linux")]
+pub(crate) fn open_direct(path: &Path) -> io::Result<File> {
+    use std::fs::{self, OpenOptions};
+    use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+
+    // Opening a pipe twice would lose the data of the first open.
+    let file_type = fs::metadata(path)?.file_type();
+    if !file_type.is_file() && !file_type.is_block_device() {
+        return File::open(path);
+    }
+    let file = match OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_DIRECT)
+        .open(path)
+    {
+        Ok(file) => file,
+        Err(e) if e.raw_os_error() == Some(libc::EINVAL) => return File::open(path),
+        Err(e) => return Err(e),
+    };
+    // Some file systems accept the flag, but reject the reads, e.g. if their
+    // blocks are larger than the alignment.
+    let alignment = direct_io_alignment(logical_sector_size(&file));
+    let probe = AlignedReader::new(&file, alignment)?.read(&mut [0]);
+    (&file).rewind()?;
+    if let Err(e) = probe {
+        if e.raw_os_error() == Some(libc::EINVAL) {
+            clear_direct(&file)?;
+        }
+    }
+    Ok(file)
+}
+
+/// Returns whether `file` has been opened for direct I/O.
+#[cfg(target_os = "linux")]
+pub(crate) fn is_direct(file: &File) -> bool {
+    use std::os::unix::io::AsRawFd;
+
+    // SAFETY: `F_GETFL` only reads the flags of the open file.
+    let flags = unsafe { libc::fcntl(file.as_raw_fd(), libc::F_GETFL) };
+    flags != -1 && flags & libc::O_DIRECT != 0
+}
+
+/// Returns whether `file` has been opened for direct I/O.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn is_direct(_file: &File) -> bool {
+    false
+}
+
+/// Switches `file` from direct I/O to normal reads through the page cache.
+#[cfg(target_os = "linux")]
+fn clear_direct(file: &File) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let fd = file.as_raw_fd();
+    // SAFETY: `F_GETFL` and `F_SETFL` only read and change the flags of the
+    // open file.
+    let result = unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFL);
+        if flags == -1 {
+            flags
+        } else {
+            libc::fcntl(fd, libc::F_SETFL, flags & !libc::O_DIRECT)
+        }
+    };
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
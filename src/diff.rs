@@ -0,0 +1,405 @@
+//! A side-by-side comparison of two inputs, as an alternative to the hex dump.
+//!
+//! Every line shows the bytes at the same position of both inputs: the hex and
+//! character panels of the left input, followed by the ones of the right
+//! input. Bytes that differ are shown in inverse video, and the separator
+//! between the two sides is replaced by `≠` (`!` with ASCII borders) on lines
+//! with differences, so that they can be found without colors, too. After the
+//! end of the shorter input, its side stays empty and all bytes of the other
+//! side count as differences.
+//!
+//! ```text
+//! ┌────────┬─[a.bin]─────────────────┬────────┬─[b.bin]─────────────────┬────────┐
+//! │00000000│ 7f 45 4c 46 02 01 01 00 │•ELF•••⋄≠ 7f 45 4c 46 02 01 01 01 │•ELF••••│
+//! │00000008│ 00 00 00 00 00 00 00 00 │⋄⋄⋄⋄⋄⋄⋄⋄│ 00 00 00 00 00 00 00 00 │⋄⋄⋄⋄⋄⋄⋄⋄│
+//! │*       │                         │        │                         │        │
+//! │00000020│ 02 00                   │•⋄      ≠                         │        │
+//! └────────┴─────────────────────────┴────────┴─────────────────────────┴────────┘
+//! ```
+//!
+//! Lines are squeezed like by the [`Printer`](crate::Printer) if both sides
+//! repeat the previous line, i.e. each of them consists of a single repeated
+//! byte, or is past the end of its input.
+
+use std::io::{self, BufReader, Read, Write};
+
+use crate::squeezer::SqueezeAction;
+use crate::{
+    layout_width, single_column, BorderElements, BorderStyle, Byte, CharacterTable, OffsetStyle,
+    Theme, COLOR_RESET, DEFAULT_MAX_POSITION,
+};
+
+/// Starts the inverse video of differing bytes.
+const INVERSE: &[u8] = b"\x1b[7m";
+/// Ends the inverse video of differing bytes.
+const NO_INVERSE: &[u8] = b"\x1b[27m";
+
+/// The settings of [`write_diff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffOptions {
+    pub show_color: bool,
+    pub border_style: BorderStyle,
+    pub squeeze: bool,
+    /// The number of panels of eight bytes on each side, at least one.
+    pub panels: u64,
+    pub character_table: CharacterTable,
+    /// The largest position that is shown, which determines the width of the
+    /// position panel.
+    pub max_position: u64,
+    /// The names of the left and the right input, which are shown in the top
+    /// border above their sides if they fit.
+    pub names: Option<(String, String)>,
+    pub theme: Theme,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            show_color: true,
+            border_style: BorderStyle::Unicode,
+            squeeze: true,
+            panels: 1,
+            character_table: CharacterTable::Default,
+            max_position: DEFAULT_MAX_POSITION,
+            names: None,
+            theme: Theme::default(),
+        }
+    }
+}
+
+impl DiffOptions {
+    /// The number of columns of a line of the comparison.
+    pub fn layout_width(&self) -> usize {
+        layout_width(&self.panel_widths())
+    }
+
+    fn position_width(&self) -> usize {
+        OffsetStyle::Byte.width(self.max_position)
+    }
+
+    /// The widths of the panels of one side: the hex panels, followed by the
+    /// character panels.
+    fn side_widths(&self) -> Vec<usize> {
+        let panels = self.panels as usize;
+        let mut widths = vec![1 + 3 * 8; panels];
+        widths.extend(std::iter::repeat(8).take(panels));
+        widths
+    }
+
+    fn panel_widths(&self) -> Vec<usize> {
+        let mut widths = vec![self.position_width()];
+        widths.extend(self.side_widths());
+        widths.extend(self.side_widths());
+        widths
+    }
+}
+
+/// The result of a comparison by [`write_diff`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DiffSummary {
+    /// The number of positions at which the inputs differ, including the
+    /// bytes of the longer input after the end of the shorter one.
+    pub differences: u64,
+    /// The first position at which the inputs differ.
+    pub first_difference: Option<u64>,
+    /// The number of bytes of the left input.
+    pub left_len: u64,
+    /// The number of bytes of the right input.
+    pub right_len: u64,
+}
+
+impl DiffSummary {
+    /// Returns true if both inputs have the same bytes.
+    pub fn is_identical(&self) -> bool {
+        self.differences == 0
+    }
+}
+
+/// Writes the side-by-side comparison of `left` and `right` to `writer`, see
+/// the [module documentation](self), and returns what differs.
+///
+/// # Panics
+///
+/// Panics if `options.panels` is zero.
+pub fn write_diff<L: Read, R: Read, W: Write + ?Sized>(
+    left: L,
+    right: R,
+    writer: &mut W,
+    options: &DiffOptions,
+) -> io::Result<DiffSummary> {
+    assert!(options.panels > 0, "at least one panel is required");
+    let line_size = 8 * options.panels as usize;
+    let mut left = BufReader::new(left);
+    let mut right = BufReader::new(right);
+    let mut left_line = vec![0; line_size];
+    let mut right_line = vec![0; line_size];
+
+    let mut diff = DiffWriter {
+        writer,
+        options,
+        summary: DiffSummary::default(),
+        last_action: SqueezeAction::Ignore,
+        repeated: None,
+        curr_color: None,
+    };
+    diff.print_border(options.border_style.header_elems(), true)?;
+    let mut position = 0;
+    loop {
+        let left_len = read_line(&mut left, &mut left_line)?;
+        let right_len = read_line(&mut right, &mut right_line)?;
+        if left_len == 0 && right_len == 0 {
+            break;
+        }
+        diff.print_line(position, &left_line[..left_len], &right_line[..right_len])?;
+        position += line_size as u64;
+    }
+    diff.finish(position)?;
+    Ok(diff.summary)
+}
+
+/// Fills `buf` from `reader` as far as possible and returns the number of
+/// bytes, which is only less than the length of `buf` at the end of the input.
+fn read_line<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut len = 0;
+    while len < buf.len() {
+        match reader.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(len)
+}
+
+/// What a side of a line repeats, if the line can be squeezed: a single
+/// repeated byte of a complete line, or `None` after the end of the input.
+fn repeated_byte(line: &[u8], line_size: usize) -> Option<Option<u8>> {
+    match line.first() {
+        None => Some(None),
+        Some(&first) if line.len() == line_size && line.iter().all(|&b| b == first) => {
+            Some(Some(first))
+        }
+        Some(_) => None,
+    }
+}
+
+struct DiffWriter<'a, W: Write + ?Sized> {
+    writer: &'a mut W,
+    options: &'a DiffOptions,
+    summary: DiffSummary,
+    /// The squeeze action for the previous line.
+    last_action: SqueezeAction,
+    /// What both sides of the previous line repeat, if it can be squeezed.
+    repeated: Option<(Option<u8>, Option<u8>)>,
+    curr_color: Option<&'static [u8]>,
+}
+
+impl<W: Write + ?Sized> DiffWriter<'_, W> {
+    /// Writes the top or bottom border. The top border shows the names of
+    /// the inputs, if they fit.
+    fn print_border(&mut self, elements: Option<BorderElements>, names: bool) -> io::Result<()> {
+        let Some(e) = elements else {
+            return Ok(());
+        };
+        let h = e.horizontal_line;
+        let mut border: Vec<char> = vec![e.left_corner];
+        for (i, width) in self.options.panel_widths().into_iter().enumerate() {
+            if i > 0 {
+                border.push(e.column_separator);
+            }
+            border.extend(std::iter::repeat(h).take(width));
+        }
+        border.push(e.right_corner);
+
+        if let (true, Some((left, right))) = (names, &self.options.names) {
+            let side_width = layout_width(&self.options.side_widths()) - 2;
+            let left_start = 2 + self.options.position_width();
+            for (name, start) in [(left, left_start), (right, left_start + side_width + 1)] {
+                // Keep a horizontal line on both sides of the name.
+                let legend: Vec<char> = format!("[{name}]").chars().map(single_column).collect();
+                if legend.len() + 2 <= side_width {
+                    border[start + 1..][..legend.len()].copy_from_slice(&legend);
+                }
+            }
+        }
+
+        let border: String = border.into_iter().collect();
+        writeln!(self.writer, "{border}")
+    }
+
+    fn print_line(&mut self, position: u64, left: &[u8], right: &[u8]) -> io::Result<()> {
+        let line_size = 8 * self.options.panels as usize;
+        let mut differs = false;
+        for i in 0..left.len().max(right.len()) {
+            if left.get(i) != right.get(i) {
+                differs = true;
+                self.summary.differences += 1;
+                self.summary
+                    .first_difference
+                    .get_or_insert(position + i as u64);
+            }
+        }
+        self.summary.left_len += left.len() as u64;
+        self.summary.right_len += right.len() as u64;
+
+        let repeated = repeated_byte(left, line_size).zip(repeated_byte(right, line_size));
+        let action = match self.last_action {
+            _ if !self.options.squeeze || repeated.is_none() || repeated != self.repeated => {
+                SqueezeAction::Ignore
+            }
+            SqueezeAction::Ignore => SqueezeAction::Print,
+            SqueezeAction::Print | SqueezeAction::Delete => SqueezeAction::Delete,
+        };
+        if action == SqueezeAction::Ignore {
+            self.repeated = repeated;
+        }
+        self.last_action = action;
+
+        match action {
+            SqueezeAction::Ignore => self.print_row(Some(position), left, right, differs),
+            SqueezeAction::Print => self.print_row(None, &[], &[], differs),
+            SqueezeAction::Delete => Ok(()),
+        }
+    }
+
+    /// Prints the line at the end of the input, which marks the end of a
+    /// squeezed region, and the bottom border.
+    fn finish(&mut self, position: u64) -> io::Result<()> {
+        if self.last_action != SqueezeAction::Ignore {
+            self.print_row(Some(position), &[], &[], false)?;
+        } else if position == 0 {
+            self.print_empty_row()?;
+        }
+        self.print_border(self.options.border_style.footer_elems(), false)?;
+        self.writer.flush()
+    }
+
+    /// Prints a row with the bytes of both sides, or a squeeze marker
+    /// without a `position`.
+    fn print_row(
+        &mut self,
+        position: Option<u64>,
+        left: &[u8],
+        right: &[u8],
+        differs: bool,
+    ) -> io::Result<()> {
+        let outer_sep = self.options.border_style.outer_sep();
+        let width = self.options.position_width();
+        write!(self.writer, "{outer_sep}")?;
+        if self.options.show_color {
+            let color = match position {
+                Some(_) => self.options.theme.offset,
+                None => self.options.theme.squeeze,
+            };
+            self.writer.write_all(color)?;
+        }
+        match position {
+            Some(position) => write!(self.writer, "{position:0width$x}")?,
+            None => write!(self.writer, "{:width$}", "*")?,
+        }
+        if self.options.show_color {
+            self.writer.write_all(COLOR_RESET)?;
+        }
+        write!(self.writer, "{outer_sep}")?;
+
+        self.print_side(left, right)?;
+        let middle_sep = match (differs, self.options.border_style) {
+            (false, border_style) => border_style.outer_sep(),
+            (true, BorderStyle::Ascii) => '!',
+            (true, _) => '≠',
+        };
+        write!(self.writer, "{middle_sep}")?;
+        self.print_side(right, left)?;
+        writeln!(self.writer, "{outer_sep}")
+    }
+
+    /// Prints the hex and character panels of `bytes`, without the separator
+    /// after the last panel. Bytes that differ from the ones in `other` are
+    /// highlighted.
+    fn print_side(&mut self, bytes: &[u8], other: &[u8]) -> io::Result<()> {
+        let inner_sep = self.options.border_style.inner_sep();
+        let outer_sep = self.options.border_style.outer_sep();
+        let panels = self.options.panels as usize;
+
+        for panel in 0..panels {
+            self.writer.write_all(b" ")?;
+            for i in 8 * panel..8 * (panel + 1) {
+                match bytes.get(i) {
+                    Some(&b) => {
+                        let differs = other.get(i) != Some(&b);
+                        self.print_cell(b, differs, |b| format!("{b:02x}"))?;
+                    }
+                    None => self.writer.write_all(b"  ")?,
+                }
+                self.writer.write_all(b" ")?;
+            }
+            self.end_panel()?;
+            let sep = if panel + 1 < panels {
+                inner_sep
+            } else {
+                outer_sep
+            };
+            write!(self.writer, "{sep}")?;
+        }
+
+        let character_table = self.options.character_table;
+        for panel in 0..panels {
+            for i in 8 * panel..8 * (panel + 1) {
+                match bytes.get(i) {
+                    Some(&b) => {
+                        let differs = other.get(i) != Some(&b);
+                        self.print_cell(b, differs, |b| {
+                            single_column(Byte(b).as_char(character_table)).to_string()
+                        })?;
+                    }
+                    None => self.writer.write_all(b" ")?,
+                }
+            }
+            self.end_panel()?;
+            if panel + 1 < panels {
+                write!(self.writer, "{inner_sep}")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the text of a byte in the color of its category, and in inverse
+    /// video if it `differs`.
+    fn print_cell(&mut self, b: u8, differs: bool, text: impl Fn(u8) -> String) -> io::Result<()> {
+        if !self.options.show_color {
+            return self.writer.write_all(text(b).as_bytes());
+        }
+        let color = self.options.theme.category_color(Byte(b).category());
+        if self.curr_color != Some(color) {
+            self.writer.write_all(color)?;
+            self.curr_color = Some(color);
+        }
+        if differs {
+            self.writer.write_all(INVERSE)?;
+        }
+        self.writer.write_all(text(b).as_bytes())?;
+        if differs {
+            self.writer.write_all(NO_INVERSE)?;
+        }
+        Ok(())
+    }
+
+    fn end_panel(&mut self) -> io::Result<()> {
+        if self.curr_color.take().is_some() {
+            self.writer.write_all(COLOR_RESET)?;
+        }
+        Ok(())
+    }
+
+    /// Prints the row for two empty inputs.
+    fn print_empty_row(&mut self) -> io::Result<()> {
+        let outer_sep = self.options.border_style.outer_sep();
+        write!(self.writer, "{outer_sep}")?;
+        for (i, width) in self.options.panel_widths().into_iter().enumerate() {
+            let text = if i == 1 { " No content" } else { "" };
+            write!(self.writer, "{text:width$}{outer_sep}")?;
+        }
+        writeln!(self.writer)
+    }
+}
@@ -0,0 +1,252 @@
+//! The fields of a struct from the DWARF debug information of a binary, for
+//! `--dwarf BINARY --type NAME --at ADDRESS`.
+
+use std::borrow::Cow;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use gimli::{AttributeValue, DwAt, EndianSlice, RunTimeEndian, Unit, UnitOffset};
+use object::{Object, ObjectSection};
+
+use hexyl::{color_from_name, Highlight};
+
+type Reader<'a> = EndianSlice<'a, RunTimeEndian>;
+
+/// The colors of the fields, one after another.
+const FIELD_COLORS: [&str; 4] = [
+    "bright-blue",
+    "bright-green",
+    "bright-magenta",
+    "bright-cyan",
+];
+
+/// A field of a struct, with nested structs flattened into their fields.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Field {
+    /// The name of the field, e.g. `origin.x` for a field of a nested struct.
+    pub name: String,
+    /// The offset from the start of the struct.
+    pub offset: u64,
+    pub size: u64,
+}
+
+/// Looks up the struct, union or class `type_name` in the debug information
+/// of `binary`, which may also be a separate debug file, and returns its
+/// fields ordered by their offsets. Typedefs of the type can be used as well.
+pub(crate) fn struct_fields(binary: &Path, type_name: &str) -> Result<Vec<Field>> {
+    let data = fs::read(binary).with_context(|| format!("failed to read {}", binary.display()))?;
+    let file = object::File::parse(&*data)
+        .with_context(|| format!("failed to parse {}", binary.display()))?;
+    let endian = if file.is_little_endian() {
+        RunTimeEndian::Little
+    } else {
+        RunTimeEndian::Big
+    };
+    let sections = gimli::DwarfSections::load(|id| -> Result<Cow<[u8]>> {
+        match file.section_by_name(id.name()) {
+            Some(section) => Ok(section.uncompressed_data()?),
+            None => Ok(Cow::Borrowed(&[])),
+        }
+    })?;
+    let dwarf = sections.borrow(|section| EndianSlice::new(section, endian));
+
+    let mut units = dwarf.units();
+    while let Some(header) = units.next()? {
+        let unit = dwarf.unit(header)?;
+        let mut entries = unit.entries();
+        while let Some((_, entry)) = entries.next_dfs()? {
+            let is_type = matches!(
+                entry.tag(),
+                gimli::DW_TAG_structure_type
+                    | gimli::DW_TAG_union_type
+                    | gimli::DW_TAG_class_type
+                    | gimli::DW_TAG_typedef
+            );
+            if !is_type || entry.attr(gimli::DW_AT_declaration)?.is_some() {
+                continue;
+            }
+            let Some(name) = entry.attr_value(gimli::DW_AT_name)? else {
+                continue;
+            };
+            if dwarf.attr_string(&unit, name)?.slice() != type_name.as_bytes() {
+                continue;
+            }
+            let offset = resolve_typedefs(&unit, entry.offset())?;
+            if !is_aggregate(&unit, offset)? {
+                return Err(anyhow!("`{type_name}` is not a struct, union or class"));
+            }
+            let mut fields = vec![];
+            collect_fields(&dwarf, &unit, offset, "", 0, &mut fields)?;
+            fields.sort_by_key(|field| field.offset);
+            return Ok(fields);
+        }
+    }
+    Err(anyhow!(
+        "no type `{type_name}` in the debug information of {}",
+        binary.display()
+    ))
+}
+
+/// Returns the labeled regions of the fields of a struct at `address`, with
+/// the size of every field in its label.
+pub(crate) fn highlights(fields: &[Field], address: u64) -> Vec<Highlight> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| Highlight {
+            range: address + field.offset..address + field.offset + field.size,
+            color: color_from_name(FIELD_COLORS[i % FIELD_COLORS.len()]).expect("known color"),
+            label: Some(match field.size {
+                1 => format!("{} (1 byte)", field.name),
+                size => format!("{} ({size} bytes)", field.name),
+            }),
+        })
+        .collect()
+}
+
+fn is_aggregate(unit: &Unit<Reader>, offset: UnitOffset) -> Result<bool> {
+    Ok(matches!(
+        unit.entry(offset)?.tag(),
+        gimli::DW_TAG_structure_type | gimli::DW_TAG_union_type | gimli::DW_TAG_class_type
+    ))
+}
+
+/// Follows typedefs and qualifiers like `const` to the actual type.
+fn resolve_typedefs(unit: &Unit<Reader>, mut offset: UnitOffset) -> Result<UnitOffset> {
+    loop {
+        let entry = unit.entry(offset)?;
+        match entry.tag() {
+            gimli::DW_TAG_typedef
+            | gimli::DW_TAG_const_type
+            | gimli::DW_TAG_volatile_type
+            | gimli::DW_TAG_restrict_type
+            | gimli::DW_TAG_atomic_type => match type_attr(unit, offset)? {
+                Some(target) => offset = target,
+                None => return Ok(offset),
+            },
+            _ => return Ok(offset),
+        }
+    }
+}
+
+/// Returns the `DW_AT_type` of an entry, if it refers to the same unit.
+fn type_attr(unit: &Unit<Reader>, offset: UnitOffset) -> Result<Option<UnitOffset>> {
+    Ok(match unit.entry(offset)?.attr_value(gimli::DW_AT_type)? {
+        Some(AttributeValue::UnitRef(target)) => Some(target),
+        _ => None,
+    })
+}
+
+fn udata_attr(unit: &Unit<Reader>, offset: UnitOffset, name: DwAt) -> Result<Option<u64>> {
+    Ok(unit
+        .entry(offset)?
+        .attr(name)?
+        .and_then(|attr| attr.udata_value()))
+}
+
+/// Returns the size of a type, or `None` if it is not known.
+fn type_size(unit: &Unit<Reader>, offset: UnitOffset) -> Result<Option<u64>> {
+    let offset = resolve_typedefs(unit, offset)?;
+    if let Some(size) = udata_attr(unit, offset, gimli::DW_AT_byte_size)? {
+        return Ok(Some(size));
+    }
+    if unit.entry(offset)?.tag() != gimli::DW_TAG_array_type {
+        return Ok(None);
+    }
+    let Some(element) = type_attr(unit, offset)? else {
+        return Ok(None);
+    };
+    let Some(mut size) = type_size(unit, element)? else {
+        return Ok(None);
+    };
+    let mut tree = unit.entries_tree(Some(offset))?;
+    let mut children = tree.root()?.children();
+    while let Some(child) = children.next()? {
+        let subrange = child.entry().offset();
+        if child.entry().tag() != gimli::DW_TAG_subrange_type {
+            continue;
+        }
+        let count = match udata_attr(unit, subrange, gimli::DW_AT_count)? {
+            Some(count) => count,
+            None => match udata_attr(unit, subrange, gimli::DW_AT_upper_bound)? {
+                Some(upper_bound) => upper_bound + 1,
+                None => return Ok(None),
+            },
+        };
+        size *= count;
+    }
+    Ok(Some(size))
+}
+
+/// Adds the members of the struct at `offset` to `fields`, with nested
+/// structs (but not unions) flattened into their members.
+fn collect_fields(
+    dwarf: &gimli::Dwarf<Reader>,
+    unit: &Unit<Reader>,
+    offset: UnitOffset,
+    prefix: &str,
+    base: u64,
+    fields: &mut Vec<Field>,
+) -> Result<()> {
+    let struct_size = udata_attr(unit, offset, gimli::DW_AT_byte_size)?.unwrap_or(0);
+    let mut members = vec![];
+    let mut tree = unit.entries_tree(Some(offset))?;
+    let mut children = tree.root()?.children();
+    while let Some(child) = children.next()? {
+        let entry = child.entry();
+        if entry.tag() != gimli::DW_TAG_member {
+            continue;
+        }
+        let name = match entry.attr_value(gimli::DW_AT_name)? {
+            Some(name) => dwarf
+                .attr_string(unit, name)?
+                .to_string_lossy()
+                .into_owned(),
+            None => "<anonymous>".to_owned(),
+        };
+        let member_offset = entry
+            .attr(gimli::DW_AT_data_member_location)?
+            .and_then(|attr| attr.udata_value())
+            .unwrap_or(0);
+        members.push((name, member_offset, type_attr(unit, entry.offset())?));
+    }
+
+    for (i, (name, member_offset, member_type)) in members.iter().enumerate() {
+        let name = format!("{prefix}{name}");
+        let size = match member_type {
+            Some(member_type) => type_size(unit, *member_type)?,
+            None => None,
+        };
+        // Without a known size, the field extends to the next one.
+        let size = size.unwrap_or_else(|| {
+            let end = members
+                .get(i + 1)
+                .map_or(struct_size, |(_, next_offset, _)| *next_offset);
+            end.saturating_sub(*member_offset)
+        });
+        let nested = match member_type {
+            Some(member_type) => {
+                let resolved = resolve_typedefs(unit, *member_type)?;
+                (unit.entry(resolved)?.tag() == gimli::DW_TAG_structure_type).then_some(resolved)
+            }
+            None => None,
+        };
+        match nested {
+            Some(nested) => collect_fields(
+                dwarf,
+                unit,
+                nested,
+                &format!("{name}."),
+                base + member_offset,
+                fields,
+            )?,
+            None => fields.push(Field {
+                name,
+                offset: base + member_offset,
+                size,
+            }),
+        }
+    }
+    Ok(())
+}
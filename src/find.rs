@@ -0,0 +1,177 @@
+use std::collections::VecDeque;
+use std::io;
+use std::mem;
+use std::ops::Range;
+
+/// A sequence of bytes to search for, see [`PrinterBuilder::find`]. A `None`
+/// is a wildcard that matches any byte.
+///
+/// [`PrinterBuilder::find`]: crate::PrinterBuilder::find
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FindPattern {
+    pub bytes: Vec<Option<u8>>,
+}
+
+impl FindPattern {
+    /// A pattern without wildcards that matches exactly `bytes`.
+    pub fn exact(bytes: &[u8]) -> Self {
+        FindPattern {
+            bytes: bytes.iter().copied().map(Some).collect(),
+        }
+    }
+
+    /// Returns whether the pattern matches the bytes at the end of `window`.
+    fn matches_end(&self, window: &VecDeque<u8>) -> bool {
+        let Some(start) = window.len().checked_sub(self.bytes.len()) else {
+            return false;
+        };
+        window
+            .range(start..)
+            .zip(&self.bytes)
+            .all(|(&b, expected)| expected.map_or(true, |expected| b == expected))
+    }
+}
+
+/// An occurrence of a [`FindPattern`], see [`Printer::find_matches`].
+///
+/// [`Printer::find_matches`]: crate::Printer::find_matches
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FindMatch {
+    /// The position (including the display offset) of the first byte.
+    pub position: u64,
+    /// The index of the pattern that matched.
+    pub pattern: usize,
+}
+
+/// Searches the patterns in the input of a [`Printer`](crate::Printer) as it
+/// is read. The last bytes are held back until it is known whether a match
+/// starts in them, so that a line is only printed once all matches that
+/// cover it have been found.
+pub(crate) struct Finder {
+    patterns: Vec<FindPattern>,
+    /// The length of the longest pattern.
+    max_len: usize,
+    /// The bytes that are held back, plus the byte that was pushed last.
+    window: VecDeque<u8>,
+    /// The index of the next byte in the input.
+    index: u64,
+    /// The indices of the matched bytes of the lines that have not been
+    /// printed yet, ordered by their end.
+    ranges: VecDeque<Range<u64>>,
+    matches: Vec<FindMatch>,
+    max_memory: u64,
+    /// The matches that have been found already, see
+    /// [`PrinterBuilder::found_matches`](crate::PrinterBuilder::found_matches).
+    found: Option<VecDeque<FindMatch>>,
+}
+
+impl Finder {
+    /// Returns `None` if there are no patterns. The patterns must not be
+    /// empty.
+    pub(crate) fn new(patterns: Vec<FindPattern>, max_memory: u64) -> Option<Self> {
+        let max_len = patterns.iter().map(|pattern| pattern.bytes.len()).max()?;
+        Some(Finder {
+            patterns,
+            max_len,
+            window: VecDeque::with_capacity(max_len),
+            index: 0,
+            ranges: VecDeque::new(),
+            matches: vec![],
+            max_memory,
+            found: None,
+        })
+    }
+
+    /// Takes the matches from `found` instead of searching the patterns. They
+    /// must be ordered by the position of their end, and then by pattern.
+    pub(crate) fn with_found(mut self, found: Vec<FindMatch>) -> Self {
+        self.found = Some(found.into());
+        self
+    }
+
+    /// Returns the patterns that end with the byte index `end`, either
+    /// searched in the `window` or taken from `found`.
+    fn matches_ending_at(&mut self, end: u64, position: impl Fn(u64) -> u64) -> Vec<usize> {
+        let Some(found) = &mut self.found else {
+            return (self.patterns.iter().enumerate())
+                .filter(|(_, pattern)| pattern.matches_end(&self.window))
+                .map(|(i, _)| i)
+                .collect();
+        };
+        let mut patterns = vec![];
+        while let Some(next) = found.front() {
+            let Some(start) = end.checked_sub(self.patterns[next.pattern].bytes.len() as u64)
+            else {
+                break;
+            };
+            let start = position(start);
+            if start < next.position {
+                break;
+            }
+            if start == next.position {
+                patterns.push(next.pattern);
+            }
+            found.pop_front();
+        }
+        patterns
+    }
+
+    /// Returns the index of the next byte that is pushed.
+    pub(crate) fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Searches the patterns that end with `b`, and records the matches with
+    /// the position of byte index `i` being `position(i)`. Returns the byte
+    /// that is no longer held back, if any.
+    pub(crate) fn push(&mut self, b: u8, position: impl Fn(u64) -> u64) -> io::Result<Option<u8>> {
+        self.window.push_back(b);
+        self.index += 1;
+        for i in self.matches_ending_at(self.index, &position) {
+            let size = (self.matches.len() as u64 + 1) * mem::size_of::<FindMatch>() as u64;
+            if size > self.max_memory {
+                return Err(io::Error::new(
+                    io::ErrorKind::OutOfMemory,
+                    format!(
+                        "recording the matches needs more than the allowed {} bytes of memory",
+                        self.max_memory
+                    ),
+                ));
+            }
+            let start = self.index - self.patterns[i].bytes.len() as u64;
+            self.matches.push(FindMatch {
+                position: position(start),
+                pattern: i,
+            });
+            self.ranges.push_back(start..self.index);
+        }
+        if self.window.len() == self.max_len {
+            Ok(self.window.pop_front())
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns the bytes that are held back, at the end of the input.
+    pub(crate) fn take_held(&mut self) -> Vec<u8> {
+        self.window.drain(..).collect()
+    }
+
+    /// Forgets the matches that end before `range`, and returns whether one
+    /// of the others overlaps it.
+    pub(crate) fn overlaps(&mut self, range: Range<u64>) -> bool {
+        while self.ranges.front().is_some_and(|r| r.end <= range.start) {
+            self.ranges.pop_front();
+        }
+        self.ranges.iter().any(|r| r.start < range.end)
+    }
+
+    /// Returns whether the byte at index `i` is part of a match.
+    pub(crate) fn is_match(&self, i: u64) -> bool {
+        self.ranges.iter().any(|r| r.contains(&i))
+    }
+
+    pub(crate) fn matches(&self) -> &[FindMatch] {
+        &self.matches
+    }
+}
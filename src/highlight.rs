@@ -0,0 +1,46 @@
+//! The regions of `--highlight START..END[:COLOR[:LABEL]]`.
+
+use anyhow::{anyhow, Result};
+
+use hexyl::{color_from_name, COLOR_HIGHLIGHT};
+
+/// A `--highlight` argument, whose positions are not parsed yet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct HighlightArg<'a> {
+    pub start: &'a str,
+    pub end: &'a str,
+    pub color: &'static [u8],
+    pub label: Option<String>,
+}
+
+/// Splits a `START..END[:COLOR[:LABEL]]` argument. The label is everything
+/// after the second colon, so it may contain colons itself. Quotes around
+/// the label are removed. The default color is the one of `--filter-byte`.
+pub(crate) fn parse_highlight(arg: &str) -> Result<HighlightArg<'_>> {
+    let mut fields = arg.splitn(3, ':');
+    let range = fields.next().unwrap_or_default();
+    let (start, end) = range
+        .split_once("..")
+        .filter(|(start, end)| !start.is_empty() && !end.is_empty())
+        .ok_or_else(|| anyhow!("expected START..END"))?;
+    let color = match fields.next() {
+        None | Some("") => COLOR_HIGHLIGHT,
+        Some(color) => color_from_name(color).ok_or_else(|| anyhow!("unknown color {color:?}"))?,
+    };
+    let label = fields
+        .next()
+        .map(|label| {
+            label
+                .strip_prefix('"')
+                .and_then(|label| label.strip_suffix('"'))
+                .unwrap_or(label)
+        })
+        .filter(|label| !label.is_empty())
+        .map(str::to_owned);
+    Ok(HighlightArg {
+        start,
+        end,
+        color,
+        label,
+    })
+}
@@ -1,9 +1,24 @@
 use std::fs;
 use std::io::{self, copy, sink, Read, Seek, SeekFrom};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
 pub enum Input<'a> {
     File(fs::File),
     Stdin(io::StdinLock<'a>),
+    /// Input that has been read into memory already, e.g. from the clipboard.
+    Buffer(io::Cursor<Vec<u8>>),
+    /// Any other reader, e.g. a decompressor. Like stdin, it only supports
+    /// seeking forward with a relative offset.
+    Reader(Box<dyn Read + 'a>),
+    /// Several inputs, one after another, as a single stream.
+    Chain(Chain<'a>),
+    /// Several inputs at given positions of a single address space.
+    Sparse(Sparse<'a>),
+    /// A file that is read as it grows.
+    Follow(Follow),
 }
 
 impl<'a> Read for Input<'a> {
@@ -11,6 +26,11 @@ impl<'a> Read for Input<'a> {
         match *self {
             Input::File(ref mut file) => file.read(buf),
             Input::Stdin(ref mut stdin) => stdin.read(buf),
+            Input::Buffer(ref mut buffer) => buffer.read(buf),
+            Input::Reader(ref mut reader) => reader.read(buf),
+            Input::Chain(ref mut chain) => chain.read(buf),
+            Input::Sparse(ref mut sparse) => sparse.read(buf),
+            Input::Follow(ref mut follow) => follow.read(buf),
         }
     }
 }
@@ -21,7 +41,7 @@ impl<'a> Seek for Input<'a> {
         where
             R: Read,
         {
-            let cant_seek_abs_err = || Err(io::Error::new(io::ErrorKind::Other, err_desc));
+            let cant_seek_abs_err = || Err(io::Error::other(err_desc));
 
             let offset = match pos {
                 SeekFrom::Current(o) => u64::try_from(o).or_else(|_e| cant_seek_abs_err())?,
@@ -34,8 +54,7 @@ impl<'a> Seek for Input<'a> {
         match *self {
             Input::File(ref mut file) => {
                 let seek_res = file.seek(pos);
-                if let Err(Some(libc::ESPIPE)) = seek_res.as_ref().map_err(|err| err.raw_os_error())
-                {
+                if matches!(&seek_res, Err(err) if is_stream(file, err)) {
                     try_skip(
                         file,
                         pos,
@@ -50,15 +69,306 @@ impl<'a> Seek for Input<'a> {
                 pos,
                 "STDIN only supports seeking forward with a relative offset",
             ),
+            Input::Buffer(ref mut buffer) => buffer.seek(pos),
+            Input::Reader(ref mut reader) => try_skip(
+                reader,
+                pos,
+                "This input only supports seeking forward with a relative offset",
+            ),
+            Input::Chain(ref mut chain) => chain.seek(pos),
+            Input::Sparse(ref mut sparse) => sparse.seek(pos),
+            Input::Follow(ref mut follow) => follow.file.seek(pos),
         }
     }
 }
 
+/// Returns whether a seek of `file` failed with `err` because the file is a
+/// pipe, a socket or a terminal, which can only be read in order.
+fn is_stream(file: &fs::File, err: &io::Error) -> bool {
+    // A position before the start of a regular file.
+    if err.kind() == io::ErrorKind::InvalidInput {
+        return false;
+    }
+    // `io::ErrorKind::NotSeekable` is not stable yet, so the type of the file
+    // is checked instead of the error.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+
+        file.metadata().is_ok_and(|metadata| {
+            let file_type = metadata.file_type();
+            file_type.is_fifo() || file_type.is_socket() || file_type.is_char_device()
+        })
+    }
+    // Elsewhere, other reasons for a failing seek of a file are rare, so it
+    // is assumed to be a stream.
+    #[cfg(not(unix))]
+    {
+        let _ = file;
+        true
+    }
+}
+
 impl<'a> Input<'a> {
     pub fn into_inner(self) -> Box<dyn Read + 'a> {
         match self {
             Input::File(file) => Box::new(file),
             Input::Stdin(stdin) => Box::new(stdin),
+            Input::Buffer(buffer) => Box::new(buffer),
+            Input::Reader(reader) => reader,
+            Input::Chain(chain) => Box::new(chain),
+            Input::Sparse(sparse) => Box::new(sparse),
+            Input::Follow(follow) => Box::new(follow),
+        }
+    }
+}
+
+/// Reads several inputs one after another, see [`Input::Chain`].
+pub struct Chain<'a> {
+    inputs: Vec<Input<'a>>,
+    /// The index of the input that is read next.
+    current: usize,
+    /// The position in the whole chain.
+    position: u64,
+}
+
+impl<'a> Chain<'a> {
+    pub fn new(inputs: Vec<Input<'a>>) -> Self {
+        Chain {
+            inputs,
+            current: 0,
+            position: 0,
+        }
+    }
+
+    /// Returns the lengths of all inputs, which must be seekable.
+    fn lengths(&mut self) -> io::Result<Vec<u64>> {
+        self.inputs
+            .iter_mut()
+            .map(|input| {
+                let position = input.stream_position()?;
+                let length = input.seek(SeekFrom::End(0))?;
+                input.seek(SeekFrom::Start(position))?;
+                Ok(length)
+            })
+            .collect()
+    }
+}
+
+impl<'a> Read for Chain<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while let Some(input) = self.inputs.get_mut(self.current) {
+            let n = input.read(buf)?;
+            if n > 0 || buf.is_empty() {
+                self.position += n as u64;
+                return Ok(n);
+            }
+            self.current += 1;
+        }
+        Ok(0)
+    }
+}
+
+impl<'a> Seek for Chain<'a> {
+    /// Seeks to any position if all inputs are seekable, and otherwise only
+    /// forward with a relative offset.
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let lengths = match self.lengths() {
+            Ok(lengths) => lengths,
+            Err(_) => {
+                let offset = match pos {
+                    SeekFrom::Current(offset) => u64::try_from(offset).ok(),
+                    SeekFrom::Start(_) | SeekFrom::End(_) => None,
+                }
+                .ok_or_else(|| {
+                    io::Error::other(
+                        "This input only supports seeking forward with a relative offset",
+                    )
+                })?;
+                copy(&mut self.take(offset), &mut sink())?;
+                return Ok(self.position);
+            }
+        };
+
+        let total: u64 = lengths.iter().sum();
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => total.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            )
+        })?;
+
+        // Positions beyond the end are in the last input, like for a file.
+        let mut start = 0;
+        self.current = lengths.len().saturating_sub(1);
+        for (i, &length) in lengths.iter().enumerate() {
+            if target < start + length {
+                self.current = i;
+                break;
+            }
+            if i + 1 < lengths.len() {
+                start += length;
+            }
+        }
+        for (i, input) in self.inputs.iter_mut().enumerate() {
+            if i == self.current {
+                input.seek(SeekFrom::Start(target - start))?;
+            } else if i > self.current {
+                input.seek(SeekFrom::Start(0))?;
+            }
+        }
+        self.position = target;
+        Ok(target)
+    }
+}
+
+/// Reads several inputs at given positions as a single input, see
+/// [`Input::Sparse`]. The gaps between the inputs read as zeros.
+pub struct Sparse<'a> {
+    /// The inputs with their start and length, sorted by their start.
+    parts: Vec<(u64, u64, Input<'a>)>,
+    /// The position in the whole address space.
+    position: u64,
+}
+
+impl<'a> Sparse<'a> {
+    /// Places every input at its position. The inputs must be seekable and
+    /// must not overlap.
+    pub fn new(parts: Vec<(u64, Input<'a>)>) -> io::Result<Self> {
+        let mut parts = parts
+            .into_iter()
+            .map(|(start, mut input)| {
+                let length = input.seek(SeekFrom::End(0))?;
+                input.seek(SeekFrom::Start(0))?;
+                Ok((start, length, input))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        parts.sort_by_key(|&(start, _, _)| start);
+        for pair in parts.windows(2) {
+            let (start, length, _) = pair[0];
+            if start
+                .checked_add(length)
+                .map_or(true, |end| end > pair[1].0)
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("the inputs at {start:#x} and {:#x} overlap", pair[1].0),
+                ));
+            }
+        }
+        Ok(Sparse { parts, position: 0 })
+    }
+
+    /// Returns the end of the last input.
+    pub fn len(&self) -> u64 {
+        self.parts
+            .last()
+            .map_or(0, |&(start, length, _)| start + length)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a> Read for Sparse<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let position = self.position;
+        // The first input that ends after the position.
+        let i = self
+            .parts
+            .partition_point(|&(start, length, _)| start + length <= position);
+        let n = match self.parts.get_mut(i) {
+            None => 0,
+            Some((start, _, input)) if *start <= position => {
+                input.seek(SeekFrom::Start(position - *start))?;
+                input.read(buf)?
+            }
+            Some((start, _, _)) => {
+                let n = buf
+                    .len()
+                    .min((*start - position).try_into().unwrap_or(usize::MAX));
+                buf[..n].fill(0);
+                n
+            }
+        };
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl<'a> Seek for Sparse<'a> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len().checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            )
+        })?;
+        Ok(self.position)
+    }
+}
+
+/// Reads a file as it grows, like `tail -f`, see [`Input::Follow`].
+///
+/// At the current end of the file, a read returns zero bytes once and sets
+/// the [`Follow::at_end`] flag, so that everything that has been read so far
+/// can be shown. The next read waits until more bytes have been appended,
+/// checking the file every `interval`.
+pub struct Follow {
+    file: fs::File,
+    interval: Duration,
+    /// Whether the end of the file has been reported, so the next read waits.
+    waiting: bool,
+    at_end: Arc<AtomicBool>,
+}
+
+impl Follow {
+    pub fn new(file: fs::File, interval: Duration) -> Self {
+        Follow {
+            file,
+            interval,
+            waiting: false,
+            at_end: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Returns the flag that is set whenever a read stops at the current end
+    /// of the file, to tell it from the end of a reader that wraps this one,
+    /// e.g. after a given number of bytes. It is never cleared by the reader.
+    pub fn at_end(&self) -> Arc<AtomicBool> {
+        self.at_end.clone()
+    }
+}
+
+impl Read for Follow {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = self.file.read(buf)?;
+            if n > 0 || buf.is_empty() {
+                self.waiting = false;
+                return Ok(n);
+            }
+            if !self.waiting {
+                self.waiting = true;
+                self.at_end.store(true, Ordering::Relaxed);
+                return Ok(0);
+            }
+            // The bytes before the position would be lost.
+            if self.file.metadata()?.len() < self.file.stream_position()? {
+                return Err(io::Error::other("the followed file has been truncated"));
+            }
+            thread::sleep(self.interval);
         }
     }
 }
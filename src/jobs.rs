@@ -0,0 +1,217 @@
+//! Counting the bytes of a file and searching the patterns of `--find` with
+//! several threads for `--jobs`.
+//!
+//! The file is split into one chunk per job, which is read with positional
+//! reads, so that the threads do not share the position of the file. The
+//! counts of the chunks are added up in the order of the chunks, and the
+//! matches are merged in the order in which the printer would find them.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::mem;
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::thread;
+
+use hexyl::{CategoryCounts, FindMatch, FindPattern};
+
+use crate::{count_ranges, ByteRange};
+
+/// Counts the bytes of each category within every range, like
+/// [`count_ranges`], for the bytes at `positions` of `file`.
+pub(crate) fn count_ranges_parallel(
+    file: &File,
+    positions: Range<u64>,
+    ranges: &[ByteRange],
+    jobs: NonZeroUsize,
+) -> io::Result<Vec<CategoryCounts>> {
+    let chunks = split(positions, jobs);
+    let results: Vec<io::Result<Vec<CategoryCounts>>> = thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .into_iter()
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let start = chunk.start;
+                    let mut reader = FileRange {
+                        file,
+                        position: chunk.start,
+                        end: chunk.end,
+                    };
+                    count_ranges(&mut reader, start, ranges)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("counting thread panicked"))
+            .collect()
+    });
+
+    let mut counts = vec![CategoryCounts::default(); ranges.len()];
+    for result in results {
+        for (counts, chunk_counts) in counts.iter_mut().zip(result?) {
+            counts.merge(&chunk_counts);
+        }
+    }
+    Ok(counts)
+}
+
+/// Searches the `patterns` in the bytes at `positions` of `file`, and returns
+/// the matches with their positions in the file, ordered by the position of
+/// their end and then by pattern, for [`hexyl::PrinterBuilder::found_matches`].
+/// Every chunk is read together with the bytes after it that a match which
+/// starts in it can reach, and the matches that are found twice at the
+/// boundaries are only kept once.
+pub(crate) fn find_parallel(
+    file: &File,
+    positions: Range<u64>,
+    patterns: &[FindPattern],
+    jobs: NonZeroUsize,
+    max_memory: u64,
+) -> io::Result<Vec<FindMatch>> {
+    let max_len = patterns
+        .iter()
+        .map(|pattern| pattern.bytes.len())
+        .max()
+        .unwrap_or(1);
+    let chunks = split(positions.clone(), jobs);
+    let results: Vec<io::Result<Vec<FindMatch>>> = thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .into_iter()
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let reader = FileRange {
+                        file,
+                        position: chunk.start,
+                        end: chunk
+                            .end
+                            .saturating_add(max_len as u64 - 1)
+                            .min(positions.end),
+                    };
+                    find_in(reader, chunk.start, patterns, max_len, max_memory)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("searching thread panicked"))
+            .collect()
+    });
+
+    let mut matches = vec![];
+    for result in results {
+        matches.extend(result?);
+    }
+    let end = |found: &FindMatch| found.position + patterns[found.pattern].bytes.len() as u64;
+    matches.sort_by_key(|found| (end(found), found.pattern));
+    matches.dedup();
+    if (matches.len() * mem::size_of::<FindMatch>()) as u64 > max_memory {
+        return Err(too_many_matches(max_memory));
+    }
+    Ok(matches)
+}
+
+/// Returns the matches of `patterns` in `reader`, whose first byte is at
+/// `start` of the file.
+fn find_in(
+    reader: FileRange,
+    start: u64,
+    patterns: &[FindPattern],
+    max_len: usize,
+    max_memory: u64,
+) -> io::Result<Vec<FindMatch>> {
+    let mut matches = vec![];
+    let mut window = VecDeque::with_capacity(max_len + 1);
+    for (i, b) in BufReader::new(reader).bytes().enumerate() {
+        window.push_back(b?);
+        if window.len() > max_len {
+            window.pop_front();
+        }
+        for (pattern, FindPattern { bytes }) in patterns.iter().enumerate() {
+            let Some(offset) = window.len().checked_sub(bytes.len()) else {
+                continue;
+            };
+            let is_match = (window.range(offset..).zip(bytes))
+                .all(|(&b, expected)| expected.map_or(true, |expected| b == expected));
+            if is_match {
+                if ((matches.len() + 1) * mem::size_of::<FindMatch>()) as u64 > max_memory {
+                    return Err(too_many_matches(max_memory));
+                }
+                matches.push(FindMatch {
+                    position: start + (i + 1 - bytes.len()) as u64,
+                    pattern,
+                });
+            }
+        }
+    }
+    Ok(matches)
+}
+
+fn too_many_matches(max_memory: u64) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::OutOfMemory,
+        format!("recording the matches needs more than the allowed {max_memory} bytes of memory"),
+    )
+}
+
+/// Splits `positions` into at most `jobs` chunks of about the same size.
+pub(crate) fn split(positions: Range<u64>, jobs: NonZeroUsize) -> Vec<Range<u64>> {
+    let len = positions.end.saturating_sub(positions.start);
+    let chunk_size = len.div_ceil(jobs.get() as u64).max(1);
+    (0..jobs.get() as u64)
+        .map(|i| {
+            let start = positions.start.saturating_add(i * chunk_size);
+            start.min(positions.end)..start.saturating_add(chunk_size).min(positions.end)
+        })
+        .filter(|chunk| !chunk.is_empty())
+        .collect()
+}
+
+/// Reads the bytes at `position..end` of a file that is shared with other
+/// threads.
+struct FileRange<'a> {
+    file: &'a File,
+    position: u64,
+    end: u64,
+}
+
+impl Read for FileRange<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf
+            .len()
+            .min((self.end - self.position).try_into().unwrap_or(usize::MAX));
+        let n = read_at(self.file, &mut buf[..len], self.position)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(unix)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    use std::os::unix::fs::FileExt;
+
+    file.read_at(buf, offset)
+}
+
+// Unlike on Unix, this moves the position of the file, which is not used by
+// the other threads.
+#[cfg(windows)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    use std::os::windows::fs::FileExt;
+
+    file.seek_read(buf, offset)
+}
+
+// Without positional reads, the threads take turns to move the position of
+// the file and read.
+#[cfg(not(any(unix, windows)))]
+fn read_at(mut file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    use std::io::{Seek, SeekFrom};
+    use std::sync::{Mutex, PoisonError};
+
+    static POSITION: Mutex<()> = Mutex::new(());
+    let _guard = POSITION.lock().unwrap_or_else(PoisonError::into_inner);
+    file.seek(SeekFrom::Start(offset))?;
+    file.read(buf)
+}
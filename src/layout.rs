@@ -0,0 +1,86 @@
+//! Choosing the number of panels for the available space.
+
+use std::num::NonZeroU64;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Error, Result};
+
+/// How the number of panels is chosen, see `--fit`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Fit {
+    /// Two panels, unless the output goes to a terminal that is not wide
+    /// enough for that.
+    Default,
+    /// A fixed number of panels.
+    Panels(NonZeroU64),
+    /// As many panels as fit into the given number of columns, or into the
+    /// terminal if no number is given.
+    Width(Option<NonZeroU64>),
+    /// A fixed number of bytes per line, which has to be a multiple of the
+    /// panel width.
+    BytesPerLine(NonZeroU64),
+}
+
+impl Fit {
+    /// Returns the number of panels of `panel_width` bytes. `terminal_width`
+    /// is the width of the terminal, and `layout_width` returns the number of
+    /// columns that a line with the given number of panels occupies. `max_panels` limits the
+    /// number of panels that is chosen for the available space, but not a
+    /// fixed number of panels or bytes per line. If the output does not go to
+    /// a terminal (`to_terminal`), the default does not depend on its width,
+    /// so that captured output is the same everywhere.
+    pub fn panels(
+        self,
+        terminal_width: u64,
+        to_terminal: bool,
+        max_panels: Option<NonZeroU64>,
+        panel_width: u64,
+        layout_width: impl Fn(u64) -> u64,
+    ) -> u64 {
+        let panels_for_width = |width: u64| {
+            let width_one_panel = layout_width(1);
+            let width_per_panel = layout_width(2) - width_one_panel;
+            1 + width.saturating_sub(width_one_panel) / width_per_panel
+        };
+
+        let fitting_panels = |width: u64| {
+            let panels = panels_for_width(width);
+            max_panels.map_or(panels, |max| panels.min(max.get()))
+        };
+
+        match self {
+            Fit::Default if !to_terminal => max_panels.map_or(2, |max| max.get().min(2)),
+            Fit::Default => fitting_panels(terminal_width).min(2),
+            Fit::Panels(panels) => panels.get(),
+            Fit::Width(width) => fitting_panels(width.map_or(terminal_width, NonZeroU64::get)),
+            Fit::BytesPerLine(bytes) => (bytes.get() / panel_width).max(1),
+        }
+    }
+}
+
+impl FromStr for Fit {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (mode, value) = match s.split_once(':') {
+            Some((mode, value)) => (mode, Some(value)),
+            None => (s, None),
+        };
+        let parse_value = || -> Result<NonZeroU64> {
+            let value = value.ok_or_else(|| anyhow!("expected a number after '{mode}:'"))?;
+            value
+                .parse()
+                .with_context(|| format!("failed to parse {value:?} as unsigned nonzero integer"))
+        };
+
+        match mode {
+            "width" if value.is_none() => Ok(Fit::Width(None)),
+            "width" => Ok(Fit::Width(Some(parse_value()?))),
+            "panels" => Ok(Fit::Panels(parse_value()?)),
+            "bytes" => Ok(Fit::BytesPerLine(parse_value()?)),
+            _ => Err(anyhow!(
+                "expected 'width', 'width:N', 'panels:N' or 'bytes:N'"
+            )),
+        }
+    }
+}
@@ -1,13 +1,47 @@
+pub(crate) mod aligned;
+pub mod c_include;
 pub(crate) mod colors;
+pub mod diff;
+pub(crate) mod find;
 pub(crate) mod input;
+pub(crate) mod options;
+pub mod plain_hex;
+pub mod porcelain;
+pub(crate) mod recover;
+pub mod rle;
+pub mod squeezer;
+pub(crate) mod step;
+pub(crate) mod styled;
+pub(crate) mod until;
 
+pub use aligned::*;
 pub use colors::*;
+pub use find::*;
 pub use input::*;
+pub use options::*;
+pub use recover::*;
+pub use step::*;
+pub use styled::*;
+pub use until::*;
 
-use std::io::{self, BufReader, Read, Write};
+use squeezer::{SqueezeAction, Squeezer};
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::mem;
+use std::num::NonZeroU64;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 use clap::ValueEnum;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
 pub enum Base {
     Binary,
     Octal,
@@ -15,7 +49,39 @@ pub enum Base {
     Hexadecimal,
 }
 
-#[derive(Copy, Clone)]
+impl Base {
+    /// The number of digits needed to write a single byte.
+    pub fn digits(self) -> u8 {
+        match self {
+            Base::Binary => 8,
+            Base::Octal => 3,
+            Base::Decimal => 3,
+            Base::Hexadecimal => 2,
+        }
+    }
+
+    /// The representations of all byte values, right-aligned to `width`.
+    fn byte_table(self, width: u8, padding: Padding) -> Vec<String> {
+        let width = width as usize;
+        let digits = match padding {
+            Padding::Zeros => self.digits() as usize,
+            Padding::Spaces => 1,
+        };
+        (0u8..=u8::MAX)
+            .map(|i| {
+                let digits = match self {
+                    Base::Binary => format!("{i:0digits$b}"),
+                    Base::Octal => format!("{i:0digits$o}"),
+                    Base::Decimal => format!("{i:0digits$}"),
+                    Base::Hexadecimal => format!("{i:0digits$x}"),
+                };
+                format!("{digits:>width$}")
+            })
+            .collect()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ByteCategory {
     Null,
     AsciiPrintable,
@@ -24,7 +90,90 @@ pub enum ByteCategory {
     NonAscii,
 }
 
-#[derive(Copy, Clone, Debug, Default, ValueEnum)]
+impl ByteCategory {
+    /// All categories, in the order of their declaration.
+    pub const ALL: [ByteCategory; 5] = [
+        ByteCategory::Null,
+        ByteCategory::AsciiPrintable,
+        ByteCategory::AsciiWhitespace,
+        ByteCategory::AsciiOther,
+        ByteCategory::NonAscii,
+    ];
+
+    pub fn of(byte: u8) -> ByteCategory {
+        Byte(byte).category()
+    }
+}
+
+/// The number of bytes in each [`ByteCategory`].
+#[derive(Clone, Debug, Default)]
+pub struct CategoryCounts {
+    counts: [u64; 5],
+}
+
+impl CategoryCounts {
+    pub fn add(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.counts[ByteCategory::of(b) as usize] += 1;
+        }
+    }
+
+    pub fn count(&self, category: ByteCategory) -> u64 {
+        self.counts[category as usize]
+    }
+
+    /// Adds the counts of `other`, e.g. of another part of the same input.
+    pub fn merge(&mut self, other: &CategoryCounts) {
+        for (count, other) in self.counts.iter_mut().zip(other.counts) {
+            *count += other;
+        }
+    }
+
+    /// The total number of bytes in all categories.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+}
+
+/// An iterator over the bytes of a reader, together with their offset and
+/// [`ByteCategory`], for tools that want to use the same classification of
+/// bytes as hexyl without its output.
+pub struct Categorizer<Reader: Read> {
+    bytes: io::Bytes<BufReader<Reader>>,
+    offset: u64,
+}
+
+impl<Reader: Read> Categorizer<Reader> {
+    /// Iterates over the bytes of `reader`, starting at offset 0. Reads are
+    /// buffered, so `reader` does not need to be buffered.
+    pub fn wrap(reader: Reader) -> Self {
+        Categorizer {
+            bytes: BufReader::new(reader).bytes(),
+            offset: 0,
+        }
+    }
+}
+
+impl<Reader: Read> Iterator for Categorizer<Reader> {
+    type Item = io::Result<(u64, u8, ByteCategory)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let byte = match self.bytes.next()? {
+            Ok(byte) => byte,
+            Err(err) => return Some(Err(err)),
+        };
+        let offset = self.offset;
+        self.offset += 1;
+        Some(Ok((offset, byte, ByteCategory::of(byte))))
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
 #[non_exhaustive]
 pub enum CharacterTable {
     /// Show printable ASCII characters as-is, '⋄' for NULL bytes, ' ' for
@@ -38,14 +187,33 @@ pub enum CharacterTable {
 
     /// Show printable EBCDIC as-is, ' ' for space, '.' for everything else.
     #[value(name = "codepage-1047")]
+    #[cfg_attr(feature = "serde", serde(rename = "codepage-1047"))]
     CP1047,
 
     /// Uses code page 437 (for non-ASCII bytes).
     #[value(name = "codepage-437")]
+    #[cfg_attr(feature = "serde", serde(rename = "codepage-437"))]
     CP437,
+
+    /// Like [`CharacterTable::CP437`], but shows blanks instead of the
+    /// graphic symbols for the control characters 0x00-0x1f, which confuse
+    /// screen readers and copy-paste.
+    #[value(skip)]
+    #[cfg_attr(feature = "serde", serde(rename = "codepage-437-blank-controls"))]
+    CP437BlankControls,
+
+    /// Show ISO-8859-1 (Latin-1) characters for 0xa0-0xff, e.g. for legacy
+    /// European text files, and placeholders like the default table for
+    /// control characters (including 0x80-0x9f).
+    Latin1,
 }
 
-#[derive(Copy, Clone, Debug, Default, ValueEnum)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
 pub enum Endianness {
     /// Print out groups in little-endian format.
     Little,
@@ -55,12 +223,104 @@ pub enum Endianness {
     Big,
 }
 
-#[derive(PartialEq)]
-enum Squeezer {
-    Print,
-    Delete,
-    Ignore,
-    Disabled,
+/// How the digits of a byte are padded to the width of the largest value.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum Padding {
+    /// Pad with leading zeros, e.g. '007'.
+    #[default]
+    Zeros,
+
+    /// Pad with spaces, e.g. '  7'.
+    Spaces,
+}
+
+/// How the lines of the input are written, see
+/// [`PrinterBuilder::line_format`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum LineFormat {
+    /// The usual table with panels and borders.
+    #[default]
+    Table,
+
+    /// One JSON object per line for scripts, e.g. for `jq`:
+    ///
+    /// ```text
+    /// {"offset":16,"bytes":[48,49,10],"ascii":"01.","squeezed":false}
+    /// ```
+    ///
+    /// `offset` is the position of the first byte as shown in the position
+    /// panel, and `ascii` shows the bytes that are not printable ASCII as
+    /// `.`. The first of the squeezed lines of a repeated line is written
+    /// with `squeezed` set and the number of squeezed bytes as `length`, once
+    /// the region has ended, the others are left out. There is no header or
+    /// footer, and the options of the table like colors or labels have no
+    /// effect.
+    Json,
+}
+
+/// How positions are shown in the position panel.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum OffsetStyle {
+    /// Show the byte offset in hexadecimal.
+    #[default]
+    Byte,
+
+    /// Show the number of the sector with the given size and the offset
+    /// within that sector, e.g. `LBA 00000003 +1f0`.
+    Sector(NonZeroU64),
+
+    /// Show a `segment:offset` address of real mode in uppercase, like DOS
+    /// DEBUG, e.g. `0000:0100`, with the next segment every 64 KiB. The
+    /// addresses wrap around after 1 MiB, like in real mode.
+    Segment,
+}
+
+impl OffsetStyle {
+    /// The number of hexadecimal digits of the offset within a sector.
+    fn sector_offset_digits(sector_size: NonZeroU64) -> usize {
+        let max_offset = sector_size.get() - 1;
+        (64 - max_offset.leading_zeros() as usize)
+            .div_ceil(4)
+            .max(1)
+    }
+
+    /// The number of hexadecimal digits of the byte offset or sector number
+    /// of `max_position`, at least 8. Byte offsets are shown in whole bytes.
+    fn position_digits(self, max_position: u64) -> usize {
+        let digits = |n: u64| (64 - n.leading_zeros() as usize).div_ceil(4).max(8);
+        match self {
+            OffsetStyle::Byte => digits(max_position).next_multiple_of(2),
+            OffsetStyle::Sector(sector_size) => digits(max_position / sector_size),
+            OffsetStyle::Segment => "SSSSOOOO".len(),
+        }
+    }
+
+    /// The width of the position panel for positions up to `max_position`.
+    fn width(self, max_position: u64) -> usize {
+        let digits = self.position_digits(max_position);
+        match self {
+            OffsetStyle::Byte => digits,
+            OffsetStyle::Sector(sector_size) => {
+                "LBA  +".len() + digits + Self::sector_offset_digits(sector_size)
+            }
+            OffsetStyle::Segment => digits + ":".len(),
+        }
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -81,14 +341,17 @@ impl Byte {
         }
     }
 
-    fn color(self) -> &'static [u8] {
-        use crate::ByteCategory::*;
-        match self.category() {
-            Null => COLOR_NULL,
-            AsciiPrintable => COLOR_ASCII_PRINTABLE,
-            AsciiWhitespace => COLOR_ASCII_WHITESPACE,
-            AsciiOther => COLOR_ASCII_OTHER,
-            NonAscii => COLOR_NONASCII,
+    /// The ASCII mnemonic of a control character, e.g. `ESC` for `0x1b`.
+    fn mnemonic(self) -> Option<&'static str> {
+        const MNEMONICS: [&str; 32] = [
+            "NUL", "SOH", "STX", "ETX", "EOT", "ENQ", "ACK", "BEL", "BS", "HT", "LF", "VT", "FF",
+            "CR", "SO", "SI", "DLE", "DC1", "DC2", "DC3", "DC4", "NAK", "SYN", "ETB", "CAN", "EM",
+            "SUB", "ESC", "FS", "GS", "RS", "US",
+        ];
+        match self.0 {
+            0x00..=0x1f => Some(MNEMONICS[self.0 as usize]),
+            0x7f => Some("DEL"),
+            _ => None,
         }
     }
 
@@ -113,6 +376,245 @@ impl Byte {
             },
             CharacterTable::CP1047 => CP1047[self.0 as usize],
             CharacterTable::CP437 => CP437[self.0 as usize],
+            CharacterTable::CP437BlankControls => match self.0 {
+                0x00..=0x1f => ' ',
+                _ => CP437[self.0 as usize],
+            },
+            CharacterTable::Latin1 => LATIN1[self.0 as usize],
+        }
+    }
+}
+
+/// Shown in the character panel instead of a character that does not take
+/// exactly one column, like a combining mark, a zero-width space or a wide
+/// character, which would break the alignment of the panel.
+const PLACEHOLDER_CHAR: char = '◌';
+
+/// Returns whether `marker` can be used for [`PrinterBuilder::squeeze_marker`]:
+/// it must be 1 to 8 columns wide, without control characters.
+pub fn is_valid_squeeze_marker(marker: &str) -> bool {
+    (1..=8).contains(&marker.width()) && !marker.chars().any(char::is_control)
+}
+
+/// Returns `c` if it takes exactly one column, and [`PLACEHOLDER_CHAR`]
+/// otherwise. Control characters never reach the terminal this way, even if
+/// a character table maps a byte to one.
+fn single_column(c: char) -> char {
+    if !c.is_control() && c.width() == Some(1) {
+        c
+    } else {
+        PLACEHOLDER_CHAR
+    }
+}
+
+/// Removes the escape sequences of colors, cursor movements and other
+/// control functions (CSI) as well as of hyperlinks and window titles (OSC)
+/// from `text`.
+pub fn strip_escapes(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            // CSI sequences end with a byte in the range '@' to '~'.
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC sequences end with BEL or ST (ESC \).
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+/// Removes the escape sequences from `text` and replaces its other control
+/// characters, like BEL, by [`PLACEHOLDER_CHAR`], see
+/// [`PrinterBuilder::strict_sanitize`].
+fn sanitize(text: &str) -> String {
+    strip_escapes(text)
+        .chars()
+        .map(|c| if c.is_control() { PLACEHOLDER_CHAR } else { c })
+        .collect()
+}
+
+/// Writes the object of a line of [`LineFormat::Json`] up to its `squeezed`
+/// field, without the closing brace.
+fn write_json_fields<W: Write + ?Sized>(
+    out: &mut W,
+    position: u64,
+    bytes: &[u8],
+) -> io::Result<()> {
+    write!(out, "{{\"offset\":{position},\"bytes\":[")?;
+    for (i, b) in bytes.iter().enumerate() {
+        if i > 0 {
+            out.write_all(b",")?;
+        }
+        write!(out, "{b}")?;
+    }
+    out.write_all(b"],\"ascii\":\"")?;
+    for &b in bytes {
+        match b {
+            b'"' | b'\\' => out.write_all(&[b'\\', b])?,
+            0x20..=0x7e => out.write_all(&[b])?,
+            _ => out.write_all(b".")?,
+        }
+    }
+    out.write_all(b"\"")
+}
+
+/// Formats a number of bytes for humans, e.g. `1 byte`, `100 bytes`, `1 KiB`
+/// or `1.5 MiB`. Fractions are rounded down to a tenth.
+fn format_size(size: u64) -> String {
+    const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+    match size {
+        1 => return "1 byte".to_owned(),
+        0..=1023 => return format!("{size} bytes"),
+        _ => {}
+    }
+    let exponent = (size.ilog2() / 10).min(UNITS.len() as u32);
+    let unit = 1u128 << (10 * exponent);
+    let tenths = u128::from(size) * 10 / unit;
+    let name = UNITS[exponent as usize - 1];
+    if tenths % 10 == 0 {
+        format!("{} {name}", tenths / 10)
+    } else {
+        format!("{}.{} {name}", tenths / 10, tenths % 10)
+    }
+}
+
+/// A rough classification of the bytes in a single line of output, see
+/// [`PrinterBuilder::classify_lines`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LineClass {
+    /// Only printable ASCII characters and ASCII whitespace.
+    Text,
+    /// Only NULL bytes.
+    Zeros,
+    /// Bytes that look random, e.g. compressed or encrypted data.
+    HighEntropy,
+    /// Anything else.
+    Binary,
+}
+
+impl LineClass {
+    pub fn of(bytes: &[u8]) -> LineClass {
+        if !bytes.is_empty() && bytes.iter().all(|&b| b == 0x00) {
+            return LineClass::Zeros;
+        }
+        if bytes
+            .iter()
+            .all(|&b| b.is_ascii_graphic() || b.is_ascii_whitespace())
+        {
+            return LineClass::Text;
+        }
+
+        // Compare the Shannon entropy of the line with the maximum entropy
+        // that is possible for a line of this length.
+        let mut counts = [0usize; 256];
+        for &b in bytes {
+            counts[b as usize] += 1;
+        }
+        let len = bytes.len() as f64;
+        let entropy: f64 = counts
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let p = count as f64 / len;
+                -p * p.log2()
+            })
+            .sum();
+        let max_entropy = len.min(256.0).log2();
+        if bytes.len() >= 8 && entropy >= 0.9 * max_entropy {
+            LineClass::HighEntropy
+        } else {
+            LineClass::Binary
+        }
+    }
+
+    /// The single character that represents this class in the output.
+    pub fn tag(self) -> char {
+        match self {
+            LineClass::Text => 'T',
+            LineClass::Zeros => 'Z',
+            LineClass::HighEntropy => 'H',
+            LineClass::Binary => 'B',
+        }
+    }
+}
+
+/// The checksum of the bytes of a line that is shown in a column with
+/// [`PrinterBuilder::line_hash`], to compare dumps by eye or with `diff`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum LineHash {
+    /// CRC-8/SMBUS, two hex digits.
+    Crc8,
+    /// CRC-16/CCITT-FALSE, four hex digits.
+    Crc16,
+}
+
+impl LineHash {
+    /// The number of hex digits of the checksum.
+    pub fn digits(self) -> usize {
+        match self {
+            LineHash::Crc8 => 2,
+            LineHash::Crc16 => 4,
+        }
+    }
+
+    pub fn of(self, bytes: &[u8]) -> u16 {
+        match self {
+            LineHash::Crc8 => {
+                let mut crc = 0u8;
+                for &b in bytes {
+                    crc ^= b;
+                    for _ in 0..8 {
+                        crc = if crc & 0x80 != 0 {
+                            (crc << 1) ^ 0x07
+                        } else {
+                            crc << 1
+                        };
+                    }
+                }
+                u16::from(crc)
+            }
+            LineHash::Crc16 => {
+                let mut crc = 0xffffu16;
+                for &b in bytes {
+                    crc ^= u16::from(b) << 8;
+                    for _ in 0..8 {
+                        crc = if crc & 0x8000 != 0 {
+                            (crc << 1) ^ 0x1021
+                        } else {
+                            crc << 1
+                        };
+                    }
+                }
+                crc
+            }
         }
     }
 }
@@ -124,7 +626,12 @@ struct BorderElements {
     right_corner: char,
 }
 
-#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
 pub enum BorderStyle {
     /// Draw a border with Unicode characters.
     #[default]
@@ -135,6 +642,56 @@ pub enum BorderStyle {
 
     /// Do not draw a border at all.
     None,
+
+    /// No border, in the layout of `hexdump -C` with a colon after the
+    /// position, e.g. `00000000: 7f 45 4c 46 …  |.ELF…|`, without separators
+    /// before and after the line.
+    Compact,
+}
+
+/// The kind of a panel, which determines the separators around it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PanelKind {
+    Position,
+    Hex,
+    Char,
+    LineClass,
+    LineHash,
+}
+
+/// Which hex panels of a line have a character panel, see
+/// [`PrinterBuilder::char_panels`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum CharPanels {
+    /// Show a character panel for every hex panel.
+    #[default]
+    All,
+
+    /// Only show the character panel of the first hex panel.
+    First,
+
+    /// Only show the character panel of the last hex panel.
+    Last,
+
+    /// Do not show character panels, like
+    /// [`PrinterBuilder::show_char_panel`] with `false`.
+    None,
+}
+
+impl CharPanels {
+    /// The number of character panels in a line of `panels` hex panels.
+    fn count(self, panels: u64) -> u64 {
+        match self {
+            CharPanels::All => panels,
+            CharPanels::First | CharPanels::Last => panels.min(1),
+            CharPanels::None => 0,
+        }
+    }
 }
 
 impl BorderStyle {
@@ -152,7 +709,7 @@ impl BorderStyle {
                 column_separator: '+',
                 right_corner: '+',
             }),
-            BorderStyle::None => None,
+            BorderStyle::None | BorderStyle::Compact => None,
         }
     }
 
@@ -170,7 +727,7 @@ impl BorderStyle {
                 column_separator: '+',
                 right_corner: '+',
             }),
-            BorderStyle::None => None,
+            BorderStyle::None | BorderStyle::Compact => None,
         }
     }
 
@@ -178,7 +735,7 @@ impl BorderStyle {
         match self {
             BorderStyle::Unicode => '│',
             BorderStyle::Ascii => '|',
-            BorderStyle::None => ' ',
+            BorderStyle::None | BorderStyle::Compact => ' ',
         }
     }
 
@@ -186,188 +743,1175 @@ impl BorderStyle {
         match self {
             BorderStyle::Unicode => '┊',
             BorderStyle::Ascii => '|',
-            BorderStyle::None => ' ',
+            BorderStyle::None | BorderStyle::Compact => ' ',
+        }
+    }
+
+    /// The separator between a panel of kind `before` and one of kind
+    /// `after`, which are `None` at the start and at the end of a line.
+    fn separator(&self, before: Option<PanelKind>, after: Option<PanelKind>) -> &'static str {
+        use PanelKind::*;
+
+        let inner = matches!(
+            (before, after),
+            (Some(Hex), Some(Hex)) | (Some(Char), Some(Char))
+        );
+        match self {
+            BorderStyle::Unicode if inner => "┊",
+            BorderStyle::Unicode => "│",
+            BorderStyle::Ascii => "|",
+            BorderStyle::None => " ",
+            // The hex panels start and end with a space already.
+            BorderStyle::Compact => match (before, after) {
+                _ if inner => "",
+                (Some(Position), _) => ":",
+                (Some(Hex), Some(Char)) => " |",
+                (Some(Char), None) => "|",
+                (Some(Char), Some(_)) => "| ",
+                (Some(LineClass | LineHash), Some(_)) => " ",
+                _ => "",
+            },
+        }
+    }
+
+    /// The mark in the middle of every panel, see [`PrinterBuilder::tick_marks`].
+    fn tick_mark(&self) -> char {
+        match self {
+            BorderStyle::Unicode | BorderStyle::None => '·',
+            BorderStyle::Ascii | BorderStyle::Compact => ':',
         }
     }
 }
 
-pub struct PrinterBuilder<'a, Writer: Write> {
-    writer: &'a mut Writer,
-    show_color: bool,
-    show_char_panel: bool,
-    show_position_panel: bool,
-    border_style: BorderStyle,
-    use_squeeze: bool,
+/// Returns the width of a single hex panel, including the leading space and
+/// the trailing space of the last group.
+fn hex_panel_width(base_digits: u8, group_size: u8, panel_width: u64) -> usize {
+    // add one to include the trailing space of a group
+    let group_sz = base_digits as usize * group_size as usize + 1;
+    let group_per_panel = panel_width as usize / group_size as usize;
+    // add one to include the leading space
+    1 + group_sz * group_per_panel
+}
+
+/// Splits `text` into rows of at most `width` columns, at spaces where
+/// possible. Without a width, the text is a single row.
+fn wrap_text(text: &str, width: Option<usize>) -> Vec<String> {
+    let Some(width) = width else {
+        return vec![text.to_owned()];
+    };
+    let width = width.max(1);
+    let mut rows = vec![];
+    let mut row = String::new();
+    let mut row_width = 0;
+    for word in text.split(' ').filter(|word| !word.is_empty()) {
+        let word_width = word.width();
+        if row_width > 0 && row_width + 1 + word_width <= width {
+            row.push(' ');
+            row.push_str(word);
+            row_width += 1 + word_width;
+            continue;
+        }
+        if row_width > 0 {
+            rows.push(mem::take(&mut row));
+            row_width = 0;
+        }
+        // Words that are too wide for a row are split.
+        for c in word.chars() {
+            let c_width = c.width().unwrap_or(0);
+            if row_width > 0 && row_width + c_width > width {
+                rows.push(mem::take(&mut row));
+                row_width = 0;
+            }
+            row.push(c);
+            row_width += c_width;
+        }
+    }
+    if !row.is_empty() || rows.is_empty() {
+        rows.push(row);
+    }
+    rows
+}
+
+/// Returns the kinds and widths of all panels in a line, from left to right,
+/// excluding the separators between them.
+#[allow(clippy::too_many_arguments)]
+fn panel_widths(
+    position_width: Option<usize>,
+    char_panels: u64,
+    classify_lines: bool,
+    line_hash: Option<LineHash>,
     panels: u64,
+    panel_width: u64,
+    base_digits: u8,
     group_size: u8,
-    base: Base,
-    endianness: Endianness,
-    character_table: CharacterTable,
+    tick_marks: bool,
+) -> Vec<(PanelKind, usize)> {
+    let panels = panels as usize;
+    let mut widths = vec![];
+    if let Some(position_width) = position_width {
+        widths.push((PanelKind::Position, position_width));
+    }
+    widths.extend(
+        std::iter::repeat((
+            PanelKind::Hex,
+            hex_panel_width(base_digits, group_size, panel_width),
+        ))
+        .take(panels),
+    );
+    // The tick mark takes an extra column in the character panel, but
+    // replaces a space in the hex panel.
+    let tick_marks = has_tick_marks(tick_marks, panel_width);
+    widths.extend(
+        std::iter::repeat((
+            PanelKind::Char,
+            panel_width as usize + usize::from(tick_marks),
+        ))
+        .take(char_panels as usize),
+    );
+    if classify_lines {
+        widths.push((PanelKind::LineClass, 1));
+    }
+    if let Some(line_hash) = line_hash {
+        widths.push((PanelKind::LineHash, line_hash.digits()));
+    }
+    widths
+}
+
+/// Returns the separators of a line with the given panels, one before every
+/// panel and one at the end.
+fn separators(border_style: BorderStyle, panels: &[(PanelKind, usize)]) -> Vec<&'static str> {
+    let kinds: Vec<_> = panels.iter().map(|&(kind, _)| Some(kind)).collect();
+    [None]
+        .iter()
+        .chain(&kinds)
+        .zip(kinds.iter().chain([&None]))
+        .map(|(&before, &after)| border_style.separator(before, after))
+        .collect()
+}
+
+/// Returns the total width of a line with the given panels, including their
+/// separators.
+fn line_width(border_style: BorderStyle, panels: &[(PanelKind, usize)]) -> usize {
+    let separators: usize = separators(border_style, panels)
+        .iter()
+        .map(|sep| sep.chars().count())
+        .sum();
+    separators + panels.iter().map(|&(_, width)| width).sum::<usize>()
+}
+
+/// Returns whether there are tick marks with [`PrinterBuilder::tick_marks`],
+/// which needs panels of at least two bytes.
+fn has_tick_marks(tick_marks: bool, panel_width: u64) -> bool {
+    tick_marks && panel_width >= 2
+}
+
+/// Returns the total width of a line consisting of panels with the given
+/// widths, which are each followed by a separator. One more separator is
+/// added at the beginning of the line.
+fn layout_width(panel_widths: &[usize]) -> usize {
+    1 + panel_widths.iter().map(|width| width + 1).sum::<usize>()
+}
+
+/// The number of matches for [`PrinterBuilder::filter_bytes`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FilterMatches {
+    /// The number of matching bytes.
+    pub bytes: u64,
+    /// The number of lines with at least one matching byte.
+    pub lines: u64,
+}
+
+/// A region of repeated bytes that was squeezed, see
+/// [`PrinterBuilder::squeeze_summary`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SqueezedRegion {
+    /// The positions (including the display offset) of the squeezed lines.
+    pub range: Range<u64>,
+    /// The number of squeezed bytes, which differs from the length of the
+    /// range with [`PrinterBuilder::step`].
+    pub len: u64,
+    /// The byte that is repeated.
+    pub byte: u8,
+}
+
+/// A region of the input that is shown in its own color, see
+/// [`PrinterBuilder::highlights`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Highlight {
+    /// The positions of the region, as shown in the position panel.
+    pub range: Range<u64>,
+    /// The color of the bytes, e.g. from [`color_from_name`].
+    pub color: &'static [u8],
+    /// The label that is shown at the end of the line where the region starts.
+    pub label: Option<String>,
+}
+
+/// A comment that is shown at the end of the line containing its position,
+/// see [`PrinterBuilder::comments`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Comment {
+    /// The position that the comment refers to, as shown in the position
+    /// panel.
+    pub position: u64,
+    pub text: String,
+}
+
+/// A function that decides the colors of the bytes in a line. It is called
+/// with the offset of the line (including the display offset) and the bytes
+/// of the line, and returns one entry per byte. `None` keeps the default color.
+pub type ColorHook = Box<dyn FnMut(u64, &[u8]) -> io::Result<Vec<Option<&'static [u8]>>>>;
+
+/// Something that happened while a [`Printer`] processes its input, see
+/// [`PrinterBuilder::event_hook`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PrinterEvent {
+    /// Another block of [`PrinterBuilder::io_buffer_size`] bytes has been
+    /// read, `bytes_read` in total.
+    Progress { bytes_read: u64 },
+    /// A match of the patterns of [`PrinterBuilder::find`] has been found.
+    Match(FindMatch),
+    /// The lines of a region of repeated bytes have been squeezed. The event
+    /// follows the end of the region.
+    Squeezed(SqueezedRegion),
+}
+
+/// A function that is called with every [`PrinterEvent`]. An error stops the
+/// printer like an error of the writer.
+pub type EventHook = Box<dyn FnMut(&PrinterEvent) -> io::Result<()>>;
+
+/// The default of [`PrinterBuilder::io_buffer_size`].
+pub const DEFAULT_IO_BUFFER_SIZE: usize = 64 * 1024;
+
+/// The default of [`PrinterBuilder::max_position`], the largest position
+/// with 8 hex digits.
+pub const DEFAULT_MAX_POSITION: u64 = 0xffff_ffff;
+
+/// Builds a [`Printer`]. The writer may also be a trait object, so that the
+/// output can be chosen at runtime without monomorphizing the printer for
+/// every type of writer:
+///
+/// ```
+/// use std::io::{self, Write};
+///
+/// use hexyl::PrinterBuilder;
+///
+/// let mut buffer = vec![];
+/// let writer: &mut dyn Write = if std::env::args().count() > 1 {
+///     &mut buffer
+/// } else {
+///     &mut io::sink()
+/// };
+/// let mut printer = PrinterBuilder::new(writer).show_color(false).build();
+/// printer.print_all(&b"hello"[..]).unwrap();
+/// ```
+pub struct PrinterBuilder<'a, Writer: Write + ?Sized> {
+    writer: &'a mut Writer,
+    options: PrinterOptions,
+    color_hook: Option<ColorHook>,
+    event_hook: Option<EventHook>,
+    cancel_flag: Option<Arc<AtomicBool>>,
+    unreadable_bytes: Option<UnreadableBytes>,
+    gaps: Vec<Range<u64>>,
+    highlights: Vec<Highlight>,
+    position_highlights: Vec<Highlight>,
+    comments: Vec<Comment>,
+    find_patterns: Vec<FindPattern>,
+    found_matches: Option<Vec<FindMatch>>,
+    theme: Theme,
 }
 
-impl<'a, Writer: Write> PrinterBuilder<'a, Writer> {
+impl<'a, Writer: Write + ?Sized> PrinterBuilder<'a, Writer> {
     pub fn new(writer: &'a mut Writer) -> Self {
         PrinterBuilder {
             writer,
-            show_color: true,
-            show_char_panel: true,
-            show_position_panel: true,
-            border_style: BorderStyle::Unicode,
-            use_squeeze: true,
-            panels: 2,
-            group_size: 1,
-            base: Base::Hexadecimal,
-            endianness: Endianness::Big,
-            character_table: CharacterTable::Default,
+            options: PrinterOptions::default(),
+            color_hook: None,
+            event_hook: None,
+            cancel_flag: None,
+            unreadable_bytes: None,
+            gaps: vec![],
+            highlights: vec![],
+            position_highlights: vec![],
+            comments: vec![],
+            find_patterns: vec![],
+            found_matches: None,
+            theme: Theme::default(),
         }
     }
 
     pub fn show_color(mut self, show_color: bool) -> Self {
-        self.show_color = show_color;
+        self.options.show_color = show_color;
         self
     }
 
     pub fn show_char_panel(mut self, show_char_panel: bool) -> Self {
-        self.show_char_panel = show_char_panel;
+        self.options.show_char_panel = show_char_panel;
+        self
+    }
+
+    /// Selects the hex panels that have a character panel, e.g. only the
+    /// last one to save the width of the others on a narrow terminal.
+    pub fn char_panels(mut self, char_panels: CharPanels) -> Self {
+        self.options.char_panels = char_panels;
         self
     }
 
     pub fn show_position_panel(mut self, show_position_panel: bool) -> Self {
-        self.show_position_panel = show_position_panel;
+        self.options.show_position_panel = show_position_panel;
         self
     }
 
     pub fn with_border_style(mut self, border_style: BorderStyle) -> Self {
-        self.border_style = border_style;
+        self.options.border_style = border_style;
         self
     }
 
     pub fn enable_squeezing(mut self, enable: bool) -> Self {
-        self.use_squeeze = enable;
+        self.options.squeeze = enable;
         self
     }
 
     pub fn num_panels(mut self, num: u64) -> Self {
-        self.panels = num;
+        self.options.panels = num;
+        self
+    }
+
+    /// Sets the number of bytes in a panel, 8 by default, e.g. 16 for the
+    /// layout of `xxd` or 4 for narrow terminals. It has to be a multiple of
+    /// the group size.
+    pub fn panel_width(mut self, bytes: u64) -> Self {
+        self.options.panel_width = bytes;
         self
     }
 
     pub fn group_size(mut self, num: u8) -> Self {
-        self.group_size = num;
+        self.options.group_size = num;
         self
     }
 
     pub fn with_base(mut self, base: Base) -> Self {
-        self.base = base;
+        self.options.base = base;
         self
     }
 
     pub fn endianness(mut self, endianness: Endianness) -> Self {
-        self.endianness = endianness;
+        self.options.endianness = endianness;
         self
     }
 
     pub fn character_table(mut self, character_table: CharacterTable) -> Self {
-        self.character_table = character_table;
+        self.options.character_table = character_table;
         self
     }
 
-    pub fn build(self) -> Printer<'a, Writer> {
-        Printer::new(
-            self.writer,
-            self.show_color,
-            self.show_char_panel,
-            self.show_position_panel,
-            self.border_style,
-            self.use_squeeze,
-            self.panels,
-            self.group_size,
-            self.base,
-            self.endianness,
-            self.character_table,
-        )
+    /// Adds a column with a one-character [`LineClass`] tag for every line.
+    pub fn classify_lines(mut self, classify_lines: bool) -> Self {
+        self.options.classify_lines = classify_lines;
+        self
     }
-}
 
-pub struct Printer<'a, Writer: Write> {
-    idx: u64,
-    /// the buffer containing all the bytes in a line for character printing
-    line_buf: Vec<u8>,
-    writer: &'a mut Writer,
-    show_char_panel: bool,
-    show_position_panel: bool,
-    show_color: bool,
-    curr_color: Option<&'static [u8]>,
-    border_style: BorderStyle,
-    byte_hex_panel: Vec<String>,
-    byte_char_panel: Vec<String>,
-    // same as previous but in Fixed(242) gray color, for position panel
-    byte_hex_panel_g: Vec<String>,
-    squeezer: Squeezer,
-    display_offset: u64,
-    /// The number of panels to draw.
-    panels: u64,
-    squeeze_byte: usize,
-    /// The number of octets per group.
-    group_size: u8,
-    /// The number of digits used to write the base.
-    base_digits: u8,
-    /// Whether to show groups in little or big endian format.
-    endianness: Endianness,
-}
+    /// Adds a column with a [`LineHash`] of the bytes of every line, after
+    /// the one of [`classify_lines`](Self::classify_lines).
+    pub fn line_hash(mut self, line_hash: Option<LineHash>) -> Self {
+        self.options.line_hash = line_hash;
+        self
+    }
 
-impl<'a, Writer: Write> Printer<'a, Writer> {
-    fn new(
-        writer: &'a mut Writer,
-        show_color: bool,
-        show_char_panel: bool,
-        show_position_panel: bool,
-        border_style: BorderStyle,
-        use_squeeze: bool,
-        panels: u64,
-        group_size: u8,
-        base: Base,
-        endianness: Endianness,
-        character_table: CharacterTable,
-    ) -> Printer<'a, Writer> {
-        Printer {
-            idx: 0,
-            line_buf: vec![0x0; 8 * panels as usize],
-            writer,
-            show_char_panel,
+    /// Marks the position where the input ends with a `▏` in the hex panel.
+    /// If the input ends at the end of a line, an extra line with the final
+    /// position is printed to show the marker.
+    pub fn mark_eof(mut self, mark_eof: bool) -> Self {
+        self.options.mark_eof = mark_eof;
+        self
+    }
+
+    /// Prints a row below the header with the column of every byte (or
+    /// group) within the line in hex, `00 01 02 …`, in the hex panels and
+    /// its last digit in the character panels, to find the byte at a
+    /// position at a glance.
+    pub fn ruler(mut self, ruler: bool) -> Self {
+        self.options.ruler = ruler;
+        self
+    }
+
+    /// Adds one row per given base below every line, showing the same bytes
+    /// in that base (like `od -t x1 -t d1`). All rows use the width of the
+    /// widest base, so that the bytes are vertically aligned.
+    pub fn interleave(mut self, bases: Vec<Base>) -> Self {
+        self.options.interleave = bases;
+        self
+    }
+
+    /// Adds a row below every line that shows the groups in the other
+    /// endianness, labeled `le` or `be` in the position panel. This has no
+    /// effect if the group size is one.
+    pub fn both_endianness(mut self, both_endianness: bool) -> Self {
+        self.options.both_endianness = both_endianness;
+        self
+    }
+
+    /// Adds a row below every line that contains ASCII control characters,
+    /// showing their mnemonics (e.g. `NUL`, `ESC`, `DEL`) below the bytes.
+    /// All bytes are shown with (at least) three characters, so that the
+    /// mnemonics fit.
+    pub fn mnemonics(mut self, mnemonics: bool) -> Self {
+        self.options.mnemonics = mnemonics;
+        self
+    }
+
+    /// Resets all colors at the end of every line of output, so that no color
+    /// carries over to the next line if lines are cut off (e.g. by a pager).
+    pub fn reset_per_line(mut self, reset_per_line: bool) -> Self {
+        self.options.reset_per_line = reset_per_line;
+        self
+    }
+
+    /// Colors every byte (and every tick mark) on its own, with a reset after
+    /// it, instead of only switching colors where they change. The color of a
+    /// cell then does not depend on anything that was written before it, so
+    /// that parts of a line can be cut out without dangling escape codes.
+    pub fn stateless_colors(mut self, stateless_colors: bool) -> Self {
+        self.options.stateless_colors = stateless_colors;
+        self
+    }
+
+    /// Overrides the colors of individual bytes, see [`ColorHook`]. The hook
+    /// is only called for lines that are actually displayed, and only if
+    /// colors are enabled.
+    pub fn color_hook(
+        mut self,
+        hook: impl FnMut(u64, &[u8]) -> io::Result<Vec<Option<&'static [u8]>>> + 'static,
+    ) -> Self {
+        self.color_hook = Some(Box::new(hook));
+        self
+    }
+
+    /// Calls `hook` with the progress, the matches and the squeezed regions
+    /// while the input is processed, see [`PrinterEvent`], e.g. to report
+    /// them to another program. Unlike the [`ColorHook`], the hook is also
+    /// called for the lines that are filtered out.
+    pub fn event_hook(
+        mut self,
+        hook: impl FnMut(&PrinterEvent) -> io::Result<()> + 'static,
+    ) -> Self {
+        self.event_hook = Some(Box::new(hook));
+        self
+    }
+
+    /// Sets how the lines are written, as a table or as JSON objects, see
+    /// [`LineFormat`].
+    pub fn line_format(mut self, line_format: LineFormat) -> Self {
+        self.options.line_format = line_format;
+        self
+    }
+
+    /// Sets how positions are shown in the position panel.
+    pub fn offset_style(mut self, offset_style: OffsetStyle) -> Self {
+        self.options.offset_style = offset_style;
+        self
+    }
+
+    /// Sets the largest position (including the display offset) that is
+    /// shown, so that the position panel is wide enough for it, e.g. the end
+    /// of the input. By default, it is wide enough for positions below 4 GiB.
+    /// Larger positions are still shown in full, but make their lines wider
+    /// than the others.
+    pub fn max_position(mut self, max_position: u64) -> Self {
+        self.options.max_position = max_position;
+        self
+    }
+
+    /// Shows the positions in the position panel relative to `base`, e.g. a
+    /// load address or the start of a structure, by subtracting it from them.
+    /// Positions before `base` are shown as the distance to it with a `-` in
+    /// front. Everything else, like the highlights, the comments and the
+    /// hyperlinks of the offsets, still uses the positions without `base`.
+    pub fn relative_to(mut self, base: u64) -> Self {
+        self.options.relative_to = base;
+        self
+    }
+
+    /// Sets how the digits of a byte are padded, e.g. `007` or `  7` for the
+    /// decimal base.
+    pub fn padding(mut self, padding: Padding) -> Self {
+        self.options.padding = padding;
+        self
+    }
+
+    /// Shows the positions of the bytes for input that only contains every
+    /// `step`th byte of the original data (see [`StepReader`]), so that the
+    /// displayed positions are those in the original data. The phase of the
+    /// `StepReader` can be added with [`Printer::display_offset`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` is zero.
+    pub fn step(mut self, step: u64) -> Self {
+        assert!(step > 0, "the step must not be zero");
+        self.options.step = step;
+        self
+    }
+
+    /// Only shows lines that contain at least one of the given bytes, and
+    /// highlights these bytes. If no bytes are given, all lines are shown.
+    /// See [`Printer::filter_matches`] for the number of matches.
+    pub fn filter_bytes(mut self, bytes: Vec<u8>) -> Self {
+        self.options.filter_bytes = bytes;
+        self
+    }
+
+    /// Only shows lines that contain a match of one of the patterns, or a part
+    /// of one, and highlights the matched bytes. Matches can span several
+    /// lines and overlap. To find the matches that cover a line, the bytes
+    /// after it are read before it is printed. See [`Printer::find_matches`]
+    /// for the positions of the matches.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the patterns is empty.
+    pub fn find(mut self, patterns: Vec<FindPattern>) -> Self {
+        assert!(
+            patterns.iter().all(|pattern| !pattern.bytes.is_empty()),
+            "the patterns must not be empty"
+        );
+        self.find_patterns = patterns;
+        self
+    }
+
+    /// Uses these matches of the [`find`](Self::find) patterns instead of
+    /// searching the input, e.g. matches that several threads have searched
+    /// in parts of a file. They must be ordered by the position of their end,
+    /// and then by pattern.
+    pub fn found_matches(mut self, matches: Vec<FindMatch>) -> Self {
+        self.found_matches = Some(matches);
+        self
+    }
+
+    /// Stops printing once `flag` is set, e.g. by another thread, so that a
+    /// long dump can be aborted. The flag is checked before every line, and
+    /// [`Printer::append`] returns an error of kind [`io::ErrorKind::Other`]
+    /// if it is set. [`Printer::finish`] can still be called afterwards to
+    /// print the footer.
+    pub fn cancel_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.cancel_flag = Some(flag);
+        self
+    }
+
+    /// Shows the bytes that a [`RecoveringReader`] could not read in a
+    /// distinct color and as '▒' in the character panel ('?' with
+    /// [`CharacterTable::Ascii`]). The positions refer to the bytes passed to
+    /// the printer.
+    pub fn unreadable_bytes(mut self, unreadable_bytes: UnreadableBytes) -> Self {
+        self.unreadable_bytes = Some(unreadable_bytes);
+        self
+    }
+
+    /// Records the ranges of positions that are shown, see [`Printer::spans`].
+    pub fn track_spans(mut self, track_spans: bool) -> Self {
+        self.options.track_spans = track_spans;
+        self
+    }
+
+    /// Limits the number of bytes of memory used for data that grows with the
+    /// input, which are only the spans recorded with
+    /// [`PrinterBuilder::track_spans`]. Everything else only needs memory in
+    /// proportion to the length of a line. If the limit would be exceeded,
+    /// printing fails with an error of kind [`io::ErrorKind::OutOfMemory`].
+    pub fn max_memory(mut self, max_memory: u64) -> Self {
+        self.options.max_memory = max_memory;
+        self
+    }
+
+    /// Makes every offset in the position panel a hyperlink (OSC 8) to the
+    /// URL built from `template`, in which `{offset}` is replaced by the
+    /// offset in hex and `{offset_dec}` by the offset in decimal. Hyperlinks
+    /// are only emitted if colors are shown.
+    pub fn offset_hyperlink(mut self, template: String) -> Self {
+        self.options.offset_hyperlink = Some(template);
+        self
+    }
+
+    /// Shows the bytes of the given regions in their colors, with later
+    /// regions taking precedence where they overlap. The labels are shown at
+    /// the end of the line where their region starts, or of the next shown
+    /// line if that one is squeezed or filtered out.
+    pub fn highlights(mut self, highlights: Vec<Highlight>) -> Self {
+        self.highlights = highlights;
+        self
+    }
+
+    /// Shows the bytes in the ranges of positions (including the display
+    /// offset) as `__` in the hex panel and as a blank in the character panel,
+    /// e.g. the gaps between the inputs of a [`Sparse`] input, which read as
+    /// zeros. The ranges must be sorted and must not overlap.
+    pub fn gaps(mut self, gaps: Vec<Range<u64>>) -> Self {
+        self.gaps = gaps;
+        self
+    }
+
+    /// Shows the position of every line that starts in one of the regions in
+    /// the color of the region instead of the offset color of the theme, with
+    /// later regions taking precedence where they overlap. The labels are not
+    /// used.
+    pub fn position_highlights(mut self, position_highlights: Vec<Highlight>) -> Self {
+        self.position_highlights = position_highlights;
+        self
+    }
+
+    /// Shows the comments at the end of the lines that contain their
+    /// positions, after the labels of the highlights, or at the end of the
+    /// next shown line if that one is squeezed or filtered out. Further
+    /// comments of a line are shown on their own rows with empty panels below
+    /// it. Control characters in the comments are replaced.
+    pub fn comments(mut self, comments: Vec<Comment>) -> Self {
+        self.comments = comments;
+        self
+    }
+
+    /// Wraps comments that are wider than `width` columns at spaces, and
+    /// continues them on rows with empty panels, see
+    /// [`PrinterBuilder::comments`]. Words that are wider are split. By
+    /// default, comments are not wrapped.
+    pub fn comment_width(mut self, width: usize) -> Self {
+        self.options.comment_width = Some(width);
+        self
+    }
+
+    /// Shows a legend of the settings in the top border, e.g.
+    /// `[hex|2 panels|g1|BE|default]` for the base, the number of panels, the
+    /// group size, the endianness and the character table. The legend is left
+    /// out if it does not fit into the border.
+    pub fn show_settings(mut self, show_settings: bool) -> Self {
+        self.options.show_settings = show_settings;
+        self
+    }
+
+    /// Shows the name of the input, the positions of the first and the last
+    /// byte that have been printed and their number in the bottom border, e.g.
+    /// `[file.bin 0x400-0x7ff (1 KiB)]`, so that a screenshot still shows what
+    /// it is about if the top is cropped. The name is set with
+    /// [`PrinterBuilder::input_name`]. If the text does not fit into the
+    /// border, the name is left out, and then the whole text.
+    pub fn footer_info(mut self, footer_info: bool) -> Self {
+        self.options.footer_info = footer_info;
+        self
+    }
+
+    /// Sets the name of the input, e.g. its file name, for
+    /// [`PrinterBuilder::footer_info`].
+    pub fn input_name(mut self, input_name: impl Into<String>) -> Self {
+        self.options.input_name = Some(input_name.into());
+        self
+    }
+
+    /// Shows a mark in the middle of every panel, e.g. between the fourth and
+    /// the fifth byte, both in the hex panel and in the character panel, to
+    /// make it easier to find the byte of a character. In the hex panel, the
+    /// mark replaces the space before the byte, so there is no mark if a
+    /// group spans the middle. In the character panel, it takes an extra
+    /// column. Panels of a single byte have no mark.
+    pub fn tick_marks(mut self, tick_marks: bool) -> Self {
+        self.options.tick_marks = tick_marks;
+        self
+    }
+
+    /// Sets the character of the [`PrinterBuilder::tick_marks`], instead of
+    /// the one of the border style, e.g. `-` for the layout of DOS DEBUG.
+    pub fn tick_mark(mut self, tick_mark: Option<char>) -> Self {
+        self.options.tick_mark = tick_mark;
+        self
+    }
+
+    /// Whether the [`PrinterBuilder::tick_marks`] are shown in the character
+    /// panels, too (the default), or only in the hex panels.
+    pub fn char_panel_tick_marks(mut self, char_panel_tick_marks: bool) -> Self {
+        self.options.char_panel_tick_marks = char_panel_tick_marks;
+        self
+    }
+
+    /// Shows the hex digits of the bytes and of the positions in uppercase,
+    /// e.g. `DE AD BE EF`.
+    pub fn uppercase(mut self, uppercase: bool) -> Self {
+        self.options.uppercase = uppercase;
+        self
+    }
+
+    /// Prints a summary of the squeezed regions below the footer, one line
+    /// per region with its first and last position, the number of bytes and
+    /// the repeated byte, e.g. `0x400-0xfff: 3072 bytes of 0x00`. See
+    /// [`Printer::squeezed_regions`] for the regions themselves. The regions
+    /// count towards [`PrinterBuilder::max_memory`].
+    pub fn squeeze_summary(mut self, squeeze_summary: bool) -> Self {
+        self.options.squeeze_summary = squeeze_summary;
+        self
+    }
+
+    /// Sets the marker of squeezed lines, `*` by default, e.g. `…` or
+    /// `[snip]`. It is shown in the position panel, or at the start of the hex
+    /// panel without a position panel, in the color
+    /// [`Theme::squeeze`]. The marker must be 1 to 8 columns wide.
+    pub fn squeeze_marker(mut self, squeeze_marker: impl Into<String>) -> Self {
+        self.options.squeeze_marker = squeeze_marker.into();
+        self
+    }
+
+    /// Prints a legend below the footer with one line for every highlight
+    /// that has a label, see [`PrinterBuilder::highlights`]. Every line shows
+    /// the label in the color of the region and the range of the region,
+    /// ordered by the start of the regions.
+    pub fn highlight_legend(mut self, highlight_legend: bool) -> Self {
+        self.options.highlight_legend = highlight_legend;
+        self
+    }
+
+    /// Sets the number of bytes that are read from the input at once, by
+    /// default [`DEFAULT_IO_BUFFER_SIZE`]. Lines are then taken from this
+    /// buffer, so that the input is not read in tiny pieces of the length of
+    /// a line. Larger reads can be faster on network file systems, smaller
+    /// ones show the first lines of a slow input sooner.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    pub fn io_buffer_size(mut self, size: usize) -> Self {
+        assert!(size > 0, "the I/O buffer size must not be zero");
+        self.options.io_buffer_size = size;
+        self
+    }
+
+    /// Removes terminal escape sequences (CSI and OSC, e.g. colors, cursor
+    /// movements or window titles) from the texts that do not come from the
+    /// input, and replaces their other control characters, like BEL, by
+    /// placeholders: the labels of the highlights, the comments, the name of
+    /// the input and the template of [`PrinterBuilder::offset_hyperlink`].
+    /// Use it if these texts come from untrusted sources, e.g. the names in
+    /// the debug information of a binary. The character panel and the colors
+    /// of the theme never contain control characters anyway.
+    pub fn strict_sanitize(mut self, strict_sanitize: bool) -> Self {
+        self.options.strict_sanitize = strict_sanitize;
+        self
+    }
+
+    /// Sets the colors of the byte categories and of the position panel.
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Replaces all settings that [`PrinterOptions`] describes, e.g. with
+    /// options that were deserialized from a configuration file.
+    pub fn options(mut self, options: PrinterOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Returns the number of terminal columns that a line of output occupies
+    /// with the current settings, including the border (or the space that
+    /// replaces it).
+    pub fn layout_width(&self) -> usize {
+        self.options.layout_width()
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the step or the I/O buffer size of the options is zero, or if
+    /// the squeeze marker is not 1 to 8 columns wide.
+    pub fn build(self) -> Printer<'a, Writer> {
+        assert!(self.options.step > 0, "the step must not be zero");
+        assert!(
+            self.options.panel_width > 0
+                && self.options.panel_width % u64::from(self.options.group_size) == 0,
+            "the panel width must be a nonzero multiple of the group size"
+        );
+        assert!(
+            self.options.io_buffer_size > 0,
+            "the I/O buffer size must not be zero"
+        );
+        assert!(
+            is_valid_squeeze_marker(&self.options.squeeze_marker),
+            "the squeeze marker must be 1 to 8 columns wide"
+        );
+        let base_digits = self.options.base_digits();
+        let settings_legend = self
+            .options
+            .show_settings
+            .then(|| self.options.settings_legend());
+        let mut printer = Printer::new(
+            self.writer,
+            self.options.show_color,
+            self.options.show_char_panel,
+            self.options.show_position_panel,
+            self.options.border_style,
+            self.options.squeeze,
+            self.options.panels,
+            self.options.group_size,
+            self.options.base,
+            self.options.endianness,
+            self.options.character_table,
+        );
+        printer.panel_width = self.options.panel_width;
+        let bytes_per_line = printer.bytes_per_line() as usize;
+        printer.line_buf = vec![0x0; bytes_per_line];
+        printer.squeezer = Squeezer::new(self.options.squeeze, bytes_per_line);
+        printer.classify_lines = self.options.classify_lines;
+        printer.line_hash = self.options.line_hash;
+        printer.mark_eof = self.options.mark_eof;
+        printer.ruler = self.options.ruler;
+        printer.byte_hex_panel = self
+            .options
+            .base
+            .byte_table(base_digits, self.options.padding);
+        if self.options.uppercase {
+            for digits in printer
+                .byte_hex_panel
+                .iter_mut()
+                .chain(&mut printer.byte_hex_panel_g)
+            {
+                digits.make_ascii_uppercase();
+            }
+        }
+        if self.options.both_endianness && self.options.group_size > 1 {
+            let (endianness, label) = match self.options.endianness {
+                Endianness::Big => (Endianness::Little, "le"),
+                Endianness::Little => (Endianness::Big, "be"),
+            };
+            printer.interleave.push(InterleavedRow {
+                table: printer.byte_hex_panel.clone(),
+                endianness,
+                label: Some(label),
+            });
+        }
+        printer
+            .interleave
+            .extend(self.options.interleave.iter().map(|base| InterleavedRow {
+                table: base.byte_table(base_digits, self.options.padding),
+                endianness: self.options.endianness,
+                label: None,
+            }));
+        if self.options.mnemonics {
+            printer.interleave.push(InterleavedRow {
+                table: (0..=u8::MAX)
+                    .map(|b| {
+                        let mnemonic = Byte(b).mnemonic().unwrap_or("");
+                        format!("{mnemonic:>0$}", base_digits as usize)
+                    })
+                    .collect(),
+                endianness: self.options.endianness,
+                label: None,
+            });
+        }
+        printer.base_digits = base_digits;
+        printer.reset_per_line = self.options.reset_per_line;
+        printer.stateless_colors = self.options.stateless_colors;
+        printer.color_hook = self.color_hook;
+        printer.event_hook = self.event_hook;
+        printer.offset_style = self.options.offset_style;
+        printer.line_format = self.options.line_format;
+        printer.max_position = self.options.max_position;
+        printer.relative_to = self.options.relative_to;
+        printer.step = self.options.step;
+        for b in self.options.filter_bytes {
+            printer.filter_bytes[b as usize] = true;
+            printer.filter_enabled = true;
+        }
+        printer.finder = Finder::new(self.find_patterns, self.options.max_memory).map(|finder| {
+            match self.found_matches {
+                Some(found) => finder.with_found(found),
+                None => finder,
+            }
+        });
+        printer.cancel_flag = self.cancel_flag;
+        printer.unreadable_bytes = self.unreadable_bytes;
+        printer.gaps = self.gaps;
+        printer.track_spans = self.options.track_spans;
+        printer.squeeze_summary = self.options.squeeze_summary;
+        printer.squeeze_marker = self.options.squeeze_marker;
+        printer.max_memory = self.options.max_memory;
+        printer.offset_hyperlink = self.options.offset_hyperlink;
+        printer.label_order = (0..self.highlights.len()).collect();
+        printer
+            .label_order
+            .sort_by_key(|&i| self.highlights[i].range.start);
+        printer.settings_legend = settings_legend;
+        printer.footer_info = self.options.footer_info;
+        printer.tick_marks = self.options.tick_marks;
+        printer.char_panels = self.options.char_panels;
+        printer.tick_mark = self.options.tick_mark;
+        printer.char_panel_tick_marks = self.options.char_panel_tick_marks;
+        printer.highlight_legend = self.options.highlight_legend;
+        printer.io_buffer_size = self.options.io_buffer_size;
+        printer.theme = self.theme;
+        printer.highlights = self.highlights;
+        printer.position_highlights = self.position_highlights;
+        printer.comments = self.comments;
+        if self.options.strict_sanitize {
+            printer.offset_hyperlink = printer.offset_hyperlink.as_deref().map(sanitize);
+            printer.input_name = self.options.input_name.as_deref().map(sanitize);
+            for highlight in &mut printer.highlights {
+                highlight.label = highlight.label.as_deref().map(sanitize);
+            }
+            for comment in &mut printer.comments {
+                comment.text = sanitize(&comment.text);
+            }
+        } else {
+            printer.input_name = self.options.input_name;
+            for comment in &mut printer.comments {
+                comment.text = comment
+                    .text
+                    .chars()
+                    .map(|c| if c.is_control() { PLACEHOLDER_CHAR } else { c })
+                    .collect();
+            }
+        }
+        // A stable sort keeps the order of the comments at the same position.
+        printer.comments.sort_by_key(|comment| comment.position);
+        printer.comment_width = self.options.comment_width;
+        printer.separators = separators(printer.border_style, &printer.panel_widths());
+        printer
+    }
+}
+
+/// An additional row below every line, see [`PrinterBuilder::interleave`].
+struct InterleavedRow {
+    /// The representations of all byte values.
+    table: Vec<String>,
+    /// The order of the bytes within a group.
+    endianness: Endianness,
+    /// Shown in the position panel.
+    label: Option<&'static str>,
+}
+
+pub struct Printer<'a, Writer: Write + ?Sized> {
+    idx: u64,
+    /// the total number of bytes read from the input so far
+    bytes_read: u64,
+    /// the buffer containing all the bytes in a line for character printing
+    line_buf: Vec<u8>,
+    /// the number of bytes in `line_buf` that have been filled so far
+    line_len: usize,
+    /// whether the header and the first line have been printed already
+    header_printed: bool,
+    writer: &'a mut Writer,
+    show_char_panel: bool,
+    show_position_panel: bool,
+    show_color: bool,
+    curr_color: Option<&'static [u8]>,
+    border_style: BorderStyle,
+    byte_hex_panel: Vec<String>,
+    byte_char_panel: Vec<String>,
+    // same as previous but in Fixed(242) gray color, for position panel
+    byte_hex_panel_g: Vec<String>,
+    squeezer: Squeezer,
+    /// how the current line is rendered
+    squeeze_action: SqueezeAction,
+    display_offset: u64,
+    /// The distance between two displayed bytes in the original data.
+    step: u64,
+    /// Whether only lines with one of the `filter_bytes` are shown.
+    filter_enabled: bool,
+    filter_bytes: [bool; 256],
+    /// The number of matching bytes and lines with `filter_bytes`.
+    filter_matches: FilterMatches,
+    /// Searches the patterns of [`PrinterBuilder::find`], if any.
+    finder: Option<Finder>,
+    /// Printing stops once this flag is set.
+    cancel_flag: Option<Arc<AtomicBool>>,
+    event_hook: Option<EventHook>,
+    /// The squeezed region that has not been reported to the `event_hook`
+    /// yet, as it may continue.
+    squeezed_region: Option<SqueezedRegion>,
+    /// The JSON object of the first squeezed line without its end, which is
+    /// written with the length of the squeezed region once it has ended.
+    squeezed_json_line: Option<Vec<u8>>,
+    /// The positions of the bytes that could not be read.
+    unreadable_bytes: Option<UnreadableBytes>,
+    /// How unreadable bytes are shown in the character panel.
+    unreadable_char: &'static str,
+    /// The positions of the bytes that are not part of the input.
+    gaps: Vec<Range<u64>>,
+    /// Whether to record the `spans` of shown positions.
+    track_spans: bool,
+    spans: Vec<Range<u64>>,
+    /// Whether to record the `squeezed_regions` and print them after the
+    /// footer.
+    squeeze_summary: bool,
+    squeeze_marker: String,
+    squeezed_regions: Vec<SqueezedRegion>,
+    /// The number of bytes of memory that the `spans` and the
+    /// `squeezed_regions` may use, each.
+    max_memory: u64,
+    /// The URL template for hyperlinks on the offsets.
+    offset_hyperlink: Option<String>,
+    highlights: Vec<Highlight>,
+    /// The indices of the `highlights`, ordered by their start.
+    label_order: Vec<usize>,
+    /// The regions whose lines have their position shown in another color.
+    position_highlights: Vec<Highlight>,
+    /// The number of entries in `label_order` whose labels have been shown.
+    labels_shown: usize,
+    /// The comments, ordered by their position.
+    comments: Vec<Comment>,
+    /// The number of `comments` that have been shown.
+    comments_shown: usize,
+    comment_width: Option<usize>,
+    /// The rows of the comments of the current line that follow it.
+    comment_rows: Vec<String>,
+    /// The number of rows that have been printed.
+    rows: u64,
+    /// The number of rows of the incomplete line that is shown by
+    /// `print_incomplete_line`, which are overwritten by the next line.
+    incomplete_rows: u64,
+    /// The number of panels to draw.
+    panels: u64,
+    /// The number of bytes per panel.
+    panel_width: u64,
+    /// The number of octets per group.
+    group_size: u8,
+    /// The number of digits used to write the base.
+    base_digits: u8,
+    /// Whether to show groups in little or big endian format.
+    endianness: Endianness,
+    /// Whether to show a column with the class of each line.
+    classify_lines: bool,
+    /// The checksum to show in a column for each line, if any.
+    line_hash: Option<LineHash>,
+    /// The separators of a line, see [`separators`].
+    separators: Vec<&'static str>,
+    /// Whether to mark the position where the input ends.
+    mark_eof: bool,
+    /// Whether to print the columns of the bytes below the header.
+    ruler: bool,
+    /// The byte representations for each additional row below a line.
+    interleave: Vec<InterleavedRow>,
+    /// Whether to reset all colors at the end of every line.
+    reset_per_line: bool,
+    stateless_colors: bool,
+    color_hook: Option<ColorHook>,
+    /// The colors returned by the `color_hook` for the current line.
+    line_colors: Vec<Option<&'static [u8]>>,
+    /// Receives the colors instead of the writer, see
+    /// [`render_to_lines`](styled::render_to_lines).
+    style_hook: Option<Box<dyn FnMut(&'static [u8])>>,
+    offset_style: OffsetStyle,
+    line_format: LineFormat,
+    max_position: u64,
+    /// Subtracted from the positions in the position panel.
+    relative_to: u64,
+    /// Shown in the top border.
+    settings_legend: Option<String>,
+    footer_info: bool,
+    input_name: Option<String>,
+    tick_marks: bool,
+    char_panels: CharPanels,
+    /// The character of the tick marks, instead of the one of the border.
+    tick_mark: Option<char>,
+    char_panel_tick_marks: bool,
+    highlight_legend: bool,
+    /// The number of bytes that are read from the input at once.
+    io_buffer_size: usize,
+    theme: Theme,
+}
+
+impl<'a, Writer: Write + ?Sized> Printer<'a, Writer> {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        writer: &'a mut Writer,
+        show_color: bool,
+        show_char_panel: bool,
+        show_position_panel: bool,
+        border_style: BorderStyle,
+        use_squeeze: bool,
+        panels: u64,
+        group_size: u8,
+        base: Base,
+        endianness: Endianness,
+        character_table: CharacterTable,
+    ) -> Printer<'a, Writer> {
+        let mut printer = Printer {
+            idx: 0,
+            bytes_read: 0,
+            line_buf: vec![0x0; 8 * panels as usize],
+            line_len: 0,
+            header_printed: false,
+            writer,
+            show_char_panel,
             show_position_panel,
             show_color,
             curr_color: None,
             border_style,
-            byte_hex_panel: (0u8..=u8::MAX)
-                .map(|i| match base {
-                    Base::Binary => format!("{i:08b}"),
-                    Base::Octal => format!("{i:03o}"),
-                    Base::Decimal => format!("{i:03}"),
-                    Base::Hexadecimal => format!("{i:02x}"),
-                })
-                .collect(),
+            byte_hex_panel: base.byte_table(base.digits(), Padding::Zeros),
             byte_char_panel: (0u8..=u8::MAX)
-                .map(|i| format!("{}", Byte(i).as_char(character_table)))
+                .map(|i| single_column(Byte(i).as_char(character_table)).to_string())
                 .collect(),
             byte_hex_panel_g: (0u8..=u8::MAX).map(|i| format!("{i:02x}")).collect(),
-            squeezer: if use_squeeze {
-                Squeezer::Ignore
-            } else {
-                Squeezer::Disabled
-            },
+            squeezer: Squeezer::new(use_squeeze, 8 * panels as usize),
+            squeeze_action: SqueezeAction::Ignore,
             display_offset: 0,
+            step: 1,
+            filter_enabled: false,
+            filter_bytes: [false; 256],
+            filter_matches: FilterMatches::default(),
+            finder: None,
+            cancel_flag: None,
+            event_hook: None,
+            squeezed_region: None,
+            squeezed_json_line: None,
+            unreadable_bytes: None,
+            unreadable_char: match character_table {
+                CharacterTable::Ascii => "?",
+                _ => "▒",
+            },
+            gaps: vec![],
+            track_spans: false,
+            spans: vec![],
+            squeeze_summary: false,
+            squeeze_marker: "*".to_owned(),
+            squeezed_regions: vec![],
+            max_memory: u64::MAX,
+            offset_hyperlink: None,
+            highlights: vec![],
+            position_highlights: vec![],
+            label_order: vec![],
+            labels_shown: 0,
+            comments: vec![],
+            comments_shown: 0,
+            comment_width: None,
+            comment_rows: vec![],
+            rows: 0,
+            incomplete_rows: 0,
             panels,
-            squeeze_byte: 0x00,
+            panel_width: 8,
             group_size,
-            base_digits: match base {
-                Base::Binary => 8,
-                Base::Octal => 3,
-                Base::Decimal => 3,
-                Base::Hexadecimal => 2,
-            },
+            base_digits: base.digits(),
             endianness,
-        }
+            classify_lines: false,
+            line_hash: None,
+            separators: vec![],
+            mark_eof: false,
+            ruler: false,
+            interleave: vec![],
+            reset_per_line: false,
+            stateless_colors: false,
+            color_hook: None,
+            line_colors: vec![],
+            style_hook: None,
+            offset_style: OffsetStyle::Byte,
+            line_format: LineFormat::Table,
+            max_position: DEFAULT_MAX_POSITION,
+            relative_to: 0,
+            settings_legend: None,
+            footer_info: false,
+            input_name: None,
+            tick_marks: false,
+            char_panels: CharPanels::All,
+            tick_mark: None,
+            char_panel_tick_marks: true,
+            highlight_legend: false,
+            io_buffer_size: DEFAULT_IO_BUFFER_SIZE,
+            theme: Theme::default(),
+        };
+        printer.separators = separators(border_style, &printer.panel_widths());
+        printer
     }
 
     pub fn display_offset(&mut self, display_offset: u64) -> &mut Self {
@@ -375,470 +1919,3581 @@ impl<'a, Writer: Write> Printer<'a, Writer> {
         self
     }
 
-    fn panel_sz(&self) -> usize {
-        // add one to include the trailing space of a group
-        let group_sz = self.base_digits as usize * self.group_size as usize + 1;
-        let group_per_panel = 8 / self.group_size as usize;
-        // add one to include the leading space
-        1 + group_sz * group_per_panel
+    /// Returns the ranges of positions (including the display offset) that
+    /// have been shown so far, including squeezed lines. Adjacent lines are
+    /// merged into a single span. Spans are only recorded if enabled with
+    /// [`PrinterBuilder::track_spans`].
+    pub fn spans(&self) -> &[Range<u64>] {
+        &self.spans
     }
 
-    fn write_border(&mut self, border_elements: BorderElements) -> io::Result<()> {
-        let h = border_elements.horizontal_line;
-        let c = border_elements.column_separator;
-        let l = border_elements.left_corner;
-        let r = border_elements.right_corner;
-        let h8 = h.to_string().repeat(8);
-        let h_repeat = h.to_string().repeat(self.panel_sz());
+    /// Returns the regions that have been squeezed so far. Adjacent squeezed
+    /// lines are merged into a single region. Regions are only recorded if
+    /// enabled with [`PrinterBuilder::squeeze_summary`].
+    pub fn squeezed_regions(&self) -> &[SqueezedRegion] {
+        &self.squeezed_regions
+    }
 
-        if self.show_position_panel {
-            write!(self.writer, "{l}{h8}{c}")?;
-        } else {
-            write!(self.writer, "{l}")?;
-        }
+    /// Returns the number of bytes and lines that contained one of the bytes
+    /// given to [`PrinterBuilder::filter_bytes`] so far.
+    pub fn filter_matches(&self) -> FilterMatches {
+        self.filter_matches
+    }
 
-        for _ in 0..self.panels - 1 {
-            write!(self.writer, "{h_repeat}{c}")?;
-        }
+    /// Returns the matches of the patterns given to [`PrinterBuilder::find`]
+    /// that have been found so far, ordered by their end. Matches at the end
+    /// of the input that has been appended so far may only be found by
+    /// [`Printer::finish`].
+    pub fn find_matches(&self) -> &[FindMatch] {
+        self.finder.as_ref().map_or(&[], Finder::matches)
+    }
+
+    /// Returns the number of bytes that have been read from the input so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// Returns the number of terminal columns that a line of output occupies.
+    pub fn layout_width(&self) -> usize {
+        line_width(self.border_style, &self.panel_widths())
+    }
+
+    fn position_width(&self) -> usize {
+        self.offset_style.width(self.max_position)
+    }
+
+    /// The number of character panels in a line.
+    fn char_panel_count(&self) -> u64 {
         if self.show_char_panel {
-            write!(self.writer, "{h_repeat}{c}")?;
+            self.char_panels.count(self.panels)
         } else {
-            write!(self.writer, "{h_repeat}")?;
+            0
         }
+    }
 
-        if self.show_char_panel {
-            for _ in 0..self.panels - 1 {
-                write!(self.writer, "{h8}{c}")?;
-            }
-            writeln!(self.writer, "{h8}{r}")?;
-        } else {
-            writeln!(self.writer, "{r}")?;
+    /// The indices of the bytes of a line that are shown in the character
+    /// panels.
+    fn char_panel_bytes(&self) -> Range<u64> {
+        let bytes_per_line = self.bytes_per_line();
+        match self.char_panels {
+            CharPanels::All => 0..bytes_per_line,
+            CharPanels::First => 0..self.panel_width,
+            CharPanels::Last => bytes_per_line - self.panel_width..bytes_per_line,
+            CharPanels::None => 0..0,
         }
+    }
 
-        Ok(())
+    fn panel_widths(&self) -> Vec<(PanelKind, usize)> {
+        panel_widths(
+            self.show_position_panel.then(|| self.position_width()),
+            self.char_panel_count(),
+            self.classify_lines,
+            self.line_hash,
+            self.panels,
+            self.panel_width,
+            self.base_digits,
+            self.group_size,
+            self.tick_marks && self.char_panel_tick_marks,
+        )
     }
 
-    pub fn print_header(&mut self) -> io::Result<()> {
-        if let Some(e) = self.border_style.header_elems() {
-            self.write_border(e)?
-        }
-        Ok(())
+    /// The separator at the start of a line.
+    fn start_separator(&self) -> &'static str {
+        self.separators[0]
     }
 
-    pub fn print_footer(&mut self) -> io::Result<()> {
-        if let Some(e) = self.border_style.footer_elems() {
-            self.write_border(e)?
+    /// The separator after the `n`-th panel of kind `kind` in a line.
+    fn separator_after(&self, kind: PanelKind, n: usize) -> &'static str {
+        let position = usize::from(self.show_position_panel);
+        let hex = self.panels as usize;
+        let chars = self.char_panel_count() as usize;
+        let index = match kind {
+            PanelKind::Position => 0,
+            PanelKind::Hex => position + n,
+            PanelKind::Char => position + hex + n,
+            PanelKind::LineClass => position + hex + chars,
+            PanelKind::LineHash => position + hex + chars + usize::from(self.classify_lines),
+        };
+        self.separators[index + 1]
+    }
+
+    /// The number of bytes in a line.
+    fn bytes_per_line(&self) -> u64 {
+        self.panel_width * self.panels
+    }
+
+    /// The index of the byte in a panel that follows the tick mark, if any.
+    fn tick_mark_index(&self) -> Option<usize> {
+        has_tick_marks(self.tick_marks, self.panel_width).then_some(self.panel_width as usize / 2)
+    }
+
+    /// The index of the character in every character panel that is preceded
+    /// by a tick mark, if any.
+    fn char_tick_mark_index(&self) -> Option<usize> {
+        self.tick_mark_index()
+            .filter(|_| self.char_panel_tick_marks)
+    }
+
+    /// Whether the space before the byte `i` of a line is replaced by a tick
+    /// mark in the hex panel.
+    fn has_tick_mark_before(&self, i: usize) -> bool {
+        self.tick_mark_index() == Some(i % self.panel_width as usize)
+            && i % (self.group_size as usize) == 0
+    }
+
+    /// Writes the tick mark in the color of the position panel.
+    fn print_tick_mark(&mut self) -> io::Result<()> {
+        if self.show_color && self.curr_color != Some(self.theme.offset) {
+            self.write_color(self.theme.offset)?;
+            self.curr_color = Some(self.theme.offset);
         }
-        Ok(())
+        let tick_mark = self
+            .tick_mark
+            .unwrap_or_else(|| self.border_style.tick_mark());
+        self.writer
+            .write_all(tick_mark.encode_utf8(&mut [0; 4]).as_bytes())?;
+        self.end_cell()
     }
 
-    fn print_position_panel(&mut self) -> io::Result<()> {
-        self.writer.write_all(
-            self.border_style
-                .outer_sep()
-                .encode_utf8(&mut [0; 4])
-                .as_bytes(),
-        )?;
-        if self.show_color {
-            self.writer.write_all(COLOR_OFFSET)?;
+    /// Writes the space before the byte `i` of a line in the hex panel, or the
+    /// tick mark in its place.
+    fn print_group_space(&mut self, i: usize) -> io::Result<()> {
+        if self.has_tick_mark_before(i) {
+            self.print_tick_mark()
+        } else {
+            self.writer.write_all(b" ")
         }
-        if self.show_position_panel {
-            match self.squeezer {
-                Squeezer::Print => {
-                    self.writer.write_all(&[b'*'])?;
-                    if self.show_color {
-                        self.writer.write_all(COLOR_RESET)?;
-                    }
-                    self.writer.write_all(b"       ")?;
+    }
+
+    /// Writes a border line, with the first of the `legends` that fits after
+    /// the first horizontal line.
+    fn write_border(
+        &mut self,
+        border_elements: BorderElements,
+        legends: &[String],
+    ) -> io::Result<()> {
+        let h = border_elements.horizontal_line;
+        let c = border_elements.column_separator;
+        let l = border_elements.left_corner;
+        let r = border_elements.right_corner;
+
+        let mut border = self
+            .panel_widths()
+            .iter()
+            .map(|&(_, width)| h.to_string().repeat(width))
+            .collect::<Vec<_>>()
+            .join(&c.to_string());
+        let width = border.chars().count();
+        // Keep a horizontal line on both sides of the legend.
+        if let Some(legend) = legends
+            .iter()
+            .find(|legend| legend.chars().count() + 2 <= width)
+        {
+            border = border
+                .chars()
+                .take(1)
+                .chain(legend.chars())
+                .chain(border.chars().skip(1 + legend.chars().count()))
+                .collect();
+        }
+        write!(self.writer, "{l}{border}{r}")?;
+        self.end_line()
+    }
+
+    /// Ends a colored cell. With [`PrinterBuilder::stateless_colors`], its
+    /// color is reset, so that the next cell starts with its own color.
+    fn end_cell(&mut self) -> io::Result<()> {
+        if self.stateless_colors && self.curr_color.is_some() {
+            self.write_color(COLOR_RESET)?;
+            self.curr_color = None;
+        }
+        Ok(())
+    }
+
+    /// Resets the color at the end of a panel, unless it has been reset after
+    /// the last cell already.
+    fn end_panel(&mut self) -> io::Result<()> {
+        if self.show_color && (!self.stateless_colors || self.curr_color.is_some()) {
+            self.write_color(COLOR_RESET)?;
+            self.curr_color = None;
+        }
+        Ok(())
+    }
+
+    /// Switches to `color`, an SGR sequence like the colors of the [`Theme`].
+    fn write_color(&mut self, color: &'static [u8]) -> io::Result<()> {
+        match &mut self.style_hook {
+            Some(hook) => {
+                hook(color);
+                Ok(())
+            }
+            None => self.writer.write_all(color),
+        }
+    }
+
+    /// Ends the current line of output. With [`PrinterBuilder::reset_per_line`],
+    /// all colors are reset first.
+    fn end_line(&mut self) -> io::Result<()> {
+        if self.show_color && self.reset_per_line {
+            self.write_color(SGR_RESET)?;
+            self.curr_color = None;
+        }
+        self.rows += 1;
+        self.writer.write_all(b"\n")
+    }
+
+    pub fn print_header(&mut self) -> io::Result<()> {
+        if let Some(e) = self.border_style.header_elems() {
+            let legends: Vec<String> = self.settings_legend.iter().cloned().collect();
+            self.write_border(e, &legends)?;
+        }
+        if self.ruler {
+            self.print_ruler()?;
+        }
+        Ok(())
+    }
+
+    /// Prints the columns of the bytes for [`PrinterBuilder::ruler`], one
+    /// per group in the hex panels, left-aligned in the group.
+    fn print_ruler(&mut self) -> io::Result<()> {
+        let bytes_per_line = self.bytes_per_line() as usize;
+        let panel_width = self.panel_width as usize;
+        let group_size = self.group_size as usize;
+        let group_width = group_size * self.base_digits as usize;
+
+        write!(self.writer, "{}", self.start_separator())?;
+        if self.show_position_panel {
+            let sep = self.separator_after(PanelKind::Position, 0);
+            write!(self.writer, "{:1$}{sep}", "", self.position_width())?;
+        }
+        for i in (0..bytes_per_line).step_by(group_size) {
+            self.print_group_space(i)?;
+            let column = format!("{:02x}", i % 0x100);
+            self.print_ruler_text(&format!("{column:<group_width$}"))?;
+            let last = i + group_size - 1;
+            if last % panel_width == panel_width - 1 {
+                self.end_panel()?;
+                let sep = self.separator_after(PanelKind::Hex, last / panel_width);
+                write!(self.writer, " {sep}")?;
+            }
+        }
+        let char_panels = self.char_panel_count() as usize;
+        let first_char = self.char_panel_bytes().start as usize;
+        for panel in 1..=char_panels {
+            for i in 0..panel_width {
+                if self.char_tick_mark_index() == Some(i) {
+                    self.print_tick_mark()?;
+                }
+                let column = (first_char + (panel - 1) * panel_width + i) % 0x10;
+                self.print_ruler_text(&format!("{column:x}"))?;
+            }
+            self.end_panel()?;
+            let sep = self.separator_after(PanelKind::Char, panel - 1);
+            write!(self.writer, "{sep}")?;
+        }
+        if self.classify_lines {
+            let sep = self.separator_after(PanelKind::LineClass, 0);
+            write!(self.writer, " {sep}")?;
+        }
+        if let Some(line_hash) = self.line_hash {
+            let sep = self.separator_after(PanelKind::LineHash, 0);
+            write!(self.writer, "{:1$}{sep}", "", line_hash.digits())?;
+        }
+        self.end_line()
+    }
+
+    /// Writes a part of the ruler in the color of the position panel.
+    fn print_ruler_text(&mut self, text: &str) -> io::Result<()> {
+        if self.show_color && self.curr_color != Some(self.theme.offset) {
+            self.write_color(self.theme.offset)?;
+            self.curr_color = Some(self.theme.offset);
+        }
+        self.writer.write_all(text.as_bytes())?;
+        self.end_cell()
+    }
+
+    pub fn print_footer(&mut self) -> io::Result<()> {
+        if let Some(e) = self.border_style.footer_elems() {
+            let legends = if self.footer_info {
+                self.footer_legends()
+            } else {
+                vec![]
+            };
+            self.write_border(e, &legends)?
+        }
+        Ok(())
+    }
+
+    /// Returns the texts for [`PrinterBuilder::footer_info`], with and
+    /// without the name of the input.
+    fn footer_legends(&self) -> Vec<String> {
+        let range = match self.bytes_read {
+            0 => "0 bytes".to_owned(),
+            n => format!(
+                "{:#x}-{:#x} ({})",
+                self.display_offset,
+                self.display_offset
+                    .saturating_add((n - 1).saturating_mul(self.step)),
+                format_size(n)
+            ),
+        };
+        let mut legends = vec![];
+        if let Some(name) = &self.input_name {
+            let name: String = name.chars().map(single_column).collect();
+            legends.push(format!("[{name} {range}]"));
+        }
+        legends.push(format!("[{range}]"));
+        legends
+    }
+
+    /// The position of the first byte of the current line in the original data.
+    fn position(&self) -> u64 {
+        self.display_offset + self.idx * self.step
+    }
+
+    fn print_position_panel(&mut self) -> io::Result<()> {
+        self.writer.write_all(self.start_separator().as_bytes())?;
+        if self.show_color && self.show_position_panel {
+            let position = self.position();
+            let default_color = match self.squeeze_action {
+                SqueezeAction::Print => self.theme.squeeze,
+                SqueezeAction::Ignore | SqueezeAction::Delete => self.theme.offset,
+            };
+            let color = self
+                .position_highlights
+                .iter()
+                .rev()
+                .find(|highlight| highlight.range.contains(&position))
+                .map_or(default_color, |highlight| highlight.color);
+            self.write_color(color)?;
+        }
+        if self.show_position_panel {
+            match self.squeeze_action {
+                SqueezeAction::Print => {
+                    self.writer.write_all(self.squeeze_marker.as_bytes())?;
+                    if self.show_color {
+                        self.write_color(COLOR_RESET)?;
+                    }
+                    let padding = self.position_width() - self.squeeze_marker.width();
+                    write!(self.writer, "{:padding$}", "")?;
                 }
-                Squeezer::Ignore | Squeezer::Disabled | Squeezer::Delete => {
-                    let byte_index: [u8; 8] = (self.idx + self.display_offset).to_be_bytes();
-                    let mut i = 0;
-                    while byte_index[i] == 0x0 && i < 4 {
-                        i += 1;
+                SqueezeAction::Ignore | SqueezeAction::Delete => {
+                    let position = self.position();
+                    let hyperlink = self.offset_hyperlink.as_ref().filter(|_| self.show_color);
+                    if let Some(template) = hyperlink {
+                        let url = template
+                            .replace("{offset}", &format!("{position:x}"))
+                            .replace("{offset_dec}", &position.to_string());
+                        write!(self.writer, "\x1b]8;;{url}\x1b\\")?;
+                    }
+                    let digits = self.offset_style.position_digits(self.max_position);
+                    match self.offset_style {
+                        OffsetStyle::Byte => match position.checked_sub(self.relative_to) {
+                            Some(position) => {
+                                let byte_index: [u8; 8] = position.to_be_bytes();
+                                let min_bytes = digits / 2;
+                                let mut i = 0;
+                                while byte_index[i] == 0x0 && i < 8 - min_bytes {
+                                    i += 1;
+                                }
+                                for &byte in byte_index.iter().skip(i) {
+                                    self.writer.write_all(
+                                        self.byte_hex_panel_g[byte as usize].as_bytes(),
+                                    )?;
+                                }
+                            }
+                            None => {
+                                let distance = self.relative_to - position;
+                                let digits = digits - 1;
+                                write!(self.writer, "-{distance:0digits$x}")?;
+                            }
+                        },
+                        OffsetStyle::Sector(sector_size) => {
+                            // The offset within the sector is never negative,
+                            // so a position shortly before `relative_to` is
+                            // at the end of sector -1.
+                            let offset_digits = OffsetStyle::sector_offset_digits(sector_size);
+                            let size = i128::from(sector_size.get());
+                            let position = i128::from(position) - i128::from(self.relative_to);
+                            let sector = position.div_euclid(size);
+                            let offset = position.rem_euclid(size);
+                            if sector < 0 {
+                                let digits = digits - 1;
+                                write!(
+                                    self.writer,
+                                    "LBA -{:0digits$x} +{offset:0offset_digits$x}",
+                                    -sector
+                                )?;
+                            } else {
+                                write!(
+                                    self.writer,
+                                    "LBA {sector:0digits$x} +{offset:0offset_digits$x}"
+                                )?;
+                            }
+                        }
+                        OffsetStyle::Segment => {
+                            let address = position.wrapping_sub(self.relative_to) & 0xf_ffff;
+                            let segment = (address >> 16) << 12;
+                            let offset = address & 0xffff;
+                            write!(self.writer, "{segment:04X}:{offset:04X}")?;
+                        }
                     }
-                    for &byte in byte_index.iter().skip(i) {
-                        self.writer
-                            .write_all(self.byte_hex_panel_g[byte as usize].as_bytes())?;
+                    if hyperlink.is_some() {
+                        self.writer.write_all(b"\x1b]8;;\x1b\\")?;
                     }
                     if self.show_color {
-                        self.writer.write_all(COLOR_RESET)?;
+                        self.write_color(COLOR_RESET)?;
                     }
                 }
             }
-            self.writer.write_all(
-                self.border_style
-                    .outer_sep()
-                    .encode_utf8(&mut [0; 4])
-                    .as_bytes(),
-            )?;
+            self.writer
+                .write_all(self.separator_after(PanelKind::Position, 0).as_bytes())?;
         }
         Ok(())
     }
 
     fn print_char(&mut self, i: u64) -> io::Result<()> {
-        match self.squeezer {
-            Squeezer::Print | Squeezer::Delete => self.writer.write_all(b" ")?,
-            Squeezer::Ignore | Squeezer::Disabled => {
-                if let Some(&b) = self.line_buf.get(i as usize) {
-                    if self.show_color && self.curr_color != Some(Byte(b).color()) {
-                        self.writer.write_all(Byte(b).color())?;
-                        self.curr_color = Some(Byte(b).color());
+        let shown = self.char_panel_bytes();
+        if !shown.contains(&i) {
+            return Ok(());
+        }
+        match self.squeeze_action {
+            SqueezeAction::Print | SqueezeAction::Delete => self.writer.write_all(b" ")?,
+            SqueezeAction::Ignore => {
+                if let Some(&b) = self.line_buf[..self.line_len].get(i as usize) {
+                    self.set_color(i as usize, b)?;
+                    let char = if self.is_gap(i as usize) {
+                        " "
+                    } else if self.is_unreadable(i as usize) {
+                        self.unreadable_char
+                    } else {
+                        &self.byte_char_panel[b as usize]
+                    };
+                    self.writer.write_all(char.as_bytes())?;
+                    self.end_cell()?;
+                } else {
+                    self.squeeze_action = SqueezeAction::Print;
+                }
+            }
+        }
+        let panel_width = self.panel_width;
+        if self.char_tick_mark_index() == Some((i % panel_width) as usize + 1) {
+            self.print_tick_mark()?;
+        }
+        if i % panel_width == panel_width - 1 || i == shown.end - 1 {
+            self.end_panel()?;
+            let panel = ((i - shown.start) / panel_width) as usize;
+            self.writer
+                .write_all(self.separator_after(PanelKind::Char, panel).as_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn print_char_panel(&mut self) -> io::Result<()> {
+        for i in 0..self.line_len {
+            self.print_char(i as u64)?;
+        }
+        Ok(())
+    }
+
+    fn print_byte(&mut self, i: usize, b: u8) -> io::Result<()> {
+        match self.squeeze_action {
+            // Without a position panel, the marker takes the place of the
+            // first panel, unless it pads the end of an incomplete line.
+            SqueezeAction::Print
+                if !self.show_position_panel
+                    && i < self.panel_width as usize
+                    && (self.line_len == 0 || i < self.line_len) =>
+            {
+                if i == 0 {
+                    if self.show_color {
+                        self.write_color(self.theme.squeeze)?;
+                    }
+                    self.writer.write_all(self.squeeze_marker.as_bytes())?;
+                    if self.show_color {
+                        self.write_color(COLOR_RESET)?;
+                    }
+                    let panel_width = self.panel_width as usize;
+                    let group_spaces = (0..panel_width)
+                        .filter(|i| i % (self.group_size as usize) == 0)
+                        .count();
+                    let width = group_spaces + panel_width * self.base_digits as usize;
+                    // A wide marker does not fit into a narrow panel.
+                    let padding = width.saturating_sub(self.squeeze_marker.width());
+                    write!(self.writer, "{:padding$}", "")?;
+                }
+            }
+            SqueezeAction::Print => {
+                if i % (self.group_size as usize) == 0 {
+                    self.print_group_space(i)?;
+                }
+                for _ in 0..self.base_digits {
+                    self.writer.write_all(b" ")?;
+                }
+            }
+            SqueezeAction::Delete => self.writer.write_all(b"   ")?,
+            SqueezeAction::Ignore => {
+                if i % (self.group_size as usize) == 0 {
+                    self.print_group_space(i)?;
+                }
+                self.set_color(self.source_index(i), b)?;
+                if self.is_gap(self.source_index(i)) {
+                    for _ in 0..self.base_digits {
+                        self.writer.write_all(b"_")?;
                     }
-                    self.writer
-                        .write_all(self.byte_char_panel[b as usize].as_bytes())?;
                 } else {
-                    self.squeezer = Squeezer::Print;
+                    self.writer
+                        .write_all(self.byte_hex_panel[b as usize].as_bytes())?;
                 }
+                self.end_cell()?;
             }
         }
-        if i == 8 * self.panels - 1 {
-            if self.show_color {
-                self.writer.write_all(COLOR_RESET)?;
-                self.curr_color = None;
+        self.print_byte_separator(i)
+    }
+
+    /// Print the marker for the end of the input in the first column of the
+    /// slot of the (missing) byte `i`.
+    fn print_eof_marker(&mut self, i: usize) -> io::Result<()> {
+        if self.show_color {
+            self.write_color(COLOR_EOF)?;
+            self.curr_color = Some(COLOR_EOF);
+        }
+        self.writer.write_all("▏".as_bytes())?;
+        self.end_cell()?;
+        let mut blanks = self.base_digits as usize;
+        if i % (self.group_size as usize) != 0 {
+            blanks -= 1;
+        }
+        write!(self.writer, "{:blanks$}", "")?;
+        self.print_byte_separator(i)
+    }
+
+    /// Print the separator that follows the byte `i`, if it is the last one
+    /// in a panel.
+    fn print_byte_separator(&mut self, i: usize) -> io::Result<()> {
+        let panel_width = self.panel_width as usize;
+        // byte is last in panel
+        if i % panel_width == panel_width - 1 {
+            self.end_panel()?;
+            self.writer.write_all(b" ")?;
+            let panel = (i as u64 % self.bytes_per_line()) as usize / panel_width;
+            self.writer
+                .write_all(self.separator_after(PanelKind::Hex, panel).as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Returns whether the byte at index `i` of the current line could not be
+    /// read.
+    fn is_unreadable(&self, i: usize) -> bool {
+        self.unreadable_bytes
+            .as_ref()
+            .is_some_and(|unreadable| unreadable.contains(self.idx + i as u64))
+    }
+
+    /// Returns whether the byte at index `i` of the current line is in one of
+    /// the `gaps`.
+    fn is_gap(&self, i: usize) -> bool {
+        let position = self.position() + i as u64 * self.step;
+        let i = self.gaps.partition_point(|gap| gap.end <= position);
+        self.gaps.get(i).is_some_and(|gap| gap.contains(&position))
+    }
+
+    /// Switch to the color of byte `b` at index `i` of the current line, unless
+    /// it is active already.
+    fn set_color(&mut self, i: usize, b: u8) -> io::Result<()> {
+        if !self.show_color {
+            return Ok(());
+        }
+        let color = if self.is_gap(i) {
+            self.theme.offset
+        } else if self.is_unreadable(i) {
+            COLOR_UNREADABLE
+        } else if self.filter_bytes[b as usize]
+            || (self.finder.as_ref()).is_some_and(|finder| finder.is_match(self.idx + i as u64))
+        {
+            COLOR_HIGHLIGHT
+        } else {
+            self.line_colors
+                .get(i)
+                .copied()
+                .flatten()
+                .unwrap_or_else(|| self.theme.category_color(Byte(b).category()))
+        };
+        if self.curr_color != Some(color) {
+            self.write_color(color)?;
+            self.curr_color = Some(color);
+        }
+        Ok(())
+    }
+
+    /// Counts the matches of the `filter_bytes` in the current line, and
+    /// returns whether the line is shown.
+    fn filter_line(&mut self) -> bool {
+        if !self.filter_enabled && self.finder.is_none() {
+            return true;
+        }
+        let mut shown = false;
+        if self.filter_enabled {
+            let matches = self.line_buf[..self.line_len]
+                .iter()
+                .filter(|&&b| self.filter_bytes[b as usize])
+                .count() as u64;
+            if matches > 0 {
+                self.filter_matches.bytes += matches;
+                self.filter_matches.lines += 1;
+            }
+            shown = matches > 0;
+        }
+        if let Some(finder) = &mut self.finder {
+            shown |= finder.overlaps(self.idx..self.idx + self.line_len as u64);
+        }
+        shown
+    }
+
+    /// Adds the positions of the current line to the `spans`.
+    fn record_span(&mut self) -> io::Result<()> {
+        if !self.track_spans {
+            return Ok(());
+        }
+        let start = self.position();
+        let end = start + self.line_len as u64 * self.step;
+        match self.spans.last_mut() {
+            Some(span) if span.end == start => span.end = end,
+            _ => {
+                let size = (self.spans.len() as u64 + 1) * mem::size_of::<Range<u64>>() as u64;
+                if size > self.max_memory {
+                    return Err(io::Error::new(
+                        io::ErrorKind::OutOfMemory,
+                        format!(
+                            "recording the spans needs more than the allowed {} bytes of memory",
+                            self.max_memory
+                        ),
+                    ));
+                }
+                self.spans.push(start..end);
+            }
+        }
+        Ok(())
+    }
+
+    /// Records the current line as squeezed, see
+    /// [`PrinterBuilder::squeeze_summary`].
+    fn record_squeezed_line(&mut self) -> io::Result<()> {
+        let start = self.position();
+        let end = start + self.line_len as u64 * self.step;
+        let byte = self.line_buf[0];
+        if self.event_hook.is_some() || self.line_format == LineFormat::Json {
+            match self.squeezed_region.as_mut() {
+                Some(region) if region.range.end == start && region.byte == byte => {
+                    region.range.end = end;
+                    region.len += self.line_len as u64;
+                }
+                _ => {
+                    self.end_squeezed_region()?;
+                    self.squeezed_region = Some(SqueezedRegion {
+                        range: start..end,
+                        len: self.line_len as u64,
+                        byte,
+                    });
+                }
+            }
+        }
+        if !self.squeeze_summary {
+            return Ok(());
+        }
+        match self.squeezed_regions.last_mut() {
+            Some(region) if region.range.end == start && region.byte == byte => {
+                region.range.end = end;
+                region.len += self.line_len as u64;
+            }
+            _ => {
+                let size = (self.squeezed_regions.len() as u64 + 1)
+                    * mem::size_of::<SqueezedRegion>() as u64;
+                if size > self.max_memory {
+                    return Err(io::Error::new(
+                        io::ErrorKind::OutOfMemory,
+                        format!(
+                            "recording the squeezed regions needs more than the allowed {} bytes \
+                             of memory",
+                            self.max_memory
+                        ),
+                    ));
+                }
+                self.squeezed_regions.push(SqueezedRegion {
+                    range: start..end,
+                    len: self.line_len as u64,
+                    byte,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Reports the squeezed region to the `event_hook` once it has ended,
+    /// and writes the JSON object of its first line.
+    fn end_squeezed_region(&mut self) -> io::Result<()> {
+        match self.squeezed_region.take() {
+            Some(region) => {
+                if let Some(line) = self.squeezed_json_line.take() {
+                    self.writer.write_all(&line)?;
+                    writeln!(
+                        self.writer,
+                        ",\"squeezed\":true,\"length\":{}}}",
+                        region.len
+                    )?;
+                }
+                self.emit(PrinterEvent::Squeezed(region))
             }
-            self.writer.write_all(
-                self.border_style
-                    .outer_sep()
-                    .encode_utf8(&mut [0; 4])
-                    .as_bytes(),
+            None => Ok(()),
+        }
+    }
+
+    /// Calls the `event_hook`, if any.
+    fn emit(&mut self, event: PrinterEvent) -> io::Result<()> {
+        match self.event_hook.as_mut() {
+            Some(hook) => hook(&event),
+            None => Ok(()),
+        }
+    }
+
+    /// Counts `n` more bytes as read, and reports the progress to the
+    /// `event_hook` whenever another block of the I/O buffer size is
+    /// complete.
+    fn add_bytes_read(&mut self, n: usize) -> io::Result<()> {
+        let block_size = self.io_buffer_size.max(1) as u64;
+        let blocks = self.bytes_read / block_size;
+        self.bytes_read += n as u64;
+        if self.bytes_read / block_size > blocks {
+            let bytes_read = self.bytes_read;
+            self.emit(PrinterEvent::Progress { bytes_read })?;
+        }
+        Ok(())
+    }
+
+    /// Prints the summary for [`PrinterBuilder::squeeze_summary`].
+    fn print_squeeze_summary(&mut self) -> io::Result<()> {
+        for region in &self.squeezed_regions {
+            writeln!(
+                self.writer,
+                "{:#x}-{:#x}: {} bytes of {:#04x}",
+                region.range.start,
+                region.range.end - self.step,
+                region.len,
+                region.byte
             )?;
-        } else if i % 8 == 7 {
+        }
+        Ok(())
+    }
+
+    /// Ask the `color_hook` (if any) for the colors of the current line, and
+    /// apply the `highlights`.
+    fn update_line_colors(&mut self) -> io::Result<()> {
+        if !self.show_color {
+            return Ok(());
+        }
+        let offset = self.position();
+        if let Some(hook) = self.color_hook.as_mut() {
+            self.line_colors = hook(offset, &self.line_buf[..self.line_len])?;
+        } else if !self.highlights.is_empty() {
+            self.line_colors.clear();
+        }
+        if !self.highlights.is_empty() {
+            self.line_colors.resize(self.line_len, None);
+            let end = offset + self.line_len as u64 * self.step;
+            for highlight in &self.highlights {
+                if highlight.range.start >= end || highlight.range.end <= offset {
+                    continue;
+                }
+                for (i, color) in self.line_colors.iter_mut().enumerate() {
+                    if highlight.range.contains(&(offset + i as u64 * self.step)) {
+                        *color = Some(highlight.color);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Prints the labels of the `highlights` that start before the end of the
+    /// current line and have not been shown yet.
+    fn print_labels(&mut self) -> io::Result<()> {
+        let end = self.position() + self.bytes_per_line() * self.step;
+        let mut first = true;
+        while let Some(&i) = self.label_order.get(self.labels_shown) {
+            let highlight = &self.highlights[i];
+            if highlight.range.start >= end {
+                break;
+            }
+            self.labels_shown += 1;
+            let (Some(label), color) = (highlight.label.clone(), highlight.color) else {
+                continue;
+            };
+            self.writer.write_all(if first { b" " } else { b", " })?;
+            first = false;
             if self.show_color {
-                self.writer.write_all(COLOR_RESET)?;
+                self.write_color(color)?;
+                self.writer.write_all(label.as_bytes())?;
+                self.write_color(COLOR_RESET)?;
                 self.curr_color = None;
+            } else {
+                self.writer.write_all(label.as_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Prints the first row of the comments that refer to the current line and
+    /// have not been shown yet, and keeps the other rows for
+    /// `print_comment_rows`.
+    fn print_comments(&mut self) -> io::Result<()> {
+        let end = self.position() + self.bytes_per_line() * self.step;
+        while let Some(comment) = self.comments.get(self.comments_shown) {
+            if comment.position >= end {
+                break;
+            }
+            self.comments_shown += 1;
+            self.comment_rows
+                .extend(wrap_text(&comment.text, self.comment_width));
+        }
+        if !self.comment_rows.is_empty() {
+            let first = self.comment_rows.remove(0);
+            write!(self.writer, " {first}")?;
+        }
+        Ok(())
+    }
+
+    /// Prints the remaining rows of the comments of the current line, with
+    /// empty panels.
+    fn print_comment_rows(&mut self) -> io::Result<()> {
+        if self.comment_rows.is_empty() {
+            return Ok(());
+        }
+        let mut panels = self.start_separator().to_owned();
+        for ((_, width), sep) in self.panel_widths().into_iter().zip(&self.separators[1..]) {
+            panels.push_str(&" ".repeat(width));
+            panels.push_str(sep);
+        }
+        for row in mem::take(&mut self.comment_rows) {
+            write!(self.writer, "{panels} {row}")?;
+            self.end_line()?;
+        }
+        Ok(())
+    }
+
+    /// Prints the legend for [`PrinterBuilder::highlight_legend`].
+    fn print_highlight_legend(&mut self) -> io::Result<()> {
+        let labeled = self
+            .label_order
+            .iter()
+            .map(|&i| &self.highlights[i])
+            .filter_map(|highlight| Some((highlight.clone(), highlight.label.clone()?)))
+            .collect::<Vec<_>>();
+        let width = labeled
+            .iter()
+            .map(|(_, label)| label.chars().count())
+            .max()
+            .unwrap_or(0);
+        for (highlight, label) in labeled {
+            let padding = width - label.chars().count();
+            if self.show_color {
+                self.write_color(highlight.color)?;
+                self.writer.write_all(label.as_bytes())?;
+                self.write_color(COLOR_RESET)?;
+            } else {
+                self.writer.write_all(label.as_bytes())?;
             }
-            self.writer.write_all(
-                self.border_style
-                    .inner_sep()
-                    .encode_utf8(&mut [0; 4])
-                    .as_bytes(),
+            writeln!(
+                self.writer,
+                "{:padding$}  {:#x}..{:#x}",
+                "", highlight.range.start, highlight.range.end
             )?;
         }
+        Ok(())
+    }
+
+    /// Map the index of a byte as it is displayed to its index in the line,
+    /// which differs for little-endian groups.
+    fn source_index(&self, i: usize) -> usize {
+        self.source_index_in(i, self.endianness)
+    }
+
+    /// Like [`Printer::source_index`], for the given endianness.
+    fn source_index_in(&self, i: usize, endianness: Endianness) -> usize {
+        match endianness {
+            Endianness::Big => i,
+            Endianness::Little => {
+                let group_sz = self.group_size as usize;
+                let start = i - i % group_sz;
+                let len = group_sz.min(self.line_len - start);
+                start + len - 1 - (i - start)
+            }
+        }
+    }
+
+    fn print_line_class(&mut self) -> io::Result<()> {
+        let tag = match self.squeeze_action {
+            SqueezeAction::Print | SqueezeAction::Delete => ' ',
+            SqueezeAction::Ignore => LineClass::of(&self.line_buf[..self.line_len]).tag(),
+        };
+        if self.show_color {
+            self.write_color(self.theme.offset)?;
+        }
+        self.writer
+            .write_all(tag.encode_utf8(&mut [0; 4]).as_bytes())?;
+        if self.show_color {
+            self.write_color(COLOR_RESET)?;
+        }
+        self.writer
+            .write_all(self.separator_after(PanelKind::LineClass, 0).as_bytes())
+    }
+
+    fn print_line_hash(&mut self, line_hash: LineHash) -> io::Result<()> {
+        let digits = line_hash.digits();
+        match self.squeeze_action {
+            SqueezeAction::Print | SqueezeAction::Delete => {
+                write!(self.writer, "{:digits$}", "")?;
+            }
+            SqueezeAction::Ignore => {
+                let hash = line_hash.of(&self.line_buf[..self.line_len]);
+                if self.show_color {
+                    self.write_color(self.theme.offset)?;
+                }
+                write!(self.writer, "{hash:0digits$x}")?;
+                if self.show_color {
+                    self.write_color(COLOR_RESET)?;
+                }
+            }
+        }
+        self.writer
+            .write_all(self.separator_after(PanelKind::LineHash, 0).as_bytes())
+    }
+
+    pub fn print_bytes(&mut self) -> io::Result<()> {
+        for i in 0..self.line_len {
+            let b = self.line_buf[self.source_index(i)];
+            self.print_byte(i, b)?;
+        }
+        Ok(())
+    }
+
+    /// Print the additional rows for the current line, one for each
+    /// interleaved base (and one for the other endianness and the mnemonics).
+    /// Only the byte panels and labels are filled in. Rows that would be empty
+    /// for this line are left out.
+    fn print_interleaved_rows(&mut self) -> io::Result<()> {
+        let bytes_per_line = self.bytes_per_line() as usize;
+        let panel_width = self.panel_width as usize;
+
+        for row in 0..self.interleave.len() {
+            if self.line_buf[..self.line_len]
+                .iter()
+                .all(|&b| self.interleave[row].table[b as usize].trim().is_empty())
+            {
+                continue;
+            }
+            write!(self.writer, "{}", self.start_separator())?;
+            if self.show_position_panel {
+                let width = self.position_width();
+                match self.interleave[row].label {
+                    Some(label) if self.show_color => {
+                        self.write_color(self.theme.offset)?;
+                        write!(self.writer, "{label:>width$}")?;
+                        self.write_color(COLOR_RESET)?;
+                    }
+                    label => write!(self.writer, "{:>width$}", label.unwrap_or(""))?,
+                }
+                write!(
+                    self.writer,
+                    "{}",
+                    self.separator_after(PanelKind::Position, 0)
+                )?;
+            }
+            for i in 0..bytes_per_line {
+                if i % (self.group_size as usize) == 0 {
+                    self.print_group_space(i)?;
+                }
+                if i < self.line_len {
+                    let source_index = self.source_index_in(i, self.interleave[row].endianness);
+                    let b = self.line_buf[source_index];
+                    self.set_color(source_index, b)?;
+                    self.writer
+                        .write_all(self.interleave[row].table[b as usize].as_bytes())?;
+                    self.end_cell()?;
+                } else {
+                    write!(self.writer, "{:1$}", "", self.base_digits as usize)?;
+                }
+                if i % panel_width == panel_width - 1 {
+                    self.end_panel()?;
+                    let sep = self.separator_after(PanelKind::Hex, i / panel_width);
+                    write!(self.writer, " {sep}")?;
+                }
+            }
+            for panel in 0..self.char_panel_count() as usize {
+                let sep = self.separator_after(PanelKind::Char, panel);
+                if let Some(tick_mark) = self.char_tick_mark_index() {
+                    write!(self.writer, "{:tick_mark$}", "")?;
+                    self.print_tick_mark()?;
+                    self.end_panel()?;
+                    let rest = panel_width - tick_mark;
+                    write!(self.writer, "{:rest$}{sep}", "")?;
+                } else {
+                    write!(self.writer, "{:panel_width$}{sep}", "")?;
+                }
+            }
+            if self.classify_lines {
+                let sep = self.separator_after(PanelKind::LineClass, 0);
+                write!(self.writer, " {sep}")?;
+            }
+            if let Some(line_hash) = self.line_hash {
+                let sep = self.separator_after(PanelKind::LineHash, 0);
+                write!(self.writer, "{:1$}{sep}", "", line_hash.digits())?;
+            }
+            self.end_line()?;
+        }
+        Ok(())
+    }
+
+    /// Loop through the given `Reader`, printing until the `Reader` buffer
+    /// is exhausted.
+    pub fn print_all<Reader: Read>(&mut self, reader: Reader) -> io::Result<()> {
+        self.append(reader)?;
+        self.finish()
+    }
+
+    /// Like [`Printer::print_all`], but calls `visitor` for every byte of the
+    /// input, in order, with its position (as shown in the position panel)
+    /// and its [`ByteCategory`], e.g. to collect statistics or to build an
+    /// index in the same pass as the output:
+    ///
+    /// ```
+    /// use hexyl::{ByteCategory, PrinterBuilder};
+    ///
+    /// let mut output = vec![];
+    /// let mut printer = PrinterBuilder::new(&mut output).build();
+    /// let mut nulls = vec![];
+    /// printer
+    ///     .print_all_with(&b"a\0b\0"[..], |position, _, category| {
+    ///         if category == ByteCategory::Null {
+    ///             nulls.push(position);
+    ///         }
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(nulls, [1, 3]);
+    /// ```
+    ///
+    /// Unlike the [`ColorHook`], the visitor is also called for the bytes of
+    /// lines that are squeezed or filtered out.
+    pub fn print_all_with<Reader: Read>(
+        &mut self,
+        reader: Reader,
+        mut visitor: impl FnMut(u64, u8, ByteCategory),
+    ) -> io::Result<()> {
+        self.append_with(reader, &mut visitor)?;
+        self.finish()
+    }
+
+    /// Print the contents of the given `Reader` as a continuation of all
+    /// previously appended input, with continuous offsets and squeezing.
+    /// An incomplete last line is held back until more input is appended or
+    /// [`Printer::finish`] is called.
+    pub fn append<Reader: Read>(&mut self, reader: Reader) -> io::Result<()> {
+        self.read_input(reader, None)
+    }
+
+    /// Like [`Printer::append`], but calls `visitor` for every byte, see
+    /// [`Printer::print_all_with`].
+    pub fn append_with<Reader: Read>(
+        &mut self,
+        reader: Reader,
+        mut visitor: impl FnMut(u64, u8, ByteCategory),
+    ) -> io::Result<()> {
+        self.read_input(reader, Some(&mut visitor))
+    }
+
+    fn read_input<Reader: Read>(
+        &mut self,
+        reader: Reader,
+        mut visitor: Option<&mut dyn FnMut(u64, u8, ByteCategory)>,
+    ) -> io::Result<()> {
+        let mut buf = BufReader::with_capacity(self.io_buffer_size, reader);
+
+        loop {
+            if self
+                .cancel_flag
+                .as_ref()
+                .is_some_and(|flag| flag.load(Ordering::Relaxed))
+            {
+                return Err(io::Error::other("printing has been cancelled"));
+            }
+
+            if self.finder.is_some() {
+                if !self.find_chunk(&mut buf, &mut visitor)? {
+                    return Ok(());
+                }
+                continue;
+            }
+
+            // fill up the line buffer, a read of zero bytes indicates the end of input
+            let n = match buf.read(&mut self.line_buf[self.line_len..]) {
+                Ok(0) => return Ok(()),
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            self.add_bytes_read(n)?;
+            if let Some(visitor) = visitor.as_mut() {
+                let line_position = self.position();
+                for (i, &b) in (self.line_len..).zip(&self.line_buf[self.line_len..][..n]) {
+                    visitor(line_position + i as u64 * self.step, b, ByteCategory::of(b));
+                }
+            }
+            self.line_len += n;
+
+            if self.line_len == self.line_buf.len() {
+                self.print_line()?;
+                self.line_len = 0;
+            }
+        }
+    }
+
+    /// Searches the next chunk of `buf` with the `finder`, and adds the bytes
+    /// that are no longer held back to the line buffer. Returns `false` at
+    /// the end of the input.
+    fn find_chunk<Reader: Read>(
+        &mut self,
+        buf: &mut BufReader<Reader>,
+        visitor: &mut Option<&mut dyn FnMut(u64, u8, ByteCategory)>,
+    ) -> io::Result<bool> {
+        let chunk = match buf.fill_buf() {
+            Ok([]) => return Ok(false),
+            Ok(chunk) => chunk,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => return Ok(true),
+            Err(e) => return Err(e),
+        };
+        let n = chunk.len();
+        let (display_offset, step) = (self.display_offset, self.step);
+        let position = |i: u64| display_offset + i * step;
+        for &b in chunk {
+            let Some(finder) = self.finder.as_mut() else {
+                break;
+            };
+            if let Some(visitor) = visitor.as_mut() {
+                visitor(position(finder.index()), b, ByteCategory::of(b));
+            }
+            let matches = finder.matches().len();
+            let b = finder.push(b, position)?;
+            for i in matches..finder.matches().len() {
+                let found = finder.matches()[i];
+                if let Some(hook) = self.event_hook.as_mut() {
+                    hook(&PrinterEvent::Match(found))?;
+                }
+            }
+            if let Some(b) = b {
+                self.push_byte(b)?;
+            }
+        }
+        buf.consume(n);
+        self.add_bytes_read(n)?;
+        Ok(true)
+    }
+
+    /// Adds `b` to the line buffer, and prints the line once it is full.
+    fn push_byte(&mut self, b: u8) -> io::Result<()> {
+        self.line_buf[self.line_len] = b;
+        self.line_len += 1;
+        if self.line_len == self.line_buf.len() {
+            self.print_line()?;
+            self.line_len = 0;
+        }
+        Ok(())
+    }
+
+    /// Prints the incomplete last line as far as it has been appended, e.g.
+    /// to show the new bytes of a file that is followed while waiting for
+    /// more. Unlike [`Printer::finish`], the bytes are kept: the line is
+    /// printed again once it is complete, over the rows of this one, which
+    /// are erased with ANSI escape sequences. This is only useful for output
+    /// to a terminal, and does nothing while filtering or finding lines.
+    pub fn print_incomplete_line(&mut self) -> io::Result<()> {
+        self.clear_incomplete_line()?;
+        if self.line_len > 0
+            && !self.filter_enabled
+            && self.finder.is_none()
+            && self.line_format == LineFormat::Table
+        {
+            if !self.header_printed {
+                self.print_header()?;
+                self.header_printed = true;
+            }
+            // The labels and comments are shown again with the whole line.
+            let (labels_shown, comments_shown) = (self.labels_shown, self.comments_shown);
+            let rows = self.rows;
+            self.print_last_line(false)?;
+            self.incomplete_rows = self.rows - rows;
+            self.labels_shown = labels_shown;
+            self.comments_shown = comments_shown;
+        }
+        self.writer.flush()
+    }
+
+    /// Erases the rows printed by `print_incomplete_line`, if any.
+    fn clear_incomplete_line(&mut self) -> io::Result<()> {
+        if self.incomplete_rows > 0 {
+            // Move the cursor to the start of the first row and clear the
+            // screen from there.
+            write!(self.writer, "\x1b[{}F\x1b[J", self.incomplete_rows)?;
+            self.incomplete_rows = 0;
+        }
+        Ok(())
+    }
+
+    /// Flushes the lines that have been printed so far to the writer, e.g.
+    /// while waiting for more input.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Print the last (incomplete) line and the footer. This should be called
+    /// once after all input has been passed to [`Printer::append`].
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.clear_incomplete_line()?;
+        // the input has ended, so the bytes that are held back to find the
+        // matches that start in them can be printed
+        if let Some(held) = self.finder.as_mut().map(Finder::take_held) {
+            for b in held {
+                self.push_byte(b)?;
+            }
+        }
+        if self.line_len > 0 {
+            if self.filter_line() {
+                self.record_span()?;
+            } else {
+                self.idx += self.line_len as u64;
+                self.line_len = 0;
+            }
+        }
+        if self.line_format == LineFormat::Json {
+            self.end_squeezed_region()?;
+            if self.line_len > 0 {
+                self.squeeze_action = SqueezeAction::Ignore;
+                self.print_json_line()?;
+                self.idx += self.line_len as u64;
+                self.line_len = 0;
+            }
+            return self.writer.flush();
+        }
+        if !self.header_printed && self.line_len == 0 {
+            self.base_digits = 2;
+            self.print_header()?;
+            // Like `hexdump -C`, the compact layout prints nothing for an
+            // empty input.
+            if self.border_style != BorderStyle::Compact {
+                // In a border, the placeholder only has outer separators.
+                let outer_sep = self.border_style.outer_sep().to_string();
+                let seps = vec![outer_sep.as_str(); self.separators.len()];
+                let first_hex_panel = usize::from(self.show_position_panel);
+                write!(self.writer, "{}", seps[0])?;
+                for (i, (_, width)) in self.panel_widths().into_iter().enumerate() {
+                    let text = if i == first_hex_panel {
+                        " No content"
+                    } else {
+                        ""
+                    };
+                    write!(self.writer, "{text:width$}{}", seps[i + 1])?;
+                }
+                self.end_line()?;
+            }
+        } else if self.line_len > 0 || self.squeezer.is_squeezing() || self.mark_eof {
+            // print the last line, which is either incomplete or marks the
+            // end of a squeezed region or of the input
+            if !self.header_printed {
+                self.print_header()?;
+                self.header_printed = true;
+            }
+            self.print_last_line(true)?;
+            self.idx += self.line_len as u64;
+            self.line_len = 0;
+        }
+
+        self.end_squeezed_region()?;
+        self.print_footer()?;
+        if self.squeeze_summary {
+            self.print_squeeze_summary()?;
+        }
+        if self.highlight_legend {
+            self.print_highlight_legend()?;
+        }
+
+        self.writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Prints the line in the line buffer as a JSON object, see
+    /// [`LineFormat::Json`]. The first squeezed line is held back until the
+    /// length of the squeezed region is known.
+    fn print_json_line(&mut self) -> io::Result<()> {
+        let (position, bytes) = (self.position(), &self.line_buf[..self.line_len]);
+        if self.squeeze_action == SqueezeAction::Print {
+            let mut line = vec![];
+            write_json_fields(&mut line, position, bytes)?;
+            self.squeezed_json_line = Some(line);
+            return Ok(());
+        }
+        write_json_fields(self.writer, position, bytes)?;
+        writeln!(self.writer, ",\"squeezed\":false}}")
+    }
+
+    /// Print the (complete) line in the line buffer, unless it is squeezed.
+    /// Prints the last line, which is either incomplete or marks the end of a
+    /// squeezed region or, with `eof`, of the input.
+    fn print_last_line(&mut self, eof: bool) -> io::Result<()> {
+        let n = self.line_len;
+        self.squeeze_action = SqueezeAction::Ignore;
+        if n > 0 {
+            self.update_line_colors()?;
+        }
+        self.print_position_panel()?;
+        self.squeeze_action = SqueezeAction::Ignore;
+        self.print_bytes()?;
+        self.squeeze_action = SqueezeAction::Print;
+        for i in n..self.bytes_per_line() as usize {
+            if i == n && eof && self.mark_eof {
+                self.print_eof_marker(i)?;
+            } else {
+                self.print_byte(i, 0)?;
+            }
+        }
+        if self.show_char_panel {
+            self.squeeze_action = SqueezeAction::Ignore;
+            self.print_char_panel()?;
+            self.squeeze_action = SqueezeAction::Print;
+            for i in n..self.bytes_per_line() as usize {
+                self.print_char(i as u64)?;
+            }
+        }
+        if self.classify_lines {
+            self.squeeze_action = if n > 0 {
+                SqueezeAction::Ignore
+            } else {
+                SqueezeAction::Print
+            };
+            self.print_line_class()?;
+        }
+        if let Some(line_hash) = self.line_hash {
+            self.squeeze_action = if n > 0 {
+                SqueezeAction::Ignore
+            } else {
+                SqueezeAction::Print
+            };
+            self.print_line_hash(line_hash)?;
+        }
+        self.print_labels()?;
+        self.print_comments()?;
+        self.end_line()?;
+        if n > 0 {
+            self.print_interleaved_rows()?;
+        }
+        self.print_comment_rows()?;
+        Ok(())
+    }
+
+    fn print_line(&mut self) -> io::Result<()> {
+        self.clear_incomplete_line()?;
+        self.squeeze_action = self.squeezer.process(&self.line_buf);
+        let shown = self.filter_line();
+        if shown {
+            self.record_span()?;
+            if self.squeeze_action != SqueezeAction::Ignore {
+                self.record_squeezed_line()?;
+            } else {
+                self.end_squeezed_region()?;
+            }
+        }
+        if self.squeeze_action == SqueezeAction::Delete || !shown {
+            self.idx += self.bytes_per_line();
+            return Ok(());
+        }
+
+        if self.line_format == LineFormat::Json {
+            self.print_json_line()?;
+            self.idx += self.bytes_per_line();
+            return Ok(());
+        }
+
+        if !self.header_printed {
+            self.print_header()?;
+        }
+
+        // print the line
+        if self.squeeze_action != SqueezeAction::Print {
+            self.update_line_colors()?;
+        }
+        self.print_position_panel()?;
+        self.print_bytes()?;
+        if self.show_char_panel {
+            self.print_char_panel()?;
+        }
+        if self.classify_lines {
+            self.print_line_class()?;
+        }
+        if let Some(line_hash) = self.line_hash {
+            self.print_line_hash(line_hash)?;
+        }
+        self.print_labels()?;
+        self.print_comments()?;
+        self.end_line()?;
+        if self.squeeze_action != SqueezeAction::Print {
+            self.print_interleaved_rows()?;
+        }
+        self.print_comment_rows()?;
+
+        if !self.header_printed {
+            self.writer.flush()?;
+            self.header_printed = true;
+        }
+
+        // increment index to next line
+        self.idx += self.bytes_per_line();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::str;
+
+    use super::*;
+
+    fn assert_print_all_output<Reader: Read>(input: Reader, expected_string: String) {
+        let mut output = vec![];
+        let mut printer = Printer::new(
+            &mut output,
+            false,
+            true,
+            true,
+            BorderStyle::Unicode,
+            true,
+            2,
+            1,
+            Base::Hexadecimal,
+            Endianness::Big,
+            CharacterTable::Default,
+        );
+
+        printer.print_all(input).unwrap();
+
+        let actual_string: &str = str::from_utf8(&output).unwrap();
+        assert_eq!(actual_string, expected_string,)
+    }
+
+    #[test]
+    fn empty_file_passes() {
+        let input = io::empty();
+        let expected_string = "\
+┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┐
+│        │ No content              │                         │        │        │
+└────────┴─────────────────────────┴─────────────────────────┴────────┴────────┘
+"
+        .to_owned();
+        assert_print_all_output(input, expected_string);
+    }
+
+    #[test]
+    fn short_input_passes() {
+        let input = io::Cursor::new(b"spam");
+        let expected_string = "\
+┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┐
+│00000000│ 73 70 61 6d             ┊                         │spam    ┊        │
+└────────┴─────────────────────────┴─────────────────────────┴────────┴────────┘
+"
+        .to_owned();
+        assert_print_all_output(input, expected_string);
+    }
+
+    #[test]
+    fn dyn_writer() {
+        let mut output = vec![];
+        let writer: &mut dyn Write = &mut output;
+        let mut printer: Printer<dyn Write> = PrinterBuilder::new(writer)
+            .show_color(false)
+            .with_border_style(BorderStyle::None)
+            .num_panels(1)
+            .build();
+        printer.print_all(&b"dyn"[..]).unwrap();
+        drop(printer);
+
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            " 00000000  64 79 6e                 dyn      \n"
+        );
+    }
+
+    #[test]
+    fn cancel_flag() {
+        /// Sets the flag after the first read.
+        struct CancellingReader {
+            flag: Arc<AtomicBool>,
+            remaining: usize,
+        }
+
+        impl Read for CancellingReader {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                self.flag.store(true, Ordering::Relaxed);
+                let n = buf.len().min(self.remaining).min(16);
+                buf[..n].fill(b'a');
+                self.remaining -= n;
+                Ok(n)
+            }
+        }
+
+        let flag = Arc::new(AtomicBool::new(false));
+        let mut output = vec![];
+        let mut printer = PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .with_border_style(BorderStyle::None)
+            .num_panels(1)
+            .cancel_flag(flag.clone())
+            .build();
+        let reader = CancellingReader {
+            flag,
+            remaining: 1 << 20,
+        };
+        let err = printer.append(reader).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+        drop(printer);
+
+        // `BufReader` reads ahead, but only a single line is printed.
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            " 00000000  61 61 61 61 61 61 61 61  aaaaaaaa \n"
+        );
+    }
+
+    #[test]
+    fn io_buffer_size() {
+        let input = b"0123456789abcdefghijklmnopqrstuvwxyz".repeat(100);
+        let print = |size| {
+            let mut output = vec![];
+            let mut printer = PrinterBuilder::new(&mut output)
+                .show_color(false)
+                .io_buffer_size(size)
+                .build();
+            printer.print_all(&input[..]).unwrap();
+            drop(printer);
+            output
+        };
+
+        let expected = print(DEFAULT_IO_BUFFER_SIZE);
+        for size in [1, 7, 16, 1000] {
+            assert_eq!(print(size), expected);
+        }
+    }
+
+    #[test]
+    fn display_offset() {
+        let input = io::Cursor::new(b"spamspamspamspamspam");
+        let expected_string = "\
+┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┐
+│deadbeef│ 73 70 61 6d 73 70 61 6d ┊ 73 70 61 6d 73 70 61 6d │spamspam┊spamspam│
+│deadbeff│ 73 70 61 6d             ┊                         │spam    ┊        │
+└────────┴─────────────────────────┴─────────────────────────┴────────┴────────┘
+"
+        .to_owned();
+
+        let mut output = vec![];
+        let mut printer: Printer<Vec<u8>> = Printer::new(
+            &mut output,
+            false,
+            true,
+            true,
+            BorderStyle::Unicode,
+            true,
+            2,
+            1,
+            Base::Hexadecimal,
+            Endianness::Big,
+            CharacterTable::Default,
+        );
+        printer.display_offset(0xdeadbeef);
+
+        printer.print_all(input).unwrap();
+        assert_eq!(printer.bytes_read(), 20);
+
+        let actual_string: &str = str::from_utf8(&output).unwrap();
+        assert_eq!(actual_string, expected_string)
+    }
+
+    #[test]
+    fn multiple_panels() {
+        let input = io::Cursor::new(b"supercalifragilisticexpialidocioussupercalifragilisticexpialidocioussupercalifragilisticexpialidocious");
+        let expected_string = "\
+┌────────┬─────────────────────────┬─────────────────────────┬─────────────────────────┬─────────────────────────┬────────┬────────┬────────┬────────┐
+│00000000│ 73 75 70 65 72 63 61 6c ┊ 69 66 72 61 67 69 6c 69 ┊ 73 74 69 63 65 78 70 69 ┊ 61 6c 69 64 6f 63 69 6f │supercal┊ifragili┊sticexpi┊alidocio│
+│00000020│ 75 73 73 75 70 65 72 63 ┊ 61 6c 69 66 72 61 67 69 ┊ 6c 69 73 74 69 63 65 78 ┊ 70 69 61 6c 69 64 6f 63 │ussuperc┊alifragi┊listicex┊pialidoc│
+│00000040│ 69 6f 75 73 73 75 70 65 ┊ 72 63 61 6c 69 66 72 61 ┊ 67 69 6c 69 73 74 69 63 ┊ 65 78 70 69 61 6c 69 64 │ioussupe┊rcalifra┊gilistic┊expialid│
+│00000060│ 6f 63 69 6f 75 73       ┊                         ┊                         ┊                         │ocious  ┊        ┊        ┊        │
+└────────┴─────────────────────────┴─────────────────────────┴─────────────────────────┴─────────────────────────┴────────┴────────┴────────┴────────┘
+"
+        .to_owned();
+
+        let mut output = vec![];
+        let mut printer: Printer<Vec<u8>> = Printer::new(
+            &mut output,
+            false,
+            true,
+            true,
+            BorderStyle::Unicode,
+            true,
+            4,
+            1,
+            Base::Hexadecimal,
+            Endianness::Big,
+            CharacterTable::Default,
+        );
+
+        printer.print_all(input).unwrap();
+
+        let actual_string: &str = str::from_utf8(&output).unwrap();
+        assert_eq!(actual_string, expected_string)
+    }
+
+    #[test]
+    fn layout_width_matches_output() {
+        let input = b"supercalifragilisticexpialidocious";
+        for (show_position_panel, show_char_panel) in
+            [(true, true), (true, false), (false, true), (false, false)]
+        {
+            let bases = [Base::Binary, Base::Octal, Base::Decimal, Base::Hexadecimal];
+            for (base, group_size) in bases
+                .into_iter()
+                .flat_map(|base| [1, 2, 4, 8].map(|group_size| (base, group_size)))
+            {
+                for (panels, padding) in (1..=3).flat_map(|panels| {
+                    [Padding::Zeros, Padding::Spaces].map(|padding| (panels, padding))
+                }) {
+                    let mut output = vec![];
+                    let builder = PrinterBuilder::new(&mut output)
+                        .show_color(false)
+                        .show_position_panel(show_position_panel)
+                        .show_char_panel(show_char_panel)
+                        .classify_lines(panels == 2)
+                        .line_hash((panels == 3).then_some(LineHash::Crc16))
+                        .num_panels(panels)
+                        .group_size(group_size)
+                        .with_base(base)
+                        .padding(padding)
+                        .tick_marks(matches!(padding, Padding::Spaces));
+                    let width = builder.layout_width();
+                    builder.build().print_all(&input[..]).unwrap();
+
+                    for line in str::from_utf8(&output).unwrap().lines() {
+                        assert_eq!(line.chars().count(), width, "{line}");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Renders the input with every combination of the settings that change
+    /// the layout, and checks that every line occupies the same number of
+    /// terminal columns as the layout, not counting escape sequences.
+    #[test]
+    fn layout_integrity() {
+        // Squeezed zeros, text, and bytes of every category.
+        let mut input = vec![0u8; 48];
+        input.extend(b"supercalifragilisticexpialidocious\x00\x01\t\x7f\x80\xff");
+
+        let bases = [Base::Binary, Base::Octal, Base::Decimal, Base::Hexadecimal];
+        let panel_visibility = [(true, true), (true, false), (false, true), (false, false)];
+        let offset_styles = [
+            OffsetStyle::Byte,
+            OffsetStyle::Sector(NonZeroU64::new(512).unwrap()),
+        ];
+        // Positions that cross 4 GiB, where the position panel gets wider.
+        let starts = [0, 0xffff_ffe0, 0x1234_5678_9abc];
+        for (base, group_size, panels, (show_position_panel, show_char_panel)) in
+            bases.into_iter().flat_map(|base| {
+                [1, 2, 4, 8].into_iter().flat_map(move |group_size| {
+                    (1..=4).flat_map(move |panels| {
+                        panel_visibility.map(|visibility| (base, group_size, panels, visibility))
+                    })
+                })
+            })
+        {
+            for (show_color, border_style, offset_style, start) in [false, true]
+                .into_iter()
+                .flat_map(|show_color| {
+                    [
+                        BorderStyle::Unicode,
+                        BorderStyle::Ascii,
+                        BorderStyle::None,
+                        BorderStyle::Compact,
+                    ]
+                    .map(|border_style| (show_color, border_style))
+                })
+                .flat_map(|(show_color, border_style)| {
+                    offset_styles.map(|offset_style| (show_color, border_style, offset_style))
+                })
+                .flat_map(|(show_color, border_style, offset_style)| {
+                    starts.map(|start| (show_color, border_style, offset_style, start))
+                })
+            {
+                let options = PrinterOptions {
+                    show_color,
+                    show_char_panel,
+                    show_position_panel,
+                    border_style,
+                    panels,
+                    group_size,
+                    base,
+                    offset_style,
+                    max_position: start + input.len() as u64,
+                    squeeze_marker: if show_color { "[snip]" } else { "*" }.to_owned(),
+                    ..PrinterOptions::default()
+                };
+                let mut output = vec![];
+                let mut printer = PrinterBuilder::new(&mut output)
+                    .options(options.clone())
+                    .build();
+                printer.display_offset(start);
+                printer.print_all(&input[..]).unwrap();
+                drop(printer);
+
+                let lines = parse_styled_lines(str::from_utf8(&output).unwrap());
+                assert!(!lines.is_empty());
+                for line in lines {
+                    assert_eq!(
+                        line.width(),
+                        options.layout_width(),
+                        "{:?} with {options:?} at {start:#x}",
+                        line.text()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn squeeze_marker() {
+        let print = |builder: PrinterBuilder<'_, Vec<u8>>| {
+            let mut printer = builder
+                .show_color(false)
+                .num_panels(1)
+                .squeeze_marker("…")
+                .build();
+            printer.print_all(&[0u8; 32][..]).unwrap();
+        };
+
+        let mut output = vec![];
+        print(PrinterBuilder::new(&mut output));
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            "┌────────┬─────────────────────────┬────────┐\n\
+             │00000000│ 00 00 00 00 00 00 00 00 │⋄⋄⋄⋄⋄⋄⋄⋄│\n\
+             │…       │                         │        │\n\
+             │00000020│                         │        │\n\
+             └────────┴─────────────────────────┴────────┘\n"
+        );
+
+        let mut output = vec![];
+        print(PrinterBuilder::new(&mut output).show_position_panel(false));
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            "┌─────────────────────────┬────────┐\n\
+             │ 00 00 00 00 00 00 00 00 │⋄⋄⋄⋄⋄⋄⋄⋄│\n\
+             │…                        │        │\n\
+             │…                        │        │\n\
+             └─────────────────────────┴────────┘\n"
+        );
+
+        assert!(is_valid_squeeze_marker("[snip]"));
+        assert!(!is_valid_squeeze_marker(""));
+        assert!(!is_valid_squeeze_marker("[snipped]"));
+        assert!(!is_valid_squeeze_marker("\x1b[31m*"));
+    }
+
+    #[test]
+    fn relative_to() {
+        let print = |offset_style| {
+            let mut output = vec![];
+            let mut printer = PrinterBuilder::new(&mut output)
+                .show_color(false)
+                .num_panels(1)
+                .offset_style(offset_style)
+                .relative_to(0x1010)
+                .build();
+            printer.display_offset(0x1000);
+            printer.print_all(&b"0123456789abcdef0123"[..]).unwrap();
+            drop(printer);
+            String::from_utf8(output).unwrap()
+        };
+
+        assert_eq!(
+            print(OffsetStyle::Byte),
+            "┌────────┬─────────────────────────┬────────┐\n\
+             │-0000010│ 30 31 32 33 34 35 36 37 │01234567│\n\
+             │-0000008│ 38 39 61 62 63 64 65 66 │89abcdef│\n\
+             │00000000│ 30 31 32 33             │0123    │\n\
+             └────────┴─────────────────────────┴────────┘\n"
+        );
+        assert_eq!(
+            print(OffsetStyle::Sector(NonZeroU64::new(0x200).unwrap())),
+            "┌─────────────────┬─────────────────────────┬────────┐\n\
+             │LBA -0000001 +1f0│ 30 31 32 33 34 35 36 37 │01234567│\n\
+             │LBA -0000001 +1f8│ 38 39 61 62 63 64 65 66 │89abcdef│\n\
+             │LBA 00000000 +000│ 30 31 32 33             │0123    │\n\
+             └─────────────────┴─────────────────────────┴────────┘\n"
+        );
+    }
+
+    #[test]
+    fn debug_layout() {
+        let mut output = vec![];
+        let mut printer = PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .with_border_style(BorderStyle::None)
+            .num_panels(1)
+            .panel_width(16)
+            .offset_style(OffsetStyle::Segment)
+            .tick_marks(true)
+            .tick_mark(Some('-'))
+            .char_panel_tick_marks(false)
+            .uppercase(true)
+            .character_table(CharacterTable::Ascii)
+            .build();
+        printer.display_offset(0xfff0);
+        printer
+            .print_all(&b"\xb4\x09\xba\x0d\x01\xcd\x21\xb8\x00\x4c\xcd\x21Hell\xff"[..])
+            .unwrap();
+        drop(printer);
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            " 0000:FFF0  B4 09 BA 0D 01 CD 21 B8-00 4C CD 21 48 65 6C 6C  ......!..L.!Hell \n \
+             1000:0000  FF                     -                         .                \n"
+        );
+    }
+
+    #[test]
+    fn ruler() {
+        let print = |group_size, tick_marks| {
+            let mut output = vec![];
+            let mut printer = PrinterBuilder::new(&mut output)
+                .show_color(false)
+                .num_panels(2)
+                .group_size(group_size)
+                .tick_marks(tick_marks)
+                .ruler(true)
+                .build();
+            printer.print_all(&b"0123456789abcdef"[..]).unwrap();
+            drop(printer);
+            String::from_utf8(output).unwrap()
+        };
+
+        assert_eq!(
+            print(1, false),
+            "┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┐\n\
+             │        │ 00 01 02 03 04 05 06 07 ┊ 08 09 0a 0b 0c 0d 0e 0f │01234567┊89abcdef│\n\
+             │00000000│ 30 31 32 33 34 35 36 37 ┊ 38 39 61 62 63 64 65 66 │01234567┊89abcdef│\n\
+             └────────┴─────────────────────────┴─────────────────────────┴────────┴────────┘\n"
+        );
+        assert_eq!(
+            print(2, true),
+            "┌────────┬─────────────────────┬─────────────────────┬─────────┬─────────┐\n\
+             │        │ 00   02  ·04   06   ┊ 08   0a  ·0c   0e   │0123·4567┊89ab·cdef│\n\
+             │00000000│ 3031 3233·3435 3637 ┊ 3839 6162·6364 6566 │0123·4567┊89ab·cdef│\n\
+             └────────┴─────────────────────┴─────────────────────┴─────────┴─────────┘\n"
+        );
+    }
+
+    #[test]
+    fn char_panels() {
+        let print = |char_panels| {
+            let mut output = vec![];
+            let mut printer = PrinterBuilder::new(&mut output)
+                .show_color(false)
+                .num_panels(2)
+                .char_panels(char_panels)
+                .build();
+            printer
+                .print_all(&b"0123456789abcdef0123456789"[..])
+                .unwrap();
+            drop(printer);
+            String::from_utf8(output).unwrap()
+        };
+
+        assert_eq!(
+            print(CharPanels::First),
+            "┌────────┬─────────────────────────┬─────────────────────────┬────────┐\n\
+             │00000000│ 30 31 32 33 34 35 36 37 ┊ 38 39 61 62 63 64 65 66 │01234567│\n\
+             │00000010│ 30 31 32 33 34 35 36 37 ┊ 38 39                   │01234567│\n\
+             └────────┴─────────────────────────┴─────────────────────────┴────────┘\n"
+        );
+        assert_eq!(
+            print(CharPanels::Last),
+            "┌────────┬─────────────────────────┬─────────────────────────┬────────┐\n\
+             │00000000│ 30 31 32 33 34 35 36 37 ┊ 38 39 61 62 63 64 65 66 │89abcdef│\n\
+             │00000010│ 30 31 32 33 34 35 36 37 ┊ 38 39                   │89      │\n\
+             └────────┴─────────────────────────┴─────────────────────────┴────────┘\n"
+        );
+        assert_eq!(
+            print(CharPanels::None),
+            "┌────────┬─────────────────────────┬─────────────────────────┐\n\
+             │00000000│ 30 31 32 33 34 35 36 37 ┊ 38 39 61 62 63 64 65 66 │\n\
+             │00000010│ 30 31 32 33 34 35 36 37 ┊ 38 39                   │\n\
+             └────────┴─────────────────────────┴─────────────────────────┘\n"
+        );
+    }
+
+    #[test]
+    fn json_lines() {
+        let mut output = vec![];
+        let mut printer = PrinterBuilder::new(&mut output)
+            .line_format(LineFormat::Json)
+            .build();
+        let mut input = vec![0; 48];
+        input.extend_from_slice(b"a\"b\\\n");
+        printer.print_all(&input[..]).unwrap();
+        drop(printer);
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"offset\":0,\"bytes\":[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0],\"ascii\":\"................\",\"squeezed\":false}\n\
+             {\"offset\":16,\"bytes\":[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0],\"ascii\":\"................\",\"squeezed\":true,\"length\":32}\n\
+             {\"offset\":48,\"bytes\":[97,34,98,92,10],\"ascii\":\"a\\\"b\\\\.\",\"squeezed\":false}\n"
+        );
+    }
+
+    #[test]
+    fn print_incomplete_line() {
+        let mut output = vec![];
+        let mut printer = PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .num_panels(1)
+            .build();
+        printer.append(&b"0123"[..]).unwrap();
+        printer.print_incomplete_line().unwrap();
+        printer.append(&b"4567ab"[..]).unwrap();
+        printer.print_incomplete_line().unwrap();
+        printer.finish().unwrap();
+        drop(printer);
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "┌────────┬─────────────────────────┬────────┐\n\
+             │00000000│ 30 31 32 33             │0123    │\n\
+             \x1b[1F\x1b[J\
+             │00000000│ 30 31 32 33 34 35 36 37 │01234567│\n\
+             │00000008│ 61 62                   │ab      │\n\
+             \x1b[1F\x1b[J\
+             │00000008│ 61 62                   │ab      │\n\
+             └────────┴─────────────────────────┴────────┘\n"
+        );
+    }
+
+    #[test]
+    fn position_width() {
+        let print = |builder: PrinterBuilder<'_, Vec<u8>>| {
+            let mut printer = builder.show_color(false).num_panels(1).build();
+            printer.display_offset(0xffff_fff8);
+            printer.print_all(&b"0123456789abcdef"[..]).unwrap();
+        };
+
+        let mut output = vec![];
+        print(PrinterBuilder::new(&mut output).max_position(0x1_0000_0008));
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            "┌──────────┬─────────────────────────┬────────┐\n\
+             │00fffffff8│ 30 31 32 33 34 35 36 37 │01234567│\n\
+             │0100000000│ 38 39 61 62 63 64 65 66 │89abcdef│\n\
+             └──────────┴─────────────────────────┴────────┘\n"
+        );
+
+        // Without the largest position, the second line is wider than the
+        // others.
+        let mut output = vec![];
+        print(PrinterBuilder::new(&mut output));
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            "┌────────┬─────────────────────────┬────────┐\n\
+             │fffffff8│ 30 31 32 33 34 35 36 37 │01234567│\n\
+             │0100000000│ 38 39 61 62 63 64 65 66 │89abcdef│\n\
+             └────────┴─────────────────────────┴────────┘\n"
+        );
+    }
+
+    #[test]
+    fn tick_marks() {
+        let mut output = vec![];
+        let mut printer = PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .num_panels(2)
+            .group_size(2)
+            .tick_marks(true)
+            .build();
+        printer.print_all(&b"0123456789abcdefghij"[..]).unwrap();
+        drop(printer);
+
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            "┌────────┬─────────────────────┬─────────────────────┬─────────┬─────────┐\n\
+             │00000000│ 3031 3233·3435 3637 ┊ 3839 6162·6364 6566 │0123·4567┊89ab·cdef│\n\
+             │00000010│ 6768 696a·          ┊          ·          │ghij·    ┊    ·    │\n\
+             └────────┴─────────────────────┴─────────────────────┴─────────┴─────────┘\n"
+        );
+    }
+
+    #[test]
+    fn panel_width() {
+        let print = |panel_width, num_panels| {
+            let mut output = vec![];
+            let mut printer = PrinterBuilder::new(&mut output)
+                .show_color(false)
+                .panel_width(panel_width)
+                .num_panels(num_panels)
+                .tick_marks(true)
+                .build();
+            printer
+                .print_all(&b"0123456789abcdef\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0"[..])
+                .unwrap();
+            drop(printer);
+            String::from_utf8(output).unwrap()
+        };
+
+        assert_eq!(
+            print(16, 1),
+            "┌────────┬─────────────────────────────────────────────────┬─────────────────┐\n\
+             │00000000│ 30 31 32 33 34 35 36 37·38 39 61 62 63 64 65 66 │01234567·89abcdef│\n\
+             │00000010│ 00 00 00 00 00 00 00 00·00 00 00 00 00 00 00 00 │⋄⋄⋄⋄⋄⋄⋄⋄·⋄⋄⋄⋄⋄⋄⋄⋄│\n\
+             │00000020│ 00 00 00 00            ·                        │⋄⋄⋄⋄    ·        │\n\
+             └────────┴─────────────────────────────────────────────────┴─────────────────┘\n"
+        );
+        assert_eq!(
+            print(4, 3),
+            "┌────────┬─────────────┬─────────────┬─────────────┬─────┬─────┬─────┐\n\
+             │00000000│ 30 31·32 33 ┊ 34 35·36 37 ┊ 38 39·61 62 │01·23┊45·67┊89·ab│\n\
+             │0000000c│ 63 64·65 66 ┊ 00 00·00 00 ┊ 00 00·00 00 │cd·ef┊⋄⋄·⋄⋄┊⋄⋄·⋄⋄│\n\
+             │00000018│ 00 00·00 00 ┊ 00 00·00 00 ┊ 00 00·00 00 │⋄⋄·⋄⋄┊⋄⋄·⋄⋄┊⋄⋄·⋄⋄│\n\
+             └────────┴─────────────┴─────────────┴─────────────┴─────┴─────┴─────┘\n"
+        );
+    }
+
+    #[test]
+    fn printer_options() {
+        let options = PrinterOptions {
+            show_color: false,
+            panels: 1,
+            group_size: 2,
+            endianness: Endianness::Little,
+            tick_marks: true,
+            ..PrinterOptions::default()
+        };
+        let print = |builder: PrinterBuilder<'_, Vec<u8>>| {
+            let mut printer = builder.build();
+            printer.print_all(&b"0123456789"[..]).unwrap();
+        };
+
+        let mut from_options = vec![];
+        print(PrinterBuilder::new(&mut from_options).options(options.clone()));
+        let mut from_methods = vec![];
+        print(
+            PrinterBuilder::new(&mut from_methods)
+                .show_color(false)
+                .num_panels(1)
+                .group_size(2)
+                .endianness(Endianness::Little)
+                .tick_marks(true),
+        );
+        assert_eq!(from_options, from_methods);
+
+        assert_eq!(
+            PrinterBuilder::new(&mut io::sink())
+                .options(options.clone())
+                .layout_width(),
+            options.layout_width()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn printer_options_serde() {
+        let options = PrinterOptions {
+            base: Base::Octal,
+            character_table: CharacterTable::CP437,
+            offset_style: OffsetStyle::Sector(NonZeroU64::new(512).unwrap()),
+            interleave: vec![Base::Decimal],
+            ..PrinterOptions::default()
+        };
+        let json = serde_json::to_string(&options).unwrap();
+        assert_eq!(
+            serde_json::from_str::<PrinterOptions>(&json).unwrap(),
+            options
+        );
+
+        let options: PrinterOptions = serde_json::from_str(
+            r#"{"panels": 1, "base": "binary", "character-table": "codepage-1047"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            options,
+            PrinterOptions {
+                panels: 1,
+                base: Base::Binary,
+                character_table: CharacterTable::CP1047,
+                ..PrinterOptions::default()
+            }
+        );
+    }
+
+    #[test]
+    fn single_column_chars() {
+        for c in ['a', '⋄', '×', '▒', PLACEHOLDER_CHAR] {
+            assert_eq!(single_column(c), c);
+        }
+        // A combining accent, zero-width characters, a control character and
+        // a wide character.
+        for c in ['\u{301}', '\u{200b}', '\u{200d}', '\u{feff}', '\0', '世'] {
+            assert_eq!(single_column(c), PLACEHOLDER_CHAR);
+        }
+
+        for table in [
+            CharacterTable::Default,
+            CharacterTable::Ascii,
+            CharacterTable::CP1047,
+            CharacterTable::CP437,
+            CharacterTable::CP437BlankControls,
+            CharacterTable::Latin1,
+        ] {
+            let mut sink = io::sink();
+            let printer = PrinterBuilder::new(&mut sink)
+                .character_table(table)
+                .build();
+            for (b, cell) in printer.byte_char_panel.iter().enumerate() {
+                assert_eq!(cell.chars().count(), 1, "{table:?} {b:#04x}");
+                assert_eq!(
+                    cell.chars().next().unwrap().width(),
+                    Some(1),
+                    "{table:?} {b:#04x}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn strict_sanitize() {
+        let print = |strict_sanitize| {
+            let mut output = vec![];
+            PrinterBuilder::new(&mut output)
+                .show_color(false)
+                .with_border_style(BorderStyle::None)
+                .num_panels(1)
+                .highlights(vec![Highlight {
+                    range: 0..1,
+                    color: COLOR_HIGHLIGHT,
+                    label: Some("a\x1b]0;title\x07b\x1b[2Jc\x07".to_owned()),
+                }])
+                .comments(vec![Comment {
+                    position: 0,
+                    text: "\x1b[31mred".to_owned(),
+                }])
+                .strict_sanitize(strict_sanitize)
+                .build()
+                .print_all(&b"\x07\x1b"[..])
+                .unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        assert_eq!(
+            print(true),
+            " 00000000  07 1b                    ••        abc◌ red\n"
+        );
+        // Without it, only the comments are sanitized.
+        assert_eq!(
+            print(false),
+            " 00000000  07 1b                    ••        \
+             a\x1b]0;title\x07b\x1b[2Jc\x07 ◌[31mred\n"
+        );
+        assert_eq!(
+            strip_escapes("\x1b[1;31mbold\x1b[0m \x1b]8;;x\x1b\\link"),
+            "bold link"
+        );
+    }
+
+    #[test]
+    fn line_classes() {
+        assert_eq!(LineClass::of(b"Hello, World!\r\n"), LineClass::Text);
+        assert_eq!(LineClass::of(&[0; 16]), LineClass::Zeros);
+        assert_eq!(
+            LineClass::of(b"\x7fELF\x02\x01\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00"),
+            LineClass::Binary
+        );
+        assert_eq!(
+            LineClass::of(b"\x8f\x13\xa2\x5c\xe1\x07\x99\x3b\xd4\x60\x2e\xfa\x81\xc7\x4d\xb0"),
+            LineClass::HighEntropy
+        );
+    }
+
+    #[test]
+    fn categorizer() {
+        let items = Categorizer::wrap(&b"a\x00 \x1b\xff"[..])
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            items,
+            [
+                (0, b'a', ByteCategory::AsciiPrintable),
+                (1, 0x00, ByteCategory::Null),
+                (2, b' ', ByteCategory::AsciiWhitespace),
+                (3, 0x1b, ByteCategory::AsciiOther),
+                (4, 0xff, ByteCategory::NonAscii),
+            ]
+        );
+        assert_eq!(Categorizer::wrap(io::empty()).count(), 0);
+    }
+
+    #[test]
+    fn reset_per_line() {
+        let input = io::Cursor::new(b"spam");
+        let expected_string =
+            " \x1b[90m00000000\x1b[39m  \x1b[36m73 70 61 6d            \x1b[39m  \x1b[0m\n";
+
+        let mut output = vec![];
+        PrinterBuilder::new(&mut output)
+            .num_panels(1)
+            .show_char_panel(false)
+            .with_border_style(BorderStyle::None)
+            .reset_per_line(true)
+            .build()
+            .print_all(input)
+            .unwrap();
+
+        let actual_string: &str = str::from_utf8(&output).unwrap();
+        assert_eq!(actual_string, expected_string)
+    }
+
+    #[test]
+    fn stateless_colors() {
+        let print = |stateless_colors| {
+            let mut output = vec![];
+            PrinterBuilder::new(&mut output)
+                .num_panels(1)
+                .with_border_style(BorderStyle::None)
+                .show_position_panel(false)
+                .stateless_colors(stateless_colors)
+                .build()
+                .print_all(&b"ab\x00"[..])
+                .unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        assert_eq!(
+            print(false),
+            "  \x1b[36m61 62 \x1b[90m00               \x1b[39m  \
+             \x1b[36mab\x1b[90m⋄     \x1b[39m \n"
+        );
+        assert_eq!(
+            print(true),
+            "  \x1b[36m61\x1b[39m \x1b[36m62\x1b[39m \x1b[90m00\x1b[39m                 \
+             \x1b[36ma\x1b[39m\x1b[36mb\x1b[39m\x1b[90m⋄\x1b[39m      \n"
+        );
+
+        // Every color is reset before the next one is set, with all settings
+        // that add colored cells.
+        let mut output = vec![];
+        PrinterBuilder::new(&mut output)
+            .num_panels(2)
+            .tick_marks(true)
+            .mark_eof(true)
+            .interleave(vec![Base::Binary])
+            .stateless_colors(true)
+            .build()
+            .print_all(&b"\x00\x01spam\xff\n\t"[..])
+            .unwrap();
+        let mut colored = false;
+        for code in str::from_utf8(&output).unwrap().split('\x1b').skip(1) {
+            let is_reset = code.starts_with("[39m");
+            assert_ne!(colored, !is_reset, "{code:?}");
+            colored = !is_reset;
+        }
+        assert!(!colored);
+    }
+
+    #[test]
+    fn padding_with_spaces() {
+        let input = io::Cursor::new(b"\x00\x07\x2a\xff");
+        let expected_string = "\
+┌────────┬─────────────────────────────────┬────────┐
+│00000000│   0   7  42 255                 │⋄•*×    │
+└────────┴─────────────────────────────────┴────────┘
+"
+        .to_owned();
+
+        let mut output = vec![];
+        PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .num_panels(1)
+            .with_base(Base::Decimal)
+            .padding(Padding::Spaces)
+            .build()
+            .print_all(input)
+            .unwrap();
+
+        let actual_string: &str = str::from_utf8(&output).unwrap();
+        assert_eq!(actual_string, expected_string)
+    }
+
+    #[test]
+    fn step_reader() {
+        let data: Vec<u8> = (0..20).collect();
+        let mut output = vec![];
+        let mut reader = StepReader::new(&data[..], 4, 1);
+        reader.read_to_end(&mut output).unwrap();
+        assert_eq!(output, [1, 5, 9, 13, 17]);
+        assert_eq!(reader.consumed(), 20);
+
+        // Read in very small pieces
+        let mut reader = StepReader::new(&data[..], 3, 0);
+        let mut buf = [0; 1];
+        let mut output = vec![];
+        while reader.read(&mut buf).unwrap() > 0 {
+            output.push(buf[0]);
+        }
+        assert_eq!(output, [0, 3, 6, 9, 12, 15, 18]);
+
+        let mut output = vec![];
+        StepReader::new(&data[..], 1, 18)
+            .read_to_end(&mut output)
+            .unwrap();
+        assert_eq!(output, [18, 19]);
+    }
+
+    /// A reader that fails the first `failures` times it reads the bytes in
+    /// `bad`.
+    struct FlakyReader {
+        data: io::Cursor<Vec<u8>>,
+        bad: std::ops::Range<u64>,
+        failures: u32,
+    }
+
+    impl Read for FlakyReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let position = self.data.position();
+            if self.bad.contains(&position) && self.failures > 0 {
+                self.failures -= 1;
+                return Err(io::Error::other("input/output error"));
+            }
+            let end = if position < self.bad.start {
+                self.bad.start
+            } else {
+                u64::MAX
+            };
+            let n = buf.len().min((end - position) as usize);
+            self.data.read(&mut buf[..n])
+        }
+    }
+
+    impl io::Seek for FlakyReader {
+        fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+            self.data.seek(pos)
+        }
+    }
+
+    fn flaky_reader(failures: u32) -> FlakyReader {
+        FlakyReader {
+            data: io::Cursor::new((0..24).collect()),
+            bad: 8..12,
+            failures,
+        }
+    }
+
+    #[test]
+    fn recovering_reader() {
+        let read = |failures, retries, on_error| {
+            let mut reader = RecoveringReader::new(flaky_reader(failures), retries, on_error, 4);
+            let mut output = vec![];
+            let result = reader.read_to_end(&mut output).map(|_| output);
+            (result, reader.stats().clone())
+        };
+
+        let (result, stats) = read(u32::MAX, 0, OnError::Stop);
+        assert!(result.is_err());
+        assert!(stats.bad_blocks.is_empty());
+
+        let (result, stats) = read(2, 2, OnError::Stop);
+        assert_eq!(result.unwrap(), (0..24).collect::<Vec<u8>>());
+        assert_eq!(stats.retries, 2);
+
+        let (result, stats) = read(u32::MAX, 1, OnError::Skip);
+        let mut expected: Vec<u8> = (0..8).collect();
+        expected.extend(12..24);
+        assert_eq!(result.unwrap(), expected);
+        assert_eq!(stats.bad_blocks, vec![8..12]);
+        assert_eq!(stats.retries, 1);
+
+        let (result, stats) = read(u32::MAX, 0, OnError::Zero);
+        let mut expected: Vec<u8> = (0..8).collect();
+        expected.extend([0; 4]);
+        expected.extend(12..24);
+        assert_eq!(result.unwrap(), expected);
+        assert_eq!(stats.bad_blocks, vec![8..12]);
+    }
+
+    /// A device that only allows reads of whole sectors of 4 bytes, into
+    /// memory that is aligned to 4 bytes as well.
+    struct SectorDevice(io::Cursor<Vec<u8>>);
+
+    impl Read for SectorDevice {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.position() % 4 != 0 || buf.len() % 4 != 0 || buf.as_ptr() as usize % 4 != 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "unaligned read",
+                ));
+            }
+            self.0.read(buf)
+        }
+    }
+
+    impl io::Seek for SectorDevice {
+        fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+            self.0.seek(pos)
+        }
+    }
+
+    #[test]
+    fn aligned_reader() {
+        use std::io::{Seek, SeekFrom};
+
+        let device = || SectorDevice(io::Cursor::new((0..30).collect()));
+
+        let mut unaligned = device();
+        unaligned.seek(SeekFrom::Start(5)).unwrap();
+        assert!(unaligned.read(&mut [0; 8]).is_err());
+
+        let mut inner = device();
+        inner.seek(SeekFrom::Start(5)).unwrap();
+        let mut reader = AlignedReader::new(inner, 4).unwrap();
+        let mut buf = [0; 3];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [5, 6, 7]);
+        let mut rest = vec![];
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, (8..30).collect::<Vec<u8>>());
+
+        assert_eq!(reader.seek(SeekFrom::End(-3)).unwrap(), 27);
+        let mut buf = [0; 2];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [27, 28]);
+        assert_eq!(reader.seek(SeekFrom::Current(-26)).unwrap(), 3);
+        assert_eq!(reader.stream_position().unwrap(), 3);
+        let mut buf = [0; 2];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [3, 4]);
+        assert!(reader.seek(SeekFrom::Current(-6)).is_err());
+
+        let mut reader = AlignedReader::new(unaligned, 1).unwrap();
+        assert!(reader.read(&mut [0; 8]).is_err());
+    }
+
+    #[test]
+    fn until_reader() {
+        let read = |input: &[u8], pattern: &[u8], inclusive: bool, chunk_size: usize| {
+            let mut reader = UntilReader::new(input, pattern.to_vec(), inclusive);
+            let mut output = vec![];
+            let mut buf = vec![0; chunk_size];
+            loop {
+                let n = reader.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                output.extend(&buf[..n]);
+            }
+            (output, reader.found())
+        };
+
+        for chunk_size in [1, 2, 1024] {
+            assert_eq!(
+                read(b"xaaabyb", b"aab", true, chunk_size),
+                (b"xaaab".to_vec(), true)
+            );
+            assert_eq!(
+                read(b"xaaabyb", b"aab", false, chunk_size),
+                (b"xa".to_vec(), true)
+            );
+            assert_eq!(
+                read(b"abababc", b"ababc", false, chunk_size),
+                (b"ab".to_vec(), true)
+            );
+            // The input ends within a partial match.
+            assert_eq!(
+                read(b"xyzaa", b"aab", false, chunk_size),
+                (b"xyzaa".to_vec(), false)
+            );
+            assert_eq!(read(b"", b"a", true, chunk_size), (vec![], false));
+        }
+    }
+
+    #[test]
+    fn unreadable_bytes() {
+        let mut reader = RecoveringReader::new(flaky_reader(u32::MAX), 0, OnError::Zero, 4);
+        let mut output = vec![];
+        let mut printer = PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .with_border_style(BorderStyle::None)
+            .num_panels(1)
+            .unreadable_bytes(reader.unreadable_bytes())
+            .build();
+        printer.print_all(&mut reader).unwrap();
+        drop(printer);
+
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            " 00000000  00 01 02 03 04 05 06 07  ⋄••••••• \n \
+             00000008  00 00 00 00 0c 0d 0e 0f  ▒▒▒▒__•• \n \
+             00000010  10 11 12 13 14 15 16 17  •••••••• \n"
+        );
+    }
+
+    #[test]
+    fn gaps() {
+        let input = b"\x00\x0023\0\0\0\0\0\0\0\0\0\0cd";
+        let mut output = vec![];
+        let mut printer = PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .with_border_style(BorderStyle::None)
+            .num_panels(1)
+            .gaps(vec![0x10..0x12, 0x14..0x1e])
+            .build();
+        printer.display_offset(0x10);
+        printer.print_all(&input[..]).unwrap();
+        drop(printer);
+
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            " 00000010  __ __ 32 33 __ __ __ __    23     \n \
+             00000018  __ __ __ __ __ __ 63 64        cd \n"
+        );
+    }
+
+    #[test]
+    fn step_positions() {
+        let data: Vec<u8> = (0..40).collect();
+        let expected_string = "\
+┌────────┬─────────────────────────┬────────┐
+│00000002│ 02 06 0a 0e 12 16 1a 1e │••_•••••│
+│00000022│ 22 26                   │\"&      │
+└────────┴─────────────────────────┴────────┘
+"
+        .to_owned();
+
+        let mut output = vec![];
+        let mut printer = PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .num_panels(1)
+            .step(4)
+            .build();
+        printer.display_offset(2);
+        printer.print_all(StepReader::new(&data[..], 4, 2)).unwrap();
+
+        let actual_string: &str = str::from_utf8(&output).unwrap();
+        assert_eq!(actual_string, expected_string)
+    }
+
+    #[test]
+    fn filter_bytes() {
+        let input = io::Cursor::new(b"spam\xcc\xccspamspamspamspam\xccspam");
+        let expected_string = "\
+┌────────┬─────────────────────────┬────────┐
+│00000000│ 73 70 61 6d cc cc 73 70 │spam××sp│
+│00000010│ 61 6d 73 70 61 6d cc 73 │amspam×s│
+└────────┴─────────────────────────┴────────┘
+"
+        .to_owned();
+
+        let mut output = vec![];
+        let mut printer = PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .num_panels(1)
+            .filter_bytes(vec![0xcc])
+            .build();
+        printer.print_all(input).unwrap();
+        assert_eq!(
+            printer.filter_matches(),
+            FilterMatches { bytes: 3, lines: 2 }
+        );
+        drop(printer);
+
+        let actual_string: &str = str::from_utf8(&output).unwrap();
+        assert_eq!(actual_string, expected_string)
+    }
+
+    #[test]
+    fn find() {
+        let input = b"spamspamspamspXY\x01Zspamspamspamspamspam\xde\xad";
+        let expected_string = "\
+┌────────┬─────────────────────────┬────────┐
+│00000108│ 73 70 61 6d 73 70 58 59 │spamspXY│
+│00000110│ 01 5a 73 70 61 6d 73 70 │•Zspamsp│
+│00000120│ 61 6d 73 70 61 6d de ad │amspam××│
+└────────┴─────────────────────────┴────────┘
+"
+        .to_owned();
+
+        let mut output = vec![];
+        let mut printer = PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .num_panels(1)
+            .io_buffer_size(3)
+            .find(vec![
+                FindPattern {
+                    bytes: vec![Some(b'X'), Some(b'Y'), None, Some(b'Z')],
+                },
+                FindPattern::exact(b"\xde\xad"),
+            ])
+            .build();
+        printer.display_offset(0x100);
+        printer.print_all(&input[..]).unwrap();
+        assert_eq!(
+            printer.find_matches(),
+            [
+                FindMatch {
+                    position: 0x10e,
+                    pattern: 0
+                },
+                FindMatch {
+                    position: 0x126,
+                    pattern: 1
+                },
+            ]
+        );
+        drop(printer);
+
+        let actual_string: &str = str::from_utf8(&output).unwrap();
+        assert_eq!(actual_string, expected_string)
+    }
+
+    #[test]
+    fn spans() {
+        let mut input = [0u8; 64];
+        input[3] = 0xff;
+        input[20] = 0xff;
+        input[60] = 0xff;
+        let mut output = vec![];
+        let mut printer = PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .num_panels(1)
+            .filter_bytes(vec![0xff])
+            .track_spans(true)
+            .build();
+        printer.display_offset(0x100);
+        printer.print_all(&input[..62]).unwrap();
+
+        assert_eq!(printer.spans(), [0x100..0x108, 0x110..0x118, 0x138..0x13e]);
+    }
+
+    #[test]
+    fn offset_hyperlink() {
+        let mut output = vec![];
+        let mut printer = PrinterBuilder::new(&mut output)
+            .show_color(true)
+            .show_char_panel(false)
+            .with_border_style(BorderStyle::None)
+            .num_panels(1)
+            .offset_hyperlink("file:///a.out#{offset}/{offset_dec}".to_owned())
+            .build();
+        printer.display_offset(0x10);
+        printer.print_all(&b"\0"[..]).unwrap();
+        drop(printer);
+
+        let output = str::from_utf8(&output).unwrap();
+        assert!(output.starts_with(
+            " \x1b[90m\x1b]8;;file:///a.out#10/16\x1b\\00000010\x1b]8;;\x1b\\\x1b[39m "
+        ));
+    }
+
+    #[test]
+    fn chain() {
+        use std::io::{Seek, SeekFrom};
+
+        let chain = |parts: &[&'static [u8]]| {
+            Chain::new(
+                parts
+                    .iter()
+                    .map(|part| Input::Buffer(io::Cursor::new(part.to_vec())))
+                    .collect(),
+            )
+        };
+
+        let mut output = vec![];
+        chain(&[b"ab", b"", b"cde"])
+            .read_to_end(&mut output)
+            .unwrap();
+        assert_eq!(output, b"abcde");
+
+        let mut input = chain(&[b"ab", b"cde", b"f"]);
+        let read = |input: &mut Chain, pos| {
+            let mut buf = [0; 2];
+            input.seek(pos).unwrap();
+            let n = input.read(&mut buf).unwrap();
+            buf[..n].to_vec()
+        };
+        assert_eq!(read(&mut input, SeekFrom::Start(3)), b"de");
+        assert_eq!(read(&mut input, SeekFrom::End(-2)), b"e");
+        assert_eq!(read(&mut input, SeekFrom::Current(-4)), b"b");
+        assert_eq!(read(&mut input, SeekFrom::Start(6)), b"");
+        assert!(input.seek(SeekFrom::Current(-7)).is_err());
+    }
+
+    #[test]
+    fn sparse() {
+        use std::io::{Seek, SeekFrom};
+
+        let part = |offset, bytes: &[u8]| (offset, Input::Buffer(io::Cursor::new(bytes.to_vec())));
+        let mut input = Sparse::new(vec![part(6, b"xyz"), part(1, b"ab")]).unwrap();
+        assert_eq!(input.len(), 9);
+        let mut output = vec![];
+        input.read_to_end(&mut output).unwrap();
+        assert_eq!(output, b"\0ab\0\0\0xyz");
+
+        let mut read = |pos| {
+            input.seek(pos).unwrap();
+            let mut buf = [0; 2];
+            let n = input.read(&mut buf).unwrap();
+            buf[..n].to_vec()
+        };
+        assert_eq!(read(SeekFrom::Start(2)), b"b");
+        assert_eq!(read(SeekFrom::Start(3)), b"\0\0");
+        assert_eq!(read(SeekFrom::End(-2)), b"yz");
+        assert_eq!(read(SeekFrom::Current(-8)), b"ab");
+        assert_eq!(read(SeekFrom::Start(20)), b"");
+
+        assert!(Sparse::new(vec![part(0, b"abc"), part(2, b"de")]).is_err());
+    }
+
+    #[test]
+    fn position_highlights() {
+        let highlight = |range, color| Highlight {
+            range,
+            color,
+            label: None,
+        };
+        let mut output = vec![];
+        let mut printer = PrinterBuilder::new(&mut output)
+            .show_color(true)
+            .show_char_panel(false)
+            .num_panels(1)
+            .with_border_style(BorderStyle::None)
+            .position_highlights(vec![
+                highlight(0..0x10, COLOR_HIGHLIGHT),
+                highlight(0x8..0x10, COLOR_EOF),
+            ])
+            .build();
+        printer.print_all(&b"abcdefghijklmnopq"[..]).unwrap();
+        drop(printer);
+
+        let output = str::from_utf8(&output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[0].starts_with(" \x1b[95m00000000"));
+        assert!(lines[1].starts_with(" \x1b[31m00000008"));
+        assert!(lines[2].starts_with(" \x1b[90m00000010"));
+    }
+
+    #[test]
+    fn show_settings() {
+        let print = |panels, show_char_panel| {
+            let mut output = vec![];
+            let mut printer = PrinterBuilder::new(&mut output)
+                .show_color(false)
+                .num_panels(panels)
+                .show_char_panel(show_char_panel)
+                .show_position_panel(false)
+                .group_size(4)
+                .endianness(Endianness::Little)
+                .show_settings(true)
+                .build();
+            printer.print_all(&b"abcd"[..]).unwrap();
+            drop(printer);
+            String::from_utf8(output).unwrap()
+        };
+
+        assert_eq!(
+            print(2, false),
+            "┌─[hex|2 panels|g4|LE|default]──────────┐\n\
+             │ 64636261          ┊                   │\n\
+             └───────────────────┴───────────────────┘\n"
+        );
+        // The legend does not fit.
+        assert_eq!(
+            print(1, true),
+            "┌───────────────────┬────────┐\n\
+             │ 64636261          │abcd    │\n\
+             └───────────────────┴────────┘\n"
+        );
+    }
+
+    #[test]
+    fn print_all_with() {
+        let input: Vec<u8> = [b"ab\n".as_slice(), &[0; 40], b"\xff"].concat();
+        let print = |visit: bool| {
+            let mut output = vec![];
+            let mut visited = vec![];
+            let mut printer = PrinterBuilder::new(&mut output)
+                .show_color(false)
+                .num_panels(1)
+                .build();
+            printer.display_offset(0x100);
+            if visit {
+                // Several appends keep the positions going.
+                printer
+                    .append_with(&input[..20], |position, byte, category| {
+                        visited.push((position, byte, category))
+                    })
+                    .unwrap();
+                printer
+                    .print_all_with(&input[20..], |position, byte, category| {
+                        visited.push((position, byte, category))
+                    })
+                    .unwrap();
+            } else {
+                printer.print_all(&input[..]).unwrap();
+            }
+            drop(printer);
+            (output, visited)
+        };
+
+        let (output, visited) = print(true);
+        assert_eq!(output, print(false).0);
+        // The squeezed zeros are visited as well.
+        assert_eq!(visited.len(), input.len());
+        assert_eq!(visited[0], (0x100, b'a', ByteCategory::AsciiPrintable));
+        assert_eq!(visited[2], (0x102, b'\n', ByteCategory::AsciiWhitespace));
+        assert_eq!(visited[30], (0x11e, 0, ByteCategory::Null));
+        assert_eq!(visited[43], (0x12b, 0xff, ByteCategory::NonAscii));
+    }
+
+    #[test]
+    fn footer_info() {
+        let print = |show_char_panel, display_offset| {
+            let mut output = vec![];
+            let mut printer = PrinterBuilder::new(&mut output)
+                .show_color(false)
+                .num_panels(1)
+                .show_char_panel(show_char_panel)
+                .show_position_panel(false)
+                .footer_info(true)
+                .input_name("abc.bin")
+                .build();
+            printer.display_offset(display_offset);
+            printer.print_all(&b"abc"[..]).unwrap();
+            drop(printer);
+            String::from_utf8(output).unwrap()
+        };
+
+        assert_eq!(
+            print(true, 0x400),
+            "┌─────────────────────────┬────────┐\n\
+             │ 61 62 63                │abc     │\n\
+             └─[abc.bin 0x400-0x402 (3 bytes)]──┘\n"
+        );
+        // Only the range fits.
+        assert_eq!(
+            print(false, 0x400),
+            "┌─────────────────────────┐\n\
+             │ 61 62 63                │\n\
+             └─[0x400-0x402 (3 bytes)]─┘\n"
+        );
+        // Nothing fits.
+        assert_eq!(
+            print(false, 0x1234_5678),
+            "┌─────────────────────────┐\n\
+             │ 61 62 63                │\n\
+             └─────────────────────────┘\n"
+        );
+
+        assert_eq!(format_size(1), "1 byte");
+        assert_eq!(format_size(1023), "1023 bytes");
+        assert_eq!(format_size(1024), "1 KiB");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size((1 << 20) - 1), "1023.9 KiB");
+        assert_eq!(format_size(u64::MAX), "15.9 EiB");
+    }
+
+    #[test]
+    fn comments() {
+        let print = |border_style, comment_width| {
+            let mut output = vec![];
+            let mut builder = PrinterBuilder::new(&mut output)
+                .show_color(false)
+                .num_panels(1)
+                .with_border_style(border_style)
+                .comments(vec![
+                    Comment {
+                        position: 9,
+                        text: "second".to_owned(),
+                    },
+                    Comment {
+                        position: 0,
+                        text: "a rather long\tcomment".to_owned(),
+                    },
+                    Comment {
+                        position: 9,
+                        text: "third".to_owned(),
+                    },
+                ]);
+            if let Some(width) = comment_width {
+                builder = builder.comment_width(width);
+            }
+            builder.build().print_all(&b"0123456789"[..]).unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        assert_eq!(
+            print(BorderStyle::Unicode, None),
+            "┌────────┬─────────────────────────┬────────┐\n\
+             │00000000│ 30 31 32 33 34 35 36 37 │01234567│ a rather long◌comment\n\
+             │00000008│ 38 39                   │89      │ second\n\
+             │        │                         │        │ third\n\
+             └────────┴─────────────────────────┴────────┘\n"
+        );
+        assert_eq!(
+            print(BorderStyle::Ascii, Some(8)),
+            "+--------+-------------------------+--------+\n\
+             |00000000| 30 31 32 33 34 35 36 37 |01234567| a rather\n\
+             |        |                         |        | long◌com\n\
+             |        |                         |        | ment\n\
+             |00000008| 38 39                   |89      | second\n\
+             |        |                         |        | third\n\
+             +--------+-------------------------+--------+\n"
+        );
+
+        assert_eq!(wrap_text("a  b", Some(3)), ["a b"]);
+        assert_eq!(wrap_text("", Some(3)), [""]);
+        assert_eq!(wrap_text("abcdefg hi", Some(3)), ["abc", "def", "g", "hi"]);
+        assert_eq!(wrap_text("世界世", Some(4)), ["世界", "世"]);
+    }
+
+    #[test]
+    fn highlights() {
+        let highlights = vec![
+            Highlight {
+                range: 0x2a..0x30,
+                color: COLOR_EOF,
+                label: Some("squeezed".to_owned()),
+            },
+            Highlight {
+                range: 2..4,
+                color: COLOR_HIGHLIGHT,
+                label: Some("header".to_owned()),
+            },
+            Highlight {
+                range: 3..5,
+                color: COLOR_NULL,
+                label: None,
+            },
+        ];
+        let mut input = b"abcdefgh".to_vec();
+        input.extend([0; 0x30]);
+        input.extend(b"ijklmnop");
+        let mut output = vec![];
+        let mut printer = PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .num_panels(1)
+            .with_border_style(BorderStyle::None)
+            .highlights(highlights.clone())
+            .build();
+        printer.print_all(&input[..]).unwrap();
+        drop(printer);
+
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            " 00000000  61 62 63 64 65 66 67 68  abcdefgh  header\n \
+             00000008  00 00 00 00 00 00 00 00  ⋄⋄⋄⋄⋄⋄⋄⋄ \n \
+             *                                           \n \
+             00000038  69 6a 6b 6c 6d 6e 6f 70  ijklmnop  squeezed\n"
+        );
+
+        let mut output = vec![];
+        let mut printer = PrinterBuilder::new(&mut output)
+            .show_color(true)
+            .show_char_panel(false)
+            .num_panels(1)
+            .with_border_style(BorderStyle::None)
+            .highlights(highlights)
+            .build();
+        printer.print_all(&b"abcdef"[..]).unwrap();
+        drop(printer);
+
+        let output = str::from_utf8(&output).unwrap();
+        assert!(output.contains("\x1b[36m61 62 \x1b[95m63 \x1b[90m64 65 \x1b[36m66"));
+        assert!(output.ends_with(" \x1b[95mheader\x1b[39m\n"));
+    }
+
+    #[test]
+    fn highlight_legend() {
+        let highlights = vec![
+            Highlight {
+                range: 0x40..0x78,
+                color: COLOR_HIGHLIGHT,
+                label: Some("program header".to_owned()),
+            },
+            Highlight {
+                range: 0..0x40,
+                color: COLOR_NULL,
+                label: Some("ELF header".to_owned()),
+            },
+            Highlight {
+                range: 4..5,
+                color: COLOR_EOF,
+                label: None,
+            },
+        ];
+        let mut output = vec![];
+        let mut printer = PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .num_panels(1)
+            .with_border_style(BorderStyle::None)
+            .highlights(highlights)
+            .highlight_legend(true)
+            .build();
+        printer.print_all(&b"abc"[..]).unwrap();
+        drop(printer);
+
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            " 00000000  61 62 63                 abc       ELF header\n\
+             ELF header      0x0..0x40\n\
+             program header  0x40..0x78\n"
+        );
+    }
+
+    #[test]
+    fn styled_lines() {
+        let lines = render_to_lines(
+            b"ab\x00",
+            &PrinterOptions {
+                panels: 1,
+                border_style: BorderStyle::None,
+                show_char_panel: false,
+                offset_hyperlink: Some("x://{offset}".to_owned()),
+                ..PrinterOptions::default()
+            },
+            Theme::default(),
+        );
+        let span = |text: &str, fg| StyledSpan {
+            text: text.to_owned(),
+            style: Style { fg, bg: None },
+        };
+        assert_eq!(
+            lines,
+            vec![StyledLine {
+                spans: vec![
+                    span(" ", None),
+                    span("00000000", Some(Color::BrightBlack)),
+                    span("  ", None),
+                    span("61 62 ", Some(Color::Cyan)),
+                    span("00               ", Some(Color::BrightBlack)),
+                    span("  ", None),
+                ]
+            }]
+        );
+        assert_eq!(lines[0].text(), " 00000000  61 62 00                 ");
+
+        let theme = Theme {
+            null: color_from_name("red").unwrap(),
+            offset: color_from_name("bright-blue").unwrap(),
+            ..Theme::default()
+        };
+        let lines = render_to_lines(
+            b"\x00\x00",
+            &PrinterOptions {
+                panels: 1,
+                border_style: BorderStyle::None,
+                show_char_panel: false,
+                ..PrinterOptions::default()
+            },
+            theme,
+        );
+        assert_eq!(
+            lines[0].spans[1..4],
+            [
+                span("00000000", Some(Color::BrightBlue)),
+                span("  ", None),
+                span("00 00                  ", Some(Color::Red)),
+            ]
+        );
 
-        Ok(())
+        let lines = parse_styled_lines(
+            "\x1b[38;5;208ma\x1b[48;2;1;2;3mb\x1b[0mc\x1b]8;;url\x1b\\d\x1b[1;94;49me\n\nf",
+        );
+        let style = |fg, bg| Style { fg, bg };
+        assert_eq!(
+            lines,
+            vec![
+                StyledLine {
+                    spans: vec![
+                        StyledSpan {
+                            text: "a".to_owned(),
+                            style: style(Some(Color::Indexed(208)), None),
+                        },
+                        StyledSpan {
+                            text: "b".to_owned(),
+                            style: style(Some(Color::Indexed(208)), Some(Color::Rgb(1, 2, 3))),
+                        },
+                        StyledSpan {
+                            text: "cd".to_owned(),
+                            style: Style::default(),
+                        },
+                        StyledSpan {
+                            text: "e".to_owned(),
+                            style: style(Some(Color::BrightBlue), None),
+                        },
+                    ]
+                },
+                StyledLine::default(),
+                StyledLine {
+                    spans: vec![StyledSpan {
+                        text: "f".to_owned(),
+                        style: style(Some(Color::BrightBlue), None),
+                    }]
+                },
+            ]
+        );
     }
 
-    pub fn print_char_panel(&mut self) -> io::Result<()> {
-        for i in 0..self.line_buf.len() {
-            self.print_char(i as u64)?;
-        }
-        Ok(())
-    }
+    #[test]
+    fn event_hook() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
 
-    fn print_byte(&mut self, i: usize, b: u8) -> io::Result<()> {
-        match self.squeezer {
-            Squeezer::Print => {
-                if !self.show_position_panel && i == 0 {
-                    if self.show_color {
-                        self.writer.write_all(COLOR_OFFSET)?;
-                    }
-                    self.writer
-                        .write_all(self.byte_char_panel[b'*' as usize].as_bytes())?;
-                    if self.show_color {
-                        self.writer.write_all(COLOR_RESET)?;
-                    }
-                } else if i % (self.group_size as usize) == 0 {
-                    self.writer.write_all(b" ")?;
-                }
-                for _ in 0..self.base_digits {
-                    self.writer.write_all(b" ")?;
-                }
-            }
-            Squeezer::Delete => self.writer.write_all(b"   ")?,
-            Squeezer::Ignore | Squeezer::Disabled => {
-                if i % (self.group_size as usize) == 0 {
-                    self.writer.write_all(b" ")?;
+        let mut input = b"abcdefgh".to_vec();
+        input.extend([0; 0x20]);
+        input.extend(b"ijklmnop");
+        input.extend([0xff; 0x18]);
+        let events = Rc::new(RefCell::new(vec![]));
+        let mut output = vec![];
+        let mut printer = PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .num_panels(1)
+            .io_buffer_size(0x20)
+            .find(vec![FindPattern::exact(b"gh\0")])
+            .event_hook({
+                let events = events.clone();
+                move |event| {
+                    events.borrow_mut().push(event.clone());
+                    Ok(())
                 }
-                if self.show_color && self.curr_color != Some(Byte(b).color()) {
-                    self.writer.write_all(Byte(b).color())?;
-                    self.curr_color = Some(Byte(b).color());
+            })
+            .build();
+        printer.print_all(&input[..]).unwrap();
+        drop(printer);
+
+        assert_eq!(
+            *events.borrow(),
+            [
+                PrinterEvent::Match(FindMatch {
+                    position: 6,
+                    pattern: 0,
+                }),
+                PrinterEvent::Progress { bytes_read: 0x20 },
+                PrinterEvent::Progress { bytes_read: 0x40 },
+            ]
+        );
+
+        // Without `find`, every line is shown, and the squeezed lines are
+        // reported.
+        events.borrow_mut().clear();
+        let mut output = vec![];
+        let mut printer = PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .num_panels(1)
+            .event_hook({
+                let events = events.clone();
+                move |event| {
+                    events.borrow_mut().push(event.clone());
+                    Ok(())
                 }
-                self.writer
-                    .write_all(self.byte_hex_panel[b as usize].as_bytes())?;
-            }
-        }
-        // byte is last in panel
-        if i % 8 == 7 {
-            if self.show_color {
-                self.curr_color = None;
-                self.writer.write_all(COLOR_RESET)?;
-            }
-            self.writer.write_all(b" ")?;
-            // byte is last in last panel
-            if i as u64 % (8 * self.panels) == 8 * self.panels - 1 {
-                self.writer.write_all(
-                    self.border_style
-                        .outer_sep()
-                        .encode_utf8(&mut [0; 4])
-                        .as_bytes(),
-                )?;
-            } else {
-                self.writer.write_all(
-                    self.border_style
-                        .inner_sep()
-                        .encode_utf8(&mut [0; 4])
-                        .as_bytes(),
-                )?;
-            }
-        }
-        Ok(())
+            })
+            .build();
+        printer.print_all(&input[..]).unwrap();
+        drop(printer);
+
+        assert_eq!(
+            *events.borrow(),
+            [
+                PrinterEvent::Squeezed(SqueezedRegion {
+                    range: 0x10..0x28,
+                    len: 0x18,
+                    byte: 0x00,
+                }),
+                PrinterEvent::Squeezed(SqueezedRegion {
+                    range: 0x38..0x48,
+                    len: 0x10,
+                    byte: 0xff,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn squeeze_summary() {
+        let mut input = b"abcdefgh".to_vec();
+        input.extend([0; 0x40]);
+        input.extend([0xff; 0x20]);
+        input.extend(b"ijklmnop");
+        input.extend([0; 0x18]);
+        let mut output = vec![];
+        let mut printer = PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .num_panels(1)
+            .with_border_style(BorderStyle::None)
+            .squeeze_summary(true)
+            .build();
+        printer.display_offset(0x100);
+        printer.print_all(&input[..]).unwrap();
+        assert_eq!(
+            printer.squeezed_regions(),
+            [
+                SqueezedRegion {
+                    range: 0x110..0x148,
+                    len: 0x38,
+                    byte: 0x00,
+                },
+                SqueezedRegion {
+                    range: 0x150..0x168,
+                    len: 0x18,
+                    byte: 0xff,
+                },
+                SqueezedRegion {
+                    range: 0x178..0x188,
+                    len: 0x10,
+                    byte: 0x00,
+                },
+            ]
+        );
+        drop(printer);
+
+        let output = str::from_utf8(&output).unwrap();
+        assert!(output.ends_with(
+            " 00000188                                    \n\
+             0x110-0x147: 56 bytes of 0x00\n\
+             0x150-0x167: 24 bytes of 0xff\n\
+             0x178-0x187: 16 bytes of 0x00\n"
+        ));
     }
 
-    fn reorder_buffer_to_little_endian(&self, buf: &mut Vec<u8>) {
-        let n = buf.len();
-        let group_sz = self.group_size as usize;
+    #[test]
+    fn rle() {
+        let mut input = vec![1, 2, 3];
+        input.extend([0; 20]);
+        input.extend([7; 15]);
+        input.extend([9; 16]);
+        let mut output = vec![];
+        rle::write_rle(&input[..], &mut output, 0x100).unwrap();
 
-        for idx in (0..n).step_by(group_sz) {
-            let remaining = n - idx;
-            let total = remaining.min(group_sz);
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            "00000100 lit 3 01 02 03\n\
+             00000103 run 20 00\n\
+             00000117 lit 15 07 07 07 07 07 07 07 07 07 07 07 07 07 07 07\n\
+             00000126 run 16 09\n"
+        );
 
-            buf[idx..idx + total].reverse();
-        }
+        let mut output = vec![];
+        rle::write_rle(&[][..], &mut output, 0).unwrap();
+        assert!(output.is_empty());
     }
 
-    pub fn print_bytes(&mut self) -> io::Result<()> {
-        let mut buf = self.line_buf.clone();
+    #[test]
+    fn porcelain_v1() {
+        let input: Vec<u8> = (0..=0x20).collect();
+        let mut output = vec![];
+        porcelain::write_v1(&input[..], &mut output, 0x1_0000_0000).unwrap();
 
-        if matches!(self.endianness, Endianness::Little) {
-            self.reorder_buffer_to_little_endian(&mut buf);
-        };
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            "0000000100000000: 00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f\n\
+             0000000100000010: 10 11 12 13 14 15 16 17 18 19 1a 1b 1c 1d 1e 1f\n\
+             0000000100000020: 20\n"
+        );
 
-        for (i, &b) in buf.iter().enumerate() {
-            self.print_byte(i, b)?;
-        }
-        Ok(())
+        let mut output = vec![];
+        porcelain::write_v1(&[][..], &mut output, 0).unwrap();
+        assert!(output.is_empty());
     }
 
-    /// Loop through the given `Reader`, printing until the `Reader` buffer
-    /// is exhausted.
-    pub fn print_all<Reader: Read>(&mut self, reader: Reader) -> io::Result<()> {
-        let mut is_empty = true;
-
-        let mut buf = BufReader::new(reader);
-
-        let leftover = loop {
-            // read a maximum of 8 * self.panels bytes from the reader
-            if let Ok(n) = buf.read(&mut self.line_buf) {
-                if n > 0 && n < 8 * self.panels as usize {
-                    // if less are read, that indicates end of file after
-                    if is_empty {
-                        self.print_header()?;
-                        is_empty = false;
-                    }
-                    let mut leftover = n;
-                    // loop until input is ceased
-                    if let Some(s) = loop {
-                        if let Ok(n) = buf.read(&mut self.line_buf[leftover..]) {
-                            leftover += n;
-                            // there is no more input being read
-                            if n == 0 {
-                                self.line_buf.resize(leftover, 0);
-                                break Some(leftover);
-                            }
-                            // amount read has exceeded line buffer
-                            if leftover >= 8 * self.panels as usize {
-                                break None;
-                            }
-                        }
-                    } {
-                        break Some(s);
-                    };
-                } else if n == 0 {
-                    // if no bytes are read, that indicates end of file
-                    if self.squeezer == Squeezer::Delete {
-                        // empty the last line when ending is squeezed
-                        self.line_buf.clear();
-                        break Some(0);
-                    }
-                    break None;
-                }
-            }
-            if is_empty {
-                self.print_header()?;
-            }
+    #[test]
+    fn diff() {
+        let options = diff::DiffOptions {
+            show_color: false,
+            ..diff::DiffOptions::default()
+        };
+        let mut left = vec![0x7f, b'E', b'L', b'F', 0, 0, 0, 0];
+        left.extend([0; 24]);
+        let mut right = left.clone();
+        right[3] = b'f';
+        right.truncate(26);
+        let mut output = vec![];
+        let summary = diff::write_diff(&left[..], &right[..], &mut output, &options).unwrap();
 
-            // squeeze is active, check if the line is the same
-            // skip print if still squeezed, otherwise print and deactivate squeeze
-            if matches!(self.squeezer, Squeezer::Print | Squeezer::Delete) {
-                if self
-                    .line_buf
-                    .chunks_exact(std::mem::size_of::<usize>())
-                    .all(|w| usize::from_ne_bytes(w.try_into().unwrap()) == self.squeeze_byte)
-                {
-                    if self.squeezer == Squeezer::Delete {
-                        self.idx += 8 * self.panels;
-                        continue;
-                    }
-                } else {
-                    self.squeezer = Squeezer::Ignore;
-                }
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            "┌────────┬─────────────────────────┬────────┬─────────────────────────┬────────┐\n\
+             │00000000│ 7f 45 4c 46 00 00 00 00 │•ELF⋄⋄⋄⋄≠ 7f 45 4c 66 00 00 00 00 │•ELf⋄⋄⋄⋄│\n\
+             │00000008│ 00 00 00 00 00 00 00 00 │⋄⋄⋄⋄⋄⋄⋄⋄│ 00 00 00 00 00 00 00 00 │⋄⋄⋄⋄⋄⋄⋄⋄│\n\
+             │*       │                         │        │                         │        │\n\
+             │00000018│ 00 00 00 00 00 00 00 00 │⋄⋄⋄⋄⋄⋄⋄⋄≠ 00 00                   │⋄⋄      │\n\
+             └────────┴─────────────────────────┴────────┴─────────────────────────┴────────┘\n"
+        );
+        assert_eq!(
+            summary,
+            diff::DiffSummary {
+                differences: 7,
+                first_difference: Some(3),
+                left_len: 32,
+                right_len: 26,
             }
+        );
 
-            // print the line
-            self.print_position_panel()?;
-            self.print_bytes()?;
-            if self.show_char_panel {
-                self.print_char_panel()?;
-            }
-            self.writer.write_all(b"\n")?;
+        let mut output = vec![];
+        let summary = diff::write_diff(&left[..], &left[..], &mut output, &options).unwrap();
+        assert!(summary.is_identical());
+    }
 
-            if is_empty {
-                self.writer.flush()?;
-                is_empty = false;
-            }
+    #[test]
+    fn read_plain_hex() {
+        let mut output = vec![];
+        let len = plain_hex::read_plain_hex(&b"7f454c46\n02 01\n"[..], &mut output).unwrap();
+        assert_eq!(len, 6);
+        assert_eq!(output, b"\x7fELF\x02\x01");
 
-            // increment index to next line
-            self.idx += 8 * self.panels;
+        let mut hex = vec![];
+        plain_hex::write_plain_hex(&output[..], &mut hex, std::num::NonZeroUsize::new(4)).unwrap();
+        let mut bytes = vec![];
+        plain_hex::read_plain_hex(&hex[..], &mut bytes).unwrap();
+        assert_eq!(bytes, output);
 
-            // change from print to delete if squeeze is still active
-            if self.squeezer == Squeezer::Print {
-                self.squeezer = Squeezer::Delete;
-            }
+        let err = plain_hex::read_plain_hex(&b"7f4x"[..], &mut vec![]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "expected a hex digit at position 0x3, found 'x'"
+        );
+        let err = plain_hex::read_plain_hex(&b"7f4"[..], &mut vec![]).unwrap_err();
+        assert_eq!(err.to_string(), "expected an even number of hex digits");
+    }
 
-            // repeat the first byte in the line until it's a usize
-            // compare that usize with each usize chunk in the line
-            // if they are all the same, change squeezer to print
-            let repeat_byte = (self.line_buf[0] as usize) * (usize::MAX / 255);
-            if !matches!(self.squeezer, Squeezer::Disabled | Squeezer::Delete)
-                && self
-                    .line_buf
-                    .chunks_exact(std::mem::size_of::<usize>())
-                    .all(|w| usize::from_ne_bytes(w.try_into().unwrap()) == repeat_byte)
-            {
-                self.squeezer = Squeezer::Print;
-                self.squeeze_byte = repeat_byte;
-            };
-        };
+    #[test]
+    fn c_include() {
+        let input: Vec<u8> = (0..=0x0c).collect();
+        let mut output = vec![];
+        c_include::write_c_include(&input[..], &mut output, "blob").unwrap();
 
-        // special ending
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            "unsigned char blob[] = {\n  \
+             0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b,\n  \
+             0x0c\n\
+             };\n\
+             unsigned int blob_len = 13;\n"
+        );
 
-        if is_empty {
-            self.base_digits = 2;
-            self.print_header()?;
-            if self.show_position_panel {
-                write!(self.writer, "{0:9}", "│")?;
-            }
-            write!(
-                self.writer,
-                "{0:2}{1:2$}{0}{0:>3$}",
-                "│",
-                "No content",
-                self.panel_sz() - 1,
-                self.panel_sz() + 1,
-            )?;
-            if self.show_char_panel {
-                write!(self.writer, "{0:>9}{0:>9}", "│")?;
-            }
-            writeln!(self.writer)?;
-        } else if let Some(n) = leftover {
-            // last line is incomplete
-            self.print_position_panel()?;
-            self.squeezer = Squeezer::Ignore;
-            self.print_bytes()?;
-            self.squeezer = Squeezer::Print;
-            for i in n..8 * self.panels as usize {
-                self.print_byte(i, 0)?;
-            }
-            if self.show_char_panel {
-                self.squeezer = Squeezer::Ignore;
-                self.print_char_panel()?;
-                self.squeezer = Squeezer::Print;
-                for i in n..8 * self.panels as usize {
-                    self.print_char(i as u64)?;
-                }
-            }
-            self.writer.write_all(b"\n")?;
-        }
+        let mut output = vec![];
+        c_include::write_c_include(&[][..], &mut output, "blob").unwrap();
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            "unsigned char blob[] = {\n};\nunsigned int blob_len = 0;\n"
+        );
 
-        self.print_footer()?;
+        assert_eq!(c_include::c_identifier("firmware.bin"), "firmware_bin");
+        assert_eq!(c_include::c_identifier("boot-loader_v2"), "boot_loader_v2");
+        assert_eq!(c_include::c_identifier("1.img"), "__1_img");
+    }
 
-        self.writer.flush()?;
+    #[test]
+    fn category_counts() {
+        let mut counts = CategoryCounts::default();
+        counts.add(b"spam\n\x00\x00\xff");
+        assert_eq!(counts.total(), 8);
+        assert_eq!(counts.count(ByteCategory::Null), 2);
+        assert_eq!(counts.count(ByteCategory::AsciiPrintable), 4);
+        assert_eq!(counts.count(ByteCategory::AsciiWhitespace), 1);
+        assert_eq!(counts.count(ByteCategory::AsciiOther), 0);
+        assert_eq!(counts.count(ByteCategory::NonAscii), 1);
+    }
 
-        Ok(())
+    #[test]
+    fn classify_lines() {
+        let input = io::Cursor::new(b"spamspamspamspam\x00\x00\x00");
+        let expected_string = "\
+┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┬─┐
+│00000000│ 73 70 61 6d 73 70 61 6d ┊ 73 70 61 6d 73 70 61 6d │spamspam┊spamspam│T│
+│00000010│ 00 00 00                ┊                         │⋄⋄⋄     ┊        │Z│
+└────────┴─────────────────────────┴─────────────────────────┴────────┴────────┴─┘
+"
+        .to_owned();
+
+        let mut output = vec![];
+        PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .classify_lines(true)
+            .build()
+            .print_all(input)
+            .unwrap();
+
+        let actual_string: &str = str::from_utf8(&output).unwrap();
+        assert_eq!(actual_string, expected_string)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::io;
-    use std::str;
+    #[test]
+    fn compact_border() {
+        let print = |builder: PrinterBuilder<'_, Vec<u8>>, input: &[u8]| {
+            let mut printer = builder
+                .show_color(false)
+                .with_border_style(BorderStyle::Compact)
+                .character_table(CharacterTable::Ascii)
+                .build();
+            printer.print_all(input).unwrap();
+        };
 
-    use super::*;
+        let mut output = vec![];
+        print(
+            PrinterBuilder::new(&mut output),
+            b"\x7fELF\x02\x01\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00spam",
+        );
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            "00000000: 7f 45 4c 46 02 01 01 00  00 00 00 00 00 00 00 00  |.ELF............|\n\
+             00000010: 73 70 61 6d                                       |spam            |\n"
+        );
 
-    fn assert_print_all_output<Reader: Read>(input: Reader, expected_string: String) {
         let mut output = vec![];
-        let mut printer = Printer::new(
-            &mut output,
-            false,
-            true,
-            true,
-            BorderStyle::Unicode,
-            true,
-            2,
-            1,
-            Base::Hexadecimal,
-            Endianness::Big,
-            CharacterTable::Default,
+        print(
+            PrinterBuilder::new(&mut output)
+                .num_panels(1)
+                .classify_lines(true)
+                .line_hash(Some(LineHash::Crc8)),
+            b"spam",
+        );
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            "00000000: 73 70 61 6d              |spam    | T 8b\n"
         );
 
-        printer.print_all(input).unwrap();
+        let mut output = vec![];
+        print(
+            PrinterBuilder::new(&mut output)
+                .num_panels(1)
+                .show_char_panel(false),
+            b"spam",
+        );
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            "00000000: 73 70 61 6d             \n"
+        );
+    }
+
+    #[test]
+    fn line_hash() {
+        assert_eq!(LineHash::Crc8.of(b"123456789"), 0xf4);
+        assert_eq!(LineHash::Crc16.of(b"123456789"), 0x29b1);
+
+        let input = io::Cursor::new(b"spamspamspamspam\x00\x00\x00");
+        let expected_string = "\
+┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┬──┐
+│00000000│ 73 70 61 6d 73 70 61 6d ┊ 73 70 61 6d 73 70 61 6d │spamspam┊spamspam│69│
+│00000010│ 00 00 00                ┊                         │⋄⋄⋄     ┊        │00│
+└────────┴─────────────────────────┴─────────────────────────┴────────┴────────┴──┘
+"
+        .to_owned();
+
+        let mut output = vec![];
+        PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .line_hash(Some(LineHash::Crc8))
+            .build()
+            .print_all(input)
+            .unwrap();
 
         let actual_string: &str = str::from_utf8(&output).unwrap();
-        assert_eq!(actual_string, expected_string,)
+        assert_eq!(actual_string, expected_string)
     }
 
     #[test]
-    fn empty_file_passes() {
-        let input = io::empty();
+    fn interleaved_rows() {
+        let input = io::Cursor::new(b"spam\nspam\x00\xff");
         let expected_string = "\
-┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┐
-│        │ No content              │                         │        │        │
-└────────┴─────────────────────────┴─────────────────────────┴────────┴────────┘
+┌────────┬─────────────────────────────────┬────────┐
+│00000000│  73  70  61  6d  0a  73  70  61 │spam_spa│
+│        │ 115 112 097 109 010 115 112 097 │        │
+│00000008│  6d  00  ff                     │m⋄×     │
+│        │ 109 000 255                     │        │
+└────────┴─────────────────────────────────┴────────┘
 "
         .to_owned();
-        assert_print_all_output(input, expected_string);
+
+        let mut output = vec![];
+        PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .num_panels(1)
+            .interleave(vec![Base::Decimal])
+            .build()
+            .print_all(input)
+            .unwrap();
+
+        let actual_string: &str = str::from_utf8(&output).unwrap();
+        assert_eq!(actual_string, expected_string)
     }
 
     #[test]
-    fn short_input_passes() {
-        let input = io::Cursor::new(b"spam");
+    fn mnemonics() {
+        let input = io::Cursor::new(b"GET /\r\n\x1bspam\x00\x7f");
         let expected_string = "\
-┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┐
-│00000000│ 73 70 61 6d             ┊                         │spam    ┊        │
-└────────┴─────────────────────────┴─────────────────────────┴────────┴────────┘
+┌────────┬─────────────────────────────────┬────────┐
+│00000000│  47  45  54  20  2f  0d  0a  1b │GET /__•│
+│        │                      CR  LF ESC │        │
+│00000008│  73  70  61  6d  00  7f         │spam⋄•  │
+│        │                 NUL DEL         │        │
+└────────┴─────────────────────────────────┴────────┘
 "
         .to_owned();
-        assert_print_all_output(input, expected_string);
+
+        let mut output = vec![];
+        PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .num_panels(1)
+            .mnemonics(true)
+            .build()
+            .print_all(input)
+            .unwrap();
+
+        let actual_string: &str = str::from_utf8(&output).unwrap();
+        assert_eq!(actual_string, expected_string)
     }
 
     #[test]
-    fn display_offset() {
+    fn sector_offsets() {
         let input = io::Cursor::new(b"spamspamspamspamspam");
         let expected_string = "\
-┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┐
-│deadbeef│ 73 70 61 6d 73 70 61 6d ┊ 73 70 61 6d 73 70 61 6d │spamspam┊spamspam│
-│deadbeff│ 73 70 61 6d             ┊                         │spam    ┊        │
-└────────┴─────────────────────────┴─────────────────────────┴────────┴────────┘
+┌───────────────┬─────────────────────────┬────────┐
+│LBA 00000002 +8│ 73 70 61 6d 73 70 61 6d │spamspam│
+│LBA 00000003 +0│ 73 70 61 6d 73 70 61 6d │spamspam│
+│LBA 00000003 +8│ 73 70 61 6d             │spam    │
+└───────────────┴─────────────────────────┴────────┘
 "
         .to_owned();
 
         let mut output = vec![];
-        let mut printer: Printer<Vec<u8>> = Printer::new(
-            &mut output,
-            false,
-            true,
-            true,
-            BorderStyle::Unicode,
-            true,
-            2,
-            1,
-            Base::Hexadecimal,
-            Endianness::Big,
-            CharacterTable::Default,
-        );
-        printer.display_offset(0xdeadbeef);
-
+        let mut printer = PrinterBuilder::new(&mut output)
+            .show_color(false)
+            .num_panels(1)
+            .offset_style(OffsetStyle::Sector(NonZeroU64::new(16).unwrap()))
+            .build();
+        printer.display_offset(40);
         printer.print_all(input).unwrap();
 
         let actual_string: &str = str::from_utf8(&output).unwrap();
@@ -846,39 +5501,77 @@ mod tests {
     }
 
     #[test]
-    fn multiple_panels() {
-        let input = io::Cursor::new(b"supercalifragilisticexpialidocioussupercalifragilisticexpialidocioussupercalifragilisticexpialidocious");
+    fn mark_eof() {
+        let print = |input: &[u8]| {
+            let mut output = vec![];
+            PrinterBuilder::new(&mut output)
+                .show_color(false)
+                .num_panels(1)
+                .mark_eof(true)
+                .build()
+                .print_all(input)
+                .unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        assert_eq!(
+            print(b"spam"),
+            "\
+┌────────┬─────────────────────────┬────────┐
+│00000000│ 73 70 61 6d▏            │spam    │
+└────────┴─────────────────────────┴────────┘
+"
+        );
+        assert_eq!(
+            print(b"spamspam"),
+            "\
+┌────────┬─────────────────────────┬────────┐
+│00000000│ 73 70 61 6d 73 70 61 6d │spamspam│
+│00000008│▏                        │        │
+└────────┴─────────────────────────┴────────┘
+"
+        );
+    }
+
+    #[test]
+    fn append_continues_across_readers() {
         let expected_string = "\
-┌────────┬─────────────────────────┬─────────────────────────┬─────────────────────────┬─────────────────────────┬────────┬────────┬────────┬────────┐
-│00000000│ 73 75 70 65 72 63 61 6c ┊ 69 66 72 61 67 69 6c 69 ┊ 73 74 69 63 65 78 70 69 ┊ 61 6c 69 64 6f 63 69 6f │supercal┊ifragili┊sticexpi┊alidocio│
-│00000020│ 75 73 73 75 70 65 72 63 ┊ 61 6c 69 66 72 61 67 69 ┊ 6c 69 73 74 69 63 65 78 ┊ 70 69 61 6c 69 64 6f 63 │ussuperc┊alifragi┊listicex┊pialidoc│
-│00000040│ 69 6f 75 73 73 75 70 65 ┊ 72 63 61 6c 69 66 72 61 ┊ 67 69 6c 69 73 74 69 63 ┊ 65 78 70 69 61 6c 69 64 │ioussupe┊rcalifra┊gilistic┊expialid│
-│00000060│ 6f 63 69 6f 75 73       ┊                         ┊                         ┊                         │ocious  ┊        ┊        ┊        │
-└────────┴─────────────────────────┴─────────────────────────┴─────────────────────────┴─────────────────────────┴────────┴────────┴────────┴────────┘
+┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┐
+│00000000│ 73 70 61 6d 00 00 00 00 ┊ 00 00 00 00 00 00 00 00 │spam⋄⋄⋄⋄┊⋄⋄⋄⋄⋄⋄⋄⋄│
+│00000010│ 00 00 00 00 00 00 00 00 ┊ 00 00 00 00 00 00 00 00 │⋄⋄⋄⋄⋄⋄⋄⋄┊⋄⋄⋄⋄⋄⋄⋄⋄│
+│*       │                         ┊                         │        ┊        │
+│00000040│ 65 67 67 73             ┊                         │eggs    ┊        │
+└────────┴─────────────────────────┴─────────────────────────┴────────┴────────┘
 "
         .to_owned();
 
         let mut output = vec![];
-        let mut printer: Printer<Vec<u8>> = Printer::new(
-            &mut output,
-            false,
-            true,
-            true,
-            BorderStyle::Unicode,
-            true,
-            4,
-            1,
-            Base::Hexadecimal,
-            Endianness::Big,
-            CharacterTable::Default,
-        );
-
-        printer.print_all(input).unwrap();
+        let mut printer = PrinterBuilder::new(&mut output).show_color(false).build();
+        printer.append(&b"spam"[..]).unwrap();
+        printer.append(&[0u8; 30][..]).unwrap();
+        printer.append(&[0u8; 30][..]).unwrap();
+        printer.append(&b"eggs"[..]).unwrap();
+        printer.finish().unwrap();
+        assert_eq!(printer.bytes_read(), 68);
 
         let actual_string: &str = str::from_utf8(&output).unwrap();
         assert_eq!(actual_string, expected_string)
     }
 
+    #[test]
+    fn incomplete_line_after_squeezable_line() {
+        let input =
+            io::Cursor::new(b"\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00A");
+        let expected_string = "\
+┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┐
+│00000000│ 00 00 00 00 00 00 00 00 ┊ 00 00 00 00 00 00 00 00 │⋄⋄⋄⋄⋄⋄⋄⋄┊⋄⋄⋄⋄⋄⋄⋄⋄│
+│00000010│ 41                      ┊                         │A       ┊        │
+└────────┴─────────────────────────┴─────────────────────────┴────────┴────────┘
+"
+        .to_owned();
+        assert_print_all_output(input, expected_string);
+    }
+
     #[test]
     fn squeeze_works() {
         let input = io::Cursor::new(b"\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00");
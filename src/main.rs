@@ -1,263 +1,429 @@
-use std::fs::File;
-use std::io::{self, prelude::*, BufWriter, SeekFrom};
-use std::num::{NonZeroI64, NonZeroU64};
-use std::path::PathBuf;
-
-use clap::builder::ArgPredicate;
-use clap::{ArgAction, Parser, ValueEnum};
+use std::cell::RefCell;
+use std::env;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, prelude::*, BufReader, IsTerminal, SeekFrom};
+use std::num::{NonZeroI64, NonZeroU64, NonZeroUsize};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::{self, Child, ChildStdin, ChildStdout, Stdio};
+use std::rc::Rc;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use clap::{CommandFactory, FromArgMatches, Parser};
 
 use anyhow::{anyhow, Context, Result};
 
-use const_format::formatcp;
+use notify::{EventKind, RecursiveMode, Watcher};
 
 use thiserror::Error as ThisError;
 
 use terminal_size::terminal_size;
 
-use hexyl::{Base, BorderStyle, CharacterTable, Endianness, Input, PrinterBuilder};
-
+use hexyl::{
+    AlignedReader, Base, BorderStyle, ByteCategory, CategoryCounts, Chain, CharacterTable,
+    Endianness, FindMatch, FindPattern, Follow, Highlight, Input, LineFormat, OffsetStyle, OnError,
+    PrinterBuilder, PrinterOptions, ReadStats, RecoveringReader, StepReader, UntilReader,
+    COLOR_OFFSET, DEFAULT_IO_BUFFER_SIZE,
+};
+
+use hexyl::c_include::c_identifier;
+use hexyl::diff::DiffOptions;
+
+use wrap::WrapWriter;
+
+use layout::Fit;
+
+use output::Stdout;
+
+use comments::parse_comments;
+
+use reverse::{reverse, DumpFormat};
+
+use csv_ranges::parse_csv_ranges;
+
+use terminal::TerminalSession;
+
+#[cfg(unix)]
+use status::StatusWriter;
+
+use theme::{resolve_theme, Role};
+
+#[cfg(feature = "session")]
+use session::Session;
+
+#[cfg(feature = "decompress")]
+use cli::MemberOffsets;
+use cli::{
+    Cli, ColorWhen, Column, Command, ConvertArgs, ConvertInput, ConvertOutput, DiffArgs, DumpArgs,
+    OffsetStyleArg, OutputFormat, Porcelain, StrictLength, Style,
+};
+
+mod annotate;
+mod cli;
+#[cfg(feature = "clipboard")]
+mod clipboard;
+mod comments;
+mod csv_ranges;
+#[cfg(feature = "decompress")]
+mod decompress;
+mod device;
+#[cfg(feature = "dwarf")]
+mod dwarf;
+mod highlight;
+mod jobs;
+mod layout;
+mod manpage;
+mod map;
+mod output;
+mod prompt;
+mod reverse;
+mod rules;
+#[cfg(feature = "session")]
+mod session;
+#[cfg(unix)]
+mod status;
+mod stride;
+mod terminal;
 #[cfg(test)]
 mod tests;
+mod text;
+mod theme;
+#[cfg(all(feature = "tui", unix))]
+mod tui;
+mod verify;
+mod wrap;
+#[cfg(feature = "yara")]
+mod yara_scan;
+
+pub(crate) const DEFAULT_BLOCK_SIZE: i64 = 512;
+
+/// The environment variable with default options, see [`parse_default_args`].
+const DEFAULT_ARGS_VAR: &str = "HEXYL_DEFAULT_ARGS";
+
+/// The width that comments are wrapped to if the dump leaves less room in the
+/// terminal, see `--comments`.
+const MIN_COMMENT_WIDTH: usize = 20;
+
+/// How often a file is checked for new bytes with `--follow`.
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Splits the options in the environment variable with default options (a
+/// shell-like argument string, e.g. `--border=none --panels=1`).
+fn parse_default_args(default_args: &str) -> Result<Vec<String>> {
+    shell_words::split(default_args)
+        .with_context(|| format!("Could not parse the {DEFAULT_ARGS_VAR} environment variable"))
+}
 
-const DEFAULT_BLOCK_SIZE: i64 = 512;
-
-const LENGTH_HELP_TEXT: &str = "Only read N bytes from the input. The N argument can also include \
-                                a unit with a decimal prefix (kB, MB, ..) or binary prefix (kiB, \
-                                MiB, ..), or can be specified using a hex number. The short \
-                                option '-l' can be used as an alias.
-Examples: --length=64, --length=4KiB, --length=0xff";
-
-const SKIP_HELP_TEXT: &str = "Skip the first N bytes of the input. The N argument can also \
-                              include a unit (see `--length` for details).
-A negative value is valid and will seek from the end of the file.";
-
-const BLOCK_SIZE_HELP_TEXT: &str = "Sets the size of the `block` unit to SIZE.
-Examples: --block-size=1024, --block-size=4kB";
-
-const DISPLAY_OFFSET_HELP_TEXT: &str = "Add N bytes to the displayed file position. The N \
-                                        argument can also include a unit (see `--length` for \
-                                        details).
-A negative value is valid and calculates an offset relative to the end of the file.";
-
-const TERMINAL_WIDTH_HELP_TEXT: &str = "Sets the number of terminal columns to be displayed.
-Since the terminal width may not be an evenly divisible by the width per hex data column, this \
-                                        will use the greatest number of hex data panels that can \
-                                        fit in the requested width but still leave some space to \
-                                        the right.
-Cannot be used with other width-setting options.";
-
-#[derive(Debug, Parser)]
-#[command(version, about, max_term_width(90))]
-struct Opt {
-    /// The file to display. If no FILE argument is given, read from STDIN.
-    #[arg(value_name("FILE"))]
-    file: Option<PathBuf>,
-
-    #[arg(
-        help(LENGTH_HELP_TEXT),
-        short('n'),
-        long,
-        visible_short_alias('c'),
-        visible_alias("bytes"),
-        short_alias('l'),
-        value_name("N")
-    )]
-    length: Option<String>,
-
-    #[arg(help(SKIP_HELP_TEXT), short, long, value_name("N"))]
-    skip: Option<String>,
+/// Inserts the `default_args` into the command line `args`, right after the
+/// program name or subcommand. Options given on the command line are parsed
+/// later and therefore take precedence.
+fn args_with_defaults(mut args: Vec<OsString>, default_args: Vec<String>) -> Vec<OsString> {
+    let is_subcommand = args.get(1).is_some_and(|arg| {
+        Cli::command()
+            .get_subcommands()
+            .any(|subcommand| arg == subcommand.get_name())
+    });
+    let position = args.len().min(if is_subcommand { 2 } else { 1 });
+    args.splice(
+        position..position,
+        default_args.into_iter().map(OsString::from),
+    );
+    args
+}
 
-    #[arg(
-        help(BLOCK_SIZE_HELP_TEXT),
-        long,
-        default_value(formatcp!("{DEFAULT_BLOCK_SIZE}")),
-        value_name("SIZE")
-    )]
-    block_size: String,
-
-    /// Displays all input data. Otherwise any number of groups of output lines
-    /// which would be identical to the preceding group of lines, are replaced
-    /// with a line comprised of a single asterisk.
-    #[arg(short('v'), long)]
-    no_squeezing: bool,
-
-    /// When to use colors.
-    #[arg(
-        long,
-        value_enum,
-        default_value_t,
-        value_name("WHEN"),
-        default_value_if("plain", ArgPredicate::IsPresent, Some("never"))
-    )]
-    color: ColorWhen,
-
-    /// Whether to draw a border.
-    #[arg(
-        long,
-        value_enum,
-        default_value_t,
-        value_name("STYLE"),
-        default_value_if("plain", ArgPredicate::IsPresent, Some("none"))
-    )]
-    border: BorderStyle,
-
-    /// Display output with --no-characters, --no-position, --border=none, and
-    /// --color=never.
-    #[arg(short, long)]
-    plain: bool,
-
-    /// Do not show the character panel on the right.
-    #[arg(long)]
-    no_characters: bool,
-
-    /// Show the character panel on the right. This is the default, unless
-    /// --no-characters has been specified.
-    #[arg(
-        short('C'),
-        long,
-        action(ArgAction::SetTrue),
-        overrides_with("no_characters")
-    )]
-    characters: (),
-
-    /// Defines how bytes are mapped to characters.
-    #[arg(long, value_enum, default_value_t, value_name("FORMAT"))]
-    character_table: CharacterTable,
-
-    /// Whether to display the position panel on the left.
-    #[arg(short('P'), long)]
-    no_position: bool,
-
-    #[arg(
-        help(DISPLAY_OFFSET_HELP_TEXT),
-        short('o'),
-        long,
-        default_value("0"),
-        value_name("N")
-    )]
-    display_offset: String,
-
-    /// Sets the number of hex data panels to be displayed. `--panels=auto` will
-    /// display the maximum number of hex data panels based on the current
-    /// terminal width. By default, hexyl will show two panels, unless the
-    /// terminal is not wide enough for that.
-    #[arg(long, value_name("N"))]
-    panels: Option<String>,
-
-    /// Number of bytes/octets that should be grouped together. You can use the
-    /// '--endianness' option to control the ordering of the bytes within a
-    /// group. '--groupsize' can be used as an alias (xxd-compatibility).
-    #[arg(
-        short('g'),
-        long,
-        value_enum,
-        default_value_t,
-        alias("groupsize"),
-        value_name("N")
-    )]
-    group_size: GroupSize,
-
-    /// Whether to print out groups in little-endian or big-endian format. This
-    /// option only has an effect if the '--group-size' is larger than 1. '-e'
-    /// can be used as an alias for '--endianness=little'.
-    #[arg(long, value_enum, default_value_t, value_name("FORMAT"))]
-    endianness: Endianness,
-
-    /// An alias for '--endianness=little'.
-    #[arg(short('e'), hide(true), overrides_with("endianness"))]
-    little_endian_format: bool,
-
-    /// Sets the base used for the bytes. The possible options are binary,
-    /// octal, decimal, and hexadecimal.
-    #[arg(short('b'), long, default_value("hexadecimal"), value_name("B"))]
-    base: String,
-
-    #[arg(
-        help(TERMINAL_WIDTH_HELP_TEXT),
-        long,
-        value_name("N"),
-        conflicts_with("panels")
-    )]
-    terminal_width: Option<NonZeroU64>,
+/// Parses the command line `args`, where options that are given more than
+/// once override the earlier ones.
+fn parse_overriding(args: Vec<OsString>) -> Result<Cli, clap::Error> {
+    let mut command = Cli::command().args_override_self(true);
+    let subcommands: Vec<String> = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_owned())
+        .collect();
+    for name in subcommands {
+        command = command.mut_subcommand(name, |subcommand| subcommand.args_override_self(true));
+    }
+    let matches = command.try_get_matches_from(args)?;
+    Cli::from_arg_matches(&matches)
 }
 
-#[derive(Clone, Debug, Default, ValueEnum)]
-enum ColorWhen {
-    /// Always use colorized output.
-    #[default]
-    Always,
+/// Returns the options of the session in `path`, with the input of the
+/// session in place of FILE.
+#[cfg(feature = "session")]
+fn load_session(path: &Path) -> Result<DumpArgs> {
+    let session = Session::load(path)?;
+    let args = std::iter::once(OsString::from("hexyl"))
+        .chain(session.args.iter().map(OsString::from))
+        .collect();
+    let cli = parse_overriding(args)
+        .with_context(|| format!("invalid options in the session {}", path.display()))?;
+    let Command::Dump(mut opt) = cli.into_command() else {
+        return Err(anyhow!(
+            "the session {} does not contain the options of a dump",
+            path.display()
+        ));
+    };
+    opt.file = None;
+    opt.save_session = None;
+    #[cfg(feature = "clipboard")]
+    {
+        opt.clipboard = false;
+    }
+    opt.session_input = Some(session.data);
+    Ok(*opt)
+}
 
-    /// Only displays colors if the output goes to an interactive terminal.
-    Auto,
+fn run() -> Result<()> {
+    let mut args: Vec<OsString> = env::args_os().collect();
+    let mut cli = Cli::parse_from(&args);
 
-    /// Do not use colorized output.
-    Never,
+    #[cfg(feature = "session")]
+    if let Some(path) = cli.dump_args().and_then(|opt| opt.load_session.as_ref()) {
+        return dump(load_session(path)?);
+    }
 
-    /// Override the NO_COLOR environment variable.
-    Force,
-}
+    // The output of `--porcelain` must not depend on the environment.
+    let mut default_args = vec![];
+    if let Some(opt) = cli.dump_args().filter(|opt| opt.porcelain.is_none()) {
+        if let Some(env_args) = env::var_os(DEFAULT_ARGS_VAR) {
+            let env_args = env_args.into_string().map_err(|_| {
+                usage_error(anyhow!(
+                    "The {DEFAULT_ARGS_VAR} environment variable is not valid UTF-8"
+                ))
+            })?;
+            default_args.extend(parse_default_args(&env_args).map_err(usage_error)?);
+        }
+        // The options of the rules for the file override the ones of the
+        // environment variable.
+        if let (Some(file), Some(path)) = (&opt.file, rules::rules_path()) {
+            let rules = rules::read_rules(&path).map_err(usage_error)?;
+            default_args.extend(rules::args_for(&rules, file));
+        }
+    }
+    if !default_args.is_empty() {
+        // The command line on its own has been checked above, so options that
+        // are given more than once are still reported. Here, options on the
+        // command line only override the default options.
+        args = args_with_defaults(args, default_args);
+        cli = parse_overriding(args.clone()).unwrap_or_else(|err| err.exit());
+    }
 
-#[derive(Clone, Debug, Default, ValueEnum)]
-enum GroupSize {
-    /// Grouped together every byte/octet.
-    #[default]
-    #[value(name = "1")]
-    One,
+    if let Some(shell) = cli.completion {
+        let mut cmd = Cli::command();
+        let name = cmd.get_name().to_string();
+        clap_complete::generate(shell, &mut cmd, name, &mut Stdout::lock());
+        return Ok(());
+    }
 
-    /// Grouped together every 2 bytes/octets.
-    #[value(name = "2")]
-    Two,
+    if cli.generate_manpage {
+        Stdout::lock().write_all(manpage::manpage(Cli::command()).as_bytes())?;
+        return Ok(());
+    }
 
-    /// Grouped together every 4 bytes/octets.
-    #[value(name = "4")]
-    Four,
+    match cli.into_command() {
+        #[cfg(feature = "session")]
+        Command::Dump(mut opt) => {
+            opt.command_line = args.split_off(1);
+            dump(*opt)
+        }
+        #[cfg(not(feature = "session"))]
+        Command::Dump(opt) => dump(*opt),
+        Command::Diff(opt) => diff(opt),
+        Command::Convert(opt) => convert(opt),
+    }
+}
 
-    /// Grouped together every 8 bytes/octets.
-    #[value(name = "8")]
-    Eight,
+/// Returns whether to use colors for the `--color` option.
+fn show_color(color: ColorWhen) -> bool {
+    let no_color = std::env::var_os("NO_COLOR").is_some();
+    match color {
+        ColorWhen::Never => false,
+        ColorWhen::Always => !no_color,
+        ColorWhen::Force => true,
+        ColorWhen::Auto => {
+            if no_color {
+                false
+            } else {
+                supports_color::on(supports_color::Stream::Stdout)
+                    .map(|level| level.has_basic)
+                    .unwrap_or(false)
+            }
+        }
+    }
 }
 
-impl From<GroupSize> for u8 {
-    fn from(number: GroupSize) -> Self {
-        match number {
-            GroupSize::One => 1,
-            GroupSize::Two => 2,
-            GroupSize::Four => 4,
-            GroupSize::Eight => 8,
+fn diff(opt: DiffArgs) -> Result<()> {
+    let open = |path: &Path| {
+        File::open(path).with_context(|| format!("failed to open {}", path.display()))
+    };
+    let left = open(&opt.left)?;
+    let right = open(&opt.right)?;
+
+    let file_size = |file: &File| file.metadata().map_or(0, |metadata| metadata.len());
+    let mut options = DiffOptions {
+        show_color: show_color(opt.color),
+        border_style: opt.border,
+        squeeze: !opt.no_squeezing,
+        character_table: opt.character_table,
+        max_position: file_size(&left).max(file_size(&right)),
+        names: Some((
+            opt.left.display().to_string(),
+            opt.right.display().to_string(),
+        )),
+        theme: resolve_theme(|_| None, |name| env::var(name).ok()).map_err(usage_error)?,
+        ..DiffOptions::default()
+    };
+    options.panels = match opt.panels {
+        Some(panels) => panels.get(),
+        None if io::stdout().is_terminal() => {
+            let terminal_width = terminal_size().map(|s| s.0 .0 as u64).unwrap_or(80);
+            Fit::Width(None).panels(terminal_width, true, None, 8, |panels| {
+                DiffOptions {
+                    panels,
+                    ..options.clone()
+                }
+                .layout_width() as u64
+            })
+        }
+        None => 1,
+    };
+
+    let mut writer = Stdout::buffered();
+    let summary = hexyl::diff::write_diff(left, right, &mut writer, &options)?;
+    match summary.first_difference {
+        Some(first) => Err(DiffersError {
+            differences: summary.differences,
+            first,
         }
+        .into()),
+        None => Ok(()),
     }
 }
 
-fn run() -> Result<()> {
-    let opt = Opt::parse();
+/// Converts the input of `hexyl convert` from one format to another. The
+/// digits of `--from=hex` and the dump of `--from=dump` are turned into bytes
+/// in memory, and `--skip` and `--length` count these bytes.
+fn convert(opt: ConvertArgs) -> Result<()> {
+    let block_size = PositiveI64::new(DEFAULT_BLOCK_SIZE).unwrap();
+    let parse_byte_count = |option: &str, s: &Option<String>| -> Result<Option<u64>> {
+        s.as_ref()
+            .map(|s| -> Result<u64> {
+                Ok(parse_byte_offset(s, block_size)?
+                    .assume_forward_offset_from_start()?
+                    .into())
+            })
+            .transpose()
+            .with_context(|| format!("failed to parse `{option}` arg {s:?} as byte count"))
+            .map_err(usage_error)
+    };
+    let skip = parse_byte_count("--skip", &opt.skip)?.unwrap_or(0);
+    let length = parse_byte_count("--length", &opt.length)?.unwrap_or(u64::MAX);
+
+    let name = match &opt.name {
+        Some(name) if c_identifier(name) != *name => {
+            return Err(usage_error(anyhow!(
+                "`--name` {name:?} is not a valid C identifier"
+            )))
+        }
+        Some(name) => name.clone(),
+        None => opt
+            .file
+            .as_ref()
+            .and_then(|file| file.file_name())
+            .map_or_else(
+                || "buf".to_owned(),
+                |name| c_identifier(&name.to_string_lossy()),
+            ),
+    };
 
-    let stdin = io::stdin();
+    let input: Box<dyn BufRead> = match &opt.file {
+        Some(path) => Box::new(BufReader::new(
+            File::open(path).with_context(|| format!("failed to open {}", path.display()))?,
+        )),
+        None => Box::new(io::stdin().lock()),
+    };
+    let mut bytes: Box<dyn Read> = match opt.from {
+        ConvertInput::Raw => input,
+        ConvertInput::Hex => {
+            let mut bytes = vec![];
+            hexyl::plain_hex::read_plain_hex(input, &mut bytes)?;
+            Box::new(io::Cursor::new(bytes))
+        }
+        ConvertInput::Dump => {
+            let format = DumpFormat {
+                base: Base::Hexadecimal,
+                digits: 2,
+                group_size: 1,
+                endianness: Endianness::Big,
+                bytes_per_panel: 8,
+                offset_style: OffsetStyle::Byte,
+            };
+            let mut bytes = vec![];
+            reverse(input, &mut bytes, &format)?;
+            Box::new(io::Cursor::new(bytes))
+        }
+    };
+    io::copy(&mut bytes.by_ref().take(skip), &mut io::sink())?;
+    let mut bytes = bytes.take(length);
 
-    let mut reader = match opt.file {
-        Some(filename) => Input::File(File::open(filename)?),
-        None => Input::Stdin(stdin.lock()),
+    let mut writer: Box<dyn Write> = match &opt.output {
+        Some(path) => Box::new(io::BufWriter::new(
+            File::create(path)
+                .with_context(|| format!("failed to create `--output` file {path:?}"))?,
+        )),
+        None => {
+            if opt.to == ConvertOutput::Raw {
+                check_binary_output("--to=raw", opt.force_tty)?;
+            }
+            Box::new(Stdout::buffered())
+        }
     };
+    match opt.to {
+        ConvertOutput::Raw => {
+            io::copy(&mut bytes, &mut writer)?;
+            writer.flush()?;
+        }
+        ConvertOutput::Hex => {
+            hexyl::plain_hex::write_plain_hex(bytes, &mut writer, NonZeroUsize::new(30))?
+        }
+        ConvertOutput::Include => hexyl::c_include::write_c_include(bytes, &mut writer, &name)?,
+    }
+    Ok(())
+}
 
+fn dump(opt: DumpArgs) -> Result<()> {
+    #[cfg(feature = "session")]
+    let (opt, session_input) = {
+        let mut opt = opt;
+        let session_input = opt.session_input.take();
+        (opt, session_input)
+    };
     if let Some(hex_number) = try_parse_as_hex_number(&opt.block_size) {
         return hex_number
             .map_err(|e| anyhow!(e))
             .and_then(|x| {
                 PositiveI64::new(x).ok_or_else(|| anyhow!("block size argument must be positive"))
             })
-            .map(|_| ());
+            .map(|_| ())
+            .map_err(usage_error);
     }
-    let (num, unit) = extract_num_and_unit_from(&opt.block_size)?;
+    let (num, unit) = extract_num_and_unit_from(&opt.block_size).map_err(usage_error)?;
     if let Unit::Block { custom_size: _ } = unit {
-        return Err(anyhow!(
+        return Err(usage_error(anyhow!(
             "can not use 'block(s)' as a unit to specify block size"
-        ));
+        )));
     };
     let block_size = num
         .checked_mul(unit.get_multiplier())
         .ok_or_else(|| anyhow!(ByteOffsetParseError::UnitMultiplicationOverflow))
         .and_then(|x| {
             PositiveI64::new(x).ok_or_else(|| anyhow!("block size argument must be positive"))
-        })?;
+        })
+        .map_err(usage_error)?;
 
     let skip_arg = opt
         .skip
@@ -268,27 +434,8 @@ fn run() -> Result<()> {
                 s
             ))
         })
-        .transpose()?;
-
-    let skip_offset = if let Some(ByteOffset { kind, value }) = skip_arg {
-        let value = value.into_inner();
-        reader
-            .seek(match kind {
-                ByteOffsetKind::ForwardFromBeginning | ByteOffsetKind::ForwardFromLastOffset => {
-                    SeekFrom::Current(value)
-                }
-                ByteOffsetKind::BackwardFromEnd => SeekFrom::End(value.checked_neg().unwrap()),
-            })
-            .map_err(|_| {
-                anyhow!(
-                    "Failed to jump to the desired input position. \
-                     This could be caused by a negative offset that is too large or by \
-                     an input that is not seek-able (e.g. if the input comes from a pipe)."
-                )
-            })?
-    } else {
-        0
-    };
+        .transpose()
+        .map_err(usage_error)?;
 
     let parse_byte_count = |s| -> Result<u64> {
         Ok(parse_byte_offset(s, block_size)?
@@ -296,151 +443,1614 @@ fn run() -> Result<()> {
             .into())
     };
 
-    let mut reader = if let Some(ref length) = opt.length {
-        let length = parse_byte_count(length).context(anyhow!(
-            "failed to parse `--length` arg {:?} as byte count",
-            length
-        ))?;
-        Box::new(reader.take(length))
-    } else {
-        reader.into_inner()
+    let per_file_length = opt
+        .per_file_length
+        .as_ref()
+        .map(|length| {
+            parse_byte_count(length).context(anyhow!(
+                "failed to parse `--per-file-length` arg {:?} as byte count",
+                length
+            ))
+        })
+        .transpose()
+        .map_err(usage_error)?;
+
+    let length = opt
+        .length
+        .as_ref()
+        .map(|length| {
+            parse_byte_count(length).context(anyhow!(
+                "failed to parse `--length` arg {:?} as byte count",
+                length
+            ))
+        })
+        .transpose()
+        .map_err(usage_error)?
+        .or(per_file_length);
+
+    let end = opt
+        .end
+        .as_ref()
+        .map(|end| {
+            parse_byte_count(end).context(anyhow!(
+                "failed to parse `--end` arg {:?} as byte count",
+                end
+            ))
+        })
+        .transpose()
+        .map_err(usage_error)?;
+
+    let until = opt
+        .until
+        .as_ref()
+        .map(|pattern| {
+            parse_pattern(pattern).context(anyhow!(
+                "failed to parse `--until` arg {:?} as a pattern",
+                pattern
+            ))
+        })
+        .transpose()
+        .map_err(usage_error)?;
+
+    // Every byte takes two digits, `--plain-hex=0` does not wrap.
+    let plain_hex_bytes_per_line = match opt.plain_hex {
+        Some(1) => {
+            return Err(usage_error(anyhow!(
+                "`--plain-hex` needs at least 2 columns for a byte"
+            )))
+        }
+        Some(cols) => Some(NonZeroUsize::new(cols / 2)),
+        None => None,
     };
 
-    let no_color = std::env::var_os("NO_COLOR").is_some();
-    let show_color = match opt.color {
-        ColorWhen::Never => false,
-        ColorWhen::Always => !no_color,
-        ColorWhen::Force => true,
-        ColorWhen::Auto => {
-            if no_color {
-                false
-            } else {
-                supports_color::on(supports_color::Stream::Stdout)
-                    .map(|level| level.has_basic)
-                    .unwrap_or(false)
-            }
+    // The name of the array for `--include`.
+    let include_name = match &opt.include {
+        Some(Some(name)) if c_identifier(name) != *name => {
+            return Err(usage_error(anyhow!(
+                "`--include` name {name:?} is not a valid C identifier"
+            )))
         }
+        Some(Some(name)) => Some(name.clone()),
+        Some(None) => Some(
+            opt.file
+                .as_ref()
+                .and_then(|file| file.file_name())
+                .map_or_else(
+                    || "buf".to_owned(),
+                    |name| c_identifier(&name.to_string_lossy()),
+                ),
+        ),
+        None => None,
     };
 
+    let max_memory = opt
+        .max_memory
+        .as_ref()
+        .map(|size| {
+            parse_byte_count(size).context(anyhow!(
+                "failed to parse `--max-memory` arg {:?} as byte count",
+                size
+            ))
+        })
+        .transpose()
+        .map_err(usage_error)?
+        .unwrap_or(u64::MAX);
+
+    let io_buffer_size = opt
+        .io_buffer_size
+        .as_ref()
+        .map(|size| {
+            parse_byte_count(size)
+                .ok()
+                .and_then(|size| usize::try_from(size).ok())
+                .filter(|&size| size > 0)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "failed to parse `--io-buffer-size` arg {size:?} as positive byte count"
+                    )
+                })
+        })
+        .transpose()
+        .map_err(usage_error)?
+        .unwrap_or(DEFAULT_IO_BUFFER_SIZE);
+
+    let show_color = show_color(opt.color);
+
     let border_style = opt.border;
 
     let &squeeze = &!opt.no_squeezing;
 
-    let show_char_panel = !opt.no_characters && !opt.plain;
+    let (show_position_panel, show_char_panel) = match &opt.columns {
+        Some(columns) => {
+            if !columns.contains(&Column::Hex) {
+                return Err(usage_error(anyhow!(
+                    "`--columns` must include the `hex` panel"
+                )));
+            }
+            if !columns.windows(2).all(|pair| pair[0] < pair[1]) {
+                return Err(usage_error(anyhow!(
+                    "`--columns` must list every panel at most once and in the order \
+                     `offset,hex,chars`"
+                )));
+            }
+            (
+                columns.contains(&Column::Offset),
+                columns.contains(&Column::Chars),
+            )
+        }
+        None => (
+            !opt.no_position && !opt.plain,
+            !opt.no_characters && !opt.plain,
+        ),
+    };
 
-    let show_position_panel = !opt.no_position && !opt.plain;
+    let mut ranges = opt
+        .range
+        .iter()
+        .map(|range| {
+            let parse_range = || -> Result<ByteRange> {
+                let (start, end) = range
+                    .split_once(':')
+                    .ok_or_else(|| anyhow!("expected START:END"))?;
+                let start = if start.is_empty() {
+                    0
+                } else {
+                    parse_byte_count(start)?
+                };
+                let end = if end.is_empty() {
+                    None
+                } else {
+                    Some(parse_byte_count(end)?)
+                };
+                if end.is_some_and(|end| end < start) {
+                    return Err(anyhow!("END must not be smaller than START"));
+                }
+                Ok(ByteRange { start, end })
+            };
+            parse_range().context(anyhow!("failed to parse `--range` arg {:?}", range))
+        })
+        .collect::<Result<Vec<_>>>()
+        .map_err(usage_error)?;
+    if ranges.is_empty() {
+        ranges.push(ByteRange {
+            start: 0,
+            end: None,
+        });
+    }
 
-    let display_offset: u64 = parse_byte_count(&opt.display_offset).context(anyhow!(
-        "failed to parse `--display-offset` arg {:?} as byte count",
-        opt.display_offset
-    ))?;
+    let display_offset = parse_relative_offset(&opt.display_offset, block_size)
+        .context(anyhow!(
+            "failed to parse `--display-offset` arg {:?} as byte count",
+            opt.display_offset
+        ))
+        .map_err(usage_error)?;
 
-    let max_panels_fn = |terminal_width: u64, base_digits: u64, group_size: u64| {
-        let offset = if show_position_panel { 10 } else { 1 };
-        let col_width = if show_char_panel {
-            ((8 / group_size) * (base_digits * group_size + 1)) + 2 + 8
-        } else {
-            ((8 / group_size) * (base_digits * group_size + 1)) + 2
-        };
-        if (terminal_width - offset) / col_width < 1 {
-            1
-        } else {
-            (terminal_width - offset) / col_width
+    let relative_to = opt
+        .relative_to
+        .as_ref()
+        .map(|offset| {
+            parse_byte_count(offset).context(anyhow!(
+                "failed to parse `--relative-to` arg {:?} as byte count",
+                offset
+            ))
+        })
+        .transpose()
+        .map_err(usage_error)?
+        .unwrap_or(0);
+
+    let mut bases = opt
+        .interleave
+        .iter()
+        .map(|base| parse_base(base))
+        .collect::<Result<Vec<_>>>()
+        .map_err(usage_error)?;
+    let base = if bases.is_empty() {
+        parse_base(&opt.base).map_err(usage_error)?
+    } else {
+        bases.remove(0)
+    };
+    let interleave = bases;
+
+    let group_size = u8::from(opt.group_size);
+    let panel_width = opt.bytes_per_panel.map_or(8, NonZeroU64::get);
+    if panel_width % u64::from(group_size) != 0 {
+        return Err(usage_error(anyhow!(
+            "`--bytes-per-panel` ({panel_width}) must be a multiple of the `--group-size` \
+             ({group_size})"
+        )));
+    }
+    if opt.tick_marks && (panel_width < 2 || (panel_width / 2) % u64::from(group_size) != 0) {
+        return Err(usage_error(anyhow!(
+            "`--tick-marks` requires a `--group-size` that divides half of the \
+             `--bytes-per-panel` ({panel_width})"
+        )));
+    }
+
+    let filter_bytes = opt
+        .filter_byte
+        .iter()
+        .map(|value| {
+            parse_byte_value(value)
+                .context(anyhow!("failed to parse `--filter-byte` arg {:?}", value))
+        })
+        .collect::<Result<Vec<_>>>()
+        .map_err(usage_error)?;
+
+    let find_patterns = opt
+        .find
+        .iter()
+        .map(|hex| parse_find_hex(hex).context(anyhow!("failed to parse `--find` arg {:?}", hex)))
+        .chain(opt.find_text.iter().map(|text| {
+            parse_pattern(text)
+                .map(|bytes| FindPattern::exact(&bytes))
+                .context(anyhow!("failed to parse `--find-text` arg {:?}", text))
+        }))
+        .collect::<Result<Vec<_>>>()
+        .map_err(usage_error)?;
+
+    let mut highlights = match &opt.csv_ranges {
+        Some(path) => {
+            check_memory(
+                "the `--csv-ranges` file",
+                fs::metadata(path)?.len(),
+                max_memory,
+            )?;
+            let text = fs::read_to_string(path)
+                .with_context(|| format!("failed to read `--csv-ranges` file {path:?}"))?;
+            parse_csv_ranges(&text)
+                .with_context(|| format!("failed to parse `--csv-ranges` file {path:?}"))
+                .map_err(usage_error)?
         }
+        None => vec![],
     };
 
-    let base = if let Ok(base_num) = opt.base.parse::<u8>() {
-        match base_num {
-            2 => Ok(Base::Binary),
-            8 => Ok(Base::Octal),
-            10 => Ok(Base::Decimal),
-            16 => Ok(Base::Hexadecimal),
-            _ => Err(anyhow!(
-                "The number provided is not a valid base. Valid bases are 2, 8, 10, and 16."
-            )),
+    let comments = match &opt.comments {
+        Some(path) => {
+            check_memory(
+                "the `--comments` file",
+                fs::metadata(path)?.len(),
+                max_memory,
+            )?;
+            let text = fs::read_to_string(path)
+                .with_context(|| format!("failed to read `--comments` file {path:?}"))?;
+            parse_comments(&text)
+                .with_context(|| format!("failed to parse `--comments` file {path:?}"))
+                .map_err(usage_error)?
         }
+        None => vec![],
+    };
+
+    for arg in &opt.highlight {
+        let highlight = highlight::parse_highlight(arg)
+            .and_then(|highlight| {
+                let start = parse_byte_count(highlight.start)?;
+                let end = parse_byte_count(highlight.end)?;
+                if end < start {
+                    return Err(anyhow!("END must not be smaller than START"));
+                }
+                Ok(Highlight {
+                    range: start..end,
+                    color: highlight.color,
+                    label: highlight.label,
+                })
+            })
+            .with_context(|| format!("failed to parse `--highlight` arg {arg:?}"))
+            .map_err(usage_error)?;
+        highlights.push(highlight);
+    }
+
+    let highlight_legend = !opt.highlight.is_empty();
+
+    #[cfg(feature = "dwarf")]
+    let highlight_legend = highlight_legend || opt.dwarf.is_some();
+    #[cfg(feature = "dwarf")]
+    if let (Some(binary), Some(type_name)) = (&opt.dwarf, &opt.type_name) {
+        let at = opt
+            .at
+            .as_deref()
+            .map(parse_byte_count)
+            .transpose()
+            .context("failed to parse `--at` arg")
+            .map_err(usage_error)?
+            .unwrap_or(0);
+        let fields = dwarf::struct_fields(binary, type_name)?;
+        highlights.extend(dwarf::highlights(&fields, at));
+    }
+
+    let theme = resolve_theme(
+        |role| {
+            match role {
+                Role::Null => &opt.color_null,
+                Role::Printable => &opt.color_printable,
+                Role::Whitespace => &opt.color_whitespace,
+                Role::Other => &opt.color_other,
+                Role::NonAscii => &opt.color_nonascii,
+                Role::Offset => &opt.color_offset,
+                Role::Squeeze => &opt.color_squeeze,
+            }
+            .clone()
+        },
+        |name| env::var(name).ok(),
+    )
+    .map_err(usage_error)?;
+
+    // Regions at positions in the input, unlike the ones of `--csv-ranges`.
+    let mut input_highlights = vec![];
+
+    if let (true, Some(file)) = (opt.annotate, &opt.file) {
+        let fields = annotate::annotate(&mut File::open(file)?)
+            .with_context(|| format!("failed to annotate {file:?}"))?;
+        match fields {
+            Some(fields) => input_highlights.extend(annotate::highlights(&fields)),
+            None => eprintln!("hexyl: `--annotate` does not know the format of {file:?}"),
+        }
+    }
+
+    #[cfg(feature = "yara")]
+    let yara_matches = match (&opt.yara, &opt.file) {
+        (Some(rules), Some(file)) => yara_scan::scan(rules, file)?,
+        _ => vec![],
+    };
+    #[cfg(feature = "yara")]
+    input_highlights.extend(yara_scan::highlights(&yara_matches));
+
+    if let (Some(files), true) = (&opt.cat, opt.boundaries) {
+        let mut start = 0;
+        for file in files {
+            input_highlights.push(Highlight {
+                range: start..start,
+                color: COLOR_OFFSET,
+                label: Some(format!("{}", file.display())),
+            });
+            start += fs::metadata(file)?.len();
+        }
+    }
+
+    let mappings = opt
+        .map
+        .as_ref()
+        .map(|args| {
+            let files = args
+                .iter()
+                .map(|arg| {
+                    map::parse_mapping(arg)
+                        .and_then(|(path, offset)| Ok((path, parse_byte_count(offset)?)))
+                        .with_context(|| format!("failed to parse `--map` arg {arg:?}"))
+                })
+                .collect::<Result<Vec<_>>>()
+                .map_err(usage_error)?;
+            let mappings = map::mappings(files)?;
+            map::check_overlaps(&mappings).map_err(usage_error)?;
+            Ok::<_, anyhow::Error>(mappings)
+        })
+        .transpose()?;
+    // Like `input_highlights`, for the colors of the position panel.
+    let mut input_position_highlights = vec![];
+    // The positions between the files of `--map`.
+    let mut input_gaps = vec![];
+    if let Some(mappings) = &mappings {
+        let (labels, positions) = map::highlights(mappings);
+        input_highlights.extend(labels);
+        input_position_highlights = positions;
+        input_gaps = map::gaps(mappings);
+    }
+
+    let offset_style = match opt.offset_style {
+        OffsetStyleArg::Byte => OffsetStyle::Byte,
+        OffsetStyleArg::Sector => {
+            let sector_size = parse_byte_count(&opt.sector_size)
+                .ok()
+                .and_then(NonZeroU64::new)
+                .ok_or_else(|| {
+                    usage_error(anyhow!(
+                        "failed to parse `--sector-size` arg {:?} as positive byte count",
+                        opt.sector_size
+                    ))
+                })?;
+            OffsetStyle::Sector(sector_size)
+        }
+        OffsetStyleArg::Segment => OffsetStyle::Segment,
+    };
+
+    let endianness = if opt.little_endian_format {
+        Endianness::Little
     } else {
-        match opt.base.as_str() {
-            "b" | "bin" | "binary" => Ok(Base::Binary),
-            "o" | "oct" | "octal" => Ok(Base::Octal),
-            "d" | "dec" | "decimal" => Ok(Base::Decimal),
-            "x" | "hex" | "hexadecimal" => Ok(Base::Hexadecimal),
-            _ => Err(anyhow!(
-                "The base provided is not valid. Valid bases are \"b\", \"o\", \"d\", and \"x\"."
-            )),
+        opt.endianness
+    };
+
+    if opt.reverse {
+        let format = DumpFormat {
+            base,
+            digits: interleave
+                .iter()
+                .map(|base| base.digits())
+                .fold(base.digits(), u8::max) as usize,
+            group_size: group_size as usize,
+            endianness,
+            bytes_per_panel: panel_width as usize,
+            offset_style,
+        };
+        let input: Box<dyn BufRead> = match &opt.file {
+            Some(path) => Box::new(BufReader::new(File::open(path)?)),
+            None => Box::new(io::stdin().lock()),
+        };
+        return match &opt.output {
+            Some(path) => {
+                let file = File::create(path)
+                    .with_context(|| format!("failed to create `--output` file {path:?}"))?;
+                reverse(input, &mut io::BufWriter::new(file), &format)
+            }
+            None => {
+                check_binary_output("--reverse", opt.force_tty)?;
+                reverse(input, &mut Stdout::buffered(), &format)
+            }
+        };
+    }
+
+    let character_table = match opt.character_table {
+        CharacterTable::CP437 if opt.cp437_blank_controls => CharacterTable::CP437BlankControls,
+        _ if opt.cp437_blank_controls => {
+            return Err(usage_error(anyhow!(
+                "`--cp437-blank-controls` requires `--character-table=codepage-437`"
+            )));
         }
-    }?;
+        table => table,
+    };
+
+    if !hexyl::is_valid_squeeze_marker(&opt.squeeze_char) {
+        return Err(usage_error(anyhow!(
+            "`--squeeze-char` must be 1 to 8 columns wide, without control characters"
+        )));
+    }
 
-    let base_digits = match base {
-        Base::Binary => 8,
-        Base::Octal => 3,
-        Base::Decimal => 3,
-        Base::Hexadecimal => 2,
+    // The name of the input for `--footer-info`, if it is a single file or
+    // stdin.
+    let input_name = match &opt.file {
+        Some(file) if opt.recursive && file.is_dir() => None,
+        Some(file) => Some(file.display().to_string()),
+        None if opt.cat.is_some() || opt.map.is_some() => None,
+        None => Some("stdin".to_owned()),
+    };
+    #[cfg(feature = "clipboard")]
+    let input_name = if opt.clipboard {
+        Some("clipboard".to_owned())
+    } else {
+        input_name
     };
 
-    let group_size = u8::from(opt.group_size);
+    let mut options = PrinterOptions {
+        show_color,
+        show_char_panel,
+        char_panels: opt.char_panels,
+        show_position_panel,
+        border_style,
+        squeeze,
+        panel_width,
+        group_size,
+        base,
+        endianness,
+        character_table,
+        classify_lines: opt.classify_lines,
+        line_hash: opt.line_hash,
+        mark_eof: opt.mark_eof,
+        ruler: opt.ruler,
+        interleave,
+        both_endianness: opt.show_both_endianness,
+        mnemonics: opt.mnemonics,
+        reset_per_line: opt.reset_per_line,
+        stateless_colors: opt.stateless_colors,
+        offset_style,
+        line_format: if opt.output_format == OutputFormat::Json {
+            LineFormat::Json
+        } else {
+            LineFormat::Table
+        },
+        relative_to,
+        padding: opt.padding,
+        step: opt.step.get(),
+        filter_bytes,
+        track_spans: opt.print_spans,
+        max_memory,
+        offset_hyperlink: opt.hyperlink_offsets.clone(),
+        show_settings: opt.show_settings,
+        footer_info: opt.footer_info,
+        input_name,
+        tick_marks: opt.tick_marks,
+        // DOS DEBUG separates the halves of a line with a dash in the hex
+        // panel only.
+        tick_mark: (opt.style == Some(Style::Debug)).then_some('-'),
+        char_panel_tick_marks: opt.style != Some(Style::Debug),
+        uppercase: opt.uppercase,
+        highlight_legend,
+        squeeze_summary: opt.summary,
+        squeeze_marker: opt.squeeze_char.clone(),
+        strict_sanitize: opt.strict_sanitize,
+        io_buffer_size,
+        ..PrinterOptions::default()
+    };
 
-    let terminal_width = terminal_size().map(|s| s.0 .0 as u64).unwrap_or(80);
+    let layout_width = |panels| {
+        PrinterOptions {
+            panels,
+            ..options.clone()
+        }
+        .layout_width() as u64
+    };
 
-    let panels = if opt.panels.as_deref() == Some("auto") {
-        max_panels_fn(terminal_width, base_digits, group_size.into())
-    } else if let Some(panels) = opt.panels {
-        panels
+    let terminal_width = terminal_size().map(|s| s.0 .0 as u64).unwrap_or(80);
+    // `terminal_size` also finds the terminal of stderr or stdin if the output
+    // is redirected.
+    let to_terminal = io::stdout().is_terminal();
+
+    // `--panels` and `--terminal-width` are shorthands for `--fit`.
+    let mut max_panels = opt.max_panels;
+    let fit = if let Some(fit) = opt.fit {
+        fit
+    } else if opt.panels.as_deref() == Some("auto") {
+        Fit::Width(None)
+    } else if let Some(max) = opt.panels.as_deref().and_then(|p| p.strip_prefix("auto-")) {
+        let max = max
             .parse::<NonZeroU64>()
-            .map(u64::from)
             .context(anyhow!(
-                "failed to parse `--panels` arg {:?} as unsigned nonzero integer",
-                panels
-            ))?
+                "failed to parse `--panels` arg {:?} as 'auto-N' with a nonzero integer N",
+                opt.panels.as_deref().unwrap_or_default()
+            ))
+            .map_err(usage_error)?;
+        max_panels = Some(max_panels.map_or(max, |max_panels| max_panels.min(max)));
+        Fit::Width(None)
+    } else if let Some(panels) = opt.panels {
+        Fit::Panels(
+            panels
+                .parse::<NonZeroU64>()
+                .context(anyhow!(
+                    "failed to parse `--panels` arg {:?} as unsigned nonzero integer",
+                    panels
+                ))
+                .map_err(usage_error)?,
+        )
     } else if let Some(terminal_width) = opt.terminal_width {
-        max_panels_fn(terminal_width.into(), base_digits, group_size.into())
+        Fit::Width(Some(terminal_width))
+    } else if opt.wrap.is_some() {
+        Fit::Panels(NonZeroU64::new(2).unwrap())
     } else {
-        std::cmp::min(
-            2,
-            max_panels_fn(terminal_width, base_digits, group_size.into()),
-        )
+        Fit::Default
     };
+    if let Fit::BytesPerLine(bytes) = fit {
+        if bytes.get() % panel_width != 0 {
+            return Err(usage_error(anyhow!(
+                "the number of bytes per line of `--fit` must be a multiple of the \
+                 `--bytes-per-panel` ({panel_width})"
+            )));
+        }
+    }
+    options.panels = fit.panels(
+        terminal_width,
+        to_terminal,
+        max_panels,
+        panel_width,
+        layout_width,
+    );
+
+    // Comments are wrapped to the rest of the terminal that the output goes
+    // to, if its width is known.
+    if let Some(width) = opt.terminal_width.map(|width| width.get()).or_else(|| {
+        terminal_size()
+            .filter(|_| to_terminal)
+            .map(|s| s.0 .0 as u64)
+    }) {
+        let rest = width.saturating_sub(options.layout_width() as u64 + 1);
+        options.comment_width = Some((rest as usize).max(MIN_COMMENT_WIDTH));
+    }
 
-    let endianness = if opt.little_endian_format {
-        Endianness::Little
-    } else {
-        opt.endianness
+    #[cfg(unix)]
+    let status_writer = opt
+        .status_fd
+        .map(|fd| {
+            status::open_fd(fd)
+                .context("failed to open `--status-fd`")
+                .map_err(usage_error)
+        })
+        .transpose()?
+        .map(|file| Rc::new(RefCell::new(StatusWriter::new(file))));
+
+    // Prints the input, whose first byte is at `base_offset` of the original
+    // input for `--prompt`.
+    let dump_at = |mut reader: Input, base_offset: u64| -> Result<()> {
+        let sector_size = match &reader {
+            Input::File(file) => device::logical_sector_size(file),
+            _ => None,
+        };
+        let follow_at_end = match &reader {
+            Input::Follow(follow) => Some(follow.at_end()),
+            _ => None,
+        };
+        let direct_io = matches!(&reader, Input::File(file) if device::is_direct(file));
+        let skip_offset = if let Some(&ByteOffset { kind, value }) = skip_arg.as_ref() {
+            let value = value.into_inner();
+            reader
+                .seek(match kind {
+                    ByteOffsetKind::ForwardFromBeginning
+                    | ByteOffsetKind::ForwardFromLastOffset => SeekFrom::Current(value),
+                    ByteOffsetKind::BackwardFromEnd => SeekFrom::End(value.checked_neg().unwrap()),
+                })
+                .map_err(|_| {
+                    anyhow!(
+                        "Failed to jump to the desired input position. \
+                         This could be caused by a negative offset that is too large or by \
+                         an input that is not seek-able (e.g. if the input comes from a pipe)."
+                    )
+                })?
+        } else {
+            0
+        };
+        let length = match end {
+            Some(end) => Some(end.checked_sub(skip_offset).ok_or_else(|| {
+                usage_error(anyhow!(
+                    "`--end` ({end:#x}) is before the position of `--skip` ({skip_offset:#x})"
+                ))
+            })?),
+            None => length,
+        };
+
+        let display_offset = match display_offset.anchor {
+            OffsetAnchor::Start => display_offset
+                .delta
+                .try_into()
+                .ok()
+                .map(|delta: u64| skip_offset + delta),
+            // The first displayed byte is labeled `here+N`.
+            OffsetAnchor::Here => skip_offset.checked_add_signed(display_offset.delta),
+            OffsetAnchor::End => input_size(&mut reader, skip_offset)
+                .map_err(|_| {
+                    usage_error(anyhow!(
+                        "`--display-offset` relative to `end` requires an input of known size, \
+                         e.g. a file"
+                    ))
+                })?
+                .checked_add_signed(display_offset.delta)
+                .map(|position| skip_offset + position),
+        }
+        .ok_or_else(|| usage_error(anyhow!("`--display-offset` is negative")))?;
+
+        // The end of the input, if it is known, so that the position panel
+        // can be made wide enough for all positions.
+        let input_end = match (input_size(&mut reader, skip_offset).ok(), length) {
+            (Some(size), Some(length)) => Some(size.min(skip_offset.saturating_add(length))),
+            (Some(size), None) => Some(size),
+            (None, Some(length)) => Some(skip_offset.saturating_add(length)),
+            (None, None) => None,
+        };
+
+        let alignment = match sector_size {
+            _ if direct_io => device::direct_io_alignment(sector_size),
+            Some(sector_size) if opt.align_device_reads => sector_size,
+            Some(sector_size) if device::is_unaligned(sector_size, skip_offset, length) => {
+                eprintln!(
+                    "Warning: `--skip` or `--length` is not a multiple of the logical sector size \
+                     of the device ({sector_size} bytes), reading might fail. Use \
+                     `--align-device-reads` to read whole sectors."
+                );
+                1
+            }
+            _ => 1,
+        };
+        // `--jobs` reads chunks of a regular file in parallel, other inputs
+        // are counted or searched on a single thread.
+        let mut found_matches = None;
+        if opt.jobs.get() > 1 {
+            if let (Input::File(file), Some(end)) = (&reader, input_end) {
+                if !direct_io && file.metadata().is_ok_and(|metadata| metadata.is_file()) {
+                    if opt.count_bytes {
+                        let counts =
+                            jobs::count_ranges_parallel(file, skip_offset..end, &ranges, opt.jobs)?;
+                        print_range_counts(&mut Stdout::lock(), &ranges, &counts)?;
+                        return Ok(());
+                    }
+                    if !find_patterns.is_empty() && options.step == 1 {
+                        let mut matches = jobs::find_parallel(
+                            file,
+                            skip_offset..end,
+                            &find_patterns,
+                            opt.jobs,
+                            max_memory,
+                        )?;
+                        // The positions of the dump, like those of the printer.
+                        for found in &mut matches {
+                            found.position =
+                                base_offset + display_offset + opt.phase + found.position
+                                    - skip_offset;
+                        }
+                        found_matches = Some(matches);
+                        // Not all positional reads leave the position alone.
+                        let mut file = file;
+                        file.seek(SeekFrom::Start(skip_offset))?;
+                    }
+                }
+            }
+        }
+
+        let reader = AlignedReader::new(reader, alignment)?;
+        let mut recovering =
+            RecoveringReader::new(reader, opt.retries, opt.on_error, block_size.into());
+        let unreadable_bytes = recovering.unreadable_bytes();
+        let mut reader: Box<dyn Read + '_> = if let Some(length) = length {
+            Box::new((&mut recovering).take(length))
+        } else {
+            Box::new(&mut recovering)
+        };
+        if let Some(pattern) = &until {
+            reader = Box::new(UntilReader::new(
+                reader,
+                pattern.clone(),
+                !opt.until_exclusive,
+            ));
+        }
+
+        if opt.count_bytes {
+            let counts = count_ranges(&mut reader, skip_offset, &ranges)?;
+            print_range_counts(&mut Stdout::lock(), &ranges, &counts)?;
+            return Ok(());
+        }
+
+        if opt.porcelain == Some(Porcelain::V1) {
+            let mut writer = Stdout::buffered();
+            hexyl::porcelain::write_v1(reader, &mut writer, base_offset + display_offset)?;
+            return Ok(());
+        }
+
+        if let Some(bytes_per_line) = plain_hex_bytes_per_line {
+            let mut writer = Stdout::buffered();
+            hexyl::plain_hex::write_plain_hex(reader, &mut writer, bytes_per_line)?;
+            return Ok(());
+        }
+
+        if let Some(name) = &include_name {
+            let mut writer = Stdout::buffered();
+            hexyl::c_include::write_c_include(reader, &mut writer, name)?;
+            return Ok(());
+        }
+
+        if opt.output_format == OutputFormat::Rle {
+            let mut writer = Stdout::buffered();
+            hexyl::rle::write_rle(reader, &mut writer, base_offset + display_offset)?;
+            return Ok(());
+        }
+
+        // The lines on stderr would be written over the screen of
+        // `--interactive` on every redraw.
+        #[cfg(all(feature = "tui", unix))]
+        let interactive = opt.interactive;
+        #[cfg(not(all(feature = "tui", unix)))]
+        let interactive = false;
+        let show_text_hint = !opt.text_ok
+            && !opt.watch
+            && !opt.follow
+            && !opt.prompt
+            && !interactive
+            && io::stdout().is_terminal();
+        // Byte order marks and shebangs only mean something at the start of a
+        // file.
+        let sniff = !opt.no_sniff
+            && !opt.watch
+            && !opt.follow
+            && !opt.prompt
+            && !interactive
+            && skip_offset == 0;
+        if opt.auto_text || show_text_hint || sniff {
+            let detect_text = opt.auto_text || show_text_hint;
+            let sample_size = if detect_text {
+                text::SAMPLE_SIZE
+            } else {
+                text::SNIFF_SIZE
+            };
+            let mut sample = vec![];
+            (&mut reader)
+                .take(sample_size.min(max_memory))
+                .read_to_end(&mut sample)?;
+            if sniff {
+                if let Some(found) = text::sniff(&sample) {
+                    eprintln!("hexyl: the input starts with {found}");
+                }
+            }
+            let is_text = detect_text && text::looks_like_text(&sample);
+            reader = Box::new(io::Cursor::new(sample).chain(reader));
+            if is_text && opt.auto_text {
+                let mut writer = Stdout::buffered();
+                io::copy(&mut reader, &mut writer)?;
+                writer.flush()?;
+                return Ok(());
+            }
+            if is_text {
+                eprintln!(
+                    "hexyl: the input looks like text; use `--auto-text` to print it as it is, \
+                     or a pager like `bat` (`--text-ok` hides this hint)"
+                );
+            }
+        }
+
+        let mut options = options.clone();
+        if let Some(input_end) = input_end {
+            options.max_position = (base_offset + display_offset + opt.phase)
+                .saturating_add(input_end.saturating_sub(skip_offset))
+                .max(options.max_position);
+        }
+        if opt.detect_stride {
+            let mut sample = vec![];
+            (&mut reader)
+                .take(stride::SAMPLE_SIZE.min(max_memory))
+                .read_to_end(&mut sample)?;
+            match stride::detect_stride(&sample) {
+                Some(stride) => {
+                    match stride::panels_for_stride(stride, options.panel_width as usize) {
+                        Some(stride_panels) => {
+                            options.panels = stride_panels;
+                            eprintln!(
+                            "Detected a stride of {stride} bytes, showing {stride_panels} panels"
+                        );
+                        }
+                        None => eprintln!("Detected a stride of {stride} bytes"),
+                    }
+                }
+                None => eprintln!("No stride detected"),
+            }
+            reader = Box::new(io::Cursor::new(sample).chain(reader));
+        }
+
+        let mut reader = StepReader::new(reader, options.step, opt.phase);
+
+        // The byte at `skip_offset` of the input is shown at this position,
+        // which can be before it with `--display-offset=here-N`.
+        let shift = (base_offset + display_offset).wrapping_sub(skip_offset);
+        let shifted = |highlight: &Highlight| Highlight {
+            range: highlight.range.start.wrapping_add(shift)
+                ..highlight.range.end.wrapping_add(shift),
+            ..highlight.clone()
+        };
+        let mut highlights = highlights.clone();
+        highlights.extend(input_highlights.iter().map(shifted));
+        let position_highlights = input_position_highlights.iter().map(shifted).collect();
+        let gaps = input_gaps
+            .iter()
+            .map(|gap| gap.start.wrapping_add(shift)..gap.end.wrapping_add(shift))
+            .collect();
+
+        // The dump of `--verify`, which is compared instead of printed.
+        let mut rendered = vec![];
+        let output: Box<dyn Write> = match opt.verify {
+            Some(_) => Box::new(verify::DumpBuffer {
+                dump: &mut rendered,
+                max_memory,
+            }),
+            None => Box::new(Stdout::buffered()),
+        };
+        let mut writer: Box<dyn Write> = match opt.wrap {
+            Some(width) => {
+                let marker = match border_style {
+                    BorderStyle::Unicode => '↩',
+                    BorderStyle::Ascii | BorderStyle::None | BorderStyle::Compact => '\\',
+                };
+                Box::new(WrapWriter::new(output, width.into(), marker))
+            }
+            None => output,
+        };
+
+        let color_command = match &opt.color_command {
+            Some(command) if show_color => {
+                Some(Rc::new(RefCell::new(ColorCommand::spawn(command)?)))
+            }
+            _ => None,
+        };
+
+        let mut builder = PrinterBuilder::new(&mut writer)
+            .options(options)
+            .unreadable_bytes(unreadable_bytes)
+            .highlights(highlights)
+            .position_highlights(position_highlights)
+            .gaps(gaps)
+            .comments(comments.clone())
+            .find(find_patterns.clone())
+            .theme(theme);
+        if let Some(found_matches) = found_matches.take() {
+            builder = builder.found_matches(found_matches);
+        }
+        if let Some(color_command) = color_command.clone() {
+            builder = builder
+                .color_hook(move |offset, bytes| color_command.borrow_mut().query(offset, bytes));
+        }
+        #[cfg(unix)]
+        if let Some(status_writer) = status_writer.clone() {
+            builder = builder.event_hook(move |event| status_writer.borrow_mut().event(event));
+        }
+        let mut printer = builder.build();
+        printer.display_offset(base_offset + display_offset + opt.phase);
+        match follow_at_end {
+            Some(at_end) => {
+                // Show what has been appended whenever the current end of the
+                // file is reached, until the end of the input, e.g. the end of
+                // `--length`.
+                loop {
+                    printer.append(&mut reader)?;
+                    if !at_end.swap(false, Ordering::Relaxed) {
+                        break;
+                    }
+                    if to_terminal {
+                        printer.print_incomplete_line()?;
+                    } else {
+                        printer.flush()?;
+                    }
+                }
+                printer.finish()?;
+            }
+            None => printer.print_all(&mut reader).map_err(|e| anyhow!(e))?,
+        }
+        #[cfg(unix)]
+        if let Some(status_writer) = &status_writer {
+            status_writer.borrow_mut().summary(printer.bytes_read())?;
+        }
+        let filter_matches = printer.filter_matches();
+        let find_matches = printer.find_matches().to_vec();
+        let spans = printer.spans().to_vec();
+        drop(printer);
+        drop(writer);
+
+        if opt.print_spans {
+            print_spans(&spans);
+        }
+
+        if !opt.filter_byte.is_empty() {
+            eprintln!(
+                "Found {} matching bytes in {} lines",
+                filter_matches.bytes, filter_matches.lines
+            );
+        }
+
+        if !find_patterns.is_empty() {
+            print_find_matches(&find_matches);
+        }
+
+        #[cfg(feature = "yara")]
+        if opt.yara.is_some() {
+            yara_scan::print_summary(&yara_matches);
+        }
+        let bytes_read = reader.consumed();
+        drop(reader);
+
+        if opt.retries > 0 || opt.on_error != OnError::Stop {
+            print_read_stats(recovering.stats(), skip_offset, opt.on_error);
+        }
+
+        if let Some(color_command) = color_command {
+            color_command.borrow_mut().finish()?;
+        }
+
+        if let (Some(length), Some(mode)) = (length, opt.strict_length) {
+            if bytes_read < length {
+                let message = format!(
+                    "requested {length} bytes with `--length`, but the input ended after \
+                     {bytes_read} bytes"
+                );
+                match mode {
+                    StrictLength::Warn => eprintln!("Warning: {message}"),
+                    StrictLength::Error => return Err(anyhow!(message)),
+                }
+            }
+        }
+
+        if let Some(path) = &opt.verify {
+            check_memory("the `--verify` file", fs::metadata(path)?.len(), max_memory)?;
+            let expected = fs::read(path)
+                .with_context(|| format!("failed to read `--verify` file {path:?}"))?;
+            if let Some(diff) = verify::unified_diff(
+                &String::from_utf8_lossy(&expected),
+                &String::from_utf8_lossy(&rendered),
+                &path.display().to_string(),
+                "dump",
+            ) {
+                let mut stdout = Stdout::buffered();
+                stdout.write_all(diff.as_bytes())?;
+                stdout.flush()?;
+                return Err(MismatchError(path.clone()).into());
+            }
+        }
+
+        if (!opt.filter_byte.is_empty() || !find_patterns.is_empty())
+            && filter_matches.bytes == 0
+            && find_matches.is_empty()
+        {
+            return Err(NoMatchError.into());
+        }
+
+        Ok(())
     };
 
-    let character_table = opt.character_table;
+    let dump = |reader: Input| dump_at(reader, 0);
+
+    // With `--save-session`, the whole input is read into memory and saved
+    // before it is displayed.
+    let save_session = |input: Input<'static>| -> Result<Input<'static>> {
+        #[cfg(feature = "session")]
+        if let Some(path) = &opt.save_session {
+            let mut data = vec![];
+            input
+                .take(max_memory.saturating_add(1))
+                .read_to_end(&mut data)?;
+            check_memory("the session", data.len() as u64, max_memory)?;
+            let session = Session::new(&opt.command_line, data)?;
+            session.save(path)?;
+            return Ok(Input::Buffer(io::Cursor::new(session.data)));
+        }
+        Ok(input)
+    };
 
-    let stdout = io::stdout();
-    let mut stdout_lock = BufWriter::new(stdout.lock());
+    #[cfg(feature = "session")]
+    if let Some(data) = session_input {
+        check_memory("the session", data.len() as u64, max_memory)?;
+        let input = Input::Buffer(io::Cursor::new(data));
+        #[cfg(feature = "decompress")]
+        if opt.decompress {
+            return dump_members(input, opt.member_offsets, dump_at);
+        }
+        return dump(input);
+    }
+
+    #[cfg(feature = "clipboard")]
+    if opt.clipboard {
+        let contents = clipboard::read()?;
+        check_memory("the clipboard", contents.len() as u64, max_memory)?;
+        return dump(save_session(Input::Buffer(io::Cursor::new(contents)))?);
+    }
+
+    #[cfg(feature = "decompress")]
+    if opt.decompress {
+        let input = match &opt.file {
+            Some(filename) => Input::File(File::open(filename)?),
+            None => Input::Stdin(io::stdin().lock()),
+        };
+        return dump_members(save_session(input)?, opt.member_offsets, dump_at);
+    }
+
+    if let Some(files) = &opt.cat {
+        let inputs = files
+            .iter()
+            .map(|file| File::open(file).map(Input::File))
+            .collect::<io::Result<Vec<_>>>()?;
+        return dump(Input::Chain(Chain::new(inputs)));
+    }
+
+    if let Some(mappings) = &mappings {
+        return dump(map::open(mappings)?);
+    }
+
+    // Opens the FILE, for direct I/O with `--direct-io`.
+    let open_file = |path: &Path| -> io::Result<File> {
+        #[cfg(target_os = "linux")]
+        if opt.direct_io {
+            let file = device::open_direct(path)?;
+            if !device::is_direct(&file) {
+                eprintln!(
+                    "Warning: {} cannot be read with direct I/O, reading it through the \
+                     page cache.",
+                    path.display()
+                );
+            }
+            return Ok(file);
+        }
+        File::open(path)
+    };
+
+    // Parses the positions of `--prompt` and `--interactive`, which are
+    // negative from the end of the file.
+    let parse_offset = |offset: &str| -> Result<i64> {
+        let ByteOffset { kind, value } = parse_byte_offset(offset, block_size)?;
+        Ok(match kind {
+            ByteOffsetKind::BackwardFromEnd => -value.into_inner(),
+            _ => value.into_inner(),
+        })
+    };
+
+    match opt.file {
+        Some(filename) if opt.follow => {
+            // `--follow` only ends with Ctrl-C, which must not leave the colors
+            // of the dump behind.
+            let _session = TerminalSession::start(false)?;
+            dump(Input::Follow(Follow::new(
+                open_file(&filename)?,
+                FOLLOW_INTERVAL,
+            )))
+        }
+        Some(filename) if opt.watch => {
+            watch(&filename, || dump(Input::File(open_file(&filename)?)))
+        }
+        Some(filename) if opt.recursive && filename.is_dir() => dump_directory(&filename, dump),
+        #[cfg(all(feature = "tui", unix))]
+        Some(filename) if opt.interactive => {
+            if !io::stdout().is_terminal() {
+                return Err(usage_error(anyhow!("`--interactive` needs a terminal")));
+            }
+            let border_lines = match options.border_style {
+                BorderStyle::None | BorderStyle::Compact => 0,
+                _ => 2,
+            } + u64::from(options.ruler);
+            tui::run(
+                File::open(filename)?,
+                options.panel_width * options.panels,
+                border_lines,
+                parse_offset,
+                |chunk, position| dump_at(Input::Buffer(io::Cursor::new(chunk)), position),
+            )
+        }
+        Some(filename) if opt.prompt => {
+            // Fill the terminal, except for the border and the prompt.
+            let lines = terminal_size().map_or(16, |size| u64::from(size.1 .0).saturating_sub(4));
+            let chunk_size =
+                (lines.max(1) * options.panel_width * options.panels).min(max_memory.max(1));
+            let _session = TerminalSession::start(false)?;
+            prompt::run(
+                File::open(filename)?,
+                chunk_size,
+                parse_offset,
+                |chunk, position| dump_at(Input::Buffer(io::Cursor::new(chunk)), position),
+            )
+        }
+        Some(filename) => dump(save_session(Input::File(open_file(&filename)?))?),
+        None => {
+            let stdin = io::stdin();
+            if !opt.stdin_ok && stdin.is_terminal() {
+                let eof = if cfg!(windows) { "Ctrl-Z" } else { "Ctrl-D" };
+                eprintln!("hexyl: reading from stdin; press {eof} to end or pass a FILE");
+            }
+            dump(save_session(Input::Stdin(stdin.lock()))?)
+        }
+    }
+}
+
+/// Refuses to write the raw bytes of `option` to a terminal unless
+/// `--force-tty` is given.
+fn check_binary_output(option: &str, force_tty: bool) -> Result<()> {
+    if !force_tty && io::stdout().is_terminal() {
+        return Err(usage_error(anyhow!(
+            "refusing to write the binary output of `{option}` to a terminal, \
+             redirect stdout or use `--output` or `--force-tty`"
+        )));
+    }
+    Ok(())
+}
+
+/// Fails if keeping `size` bytes of `what` in memory exceeds `--max-memory`.
+fn check_memory(what: &str, size: u64, max_memory: u64) -> Result<()> {
+    if size > max_memory {
+        return Err(anyhow!(
+            "{what} needs {size} bytes of memory, more than `--max-memory` allows \
+             ({max_memory} bytes)"
+        ));
+    }
+    Ok(())
+}
+
+/// A range of input positions for `--count-bytes`, the end is exclusive.
+struct ByteRange {
+    start: u64,
+    end: Option<u64>,
+}
+
+/// Counts the bytes of each category within every range. `offset` is the
+/// input position of the first byte of `reader`.
+fn count_ranges(
+    reader: &mut dyn Read,
+    offset: u64,
+    ranges: &[ByteRange],
+) -> io::Result<Vec<CategoryCounts>> {
+    let mut counts = vec![CategoryCounts::default(); ranges.len()];
+    let mut buf = vec![0; 64 * 1024];
+    let mut pos = offset;
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        let chunk_end = pos + n as u64;
+        for (range, counts) in ranges.iter().zip(&mut counts) {
+            let start = range.start.clamp(pos, chunk_end);
+            let end = range.end.unwrap_or(u64::MAX).clamp(pos, chunk_end);
+            if start < end {
+                counts.add(&buf[(start - pos) as usize..(end - pos) as usize]);
+            }
+        }
+        pos = chunk_end;
+    }
+    Ok(counts)
+}
+
+fn print_range_counts(
+    out: &mut impl Write,
+    ranges: &[ByteRange],
+    counts: &[CategoryCounts],
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "{:<17} {:>10} {:>6} {:>9} {:>10} {:>6} {:>9}",
+        "Range", "Bytes", "Null", "Printable", "Whitespace", "Other", "Non-ASCII"
+    )?;
+    for (range, counts) in ranges.iter().zip(counts) {
+        let end = match range.end {
+            Some(end) => format!("{end:08x}"),
+            None => "end".to_owned(),
+        };
+        let total = counts.total();
+        write!(
+            out,
+            "{:<17} {total:>10}",
+            format!("{:08x}-{end}", range.start)
+        )?;
+        for (category, width) in ByteCategory::ALL.into_iter().zip([6, 9, 10, 6, 9]) {
+            let share = if total == 0 {
+                "-".to_owned()
+            } else {
+                format!(
+                    "{:.1}%",
+                    100.0 * counts.count(category) as f64 / total as f64
+                )
+            };
+            write!(out, " {share:>width$}")?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Prints the start, end (exclusive) and length of every span for
+/// `--print-spans` on stderr.
+fn print_spans(spans: &[Range<u64>]) {
+    for span in spans {
+        let length = span.end - span.start;
+        eprintln!(
+            "start {:#x} ({}), end {:#x} ({}), length {length:#x} ({length})",
+            span.start, span.start, span.end, span.end
+        );
+    }
+}
+
+/// Prints the bad blocks and the number of retries and short reads for
+/// `--retries` and `--on-error` on stderr. `offset` is the input position of
+/// the first byte that has been read.
+fn print_read_stats(stats: &ReadStats, offset: u64, on_error: OnError) {
+    if !stats.bad_blocks.is_empty() {
+        let bytes: u64 = stats
+            .bad_blocks
+            .iter()
+            .map(|block| block.end - block.start)
+            .sum();
+        let action = match on_error {
+            OnError::Skip => "skipped",
+            OnError::Stop | OnError::Zero => "replaced by zeros",
+        };
+        eprintln!(
+            "Warning: {} unreadable blocks ({bytes} bytes) have been {action}:",
+            stats.bad_blocks.len()
+        );
+        for block in &stats.bad_blocks {
+            eprintln!(
+                "  {:#010x}-{:#010x}",
+                offset + block.start,
+                offset + block.end - 1
+            );
+        }
+    }
+    eprintln!(
+        "Read statistics: {} retries, {} short reads",
+        stats.retries, stats.short_reads
+    );
+}
+
+/// Dumps every file below `dir` for `--recursive`, each after a header line,
+/// and prints a summary on stderr. Files that cannot be read are reported and
+/// skipped.
+fn dump_directory(dir: &Path, dump: impl Fn(Input) -> Result<()>) -> Result<()> {
+    let mut files = vec![];
+    let mut failed = 0;
+    collect_files(dir, &mut files, &mut failed);
+
+    let mut dumped = 0;
+    for (i, path) in files.iter().enumerate() {
+        {
+            let mut stdout = Stdout::lock();
+            if i > 0 {
+                writeln!(stdout)?;
+            }
+            writeln!(stdout, "==> {} <==", path.display())?;
+        }
+        let result = File::open(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| dump(Input::File(file)));
+        match result {
+            Ok(()) => dumped += 1,
+            Err(err) if err.is::<NoMatchError>() => dumped += 1,
+            Err(err) => {
+                eprintln!("hexyl: {}: {err:#}", path.display());
+                failed += 1;
+            }
+        }
+    }
 
-    let mut printer = PrinterBuilder::new(&mut stdout_lock)
-        .show_color(show_color)
-        .show_char_panel(show_char_panel)
-        .show_position_panel(show_position_panel)
-        .with_border_style(border_style)
-        .enable_squeezing(squeeze)
-        .num_panels(panels)
-        .group_size(group_size)
-        .with_base(base)
-        .endianness(endianness)
-        .character_table(character_table)
-        .build();
-    printer.display_offset(skip_offset + display_offset);
-    printer.print_all(&mut reader).map_err(|e| anyhow!(e))?;
+    eprintln!("Dumped {dumped} files, {failed} could not be read");
+    if failed > 0 {
+        return Err(anyhow!("failed to read {failed} files below {dir:?}"));
+    }
+    Ok(())
+}
 
+/// Dumps every member of the compressed `input` for `--decompress`.
+#[cfg(feature = "decompress")]
+fn dump_members(
+    input: Input,
+    member_offsets: MemberOffsets,
+    dump_at: impl Fn(Input, u64) -> Result<()>,
+) -> Result<()> {
+    let mut members = 0;
+    let mut unmatched = 0;
+    decompress::for_each_member(input, |member, reader| {
+        {
+            let mut stdout = Stdout::lock();
+            if member.number > 1 {
+                writeln!(stdout)?;
+            }
+            writeln!(
+                stdout,
+                "==> {} {} at compressed offset {:#x} <==",
+                member.format, member.number, member.compressed_offset
+            )?;
+        }
+        let offset = match member_offsets {
+            MemberOffsets::Continue => member.offset,
+            MemberOffsets::Reset => 0,
+        };
+        members += 1;
+        match dump_at(Input::Reader(Box::new(reader)), offset) {
+            Err(err) if err.is::<NoMatchError>() => {
+                unmatched += 1;
+                Ok(())
+            }
+            result => result,
+        }
+    })?;
+    if members > 0 && unmatched == members {
+        return Err(NoMatchError.into());
+    }
     Ok(())
 }
 
+/// Appends the paths of all files below `dir` to `files`, sorted by path.
+/// Symbolic links to directories are not followed.
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>, failed: &mut usize) {
+    let entries =
+        match fs::read_dir(dir).and_then(|entries| entries.collect::<io::Result<Vec<_>>>()) {
+            Ok(entries) => entries,
+            Err(err) => {
+                eprintln!("hexyl: {}: {err}", dir.display());
+                *failed += 1;
+                return;
+            }
+        };
+    let mut paths: Vec<_> = entries.iter().map(|entry| entry.path()).collect();
+    paths.sort();
+    for path in paths {
+        match fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.is_dir() => collect_files(&path, files, failed),
+            Ok(_) if path.is_file() => files.push(path),
+            Ok(_) => {}
+            Err(err) => {
+                eprintln!("hexyl: {}: {err}", path.display());
+                *failed += 1;
+            }
+        }
+    }
+}
+
+/// Re-runs `dump` whenever the file at `path` is modified, clearing the
+/// screen between two renders. This only returns if watching the file fails.
+fn watch(path: &Path, dump: impl Fn() -> Result<()>) -> Result<()> {
+    let path = path
+        .canonicalize()
+        .with_context(|| format!("failed to watch {path:?}"))?;
+    // Watch the parent directory, as many editors replace a file on save
+    // instead of writing to it in place.
+    let dir = path.parent().unwrap_or(&path);
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+    let session = TerminalSession::start(true)?;
+    loop {
+        session.clear()?;
+        if let Err(err) = dump() {
+            eprintln!("Error: {err:?}");
+        }
+
+        loop {
+            let event = rx.recv()??;
+            if !matches!(event.kind, EventKind::Access(_)) && event.paths.contains(&path) {
+                break;
+            }
+        }
+        // Give the writer some time to finish and coalesce the resulting events.
+        thread::sleep(Duration::from_millis(50));
+        while rx.try_recv().is_ok() {}
+    }
+}
+
+/// A child process that decides the colors of the bytes for `--color-command`.
+struct ColorCommand {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+    request: String,
+    response: String,
+}
+
+impl ColorCommand {
+    fn spawn(command: &str) -> Result<Self> {
+        let (shell, flag) = if cfg!(windows) {
+            ("cmd", "/C")
+        } else {
+            ("sh", "-c")
+        };
+        let mut child = process::Command::new(shell)
+            .arg(flag)
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to run color command {command:?}"))?;
+        let stdin = child.stdin.take();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        Ok(ColorCommand {
+            child,
+            stdin,
+            stdout,
+            request: String::new(),
+            response: String::new(),
+        })
+    }
+
+    /// Sends a single line to the command and parses its answer. Errors are
+    /// never reported as broken pipes, which would be silently ignored.
+    fn query(&mut self, offset: u64, bytes: &[u8]) -> io::Result<Vec<Option<&'static [u8]>>> {
+        self.try_query(offset, bytes)
+            .map_err(|err| io::Error::other(format!("color command: {err}")))
+    }
+
+    fn try_query(&mut self, offset: u64, bytes: &[u8]) -> io::Result<Vec<Option<&'static [u8]>>> {
+        use std::fmt::Write as _;
+
+        self.request.clear();
+        write!(self.request, "{offset:08x}").unwrap();
+        for b in bytes {
+            write!(self.request, " {b:02x}").unwrap();
+        }
+        self.request.push('\n');
+        let stdin = self.stdin.as_mut().unwrap();
+        stdin.write_all(self.request.as_bytes())?;
+        stdin.flush()?;
+
+        self.response.clear();
+        if self.stdout.read_line(&mut self.response)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "exited before answering",
+            ));
+        }
+        self.response
+            .split_whitespace()
+            .map(|entry| match entry {
+                "-" => Ok(None),
+                _ => hexyl::color_from_name(entry).map(Some).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("unknown color {entry:?}"),
+                    )
+                }),
+            })
+            .collect()
+    }
+
+    /// Closes the input of the command and waits for it to exit.
+    fn finish(&mut self) -> Result<()> {
+        drop(self.stdin.take());
+        let status = self.child.wait()?;
+        if !status.success() {
+            return Err(anyhow!("the color command failed with {status}"));
+        }
+        Ok(())
+    }
+}
+
+/// The exit codes of hexyl, which are listed in the `--help` text.
+const EXIT_SUCCESS: i32 = 0;
+const EXIT_ERROR: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_NOT_FOUND: i32 = 3;
+const EXIT_IO: i32 = 4;
+const EXIT_NO_MATCH: i32 = 5;
+const EXIT_MISMATCH: i32 = 6;
+
+/// An invalid command line argument, which exits with [`EXIT_USAGE`].
+#[derive(Debug)]
+struct UsageError(anyhow::Error);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for UsageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+fn usage_error(err: impl Into<anyhow::Error>) -> anyhow::Error {
+    UsageError(err.into()).into()
+}
+
+/// Nothing in the input matched, which exits with [`EXIT_NO_MATCH`].
+#[derive(Clone, Debug, ThisError)]
+#[error("no matches found")]
+struct NoMatchError;
+
+/// The dump differs from the `--verify` file, which exits with
+/// [`EXIT_MISMATCH`].
+#[derive(Clone, Debug, ThisError)]
+#[error("the dump differs from {}", .0.display())]
+struct MismatchError(PathBuf);
+
+/// The files of `hexyl diff` differ, which exits with [`EXIT_MISMATCH`].
+#[derive(Clone, Debug, ThisError)]
+#[error("the files differ in {differences} bytes, starting at {first:#x}")]
+struct DiffersError {
+    differences: u64,
+    first: u64,
+}
+
+/// Maps an error to the exit code of hexyl.
+fn exit_code(err: &anyhow::Error) -> i32 {
+    if err.chain().any(|cause| cause.is::<UsageError>()) {
+        return EXIT_USAGE;
+    }
+    if err.chain().any(|cause| cause.is::<NoMatchError>()) {
+        return EXIT_NO_MATCH;
+    }
+    if err
+        .chain()
+        .any(|cause| cause.is::<MismatchError>() || cause.is::<DiffersError>())
+    {
+        return EXIT_MISMATCH;
+    }
+    match err
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<io::Error>())
+        .map(io::Error::kind)
+    {
+        Some(io::ErrorKind::NotFound) => EXIT_NOT_FOUND,
+        // Used for errors that are not caused by the input or output, e.g.
+        // by the color command.
+        Some(io::ErrorKind::Other) | None => EXIT_ERROR,
+        Some(_) => EXIT_IO,
+    }
+}
+
 fn main() {
     let result = run();
 
     if let Err(err) = result {
-        if let Some(io_error) = err.downcast_ref::<io::Error>() {
-            if io_error.kind() == ::std::io::ErrorKind::BrokenPipe {
-                std::process::exit(0);
-            }
+        let code = exit_code(&err);
+        if !err.is::<NoMatchError>() {
+            eprintln!("Error: {err:?}");
         }
-        eprintln!("Error: {err:?}");
-        std::process::exit(1);
+        std::process::exit(code);
     }
 }
 
@@ -585,6 +2195,76 @@ enum ByteOffsetParseError {
     ParseNum(#[source] std::num::ParseIntError),
     #[error("count multiplied by the unit overflowed a signed 64-bit integer; are you sure it should be that big?")]
     UnitMultiplicationOverflow,
+    #[error("expected '+' or '-' after {0:?}")]
+    ExpectedSignAfterAnchor(&'static str),
+}
+
+/// The position that an offset like `end-0x100` is relative to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum OffsetAnchor {
+    /// The start of the input.
+    Start,
+    /// The position where the dump starts, see `--skip`.
+    Here,
+    /// The end of the input.
+    End,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct RelativeOffset {
+    anchor: OffsetAnchor,
+    delta: i64,
+}
+
+/// Parses an offset that is relative to the start, the end or the current
+/// position of the input, e.g. `start+1MiB`, `end-0x100` or `here+16`. Plain
+/// numbers are relative to the start, or to the end if they are negative.
+fn parse_relative_offset(
+    n: &str,
+    block_size: PositiveI64,
+) -> Result<RelativeOffset, ByteOffsetParseError> {
+    let anchors = [
+        ("start", OffsetAnchor::Start),
+        ("here", OffsetAnchor::Here),
+        ("end", OffsetAnchor::End),
+    ];
+    for (name, anchor) in anchors {
+        if let Some(rest) = n.strip_prefix(name) {
+            if rest.is_empty() {
+                return Ok(RelativeOffset { anchor, delta: 0 });
+            }
+            let ByteOffset { value, kind } = parse_byte_offset(rest, block_size)?;
+            let delta = match kind {
+                ByteOffsetKind::ForwardFromLastOffset => value.into_inner(),
+                ByteOffsetKind::BackwardFromEnd => -value.into_inner(),
+                ByteOffsetKind::ForwardFromBeginning => {
+                    return Err(ByteOffsetParseError::ExpectedSignAfterAnchor(name))
+                }
+            };
+            return Ok(RelativeOffset { anchor, delta });
+        }
+    }
+
+    let ByteOffset { value, kind } = parse_byte_offset(n, block_size)?;
+    Ok(match kind {
+        ByteOffsetKind::ForwardFromBeginning | ByteOffsetKind::ForwardFromLastOffset => {
+            RelativeOffset {
+                anchor: OffsetAnchor::Start,
+                delta: value.into_inner(),
+            }
+        }
+        ByteOffsetKind::BackwardFromEnd => RelativeOffset {
+            anchor: OffsetAnchor::End,
+            delta: -value.into_inner(),
+        },
+    })
+}
+
+/// Returns the size of a seekable input, and seeks back to `position`.
+fn input_size(reader: &mut Input, position: u64) -> io::Result<u64> {
+    let size = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(position))?;
+    Ok(size)
 }
 
 fn parse_byte_offset(n: &str, block_size: PositiveI64) -> Result<ByteOffset, ByteOffsetParseError> {
@@ -617,6 +2297,122 @@ fn parse_byte_offset(n: &str, block_size: PositiveI64) -> Result<ByteOffset, Byt
         .and_then(into_byte_offset)
 }
 
+fn parse_base(base: &str) -> Result<Base> {
+    if let Ok(base_num) = base.parse::<u8>() {
+        match base_num {
+            2 => Ok(Base::Binary),
+            8 => Ok(Base::Octal),
+            10 => Ok(Base::Decimal),
+            16 => Ok(Base::Hexadecimal),
+            _ => Err(anyhow!(
+                "The number provided is not a valid base. Valid bases are 2, 8, 10, and 16."
+            )),
+        }
+    } else {
+        match base {
+            "b" | "bin" | "binary" => Ok(Base::Binary),
+            "o" | "oct" | "octal" => Ok(Base::Octal),
+            "d" | "dec" | "decimal" => Ok(Base::Decimal),
+            "x" | "hex" | "hexadecimal" => Ok(Base::Hexadecimal),
+            _ => Err(anyhow!(
+                "The base provided is not valid. Valid bases are \"b\", \"o\", \"d\", and \"x\"."
+            )),
+        }
+    }
+}
+
+/// Parses the pattern of `--until`, in which `\xHH` stands for a byte in hex
+/// and `\\`, `\0`, `\n`, `\r` and `\t` for the usual characters.
+fn parse_pattern(pattern: &str) -> Result<Vec<u8>> {
+    let mut bytes = vec![];
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            bytes.extend(c.encode_utf8(&mut [0; 4]).as_bytes());
+            continue;
+        }
+        bytes.push(match chars.next() {
+            Some('\\') => b'\\',
+            Some('0') => b'\0',
+            Some('n') => b'\n',
+            Some('r') => b'\r',
+            Some('t') => b'\t',
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                u8::from_str_radix(&hex, 16)
+                    .ok()
+                    .filter(|_| hex.len() == 2)
+                    .ok_or_else(|| anyhow!("expected two hex digits after `\\x`"))?
+            }
+            Some(c) => return Err(anyhow!("unknown escape sequence `\\{c}`")),
+            None => return Err(anyhow!("the pattern ends with a backslash")),
+        });
+    }
+    if bytes.is_empty() {
+        return Err(anyhow!("the pattern is empty"));
+    }
+    Ok(bytes)
+}
+
+/// Parses the hex string of `--find`, in which `??` matches any byte and
+/// spaces are ignored (e.g. `DE AD ?? EF`).
+fn parse_find_hex(hex: &str) -> Result<FindPattern> {
+    let digits: Vec<char> = hex.chars().filter(|c| !c.is_whitespace()).collect();
+    if digits.len() % 2 != 0 {
+        return Err(anyhow!("expected an even number of hex digits"));
+    }
+    let bytes = digits
+        .chunks(2)
+        .map(|pair| match pair {
+            ['?', '?'] => Ok(None),
+            [high, low] => match (high.to_digit(16), low.to_digit(16)) {
+                (Some(high), Some(low)) => Ok(Some((high * 16 + low) as u8)),
+                _ => Err(anyhow!("expected hex digits or `??`, found `{high}{low}`")),
+            },
+            _ => unreachable!(),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if bytes.is_empty() {
+        return Err(anyhow!("the hex string is empty"));
+    }
+    Ok(FindPattern { bytes })
+}
+
+/// The number of positions of matches that `--find` prints at most.
+const MAX_SHOWN_FIND_MATCHES: usize = 20;
+
+/// Prints the number and the positions of the matches of `--find` to stderr.
+fn print_find_matches(matches: &[FindMatch]) {
+    let mut positions: Vec<u64> = matches.iter().map(|m| m.position).collect();
+    positions.sort_unstable();
+    let mut shown: Vec<String> = positions
+        .iter()
+        .take(MAX_SHOWN_FIND_MATCHES)
+        .map(|position| format!("{position:#x}"))
+        .collect();
+    if positions.len() > MAX_SHOWN_FIND_MATCHES {
+        shown.push(format!(
+            "and {} more",
+            positions.len() - MAX_SHOWN_FIND_MATCHES
+        ));
+    }
+    match positions.len() {
+        0 => eprintln!("Found no matches"),
+        1 => eprintln!("Found 1 match at {}", shown[0]),
+        n => eprintln!("Found {n} matches at {}", shown.join(", ")),
+    }
+}
+
+/// Parses a byte value, either as a decimal number or as a hex number with a
+/// `0x` prefix (e.g. `204` or `0xcc`).
+fn parse_byte_value(value: &str) -> Result<u8> {
+    let parsed = match value.strip_prefix("0x") {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    parsed.map_err(|_| anyhow!("expected a byte value from 0 to 255 (or 0x00 to 0xff)"))
+}
+
 /// Takes a string containing a base-10 number and an optional unit, and returns them with their proper types.
 /// The unit must directly follow the number (e.g. no whitespace is allowed between them).
 /// When no unit is given, [Unit::Byte] is assumed.
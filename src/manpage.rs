@@ -0,0 +1,195 @@
+//! Generating the man page for `--generate-manpage` from the definition of the
+//! command line, so that it always matches the options of the binary.
+
+use clap::{Arg, Command};
+
+/// Returns the man page of `cmd` in roff format.
+pub(crate) fn manpage(mut cmd: Command) -> String {
+    cmd.build();
+    let name = cmd.get_name().to_owned();
+    let version = cmd.get_version().unwrap_or_default();
+    let about = cmd.get_about().map(|about| about.to_string());
+    let long_about = cmd.get_long_about().map(|about| about.to_string());
+
+    let mut page = format!(
+        ".TH {} 1 \"{name} {version}\"\n.SH NAME\n{name} \\- {}\n",
+        name.to_uppercase(),
+        escape(about.as_deref().unwrap_or_default())
+    );
+
+    page.push_str(".SH SYNOPSIS\n");
+    page.push_str(&format!("\\fB{name}\\fR [\\fIOPTIONS\\fR]"));
+    for arg in cmd.get_positionals().filter(|arg| !arg.is_hide_set()) {
+        page.push_str(&format!(" [\\fI{}\\fR]", escape(&value_names(arg))));
+    }
+    page.push('\n');
+    if cmd.has_subcommands() {
+        page.push_str(&format!(".br\n\\fB{name}\\fR \\fICOMMAND\\fR\n"));
+    }
+
+    if let Some(description) = long_about.or(about) {
+        page.push_str(".SH DESCRIPTION\n");
+        page.push_str(&paragraphs(&description));
+    }
+
+    page.push_str(".SH OPTIONS\n");
+    for arg in cmd
+        .get_arguments()
+        .filter(|arg| !arg.is_positional() && !arg.is_hide_set())
+    {
+        page.push_str(".TP\n");
+        page.push_str(&option_header(arg));
+        page.push('\n');
+        page.push_str(&arg_help(arg));
+    }
+
+    let positionals: Vec<&Arg> = cmd
+        .get_positionals()
+        .filter(|arg| !arg.is_hide_set())
+        .collect();
+    if !positionals.is_empty() {
+        page.push_str(".SH ARGUMENTS\n");
+        for arg in positionals {
+            page.push_str(&format!(".TP\n\\fI{}\\fR\n", escape(&value_names(arg))));
+            page.push_str(&arg_help(arg));
+        }
+    }
+
+    let subcommands: Vec<&Command> = cmd
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set())
+        .collect();
+    if !subcommands.is_empty() {
+        page.push_str(".SH COMMANDS\n");
+        for subcommand in subcommands {
+            page.push_str(&format!(".TP\n\\fB{}\\fR\n", subcommand.get_name()));
+            if let Some(about) = subcommand.get_about() {
+                page.push_str(&paragraphs(&about.to_string()));
+            }
+        }
+    }
+
+    if let Some(after_help) = cmd.get_after_long_help().or(cmd.get_after_help()) {
+        page.push_str(".SH NOTES\n");
+        page.push_str(&paragraphs(&after_help.to_string()));
+    }
+
+    page.push_str(&format!(".SH VERSION\n{}\n", escape(version)));
+    page
+}
+
+/// The names of the values of `arg`, e.g. `N` or `START..END`.
+fn value_names(arg: &Arg) -> String {
+    match arg.get_value_names() {
+        Some(names) => names
+            .iter()
+            .map(|name| name.as_str())
+            .collect::<Vec<_>>()
+            .join(" "),
+        None => arg.get_id().as_str().to_uppercase(),
+    }
+}
+
+/// The flags of an option with its value, e.g. `-n, --length N`.
+fn option_header(arg: &Arg) -> String {
+    let mut flags = vec![];
+    if let Some(short) = arg.get_short() {
+        flags.push(format!("\\fB\\-{}\\fR", escape(&short.to_string())));
+    }
+    for short in arg.get_visible_short_aliases().unwrap_or_default() {
+        flags.push(format!("\\fB\\-{}\\fR", escape(&short.to_string())));
+    }
+    if let Some(long) = arg.get_long() {
+        flags.push(format!("\\fB\\-\\-{}\\fR", escape(long)));
+    }
+    for long in arg.get_visible_aliases().unwrap_or_default() {
+        flags.push(format!("\\fB\\-\\-{}\\fR", escape(long)));
+    }
+    let mut header = flags.join(", ");
+
+    let num_args = arg.get_num_args().unwrap_or_default();
+    if arg.get_action().takes_values() && num_args.takes_values() {
+        let value = format!("\\fI{}\\fR", escape(&value_names(arg)));
+        if num_args.min_values() == 0 {
+            header.push_str(&format!("[={value}]"));
+        } else {
+            header.push_str(&format!(" {value}"));
+        }
+    }
+    header
+}
+
+/// The help text of `arg` with its possible and default values.
+fn arg_help(arg: &Arg) -> String {
+    let help = arg
+        .get_long_help()
+        .or(arg.get_help())
+        .map(|help| help.to_string())
+        .unwrap_or_default();
+    let mut text = paragraphs(&help);
+
+    let values: Vec<_> = arg
+        .get_possible_values()
+        .into_iter()
+        .filter(|value| !value.is_hide_set())
+        .collect();
+    if !values.is_empty() && arg.get_action().takes_values() {
+        text.push_str(".RS\n.PP\nPossible values:\n");
+        for value in values {
+            text.push_str(&format!(
+                ".IP \\(bu 2\n\\fB{}\\fR",
+                escape(value.get_name())
+            ));
+            if let Some(help) = value.get_help() {
+                text.push_str(&format!(": {}", escape(&help.to_string())));
+            }
+            text.push('\n');
+        }
+        text.push_str(".RE\n");
+    }
+
+    let defaults: Vec<_> = arg
+        .get_default_values()
+        .iter()
+        .map(|value| value.to_string_lossy())
+        .collect();
+    if !defaults.is_empty() && arg.get_action().takes_values() {
+        text.push_str(&format!(
+            ".PP\nDefault: \\fI{}\\fR\n",
+            escape(&defaults.join(","))
+        ));
+    }
+    text
+}
+
+/// Turns lines of help text into roff text, with a paragraph for every empty
+/// line and a line break for every other line break.
+fn paragraphs(text: &str) -> String {
+    let mut result = String::new();
+    let mut line_break = false;
+    for line in text.trim().lines() {
+        if line.trim().is_empty() {
+            result.push_str(".PP\n");
+            line_break = false;
+            continue;
+        }
+        if line_break {
+            result.push_str(".br\n");
+        }
+        result.push_str(&escape(line.trim()));
+        result.push('\n');
+        line_break = true;
+    }
+    result
+}
+
+/// Escapes the characters that have a meaning in roff.
+fn escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    // A line that starts with a dot or an apostrophe would be a request.
+    if escaped.starts_with(['.', '\'']) {
+        format!("\\&{escaped}")
+    } else {
+        escaped
+    }
+}
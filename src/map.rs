@@ -0,0 +1,124 @@
+//! The address space of `--map`, with several files at given offsets, e.g. the
+//! blobs that a firmware image is built from.
+
+use std::fs::{self, File};
+use std::ops::Range;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+
+use hexyl::{color_from_name, Highlight, Input, Sparse};
+
+/// The colors of the positions of the files, one after another.
+const FILE_COLORS: [&str; 6] = [
+    "bright-blue",
+    "bright-green",
+    "bright-magenta",
+    "bright-cyan",
+    "bright-yellow",
+    "bright-red",
+];
+
+/// A file at an offset of the address space.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Mapping {
+    pub path: PathBuf,
+    pub offset: u64,
+    pub length: u64,
+}
+
+/// Splits a `FILE@OFFSET` argument at its last `@`.
+pub(crate) fn parse_mapping(arg: &str) -> Result<(PathBuf, &str)> {
+    let (path, offset) = arg
+        .rsplit_once('@')
+        .filter(|(path, _)| !path.is_empty())
+        .ok_or_else(|| anyhow!("expected FILE@OFFSET"))?;
+    Ok((PathBuf::from(path), offset))
+}
+
+/// Looks up the lengths of the files, and returns them sorted by their
+/// offsets.
+pub(crate) fn mappings(files: Vec<(PathBuf, u64)>) -> Result<Vec<Mapping>> {
+    let mut mappings = files
+        .into_iter()
+        .map(|(path, offset)| {
+            let length = fs::metadata(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?
+                .len();
+            Ok(Mapping {
+                path,
+                offset,
+                length,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    mappings.sort_by_key(|mapping| mapping.offset);
+    Ok(mappings)
+}
+
+/// Fails if two of the sorted `mappings` overlap.
+pub(crate) fn check_overlaps(mappings: &[Mapping]) -> Result<()> {
+    for pair in mappings.windows(2) {
+        if pair[0].offset.saturating_add(pair[0].length) > pair[1].offset {
+            return Err(anyhow!(
+                "{} at {:#x} overlaps {} at {:#x}",
+                pair[0].path.display(),
+                pair[0].offset,
+                pair[1].path.display(),
+                pair[1].offset
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the ranges of positions before and between the sorted `mappings`,
+/// which are not part of any file, for [`hexyl::PrinterBuilder::gaps`].
+pub(crate) fn gaps(mappings: &[Mapping]) -> Vec<Range<u64>> {
+    let mut gaps = vec![];
+    let mut end = 0;
+    for mapping in mappings {
+        if mapping.offset > end {
+            gaps.push(end..mapping.offset);
+        }
+        end = mapping.offset + mapping.length;
+    }
+    gaps
+}
+
+pub(crate) fn open(mappings: &[Mapping]) -> Result<Input<'static>> {
+    let parts = mappings
+        .iter()
+        .map(|mapping| Ok((mapping.offset, Input::File(File::open(&mapping.path)?))))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Input::Sparse(Sparse::new(parts)?))
+}
+
+fn color(i: usize) -> &'static [u8] {
+    color_from_name(FILE_COLORS[i % FILE_COLORS.len()]).expect("known color")
+}
+
+/// Returns the labels with the names of the files at their offsets, and the
+/// regions of the files for [`hexyl::PrinterBuilder::position_highlights`],
+/// both at input positions.
+pub(crate) fn highlights(mappings: &[Mapping]) -> (Vec<Highlight>, Vec<Highlight>) {
+    let labels = mappings
+        .iter()
+        .enumerate()
+        .map(|(i, mapping)| Highlight {
+            range: mapping.offset..mapping.offset,
+            color: color(i),
+            label: Some(mapping.path.display().to_string()),
+        })
+        .collect();
+    let positions = mappings
+        .iter()
+        .enumerate()
+        .map(|(i, mapping)| Highlight {
+            range: mapping.offset..mapping.offset + mapping.length,
+            color: color(i),
+            label: None,
+        })
+        .collect();
+    (labels, positions)
+}
@@ -0,0 +1,200 @@
+use crate::{
+    line_width, panel_widths, Base, BorderStyle, CharPanels, CharacterTable, Endianness,
+    LineFormat, LineHash, OffsetStyle, Padding, DEFAULT_IO_BUFFER_SIZE, DEFAULT_MAX_POSITION,
+};
+
+/// The settings that determine how a [`Printer`](crate::Printer) renders its
+/// input, which can be passed to
+/// [`PrinterBuilder::options`](crate::PrinterBuilder::options) at once. The
+/// fields correspond to the methods of
+/// [`PrinterBuilder`](crate::PrinterBuilder) with the same names, which
+/// describe them. Settings that are not plain data, like the theme, the
+/// highlights or hooks, are only set on the builder.
+///
+/// With the `serde` feature, the options can be serialized, e.g. to store them
+/// in a configuration file. Missing fields are deserialized with their default
+/// values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, rename_all = "kebab-case")
+)]
+pub struct PrinterOptions {
+    pub show_color: bool,
+    pub show_char_panel: bool,
+    pub char_panels: CharPanels,
+    pub show_position_panel: bool,
+    pub border_style: BorderStyle,
+    /// See [`PrinterBuilder::enable_squeezing`](crate::PrinterBuilder::enable_squeezing).
+    pub squeeze: bool,
+    /// See [`PrinterBuilder::num_panels`](crate::PrinterBuilder::num_panels).
+    pub panels: u64,
+    /// See [`PrinterBuilder::panel_width`](crate::PrinterBuilder::panel_width).
+    pub panel_width: u64,
+    pub group_size: u8,
+    pub base: Base,
+    pub endianness: Endianness,
+    pub character_table: CharacterTable,
+    pub classify_lines: bool,
+    pub line_hash: Option<LineHash>,
+    pub mark_eof: bool,
+    pub ruler: bool,
+    pub interleave: Vec<Base>,
+    pub both_endianness: bool,
+    pub mnemonics: bool,
+    pub reset_per_line: bool,
+    pub stateless_colors: bool,
+    pub offset_style: OffsetStyle,
+    pub line_format: LineFormat,
+    pub max_position: u64,
+    pub relative_to: u64,
+    pub padding: Padding,
+    /// Must not be zero, see [`PrinterBuilder::step`](crate::PrinterBuilder::step).
+    pub step: u64,
+    pub filter_bytes: Vec<u8>,
+    pub track_spans: bool,
+    pub max_memory: u64,
+    pub offset_hyperlink: Option<String>,
+    pub show_settings: bool,
+    pub footer_info: bool,
+    /// See [`PrinterBuilder::input_name`](crate::PrinterBuilder::input_name).
+    pub input_name: Option<String>,
+    /// See [`PrinterBuilder::comment_width`](crate::PrinterBuilder::comment_width).
+    pub comment_width: Option<usize>,
+    pub tick_marks: bool,
+    pub tick_mark: Option<char>,
+    pub char_panel_tick_marks: bool,
+    pub uppercase: bool,
+    pub highlight_legend: bool,
+    pub squeeze_summary: bool,
+    /// See [`PrinterBuilder::squeeze_marker`](crate::PrinterBuilder::squeeze_marker).
+    pub squeeze_marker: String,
+    /// Must not be zero, see [`PrinterBuilder::io_buffer_size`](crate::PrinterBuilder::io_buffer_size).
+    pub io_buffer_size: usize,
+    pub strict_sanitize: bool,
+}
+
+impl Default for PrinterOptions {
+    fn default() -> Self {
+        PrinterOptions {
+            show_color: true,
+            show_char_panel: true,
+            char_panels: CharPanels::All,
+            show_position_panel: true,
+            border_style: BorderStyle::Unicode,
+            squeeze: true,
+            panels: 2,
+            panel_width: 8,
+            group_size: 1,
+            base: Base::Hexadecimal,
+            endianness: Endianness::Big,
+            character_table: CharacterTable::Default,
+            classify_lines: false,
+            line_hash: None,
+            mark_eof: false,
+            ruler: false,
+            interleave: vec![],
+            both_endianness: false,
+            mnemonics: false,
+            reset_per_line: false,
+            stateless_colors: false,
+            offset_style: OffsetStyle::Byte,
+            line_format: LineFormat::Table,
+            max_position: DEFAULT_MAX_POSITION,
+            relative_to: 0,
+            padding: Padding::Zeros,
+            step: 1,
+            filter_bytes: vec![],
+            track_spans: false,
+            max_memory: u64::MAX,
+            offset_hyperlink: None,
+            show_settings: false,
+            footer_info: false,
+            input_name: None,
+            comment_width: None,
+            tick_marks: false,
+            tick_mark: None,
+            char_panel_tick_marks: true,
+            uppercase: false,
+            highlight_legend: false,
+            squeeze_summary: false,
+            squeeze_marker: "*".to_owned(),
+            io_buffer_size: DEFAULT_IO_BUFFER_SIZE,
+            strict_sanitize: false,
+        }
+    }
+}
+
+impl PrinterOptions {
+    /// Returns the number of terminal columns that a line of output occupies
+    /// with these options, including the border (or the space that replaces
+    /// it).
+    pub fn layout_width(&self) -> usize {
+        line_width(
+            self.border_style,
+            &panel_widths(
+                self.show_position_panel
+                    .then(|| self.offset_style.width(self.max_position)),
+                if self.show_char_panel {
+                    self.char_panels.count(self.panels)
+                } else {
+                    0
+                },
+                self.classify_lines,
+                self.line_hash,
+                self.panels,
+                self.panel_width,
+                self.base_digits(),
+                self.group_size,
+                self.tick_marks && self.char_panel_tick_marks,
+            ),
+        )
+    }
+
+    /// The number of characters used for a single byte in all rows.
+    pub(crate) fn base_digits(&self) -> u8 {
+        let digits = self
+            .interleave
+            .iter()
+            .map(|base| base.digits())
+            .fold(self.base.digits(), u8::max);
+        if self.mnemonics {
+            digits.max(3)
+        } else {
+            digits
+        }
+    }
+
+    /// Returns the legend for [`PrinterBuilder::show_settings`](crate::PrinterBuilder::show_settings).
+    pub(crate) fn settings_legend(&self) -> String {
+        let base = match self.base {
+            Base::Binary => "bin",
+            Base::Octal => "oct",
+            Base::Decimal => "dec",
+            Base::Hexadecimal => "hex",
+        };
+        let mut panels = match self.panels {
+            1 => "1 panel".to_owned(),
+            n => format!("{n} panels"),
+        };
+        if self.panel_width != 8 {
+            panels.push_str(&format!(" of {}", self.panel_width));
+        }
+        let endianness = match self.endianness {
+            Endianness::Big => "BE",
+            Endianness::Little => "LE",
+        };
+        let character_table = match self.character_table {
+            CharacterTable::Default => "default",
+            CharacterTable::Ascii => "ascii",
+            CharacterTable::CP1047 => "cp1047",
+            CharacterTable::CP437 | CharacterTable::CP437BlankControls => "cp437",
+            CharacterTable::Latin1 => "latin1",
+        };
+        format!(
+            "[{base}|{panels}|g{}|{endianness}|{character_table}]",
+            self.group_size
+        )
+    }
+}
@@ -0,0 +1,49 @@
+//! Writing to stdout. When the output is closed early, e.g. by
+//! `hexyl FILE | head`, hexyl stops right away and exits successfully,
+//! whichever output format it was writing.
+
+use std::io::{self, BufWriter, StdoutLock, Write};
+use std::process;
+
+use crate::EXIT_SUCCESS;
+
+/// The locked stdout, which exits hexyl when the output is closed.
+pub(crate) struct Stdout {
+    inner: StdoutLock<'static>,
+}
+
+impl Stdout {
+    pub(crate) fn lock() -> Self {
+        Stdout {
+            inner: io::stdout().lock(),
+        }
+    }
+
+    /// Returns a buffered writer for larger output.
+    pub(crate) fn buffered() -> BufWriter<Self> {
+        BufWriter::new(Self::lock())
+    }
+}
+
+/// Exits on a broken pipe, the reader of the output is gone and there is
+/// nothing left to do.
+fn exit_on_broken_pipe<T>(result: io::Result<T>) -> io::Result<T> {
+    match result {
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => process::exit(EXIT_SUCCESS),
+        result => result,
+    }
+}
+
+impl Write for Stdout {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        exit_on_broken_pipe(self.inner.write(buf))
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        exit_on_broken_pipe(self.inner.write_all(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        exit_on_broken_pipe(self.inner.flush())
+    }
+}
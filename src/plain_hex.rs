@@ -0,0 +1,105 @@
+//! The bytes of the input as a continuous string of lowercase hex digits, like
+//! `xxd -p`, e.g. to paste them into other tools.
+//!
+//! There are no offsets, spaces, borders or character panels. The digits are
+//! wrapped after a number of bytes per line, or written in a single line, and
+//! the last line ends with `\n` as well. There is no output for empty input.
+//!
+//! ```text
+//! 7f454c4602010100000000000000000002003e00010000000010400000000000
+//! 400000000000
+//! ```
+//!
+//! [`read_plain_hex`] turns the digits back into the bytes.
+
+use std::io::{self, BufReader, Read, Write};
+use std::num::NonZeroUsize;
+
+/// Writes the bytes of `reader` as hex digits to `writer`, see the
+/// [module documentation](self). With `bytes_per_line`, a line break follows
+/// every that many bytes.
+pub fn write_plain_hex<R: Read, W: Write + ?Sized>(
+    reader: R,
+    writer: &mut W,
+    bytes_per_line: Option<NonZeroUsize>,
+) -> io::Result<()> {
+    let bytes_per_line = bytes_per_line.map_or(usize::MAX, NonZeroUsize::get);
+    let mut reader = BufReader::new(reader);
+    let mut buf = [0; 8 * 1024];
+    // The number of bytes in the current line.
+    let mut line_len = 0;
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        for &b in &buf[..n] {
+            if line_len == bytes_per_line {
+                writeln!(writer)?;
+                line_len = 0;
+            }
+            write!(writer, "{b:02x}")?;
+            line_len += 1;
+        }
+    }
+    if line_len > 0 {
+        writeln!(writer)?;
+    }
+    writer.flush()
+}
+
+/// Writes the bytes of the hex digits of `reader` to `writer`, the reverse of
+/// [`write_plain_hex`], and returns their number. Whitespace between the
+/// digits is ignored. Any other character and an odd number of digits are
+/// errors of the kind [`io::ErrorKind::InvalidData`].
+pub fn read_plain_hex<R: Read, W: Write + ?Sized>(reader: R, writer: &mut W) -> io::Result<u64> {
+    let mut reader = BufReader::new(reader);
+    let mut buf = [0; 8 * 1024];
+    let mut bytes = Vec::with_capacity(buf.len() / 2);
+    // The first digit of a byte whose second digit is still to come.
+    let mut high = None;
+    // The position of `buf` in the input, for errors.
+    let mut position: u64 = 0;
+    let mut len: u64 = 0;
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        bytes.clear();
+        for (i, &c) in buf[..n].iter().enumerate() {
+            if c.is_ascii_whitespace() {
+                continue;
+            }
+            let digit = (c as char).to_digit(16).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "expected a hex digit at position {:#x}, found '{}'",
+                        position + i as u64,
+                        c.escape_ascii()
+                    ),
+                )
+            })? as u8;
+            match high.take() {
+                Some(high) => bytes.push(high << 4 | digit),
+                None => high = Some(digit),
+            }
+        }
+        writer.write_all(&bytes)?;
+        position += n as u64;
+        len += bytes.len() as u64;
+    }
+    if high.is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "expected an even number of hex digits",
+        ));
+    }
+    writer.flush()?;
+    Ok(len)
+}
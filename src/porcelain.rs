@@ -0,0 +1,57 @@
+//! Output formats for scripts, which stay the same across releases, unlike
+//! the hex dump for humans.
+//!
+//! # Version 1
+//!
+//! * Every line shows 16 bytes of the input, except for the last line, which
+//!   shows the remaining 1 to 16 bytes. There is no output for empty input.
+//! * A line consists of the offset of its first byte as 16 lowercase hex
+//!   digits, a colon, and every byte as a space followed by two lowercase hex
+//!   digits. Every line ends with `\n`.
+//! * Repeated lines are not squeezed, and there are no colors, borders,
+//!   headers or character panels.
+//!
+//! ```text
+//! 0000000000000000: 7f 45 4c 46 02 01 01 00 00 00 00 00 00 00 00 00
+//! 0000000000000010: 02 00 3e 00
+//! ```
+
+use std::io::{self, BufReader, Read, Write};
+
+/// The number of bytes per line in version 1.
+const BYTES_PER_LINE: usize = 16;
+
+/// Writes `reader` in the format of version 1 to `writer`, see the
+/// [module documentation](self). `offset` is the offset of the first byte.
+pub fn write_v1<R: Read, W: Write + ?Sized>(
+    reader: R,
+    writer: &mut W,
+    mut offset: u64,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(reader);
+    let mut line = [0; BYTES_PER_LINE];
+    loop {
+        let mut len = 0;
+        while len < BYTES_PER_LINE {
+            match reader.read(&mut line[len..]) {
+                Ok(0) => break,
+                Ok(n) => len += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if len == 0 {
+            break;
+        }
+        write!(writer, "{offset:016x}:")?;
+        for b in &line[..len] {
+            write!(writer, " {b:02x}")?;
+        }
+        writeln!(writer)?;
+        offset += len as u64;
+        if len < BYTES_PER_LINE {
+            break;
+        }
+    }
+    writer.flush()
+}
@@ -0,0 +1,143 @@
+//! The `--prompt` mode, which shows one chunk of a file at a time and reads
+//! commands from stdin to move around.
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use anyhow::{anyhow, Result};
+
+/// A command that is read from stdin in `--prompt` mode.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Command {
+    /// Show the next chunk (an empty line or `+`).
+    Next,
+    /// Move forward or backward by the given number of bytes (`+N`, `-N`).
+    Move(i64),
+    /// Go to the given position (`g N`).
+    Goto(u64),
+    /// Go to the next occurrence of the given text (`/TEXT`).
+    Search(Vec<u8>),
+    /// Quit (`q`).
+    Quit,
+}
+
+/// Parses a command. `parse_offset` parses a (possibly signed) byte offset,
+/// e.g. `0x100` or `-4KiB`.
+pub(crate) fn parse_command(
+    line: &str,
+    parse_offset: impl Fn(&str) -> Result<i64>,
+) -> Result<Command> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    if let Some(text) = line.strip_prefix('/') {
+        if text.is_empty() {
+            return Err(anyhow!("expected a text to search for after '/'"));
+        }
+        return Ok(Command::Search(text.as_bytes().to_vec()));
+    }
+
+    let line = line.trim();
+    match line {
+        "" | "+" => return Ok(Command::Next),
+        "q" | "quit" => return Ok(Command::Quit),
+        _ => {}
+    }
+    if let Some(position) = line.strip_prefix("g ") {
+        let position = parse_offset(position.trim())?;
+        return u64::try_from(position)
+            .map(Command::Goto)
+            .map_err(|_| anyhow!("the position must not be negative"));
+    }
+    if line.starts_with(['+', '-']) {
+        return Ok(Command::Move(parse_offset(line)?));
+    }
+    Err(anyhow!(
+        "unknown command {line:?}, expected '+N', '-N', 'g N', '/TEXT' or 'q'"
+    ))
+}
+
+/// Returns the position of the first occurrence of `pattern` in `file` at or
+/// after `start`.
+pub(crate) fn search(file: &mut File, start: u64, pattern: &[u8]) -> io::Result<Option<u64>> {
+    const BLOCK_SIZE: usize = 64 * 1024;
+
+    file.seek(SeekFrom::Start(start))?;
+    let mut buf = Vec::with_capacity(BLOCK_SIZE + pattern.len());
+    let mut buf_start = start;
+    loop {
+        let len = buf.len();
+        let n = (&mut *file).take(BLOCK_SIZE as u64).read_to_end(&mut buf)?;
+        if n == 0 {
+            return Ok(None);
+        }
+        // Only look at the windows that end in the new data, the others have
+        // been searched already.
+        let first = len.saturating_sub(pattern.len() - 1);
+        if let Some(i) = buf[first..]
+            .windows(pattern.len())
+            .position(|window| window == pattern)
+        {
+            return Ok(Some(buf_start + (first + i) as u64));
+        }
+        let keep = (pattern.len() - 1).min(buf.len());
+        buf_start += (buf.len() - keep) as u64;
+        buf.drain(..buf.len() - keep);
+    }
+}
+
+/// Shows chunks of `chunk_size` bytes of the file, starting at position 0,
+/// and moves around according to the commands read from stdin until `q` or
+/// the end of stdin. `dump` prints a chunk that starts at the given position.
+pub(crate) fn run(
+    mut file: File,
+    chunk_size: u64,
+    parse_offset: impl Fn(&str) -> Result<i64>,
+    dump: impl Fn(Vec<u8>, u64) -> Result<()>,
+) -> Result<()> {
+    let size = file.metadata()?.len();
+    let mut position = 0;
+    let mut show = true;
+    let mut line = String::new();
+    loop {
+        if show {
+            let mut chunk = vec![];
+            file.seek(SeekFrom::Start(position))?;
+            (&mut file).take(chunk_size).read_to_end(&mut chunk)?;
+            dump(chunk, position)?;
+        }
+
+        eprint!("hexyl> ");
+        io::stderr().flush()?;
+        line.clear();
+        if io::stdin().read_line(&mut line)? == 0 {
+            eprintln!();
+            return Ok(());
+        }
+
+        let new_position = match parse_command(&line, &parse_offset) {
+            Ok(Command::Next) => position.saturating_add(chunk_size),
+            Ok(Command::Move(delta)) => position.saturating_add_signed(delta),
+            Ok(Command::Goto(target)) => target,
+            Ok(Command::Search(pattern)) => match search(&mut file, position + 1, &pattern)? {
+                Some(found) => found,
+                None => {
+                    eprintln!("Pattern not found");
+                    show = false;
+                    continue;
+                }
+            },
+            Ok(Command::Quit) => return Ok(()),
+            Err(err) => {
+                eprintln!("Error: {err}");
+                show = false;
+                continue;
+            }
+        };
+        if new_position >= size {
+            eprintln!("Position {new_position:#x} is beyond the end of the input ({size:#x})");
+            show = false;
+            continue;
+        }
+        position = new_position;
+        show = true;
+    }
+}
@@ -0,0 +1,180 @@
+use std::cell::RefCell;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::rc::Rc;
+
+use clap::ValueEnum;
+
+/// What a [`RecoveringReader`] does if reading fails even after retrying.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OnError {
+    /// Return the error.
+    #[default]
+    Stop,
+    /// Leave the unreadable block out, so later bytes move to lower positions.
+    Skip,
+    /// Replace the unreadable block by zeros, which keeps all positions.
+    Zero,
+}
+
+/// The positions of the bytes that a [`RecoveringReader`] has replaced by
+/// zeros, see [`PrinterBuilder::unreadable_bytes`]. Clones share the same
+/// positions.
+///
+/// [`PrinterBuilder::unreadable_bytes`]: crate::PrinterBuilder::unreadable_bytes
+#[derive(Clone, Debug, Default)]
+pub struct UnreadableBytes(Rc<RefCell<Vec<Range<u64>>>>);
+
+impl UnreadableBytes {
+    /// Returns whether the byte at `position` of the output of the reader
+    /// could not be read.
+    pub fn contains(&self, position: u64) -> bool {
+        let ranges = self.0.borrow();
+        let i = ranges.partition_point(|range| range.end <= position);
+        ranges.get(i).is_some_and(|range| range.contains(&position))
+    }
+
+    fn push(&self, range: Range<u64>) {
+        self.0.borrow_mut().push(range);
+    }
+}
+
+/// Statistics about the reads of a [`RecoveringReader`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReadStats {
+    /// The number of reads that returned fewer bytes than requested, except
+    /// for the last read before the end of the input.
+    pub short_reads: u64,
+    /// The number of failed reads that have been retried.
+    pub retries: u64,
+    /// The ranges of input positions that could not be read.
+    pub bad_blocks: Vec<Range<u64>>,
+}
+
+/// A reader that retries failed reads, e.g. for damaged disks that return
+/// transient errors, and skips unreadable blocks or replaces them by zeros.
+/// Positions are counted from where the inner reader was when it has been
+/// passed to [`RecoveringReader::new`].
+pub struct RecoveringReader<R: Read + Seek> {
+    inner: R,
+    retries: u32,
+    on_error: OnError,
+    block_size: u64,
+    /// The position in the inner reader.
+    input_position: u64,
+    /// The position in the output of this reader.
+    position: u64,
+    /// The number of zeros that still have to be returned for a bad block.
+    pending_zeros: u64,
+    /// Whether the last read was short, which is only counted once it is
+    /// clear that it was not the last one before the end of the input.
+    short_read: bool,
+    stats: ReadStats,
+    unreadable: UnreadableBytes,
+}
+
+impl<R: Read + Seek> RecoveringReader<R> {
+    /// Creates a reader that retries every failed read up to `retries` times.
+    /// Unreadable data is skipped in blocks of `block_size` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is zero.
+    pub fn new(inner: R, retries: u32, on_error: OnError, block_size: u64) -> Self {
+        assert!(block_size > 0, "the block size must not be zero");
+        RecoveringReader {
+            inner,
+            retries,
+            on_error,
+            block_size,
+            input_position: 0,
+            position: 0,
+            pending_zeros: 0,
+            short_read: false,
+            stats: ReadStats::default(),
+            unreadable: UnreadableBytes::default(),
+        }
+    }
+
+    pub fn stats(&self) -> &ReadStats {
+        &self.stats
+    }
+
+    /// Returns the positions of the bytes that have been replaced by zeros
+    /// with [`OnError::Zero`].
+    pub fn unreadable_bytes(&self) -> UnreadableBytes {
+        self.unreadable.clone()
+    }
+
+    /// Moves the inner reader past the unreadable block at the current
+    /// position, and returns its length. Returns `None` if the block is at the
+    /// end of the input.
+    fn skip_bad_block(&mut self) -> io::Result<Option<u64>> {
+        let start = self.input_position;
+        let mut end = (start / self.block_size + 1) * self.block_size;
+        let here = self.inner.stream_position()?;
+        let size = self.inner.seek(SeekFrom::End(0))?;
+        let input_size = size.saturating_sub(here.saturating_sub(start));
+        end = end.min(input_size);
+        if end <= start {
+            self.inner.seek(SeekFrom::Start(here))?;
+            return Ok(None);
+        }
+        self.inner.seek(SeekFrom::Start(here + (end - start)))?;
+        self.input_position = end;
+        self.stats.bad_blocks.push(start..end);
+        Ok(Some(end - start))
+    }
+}
+
+impl<R: Read + Seek> Read for RecoveringReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let mut attempts = 0;
+        loop {
+            if self.pending_zeros > 0 {
+                let n = buf.len().min(self.pending_zeros as usize);
+                buf[..n].fill(0);
+                self.pending_zeros -= n as u64;
+                self.position += n as u64;
+                return Ok(n);
+            }
+
+            match self.inner.read(buf) {
+                Ok(n) => {
+                    if self.short_read && n > 0 {
+                        self.stats.short_reads += 1;
+                    }
+                    self.short_read = n < buf.len();
+                    self.input_position += n as u64;
+                    self.position += n as u64;
+                    return Ok(n);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(_) if attempts < self.retries => {
+                    attempts += 1;
+                    self.stats.retries += 1;
+                }
+                Err(e) if self.on_error == OnError::Stop => return Err(e),
+                Err(e) => {
+                    // The inner reader has to be seekable to get past the
+                    // unreadable block, otherwise the original error is more
+                    // helpful than the one from seeking.
+                    let Ok(len) = self.skip_bad_block() else {
+                        return Err(e);
+                    };
+                    let Some(len) = len else {
+                        return Ok(0);
+                    };
+                    if self.on_error == OnError::Zero {
+                        self.unreadable.push(self.position..self.position + len);
+                        self.pending_zeros = len;
+                    }
+                    attempts = 0;
+                }
+            }
+        }
+    }
+}
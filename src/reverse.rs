@@ -0,0 +1,472 @@
+//! Converting a hex dump of hexyl back into the bytes for `--reverse`, like
+//! `xxd -r`.
+
+use std::io::{BufRead, Write};
+
+use anyhow::{anyhow, Context, Result};
+
+use hexyl::{strip_escapes, Base, BorderStyle, Endianness, OffsetStyle};
+
+/// How the bytes are written in the dump, which can not be told from the dump
+/// itself.
+pub(crate) struct DumpFormat {
+    pub base: Base,
+    /// The number of characters of every byte, which may be more than the
+    /// digits of `base` with `--interleave`.
+    pub digits: usize,
+    pub group_size: usize,
+    pub endianness: Endianness,
+    /// The number of bytes in a full panel.
+    pub bytes_per_panel: usize,
+    /// The sector size of positions like `LBA 00000003 +1f0`.
+    pub offset_style: OffsetStyle,
+}
+
+impl DumpFormat {
+    /// The width of a hex panel, including the leading space and the space
+    /// after every group.
+    fn panel_width(&self) -> usize {
+        1 + (self.group_size * self.digits + 1) * (self.bytes_per_panel / self.group_size)
+    }
+
+    fn radix(&self) -> u32 {
+        match self.base {
+            Base::Binary => 2,
+            Base::Octal => 8,
+            Base::Decimal => 10,
+            Base::Hexadecimal => 16,
+        }
+    }
+
+    /// Returns the bytes in a hex panel, or `None` if it is not one. Only the
+    /// last bytes of the panel may be missing.
+    fn parse_panel(&self, cell: &[char]) -> Option<Vec<u8>> {
+        if cell.len() != self.panel_width() || !is_space(cell[0]) {
+            return None;
+        }
+        let mut bytes = vec![];
+        let mut ended = false;
+        for group in cell[1..].chunks(self.group_size * self.digits + 1) {
+            let (digits, sep) = group.split_at(group.len() - 1);
+            if !is_space(sep[0]) {
+                return None;
+            }
+            let mut group_bytes = vec![];
+            for slot in digits.chunks(self.digits) {
+                // The digits are right-aligned, padded with spaces or zeros.
+                let text = slot.iter().collect::<String>();
+                let text = text.trim_start();
+                if text.is_empty() {
+                    ended = true;
+                    continue;
+                }
+                if ended {
+                    return None;
+                }
+                group_bytes.push(u8::from_str_radix(text, self.radix()).ok()?);
+            }
+            // An incomplete group at the end is reversed as a whole, too.
+            if self.endianness == Endianness::Little {
+                group_bytes.reverse();
+            }
+            bytes.extend(group_bytes);
+        }
+        Some(bytes)
+    }
+}
+
+/// The panels of the lines of a dump, as found in its first line.
+struct Layout {
+    style: BorderStyle,
+    position_width: Option<usize>,
+    /// Whether the positions are `segment:offset` addresses, which wrap
+    /// around after 1 MiB.
+    segments: bool,
+    panels: usize,
+}
+
+/// A line of the dump.
+enum Row {
+    /// A line with bytes of the input, with its position if there is a
+    /// position panel. Positions before `--relative-to` are negative.
+    Bytes(Option<i128>, Vec<u8>),
+    /// The marker of squeezed lines.
+    Squeezed,
+    /// The position after the squeezed lines at the end of the input.
+    End(i128),
+    /// A line without bytes of the input, e.g. an interleaved row, the
+    /// continuation of a comment or the placeholder of an empty input.
+    Other,
+}
+
+/// Whether `c` is a space of a hex panel, or a mark that replaces one: a tick
+/// mark (`--tick-marks`) or the end of the input (`--mark-eof`).
+fn is_space(c: char) -> bool {
+    matches!(c, ' ' | '·' | ':' | '▏')
+}
+
+fn outer_sep(style: BorderStyle) -> char {
+    match style {
+        BorderStyle::Unicode => '│',
+        BorderStyle::Ascii => '|',
+        BorderStyle::None | BorderStyle::Compact => ' ',
+    }
+}
+
+fn inner_sep(style: BorderStyle) -> char {
+    match style {
+        BorderStyle::Unicode => '┊',
+        BorderStyle::Ascii => '|',
+        BorderStyle::None | BorderStyle::Compact => ' ',
+    }
+}
+
+fn is_border(style: BorderStyle, line: &str) -> bool {
+    match style {
+        BorderStyle::Unicode => line.starts_with(['┌', '├', '└']),
+        BorderStyle::Ascii => line.starts_with('+'),
+        BorderStyle::None | BorderStyle::Compact => false,
+    }
+}
+
+fn detect_style(line: &str) -> Result<BorderStyle> {
+    match line.chars().next() {
+        Some('┌' | '│') => Ok(BorderStyle::Unicode),
+        Some('+' | '|') => Ok(BorderStyle::Ascii),
+        Some(' ') => Ok(BorderStyle::None),
+        _ => Err(anyhow!("expected the border or a line of a hex dump")),
+    }
+}
+
+/// Returns the characters from `start` on, padded with spaces to `len` for
+/// dumps without a border, whose lines may have lost their trailing spaces.
+fn cell(chars: &[char], start: usize, len: usize, style: BorderStyle) -> Option<Vec<char>> {
+    let end = start + len;
+    if end <= chars.len() {
+        Some(chars[start..end].to_vec())
+    } else if style == BorderStyle::None && start < chars.len() {
+        let mut cell = chars[start..].to_vec();
+        cell.resize(len, ' ');
+        Some(cell)
+    } else {
+        None
+    }
+}
+
+/// Finds the position panel and the hex panels in the first line of a dump.
+fn detect_layout(style: BorderStyle, chars: &[char], format: &DumpFormat) -> Result<Layout> {
+    let width = format.panel_width();
+    let (outer, inner) = (outer_sep(style), inner_sep(style));
+    let position_width = match style {
+        // Hex panels start with a space, positions do not. Sector positions
+        // have spaces between their parts.
+        BorderStyle::None => (chars.get(1) != Some(&' ')).then(|| {
+            let words = if chars[1..].starts_with(&['L', 'B', 'A', ' ']) {
+                3
+            } else {
+                1
+            };
+            let mut spaces = 0;
+            chars[1..]
+                .iter()
+                .take_while(|&&c| {
+                    spaces += usize::from(c == ' ');
+                    spaces < words
+                })
+                .count()
+        }),
+        _ => {
+            let first = chars[1..]
+                .iter()
+                .position(|&c| c == outer || c == inner)
+                .ok_or_else(|| anyhow!("expected a separator of the panels"))?;
+            (first != width).then_some(first)
+        }
+    };
+
+    let mut start = 1 + position_width.map_or(0, |width| width + 1);
+    let mut panels = 0;
+    while let Some(panel) = cell(chars, start, width + 1, style) {
+        let sep = panel[width];
+        if (sep != outer && sep != inner) || format.parse_panel(&panel[..width]).is_none() {
+            break;
+        }
+        panels += 1;
+        // The last hex panel is followed by an outer separator, the others
+        // by inner ones. The two can only be told apart in Unicode borders.
+        if style == BorderStyle::Unicode && sep == outer {
+            break;
+        }
+        start += width + 1;
+    }
+    if panels == 0 && !chars.iter().collect::<String>().contains("No content") {
+        return Err(anyhow!(
+            "found no hex panel, pass the same '--base', '--group-size' and '--endianness' \
+             as for the dump"
+        ));
+    }
+    let segments = position_width.is_some_and(|width| chars[1..=width].contains(&':'));
+    Ok(Layout {
+        style,
+        position_width,
+        segments,
+        panels,
+    })
+}
+
+fn parse_row(layout: &Layout, chars: &[char], format: &DumpFormat) -> Result<Row> {
+    let width = format.panel_width();
+    let mut start = 1;
+    let position = match layout.position_width {
+        Some(position_width) => {
+            let position = cell(chars, start, position_width + 1, layout.style)
+                .filter(|cell| cell[position_width] == outer_sep(layout.style))
+                .ok_or_else(|| anyhow!("expected a position panel"))?;
+            start += position_width + 1;
+            Some(position[..position_width].iter().collect::<String>())
+        }
+        None => None,
+    };
+    let mut panels = vec![];
+    for _ in 0..layout.panels {
+        let panel = cell(chars, start, width, layout.style)
+            .ok_or_else(|| anyhow!("expected {} hex panels", layout.panels))?;
+        start += width + 1;
+        panels.push(panel);
+    }
+    let blank = panels.iter().flatten().all(|&c| is_space(c));
+    let parse_panels = || -> Result<Vec<u8>> {
+        let mut bytes = vec![];
+        for panel in &panels {
+            if bytes.len() % format.bytes_per_panel != 0 && !panel.iter().all(|&c| is_space(c)) {
+                return Err(anyhow!(
+                    "expected the bytes to end with the incomplete panel"
+                ));
+            }
+            let panel_bytes = format.parse_panel(panel).ok_or_else(|| {
+                anyhow!(
+                    "invalid hex panel {:?}",
+                    panel.iter().collect::<String>().trim()
+                )
+            })?;
+            bytes.extend(panel_bytes);
+        }
+        Ok(bytes)
+    };
+
+    let Some(position) = position else {
+        if blank {
+            return Ok(Row::Other);
+        }
+        if !is_space(panels[0][0]) {
+            // The marker of squeezed lines replaces the start of the hex
+            // panel.
+            return Ok(Row::Squeezed);
+        }
+        return Ok(Row::Bytes(None, parse_panels()?));
+    };
+    if position.trim().is_empty() {
+        return Ok(Row::Other);
+    }
+    // Positions take the whole panel, labels of interleaved rows like `be`
+    // and the marker of squeezed lines do not.
+    Ok(match parse_position(&position, format)? {
+        Some(position) if blank => Row::End(position),
+        Some(position) => Row::Bytes(Some(position), parse_panels()?),
+        None if blank => Row::Squeezed,
+        None if matches!(position.trim_start(), "be" | "le") => Row::Other,
+        None => {
+            return Err(anyhow!(
+                "position panel {:?} not supported by --reverse",
+                position.trim()
+            ))
+        }
+    })
+}
+
+/// Returns the position of a position panel in any `--offset-style`, or
+/// `None` if it is not a position.
+fn parse_position(position: &str, format: &DumpFormat) -> Result<Option<i128>> {
+    let hex = |digits: &str| {
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        i128::from_str_radix(digits, 16).ok()
+    };
+    // Positions before `--relative-to` have a `-`.
+    let signed = |text: &str| match text.strip_prefix('-') {
+        Some(digits) => hex(digits).map(|n| -n),
+        None => hex(text),
+    };
+    if let Some(sector) = position.strip_prefix("LBA ") {
+        let Some((sector, offset)) = sector.split_once(" +") else {
+            return Ok(None);
+        };
+        let (Some(sector), Some(offset)) = (signed(sector), hex(offset)) else {
+            return Ok(None);
+        };
+        let OffsetStyle::Sector(sector_size) = format.offset_style else {
+            return Err(anyhow!(
+                "found the position of a sector, pass the same '--offset-style=sector' and \
+                 '--sector-size' as for the dump"
+            ));
+        };
+        return Ok(Some(sector * i128::from(sector_size.get()) + offset));
+    }
+    if let Some((segment, offset)) = position.split_once(':') {
+        return Ok(hex(segment)
+            .zip(hex(offset))
+            .map(|(segment, offset)| segment * 16 + offset));
+    }
+    Ok(signed(position))
+}
+
+/// Formats a position for errors, with a `-` before `--relative-to`.
+fn format_position(position: i128) -> String {
+    if position < 0 {
+        format!("-{:#x}", -position)
+    } else {
+        format!("{position:#x}")
+    }
+}
+
+/// Writes the bytes of the rows, and restores squeezed lines.
+struct Reverser<'a, W: Write> {
+    output: &'a mut W,
+    last_line: Vec<u8>,
+    /// The position of the next line, if there is a position panel.
+    next_position: Option<i128>,
+    /// The size of the address space of `segment:offset` positions, after
+    /// which they wrap around.
+    wrap: Option<i128>,
+    squeezed: bool,
+}
+
+impl<'a, W: Write> Reverser<'a, W> {
+    fn push(&mut self, row: Row) -> Result<()> {
+        match row {
+            Row::Other => {}
+            Row::Squeezed => {
+                if self.last_line.is_empty() {
+                    return Err(anyhow!("expected a line before the squeezed lines"));
+                }
+                if self.next_position.is_none() {
+                    return Err(anyhow!(
+                        "squeezed lines can only be restored from a dump with a position \
+                         panel, or without squeezing ('--no-squeezing')"
+                    ));
+                }
+                self.squeezed = true;
+            }
+            Row::End(position) => {
+                self.continue_at(position)?;
+            }
+            Row::Bytes(position, bytes) => {
+                let position = position
+                    .map(|position| self.continue_at(position))
+                    .transpose()?;
+                self.output.write_all(&bytes)?;
+                self.next_position = position.map(|position| position + bytes.len() as i128);
+                self.last_line = bytes;
+            }
+        }
+        Ok(())
+    }
+
+    /// Continues with the line at `position`, after repeating the last line
+    /// for the squeezed lines before it. Returns the position without the
+    /// wrap-around of `segment:offset` addresses.
+    fn continue_at(&mut self, position: i128) -> Result<i128> {
+        // The first line may start anywhere, e.g. with `--skip`.
+        let Some(expected) = self.next_position else {
+            return Ok(position);
+        };
+        let position = match self.wrap {
+            Some(wrap) => expected + (position - expected).rem_euclid(wrap),
+            None => position,
+        };
+        if self.squeezed {
+            self.squeezed = false;
+            let line_len = self.last_line.len() as i128;
+            let squeezed_len = Some(position - expected)
+                .filter(|&len| len >= 0 && len % line_len == 0)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "expected the squeezed lines from position {} to end a whole number \
+                         of lines later, not at {}",
+                        format_position(expected),
+                        format_position(position)
+                    )
+                })?;
+            for _ in 0..squeezed_len / line_len {
+                self.output.write_all(&self.last_line)?;
+            }
+        } else if position != expected {
+            return Err(anyhow!(
+                "expected the line at position {}, not at {}",
+                format_position(expected),
+                format_position(position)
+            ));
+        }
+        Ok(position)
+    }
+}
+
+/// Writes the bytes of a hex dump of hexyl to `output`, starting with the
+/// first shown byte. The dump may have colors, any border style, and a
+/// position panel, which is needed to restore squeezed lines. The text after
+/// the dump, e.g. a legend of highlights, is ignored.
+pub(crate) fn reverse(
+    input: impl BufRead,
+    output: &mut impl Write,
+    format: &DumpFormat,
+) -> Result<()> {
+    let mut style = None;
+    let mut layout = None;
+    let mut reverser = Reverser {
+        output,
+        last_line: vec![],
+        next_position: None,
+        wrap: None,
+        squeezed: false,
+    };
+    for (number, line) in input.lines().enumerate() {
+        let context = || format!("line {}", number + 1);
+        let line = strip_escapes(line?.trim_end_matches('\r'));
+        if line.trim().is_empty() {
+            continue;
+        }
+        let style = match style {
+            Some(style) => style,
+            None => *style.insert(detect_style(&line).with_context(context)?),
+        };
+        let chars: Vec<char> = line.chars().collect();
+        if chars[0] != outer_sep(style) {
+            if layout.is_some() {
+                // The bottom border, or the text after the dump.
+                break;
+            }
+            if is_border(style, &line) {
+                continue;
+            }
+            return Err(anyhow!("expected a line of a hex dump")).with_context(context);
+        }
+        let layout = match &layout {
+            Some(layout) => layout,
+            None => {
+                let detected = detect_layout(style, &chars, format).with_context(context)?;
+                reverser.wrap = detected.segments.then_some(0x10_0000);
+                layout.insert(detected)
+            }
+        };
+        let row = parse_row(layout, &chars, format).with_context(context)?;
+        reverser.push(row).with_context(context)?;
+    }
+    if reverser.squeezed {
+        return Err(anyhow!(
+            "expected the position after the squeezed lines at the end of the dump"
+        ));
+    }
+    reverser.output.flush()?;
+    Ok(())
+}
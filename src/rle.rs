@@ -0,0 +1,116 @@
+//! A run-length summary of the input, as an alternative to the hex dump.
+//!
+//! Every line starts with the offset, followed by either
+//!
+//! * `run LEN BYTE` for a run of at least [`LSIZE`] identical bytes (the same
+//!   amount as a squeezed line), or
+//! * `lit LEN BYTES..` for up to [`LSIZE`] other bytes.
+//!
+//! Offsets and bytes are written in hex, lengths in decimal:
+//!
+//! ```text
+//! 00000000 lit 4 7f 45 4c 46
+//! 00000004 run 4092 00
+//! ```
+//!
+//! [`LSIZE`]: crate::squeezer::LSIZE
+
+use std::io::{self, BufReader, Read, Write};
+
+use crate::squeezer::LSIZE;
+
+/// Writes the run-length summary of `reader` to `writer`, see the
+/// [module documentation](self). `offset` is the offset of the first byte.
+pub fn write_rle<R: Read, W: Write + ?Sized>(
+    reader: R,
+    writer: &mut W,
+    offset: u64,
+) -> io::Result<()> {
+    let mut encoder = Encoder {
+        writer,
+        offset,
+        literal: Vec::with_capacity(2 * LSIZE),
+        run: None,
+    };
+    let mut reader = BufReader::new(reader);
+    let mut buf = [0; 8 * 1024];
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        for &b in &buf[..n] {
+            encoder.push(b)?;
+        }
+    }
+    encoder.finish()
+}
+
+struct Encoder<'a, W: Write + ?Sized> {
+    writer: &'a mut W,
+    /// The offset of the first byte that has not been written yet.
+    offset: u64,
+    /// Bytes that are not part of a long run, which are written in lines of
+    /// `LSIZE` bytes.
+    literal: Vec<u8>,
+    /// The current run of identical bytes after the `literal` bytes.
+    run: Option<(u8, u64)>,
+}
+
+impl<W: Write + ?Sized> Encoder<'_, W> {
+    fn push(&mut self, b: u8) -> io::Result<()> {
+        match &mut self.run {
+            Some((byte, len)) if *byte == b => *len += 1,
+            _ => {
+                self.end_run()?;
+                self.run = Some((b, 1));
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the current run, or adds it to the literal bytes if it is short.
+    fn end_run(&mut self) -> io::Result<()> {
+        let Some((byte, len)) = self.run.take() else {
+            return Ok(());
+        };
+        if len >= LSIZE as u64 {
+            self.write_literal(true)?;
+            writeln!(self.writer, "{:08x} run {len} {byte:02x}", self.offset)?;
+            self.offset += len;
+        } else {
+            self.literal
+                .extend(std::iter::repeat(byte).take(len as usize));
+            self.write_literal(false)?;
+        }
+        Ok(())
+    }
+
+    /// Writes all complete lines of literal bytes, and the incomplete last
+    /// line if `all` is set.
+    fn write_literal(&mut self, all: bool) -> io::Result<()> {
+        let mut written = 0;
+        for line in self.literal.chunks(LSIZE) {
+            if line.len() < LSIZE && !all {
+                break;
+            }
+            write!(self.writer, "{:08x} lit {}", self.offset, line.len())?;
+            for b in line {
+                write!(self.writer, " {b:02x}")?;
+            }
+            writeln!(self.writer)?;
+            self.offset += line.len() as u64;
+            written += line.len();
+        }
+        self.literal.drain(..written);
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        self.end_run()?;
+        self.write_literal(true)?;
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,125 @@
+//! The rules file, which maps file name patterns to default options, e.g. to
+//! show WAV files as 16-bit samples:
+//!
+//! ```text
+//! # Lines starting with '#' are comments.
+//! *.wav -> --group-size=2 --endianness=little
+//! ```
+//!
+//! Patterns without a `/` are matched against the file name, others against
+//! the whole path as given on the command line. `*` matches any number of
+//! characters except `/`, `?` matches a single one. The options of all
+//! matching rules are applied in the order of the file, so later rules take
+//! precedence.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Rule {
+    pub pattern: String,
+    pub args: Vec<String>,
+}
+
+/// The location of the rules file, `$XDG_CONFIG_HOME/hexyl/rules` or
+/// `~/.config/hexyl/rules` (`%APPDATA%\hexyl\rules` on Windows).
+pub(crate) fn rules_path() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            if cfg!(windows) {
+                env::var_os("APPDATA").map(PathBuf::from)
+            } else {
+                env::var_os("HOME").map(|home| Path::new(&home).join(".config"))
+            }
+        })?;
+    Some(config_dir.join("hexyl").join("rules"))
+}
+
+/// Reads the rules file at `path`, which may not exist.
+pub(crate) fn read_rules(path: &Path) -> Result<Vec<Rule>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e).with_context(|| format!("failed to read the rules file {path:?}")),
+    };
+    parse_rules(&text).with_context(|| format!("failed to parse the rules file {path:?}"))
+}
+
+/// Parses rules of the form `PATTERN -> OPTIONS`, one per line.
+pub(crate) fn parse_rules(text: &str) -> Result<Vec<Rule>> {
+    let mut rules = vec![];
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (pattern, args) = line
+            .split_once("->")
+            .ok_or_else(|| anyhow!("line {}: expected 'PATTERN -> OPTIONS'", number + 1))?;
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            return Err(anyhow!("line {}: the pattern is empty", number + 1));
+        }
+        let args = shell_words::split(args)
+            .with_context(|| format!("line {}: could not parse the options", number + 1))?;
+        rules.push(Rule {
+            pattern: pattern.to_owned(),
+            args,
+        });
+    }
+    Ok(rules)
+}
+
+/// Returns the options of all rules that match `file`.
+pub(crate) fn args_for(rules: &[Rule], file: &Path) -> Vec<String> {
+    let path = file.to_string_lossy();
+    let name = file
+        .file_name()
+        .map_or(path.clone(), |name| name.to_string_lossy());
+    rules
+        .iter()
+        .filter(|rule| {
+            let subject = if rule.pattern.contains('/') {
+                &path
+            } else {
+                &name
+            };
+            let pattern: Vec<char> = rule.pattern.chars().collect();
+            let subject: Vec<char> = subject.chars().collect();
+            glob_match(&pattern, &subject)
+        })
+        .flat_map(|rule| rule.args.iter().cloned())
+        .collect()
+}
+
+/// Matches `text` against a pattern with the wildcards `*` and `?`, which do
+/// not match `/`.
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => {
+            let mut i = 0;
+            loop {
+                if glob_match(rest, &text[i..]) {
+                    return true;
+                }
+                if i == text.len() || text[i] == '/' {
+                    return false;
+                }
+                i += 1;
+            }
+        }
+        Some(('?', rest)) => {
+            matches!(text.split_first(), Some((&c, text)) if c != '/' && glob_match(rest, text))
+        }
+        Some((&p, rest)) => {
+            matches!(text.split_first(), Some((&c, text)) if c == p && glob_match(rest, text))
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Session files for `--save-session` and `--load-session`, which contain the
+//! input and the options of a dump, so that it can be displayed again without
+//! the original file.
+//!
+//! A session file is a zstd stream of:
+//!
+//! - the magic bytes `hexyl-session` and a version byte (1),
+//! - the number of options (32 bits), followed by every option as its length
+//!   (32 bits) and its UTF-8 text,
+//! - the length of the input (64 bits), followed by the input.
+//!
+//! All numbers are little endian.
+
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+
+const MAGIC: &[u8] = b"hexyl-session";
+const VERSION: u8 = 1;
+
+/// The compression level of zstd, the default of the `zstd` tool.
+const LEVEL: i32 = 3;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Session {
+    /// The command line options, without the program name.
+    pub args: Vec<String>,
+    pub data: Vec<u8>,
+}
+
+impl Session {
+    /// Creates a session from the command line `args`, without the program
+    /// name. Fails if an option is not valid UTF-8.
+    pub fn new(args: &[OsString], data: Vec<u8>) -> Result<Self> {
+        let args = args
+            .iter()
+            .map(|arg| {
+                arg.to_str()
+                    .map(str::to_owned)
+                    .ok_or_else(|| anyhow!("the option {arg:?} is not valid UTF-8"))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Session { args, data })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let context = || format!("failed to save the session to {}", path.display());
+        let file = File::create(path).with_context(context)?;
+        let mut encoder = zstd::Encoder::new(BufWriter::new(file), LEVEL).with_context(context)?;
+        self.write(&mut encoder).with_context(context)?;
+        encoder
+            .finish()
+            .and_then(|mut writer| writer.flush())
+            .with_context(context)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let context = || format!("failed to load the session from {}", path.display());
+        let file = File::open(path).with_context(context)?;
+        let decoder = zstd::Decoder::new(BufReader::new(file)).with_context(context)?;
+        Session::read(decoder).with_context(context)
+    }
+
+    fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        writer.write_all(&(self.args.len() as u32).to_le_bytes())?;
+        for arg in &self.args {
+            writer.write_all(&(arg.len() as u32).to_le_bytes())?;
+            writer.write_all(arg.as_bytes())?;
+        }
+        writer.write_all(&(self.data.len() as u64).to_le_bytes())?;
+        writer.write_all(&self.data)
+    }
+
+    fn read(mut reader: impl Read) -> Result<Self> {
+        let mut magic = [0; MAGIC.len() + 1];
+        reader
+            .read_exact(&mut magic)
+            .map_err(|_| anyhow!("not a hexyl session file"))?;
+        if &magic[..MAGIC.len()] != MAGIC {
+            return Err(anyhow!("not a hexyl session file"));
+        }
+        let version = magic[MAGIC.len()];
+        if version != VERSION {
+            return Err(anyhow!("unsupported session version {version}"));
+        }
+
+        let truncated = |_| anyhow!("the session file is truncated");
+        let mut args = vec![];
+        for _ in 0..read_u32(&mut reader).map_err(truncated)? {
+            let len = read_u32(&mut reader).map_err(truncated)?;
+            let arg = read_bytes(&mut reader, len.into()).map_err(truncated)?;
+            args.push(String::from_utf8(arg).map_err(|_| anyhow!("invalid option in session"))?);
+        }
+        let len = read_u64(&mut reader).map_err(truncated)?;
+        let data = read_bytes(&mut reader, len).map_err(truncated)?;
+        Ok(Session { args, data })
+    }
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Reads exactly `len` bytes, without allocating them up front, as the length
+/// might be corrupted.
+fn read_bytes(reader: &mut impl Read, len: u64) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![];
+    reader.take(len).read_to_end(&mut bytes)?;
+    if (bytes.len() as u64) < len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(bytes)
+}
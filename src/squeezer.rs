@@ -0,0 +1,81 @@
+//! Squeezing of repeated lines, as done by `hexyl` (and `hexdump`) by default:
+//! if a line consists of a single repeated byte, the following identical
+//! lines are replaced by a single line with a `*` marker.
+//!
+//! The [`Squeezer`] only decides what to do with each line, so it can be used
+//! to drive other renderers with the same squeeze behavior as [`Printer`].
+//!
+//! [`Printer`]: crate::Printer
+
+/// The default number of bytes per line (two panels of eight bytes each).
+pub const LSIZE: usize = 16;
+
+/// What to do with a line.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SqueezeAction {
+    /// Print the line as usual.
+    Ignore,
+    /// Print a line with a `*` marker instead of the contents, as this is the
+    /// first one of a series of repeated lines.
+    Print,
+    /// Do not print the line at all, as it repeats the previous lines.
+    Delete,
+}
+
+/// Decides which lines to squeeze, see the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct Squeezer {
+    enabled: bool,
+    line_size: usize,
+    /// The action for the previous line.
+    last_action: SqueezeAction,
+    /// The byte that all bytes in the previous line were equal to, if any.
+    repeated_byte: Option<u8>,
+}
+
+impl Squeezer {
+    /// Creates a squeezer for lines of `line_size` bytes. If `enabled` is
+    /// false, no line is ever squeezed.
+    pub fn new(enabled: bool, line_size: usize) -> Self {
+        Squeezer {
+            enabled,
+            line_size,
+            last_action: SqueezeAction::Ignore,
+            repeated_byte: None,
+        }
+    }
+
+    /// The number of bytes per line.
+    pub fn line_size(&self) -> usize {
+        self.line_size
+    }
+
+    /// Returns true if the previous line was squeezed, i.e. the end of the
+    /// squeezed region has not been shown yet.
+    pub fn is_squeezing(&self) -> bool {
+        self.last_action != SqueezeAction::Ignore
+    }
+
+    /// Decides what to do with the next line. Lines must be passed in order.
+    /// Incomplete lines (shorter than the line size) are never squeezed.
+    pub fn process(&mut self, line: &[u8]) -> SqueezeAction {
+        if !self.enabled || line.len() != self.line_size || line.is_empty() {
+            return SqueezeAction::Ignore;
+        }
+
+        let action = match self.repeated_byte {
+            Some(byte) if line.iter().all(|&b| b == byte) => match self.last_action {
+                SqueezeAction::Ignore => SqueezeAction::Print,
+                SqueezeAction::Print | SqueezeAction::Delete => SqueezeAction::Delete,
+            },
+            _ => SqueezeAction::Ignore,
+        };
+
+        if action == SqueezeAction::Ignore {
+            let first = line[0];
+            self.repeated_byte = line.iter().all(|&b| b == first).then_some(first);
+        }
+        self.last_action = action;
+        action
+    }
+}
@@ -0,0 +1,89 @@
+//! The events of `--status-fd`, one JSON object per line, for programs that
+//! wrap hexyl, e.g. to show the progress in a GUI:
+//!
+//! ```text
+//! {"event":"progress","bytes_read":65536}
+//! {"event":"match","position":4096,"pattern":0}
+//! {"event":"squeezed","start":16,"end":4096,"bytes":4080,"byte":0}
+//! {"event":"summary","bytes_read":70000,"matches":1,"squeezed_bytes":4080}
+//! ```
+//!
+//! The positions are the ones of the position panel. A summary follows the
+//! events of every input.
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::os::unix::io::{FromRawFd, RawFd};
+
+use hexyl::PrinterEvent;
+
+/// Writes the events to a file descriptor, see the
+/// [module documentation](self).
+pub(crate) struct StatusWriter<W> {
+    writer: W,
+    /// The number of matches of the current input.
+    matches: u64,
+    /// The number of squeezed bytes of the current input.
+    squeezed_bytes: u64,
+}
+
+impl<W: Write> StatusWriter<W> {
+    pub(crate) fn new(writer: W) -> Self {
+        StatusWriter {
+            writer,
+            matches: 0,
+            squeezed_bytes: 0,
+        }
+    }
+
+    pub(crate) fn event(&mut self, event: &PrinterEvent) -> io::Result<()> {
+        let line = match event {
+            PrinterEvent::Progress { bytes_read } => {
+                format!(r#"{{"event":"progress","bytes_read":{bytes_read}}}"#)
+            }
+            PrinterEvent::Match(found) => {
+                self.matches += 1;
+                format!(
+                    r#"{{"event":"match","position":{},"pattern":{}}}"#,
+                    found.position, found.pattern
+                )
+            }
+            PrinterEvent::Squeezed(region) => {
+                self.squeezed_bytes += region.len;
+                format!(
+                    r#"{{"event":"squeezed","start":{},"end":{},"bytes":{},"byte":{}}}"#,
+                    region.range.start, region.range.end, region.len, region.byte
+                )
+            }
+        };
+        self.write_line(line)
+    }
+
+    /// Writes the summary of an input after its events.
+    pub(crate) fn summary(&mut self, bytes_read: u64) -> io::Result<()> {
+        let line = format!(
+            r#"{{"event":"summary","bytes_read":{bytes_read},"matches":{},"squeezed_bytes":{}}}"#,
+            self.matches, self.squeezed_bytes
+        );
+        self.matches = 0;
+        self.squeezed_bytes = 0;
+        self.write_line(line)
+    }
+
+    /// Writes a line at once, so that the reader never sees a part of it.
+    fn write_line(&mut self, mut line: String) -> io::Result<()> {
+        line.push('\n');
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.flush()
+    }
+}
+
+/// Opens a copy of the file descriptor `fd`, e.g. `2` for stderr or `3` with
+/// `3>status.jsonl` in a shell.
+pub(crate) fn open_fd(fd: RawFd) -> io::Result<File> {
+    let copy = unsafe { libc::dup(fd) };
+    if copy == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(unsafe { File::from_raw_fd(copy) })
+}
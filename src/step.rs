@@ -0,0 +1,82 @@
+use std::io::{self, Read};
+
+/// The maximum number of bytes that are read from the inner reader at once.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A reader that only passes on every `step`th byte of the inner reader,
+/// starting at the byte with index `phase`, e.g. to extract one channel from
+/// interleaved samples. Use [`PrinterBuilder::step`] to show the original
+/// positions of the bytes.
+///
+/// [`PrinterBuilder::step`]: crate::PrinterBuilder::step
+pub struct StepReader<R: Read> {
+    inner: R,
+    step: u64,
+    phase: u64,
+    /// The number of bytes that have been read from the inner reader.
+    consumed: u64,
+    chunk: Vec<u8>,
+}
+
+impl<R: Read> StepReader<R> {
+    /// # Panics
+    ///
+    /// Panics if `step` is zero.
+    pub fn new(inner: R, step: u64, phase: u64) -> Self {
+        assert!(step > 0, "the step must not be zero");
+        StepReader {
+            inner,
+            step,
+            phase,
+            consumed: 0,
+            chunk: vec![],
+        }
+    }
+
+    /// Returns the number of bytes that have been read from the inner reader,
+    /// including the ones that have been left out.
+    pub fn consumed(&self) -> u64 {
+        self.consumed
+    }
+
+    fn is_selected(&self, position: u64) -> bool {
+        position >= self.phase && (position - self.phase) % self.step == 0
+    }
+}
+
+impl<R: Read> Read for StepReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.step == 1 && self.consumed >= self.phase {
+            let n = self.inner.read(buf)?;
+            self.consumed += n as u64;
+            return Ok(n);
+        }
+
+        // A chunk of `buf.len() * step` bytes contains at most `buf.len()`
+        // selected bytes.
+        let chunk_size = usize::try_from(self.step)
+            .map_or(CHUNK_SIZE, |step| buf.len().saturating_mul(step))
+            .min(CHUNK_SIZE);
+        self.chunk.resize(chunk_size, 0);
+        loop {
+            let n = self.inner.read(&mut self.chunk)?;
+            if n == 0 {
+                return Ok(0);
+            }
+            let mut selected = 0;
+            for i in 0..n {
+                if self.is_selected(self.consumed + i as u64) {
+                    buf[selected] = self.chunk[i];
+                    selected += 1;
+                }
+            }
+            self.consumed += n as u64;
+            if selected > 0 {
+                return Ok(selected);
+            }
+        }
+    }
+}
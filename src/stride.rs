@@ -0,0 +1,55 @@
+//! Detecting the record length of periodic data for `--detect-stride`.
+
+/// The number of bytes that are read from the input to detect the stride.
+pub(crate) const SAMPLE_SIZE: u64 = 64 * 1024;
+
+/// The largest stride that is detected.
+const MAX_STRIDE: usize = 256;
+
+/// The largest number of panels used to show whole records in every line.
+const MAX_PANELS: usize = 16;
+
+/// Finds the dominant record length in `data` by autocorrelation: for every
+/// candidate stride, the fraction of bytes that equal the byte one stride
+/// later. Pairs of zero bytes are not counted, so that padding does not
+/// dominate the result. Returns `None` if no stride stands out.
+pub(crate) fn detect_stride(data: &[u8]) -> Option<usize> {
+    let max_stride = MAX_STRIDE.min(data.len() / 4);
+    if max_stride < 2 {
+        return None;
+    }
+
+    let scores: Vec<f64> = (2..=max_stride)
+        .map(|stride| {
+            let pairs = data.len() - stride;
+            let matches = data
+                .iter()
+                .zip(&data[stride..])
+                .filter(|&(&a, &b)| a == b && a != 0)
+                .count();
+            matches as f64 / pairs as f64
+        })
+        .collect();
+
+    let best = scores.iter().copied().fold(0.0, f64::max);
+    let mean = scores.iter().sum::<f64>() / scores.len() as f64;
+    if best < 0.1 || best < 1.5 * mean {
+        return None;
+    }
+    // Multiples of the record length score about as well, so take the
+    // smallest stride that comes close to the best score.
+    scores
+        .iter()
+        .position(|&score| score >= 0.9 * best)
+        .map(|i| i + 2)
+}
+
+/// Returns the smallest number of panels of `panel_width` bytes that holds a
+/// whole number of records of `stride` bytes, if it is not too large.
+pub(crate) fn panels_for_stride(stride: usize, panel_width: usize) -> Option<u64> {
+    let gcd = (1..=panel_width)
+        .rev()
+        .find(|d| stride % d == 0 && panel_width % d == 0)?;
+    let panels = stride / gcd;
+    (panels <= MAX_PANELS).then_some(panels as u64)
+}
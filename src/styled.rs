@@ -0,0 +1,298 @@
+//! Rendering a hex dump as lines of styled text instead of ANSI escape
+//! sequences, e.g. to show it in a widget of a TUI library.
+
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+use std::str::Chars;
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::{PrinterBuilder, PrinterOptions, Theme};
+
+/// A color of a [`Style`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    /// A color of the 256 color palette.
+    Indexed(u8),
+    /// A 24 bit color.
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    /// The color of the 16 color palette with the given index, the bright
+    /// colors are 8 to 15.
+    fn basic(index: u16) -> Color {
+        use Color::*;
+        [
+            Black,
+            Red,
+            Green,
+            Yellow,
+            Blue,
+            Magenta,
+            Cyan,
+            White,
+            BrightBlack,
+            BrightRed,
+            BrightGreen,
+            BrightYellow,
+            BrightBlue,
+            BrightMagenta,
+            BrightCyan,
+            BrightWhite,
+        ][index as usize]
+    }
+}
+
+/// The colors of a [`StyledSpan`]. `None` stands for the default color of the
+/// terminal or widget.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+}
+
+/// A piece of text with a single style.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StyledSpan {
+    pub text: String,
+    pub style: Style,
+}
+
+/// A line of output, without the newline. Adjacent spans have different
+/// styles, and no span is empty.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StyledLine {
+    pub spans: Vec<StyledSpan>,
+}
+
+impl StyledLine {
+    /// Returns the text of the line without the styles.
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    /// Returns the number of terminal columns that the line occupies.
+    pub fn width(&self) -> usize {
+        self.spans.iter().map(|span| span.text.width()).sum()
+    }
+
+    fn push(&mut self, c: char, style: Style) {
+        match self.spans.last_mut() {
+            Some(span) if span.style == style => span.text.push(c),
+            _ => self.spans.push(StyledSpan {
+                text: c.to_string(),
+                style,
+            }),
+        }
+    }
+}
+
+/// The output of a [`Printer`](crate::Printer) for [`render_to_lines`], with
+/// the styles that it switched to at positions of the text.
+#[derive(Default)]
+struct StyledOutput {
+    text: Vec<u8>,
+    styles: Vec<(usize, Style)>,
+    style: Style,
+}
+
+impl StyledOutput {
+    fn into_lines(self) -> Vec<StyledLine> {
+        let text = String::from_utf8_lossy(&self.text);
+        let mut styles = self.styles.into_iter().peekable();
+        let mut style = Style::default();
+        let mut lines = vec![];
+        let mut line = StyledLine::default();
+        for (i, c) in text.char_indices() {
+            while let Some((_, next)) = styles.next_if(|&(position, _)| position <= i) {
+                style = next;
+            }
+            match c {
+                '\n' => lines.push(std::mem::take(&mut line)),
+                c => line.push(c, style),
+            }
+        }
+        if !line.spans.is_empty() {
+            lines.push(line);
+        }
+        lines
+    }
+}
+
+/// Writes the text of a printer into a shared [`StyledOutput`].
+struct StyledWriter(Rc<RefCell<StyledOutput>>);
+
+impl Write for StyledWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().text.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Renders `bytes` as a hex dump with the given options and `theme`, as lines
+/// of styled text. The spans have the colors that the printer chooses for the
+/// byte categories, the positions and the squeeze marker, if
+/// [`PrinterOptions::show_color`] is set. Hyperlinks of
+/// [`PrinterOptions::offset_hyperlink`] are left out, and spans are not
+/// tracked. For other settings of [`PrinterBuilder`], print the dump into a
+/// buffer and convert it with [`parse_styled_lines`].
+pub fn render_to_lines(bytes: &[u8], options: &PrinterOptions, theme: Theme) -> Vec<StyledLine> {
+    let output = Rc::new(RefCell::new(StyledOutput::default()));
+    let mut writer = StyledWriter(output.clone());
+    let mut printer = PrinterBuilder::new(&mut writer)
+        .options(PrinterOptions {
+            offset_hyperlink: None,
+            track_spans: false,
+            ..options.clone()
+        })
+        .theme(theme)
+        .build();
+    let styles = output.clone();
+    printer.style_hook = Some(Box::new(move |color| {
+        let Some(params) = sgr_params(color) else {
+            return;
+        };
+        let mut output = styles.borrow_mut();
+        let mut style = output.style;
+        apply_sgr(&mut style, params);
+        output.style = style;
+        let position = output.text.len();
+        output.styles.push((position, style));
+    }));
+    printer
+        .print_all(bytes)
+        .expect("printing into memory does not fail");
+    drop(printer);
+    output.take().into_lines()
+}
+
+/// Returns the parameters of an SGR sequence `ESC [ params m`, like the colors
+/// of a [`Theme`].
+fn sgr_params(sgr: &[u8]) -> Option<&str> {
+    std::str::from_utf8(sgr)
+        .ok()?
+        .strip_prefix("\x1b[")?
+        .strip_suffix('m')
+}
+
+/// Converts the output of a [`Printer`](crate::Printer), or any other text
+/// with ANSI escape sequences, into lines of styled text. Only the
+/// foreground and background colors of SGR sequences are used, other escape
+/// sequences are removed.
+pub fn parse_styled_lines(output: &str) -> Vec<StyledLine> {
+    let mut lines = vec![];
+    let mut line = StyledLine::default();
+    let mut style = Style::default();
+    let mut chars = output.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => lines.push(std::mem::take(&mut line)),
+            '\x1b' => match chars.next() {
+                Some('[') => {
+                    let mut params = String::new();
+                    for c in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&c) {
+                            if c == 'm' {
+                                apply_sgr(&mut style, &params);
+                            }
+                            break;
+                        }
+                        params.push(c);
+                    }
+                }
+                Some(']') => skip_osc(&mut chars),
+                _ => {}
+            },
+            c => line.push(c, style),
+        }
+    }
+    if !line.spans.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Skips an OSC sequence (e.g. a hyperlink) up to its terminator, which is
+/// either BEL or `ESC \`.
+fn skip_osc(chars: &mut Chars) {
+    while let Some(c) = chars.next() {
+        match c {
+            '\x07' => break,
+            '\x1b' => {
+                chars.next();
+                break;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Applies the parameters of an SGR sequence (`ESC [ params m`) to `style`.
+/// An empty parameter stands for 0.
+fn apply_sgr(style: &mut Style, params: &str) {
+    let mut params = params.split([';', ':']).map(|param| {
+        if param.is_empty() {
+            Some(0)
+        } else {
+            param.parse::<u16>().ok()
+        }
+    });
+    while let Some(param) = params.next() {
+        let Some(param) = param else {
+            continue;
+        };
+        match param {
+            0 => *style = Style::default(),
+            30..=37 => style.fg = Some(Color::basic(param - 30)),
+            40..=47 => style.bg = Some(Color::basic(param - 40)),
+            90..=97 => style.fg = Some(Color::basic(param - 90 + 8)),
+            100..=107 => style.bg = Some(Color::basic(param - 100 + 8)),
+            39 => style.fg = None,
+            49 => style.bg = None,
+            38 | 48 => {
+                let mut component = || {
+                    params
+                        .next()
+                        .flatten()
+                        .and_then(|value| u8::try_from(value).ok())
+                };
+                let color = match component() {
+                    Some(5) => component().map(Color::Indexed),
+                    Some(2) => match (component(), component(), component()) {
+                        (Some(r), Some(g), Some(b)) => Some(Color::Rgb(r, g, b)),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                if param == 38 {
+                    style.fg = color;
+                } else {
+                    style.bg = color;
+                }
+            }
+            _ => {}
+        }
+    }
+}
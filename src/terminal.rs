@@ -0,0 +1,153 @@
+//! The terminal state for the interactive modes (`--watch`, `--follow`,
+//! `--prompt`, `--interactive`): the alternate screen, the cursor, colors and
+//! the settings of raw mode are restored when the session ends, also on a
+//! panic or on Ctrl-C.
+
+#[cfg(all(feature = "tui", unix))]
+use std::cell::UnsafeCell;
+use std::io::{self, IsTerminal, Write};
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+const ENTER_ALTERNATE_SCREEN: &[u8] = b"\x1b[?1049h";
+const CLEAR_SCREEN: &[u8] = b"\x1b[2J\x1b[H";
+/// Resets all colors and shows the cursor.
+const RESET: &[u8] = b"\x1b[0m\x1b[?25h";
+/// Also leaves the alternate screen.
+const RESET_AND_LEAVE_ALTERNATE_SCREEN: &[u8] = b"\x1b[0m\x1b[?25h\x1b[?1049l";
+
+/// Whether a session with the alternate screen is active, for the panic hook
+/// and the signal handler.
+static ALTERNATE_SCREEN: AtomicBool = AtomicBool::new(false);
+/// Whether a session is active at all.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// The terminal settings to restore in the signal handler, which can not take
+/// a lock. They are only written while `TERMIOS_SAVED` is not set.
+#[cfg(all(feature = "tui", unix))]
+struct SavedTermios(UnsafeCell<Option<(libc::c_int, libc::termios)>>);
+
+#[cfg(all(feature = "tui", unix))]
+unsafe impl Sync for SavedTermios {}
+
+#[cfg(all(feature = "tui", unix))]
+static SAVED_TERMIOS: SavedTermios = SavedTermios(UnsafeCell::new(None));
+/// Whether `SAVED_TERMIOS` holds the settings of a terminal in raw mode.
+#[cfg(all(feature = "tui", unix))]
+static TERMIOS_SAVED: AtomicBool = AtomicBool::new(false);
+
+/// An interactive session on the terminal. Nothing is written if stdout is
+/// not a terminal. Only one session should be active at a time.
+pub(crate) struct TerminalSession {
+    active: bool,
+}
+
+impl TerminalSession {
+    /// Starts a session, on the alternate screen if `alternate_screen` is set,
+    /// so that the previous contents of the terminal are shown again at the
+    /// end.
+    pub fn start(alternate_screen: bool) -> io::Result<Self> {
+        let active = io::stdout().is_terminal();
+        if active {
+            install_handlers();
+            ACTIVE.store(true, Ordering::SeqCst);
+            if alternate_screen {
+                ALTERNATE_SCREEN.store(true, Ordering::SeqCst);
+                let mut stdout = io::stdout().lock();
+                stdout.write_all(ENTER_ALTERNATE_SCREEN)?;
+                stdout.flush()?;
+            }
+        }
+        Ok(TerminalSession { active })
+    }
+
+    /// Has the settings `termios` of the terminal `fd` restored if hexyl is
+    /// ended by a signal, before they are changed, e.g. for raw mode.
+    #[cfg(all(feature = "tui", unix))]
+    pub fn save_termios(&self, fd: libc::c_int, termios: libc::termios) {
+        install_handlers();
+        TERMIOS_SAVED.store(false, Ordering::SeqCst);
+        unsafe { *SAVED_TERMIOS.0.get() = Some((fd, termios)) };
+        TERMIOS_SAVED.store(true, Ordering::SeqCst);
+    }
+
+    /// Stops restoring the settings of `save_termios`, before they are
+    /// restored when raw mode ends.
+    #[cfg(all(feature = "tui", unix))]
+    pub fn forget_termios(&self) {
+        TERMIOS_SAVED.store(false, Ordering::SeqCst);
+    }
+
+    /// Clears the screen and moves the cursor to the top left corner.
+    pub fn clear(&self) -> io::Result<()> {
+        if self.active {
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(CLEAR_SCREEN)?;
+            stdout.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TerminalSession {
+    fn drop(&mut self) {
+        if self.active {
+            let _ = io::stdout().write_all(restore_sequence());
+            let _ = io::stdout().flush();
+            ACTIVE.store(false, Ordering::SeqCst);
+            ALTERNATE_SCREEN.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+/// The bytes that restore the terminal for the active session.
+fn restore_sequence() -> &'static [u8] {
+    if ALTERNATE_SCREEN.load(Ordering::SeqCst) {
+        RESET_AND_LEAVE_ALTERNATE_SCREEN
+    } else {
+        RESET
+    }
+}
+
+/// Restores the terminal before a panic message is printed, and on Ctrl-C.
+fn install_handlers() {
+    static INSTALLED: AtomicBool = AtomicBool::new(false);
+    if INSTALLED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if ACTIVE.load(Ordering::SeqCst) {
+            let _ = io::stdout().write_all(restore_sequence());
+            let _ = io::stdout().flush();
+        }
+        default_hook(info);
+    }));
+
+    #[cfg(unix)]
+    {
+        extern "C" fn handle_signal(signal: libc::c_int) {
+            // Only async-signal-safe functions may be called here.
+            #[cfg(feature = "tui")]
+            if TERMIOS_SAVED.load(Ordering::SeqCst) {
+                if let Some((fd, termios)) = unsafe { &*SAVED_TERMIOS.0.get() } {
+                    unsafe { libc::tcsetattr(*fd, libc::TCSANOW, termios) };
+                }
+            }
+            if ACTIVE.load(Ordering::SeqCst) {
+                let restore = restore_sequence();
+                unsafe {
+                    libc::write(libc::STDOUT_FILENO, restore.as_ptr().cast(), restore.len());
+                }
+            }
+            unsafe { libc::_exit(128 + signal) };
+        }
+
+        let handler = handle_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        unsafe {
+            libc::signal(libc::SIGINT, handler);
+            libc::signal(libc::SIGTERM, handler);
+        }
+    }
+}
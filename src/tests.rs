@@ -1,5 +1,10 @@
 use super::*;
 
+#[test]
+fn cli_definition() {
+    Cli::command().debug_assert();
+}
+
 #[test]
 fn unit_multipliers() {
     use Unit::*;
@@ -154,3 +159,936 @@ fn test_parse_byte_offset() {
         }
     );
 }
+
+#[test]
+fn wrap_writer() {
+    let wrap = |input: &str, width| {
+        let mut output = vec![];
+        let mut writer = WrapWriter::new(&mut output, width, '>');
+        writer.write_all(input.as_bytes()).unwrap();
+        drop(writer);
+        String::from_utf8(output).unwrap()
+    };
+    assert_eq!(wrap("abcd\n", 4), "abcd\n");
+    assert_eq!(wrap("abcde\n", 4), "abc>\nde\n");
+    assert_eq!(wrap("abcdefg\n", 4), "abc>\ndefg\n");
+    assert_eq!(wrap("abcdefgh\nij\n", 4), "abc>\ndef>\ngh\nij\n");
+    assert_eq!(wrap("äöüß€\n", 3), "äö>\nüß€\n");
+    assert_eq!(wrap("äöüß€x\n", 3), "äö>\nüß>\n€x\n");
+    assert_eq!(
+        wrap("\x1b[36mab\x1b[39mcde\n", 4),
+        "\x1b[36mab\x1b[39mc>\nde\n"
+    );
+}
+
+#[test]
+fn default_args() {
+    fn args(args: &[&str], default_args: &str) -> Vec<String> {
+        let args = args.iter().map(OsString::from).collect();
+        args_with_defaults(args, parse_default_args(default_args).unwrap())
+            .into_iter()
+            .map(|arg| arg.into_string().unwrap())
+            .collect()
+    }
+
+    assert_eq!(
+        args(&["hexyl", "--panels=2", "file"], "--border=none -P"),
+        ["hexyl", "--border=none", "-P", "--panels=2", "file"]
+    );
+    assert_eq!(
+        args(&["hexyl", "dump", "file"], "--color 'never'"),
+        ["hexyl", "dump", "--color", "never", "file"]
+    );
+    assert_eq!(args(&["hexyl"], ""), ["hexyl"]);
+    assert!(parse_default_args("'--plain").is_err());
+}
+
+#[test]
+fn fit_panels() {
+    use layout::Fit;
+
+    // A layout like the default one: 10 columns for the position panel and
+    // borders, and 35 columns per panel.
+    let layout_width = |panels| 10 + 35 * panels;
+    let n = |n| NonZeroU64::new(n).unwrap();
+
+    assert_eq!(Fit::Default.panels(200, true, None, 8, layout_width), 2);
+    assert_eq!(Fit::Default.panels(79, true, None, 8, layout_width), 1);
+    assert_eq!(Fit::Default.panels(20, true, None, 8, layout_width), 1);
+    assert_eq!(Fit::Panels(n(5)).panels(20, true, None, 8, layout_width), 5);
+    assert_eq!(Fit::Width(None).panels(200, true, None, 8, layout_width), 5);
+    assert_eq!(Fit::Width(None).panels(184, true, None, 8, layout_width), 4);
+    assert_eq!(
+        Fit::Width(Some(n(80))).panels(200, true, None, 8, layout_width),
+        2
+    );
+    assert_eq!(
+        Fit::BytesPerLine(n(32)).panels(20, true, None, 8, layout_width),
+        4
+    );
+    assert_eq!(
+        Fit::BytesPerLine(n(32)).panels(20, true, None, 16, layout_width),
+        2
+    );
+
+    // `--max-panels` only limits the panels that are chosen for the width.
+    assert_eq!(
+        Fit::Width(None).panels(300, true, Some(n(4)), 8, layout_width),
+        4
+    );
+    assert_eq!(
+        Fit::Width(None).panels(80, true, Some(n(4)), 8, layout_width),
+        2
+    );
+    assert_eq!(
+        Fit::Width(Some(n(200))).panels(80, true, Some(n(3)), 8, layout_width),
+        3
+    );
+    assert_eq!(
+        Fit::Default.panels(200, true, Some(n(1)), 8, layout_width),
+        1
+    );
+    assert_eq!(
+        Fit::Default.panels(200, true, Some(n(4)), 8, layout_width),
+        2
+    );
+    assert_eq!(
+        Fit::Panels(n(5)).panels(20, true, Some(n(2)), 8, layout_width),
+        5
+    );
+    assert_eq!(
+        Fit::BytesPerLine(n(32)).panels(20, true, Some(n(2)), 8, layout_width),
+        4
+    );
+
+    // Without a terminal, the default does not depend on the width.
+    assert_eq!(Fit::Default.panels(20, false, None, 8, layout_width), 2);
+    assert_eq!(
+        Fit::Default.panels(200, false, Some(n(1)), 8, layout_width),
+        1
+    );
+    assert_eq!(
+        Fit::Width(None).panels(200, false, None, 8, layout_width),
+        5
+    );
+}
+
+#[test]
+fn parse_fit() {
+    use layout::Fit;
+
+    let n = |n| NonZeroU64::new(n).unwrap();
+
+    assert_eq!("width".parse::<Fit>().unwrap(), Fit::Width(None));
+    assert_eq!(
+        "width:100".parse::<Fit>().unwrap(),
+        Fit::Width(Some(n(100)))
+    );
+    assert_eq!("panels:3".parse::<Fit>().unwrap(), Fit::Panels(n(3)));
+    assert_eq!("bytes:24".parse::<Fit>().unwrap(), Fit::BytesPerLine(n(24)));
+    assert!("bytes:0".parse::<Fit>().is_err());
+    assert!("panels".parse::<Fit>().is_err());
+    assert!("panels:0".parse::<Fit>().is_err());
+    assert!("height".parse::<Fit>().is_err());
+}
+
+#[test]
+fn test_parse_relative_offset() {
+    use OffsetAnchor::*;
+
+    let parse = |n| parse_relative_offset(n, PositiveI64::new(DEFAULT_BLOCK_SIZE).unwrap());
+    let offset = |anchor, delta| Ok(RelativeOffset { anchor, delta });
+
+    assert_eq!(parse("0x100"), offset(Start, 0x100));
+    assert_eq!(parse("-16"), offset(End, -16));
+    assert_eq!(parse("start+1KiB"), offset(Start, 1024));
+    assert_eq!(parse("end-0x100"), offset(End, -0x100));
+    assert_eq!(parse("end"), offset(End, 0));
+    assert_eq!(parse("here+2block"), offset(Here, 1024));
+    assert_eq!(
+        parse("end0x100"),
+        Err(ByteOffsetParseError::ExpectedSignAfterAnchor("end"))
+    );
+    assert_eq!(parse("end-"), Err(ByteOffsetParseError::EmptyAfterSign));
+}
+
+#[test]
+fn detect_stride() {
+    let records: Vec<u8> = (0..200u8)
+        .flat_map(|i| {
+            [
+                i,
+                0x42,
+                0xbe,
+                0xef,
+                i.wrapping_mul(7),
+                1,
+                2,
+                3,
+                4,
+                5,
+                i,
+                0xff,
+            ]
+        })
+        .collect();
+    assert_eq!(stride::detect_stride(&records), Some(12));
+    assert_eq!(stride::detect_stride(b"0123456789abcdef"), None);
+    assert_eq!(stride::detect_stride(&[]), None);
+}
+
+#[test]
+fn panels_for_stride() {
+    assert_eq!(stride::panels_for_stride(8, 8), Some(1));
+    assert_eq!(stride::panels_for_stride(24, 8), Some(3));
+    assert_eq!(stride::panels_for_stride(12, 8), Some(3));
+    assert_eq!(stride::panels_for_stride(4, 8), Some(1));
+    assert_eq!(stride::panels_for_stride(17, 8), None);
+    assert_eq!(stride::panels_for_stride(24, 16), Some(3));
+    assert_eq!(stride::panels_for_stride(2, 4), Some(1));
+}
+
+#[test]
+fn parse_prompt_command() {
+    use prompt::{parse_command, Command};
+
+    let parse = |line| {
+        parse_command(line, |offset| {
+            Ok(offset
+                .parse::<i64>()
+                .or_else(|_| i64::from_str_radix(offset.trim_start_matches("0x"), 16))?)
+        })
+    };
+    assert_eq!(parse("\n").unwrap(), Command::Next);
+    assert_eq!(parse("+\n").unwrap(), Command::Next);
+    assert_eq!(parse("+16\n").unwrap(), Command::Move(16));
+    assert_eq!(parse("-16").unwrap(), Command::Move(-16));
+    assert_eq!(parse("g 0x2000\n").unwrap(), Command::Goto(0x2000));
+    assert_eq!(parse("/a b\n").unwrap(), Command::Search(b"a b".to_vec()));
+    assert_eq!(parse("q\n").unwrap(), Command::Quit);
+    assert!(parse("g -1").is_err());
+    assert!(parse("/").is_err());
+    assert!(parse("x").is_err());
+}
+
+#[test]
+fn reverse_dump() {
+    let round_trip = |input: &[u8],
+                      configure: fn(PrinterBuilder<'_, Vec<u8>>) -> PrinterBuilder<'_, Vec<u8>>,
+                      format: &DumpFormat| {
+        let mut dump = vec![];
+        configure(PrinterBuilder::new(&mut dump))
+            .build()
+            .print_all(input)
+            .unwrap();
+        let mut output = vec![];
+        reverse(&dump[..], &mut output, format).unwrap();
+        output
+    };
+    let hex = DumpFormat {
+        base: Base::Hexadecimal,
+        digits: 2,
+        group_size: 1,
+        endianness: Endianness::Big,
+        bytes_per_panel: 8,
+        offset_style: OffsetStyle::Byte,
+    };
+
+    let mut input = b"hello".repeat(7);
+    input.extend([0; 100]);
+    input.extend(b"world");
+    assert_eq!(round_trip(&input, |builder| builder, &hex), input);
+    assert_eq!(
+        round_trip(
+            &input,
+            |builder| builder
+                .show_color(false)
+                .with_border_style(BorderStyle::None)
+                .num_panels(3)
+                .tick_marks(true),
+            &hex
+        ),
+        input
+    );
+    assert_eq!(
+        round_trip(
+            &input,
+            |builder| builder
+                .with_border_style(BorderStyle::Ascii)
+                .with_base(Base::Decimal)
+                .padding(hexyl::Padding::Spaces)
+                .group_size(4)
+                .endianness(Endianness::Little),
+            &DumpFormat {
+                base: Base::Decimal,
+                digits: 3,
+                group_size: 4,
+                endianness: Endianness::Little,
+                bytes_per_panel: 8,
+                offset_style: OffsetStyle::Byte,
+            }
+        ),
+        input
+    );
+    assert_eq!(
+        round_trip(
+            &input,
+            |builder| builder.panel_width(16).num_panels(3).tick_marks(true),
+            &DumpFormat {
+                bytes_per_panel: 16,
+                ..hex
+            }
+        ),
+        input
+    );
+    assert_eq!(round_trip(b"", |builder| builder, &hex), b"");
+
+    let reverse_text = |text: &str| {
+        let mut output = vec![];
+        reverse(text.as_bytes(), &mut output, &hex).map(|()| output)
+    };
+    // The first line may start at any position.
+    assert_eq!(reverse_text(" 00000100  61 62  \n").unwrap(), b"ab");
+    let error = reverse_text(
+        "│00000000│ 61 62 63 64 65 66 67 68 │abcdefgh│\n\
+         │00000010│ 61 62 63 64 65 66 67 68 │abcdefgh│\n",
+    )
+    .unwrap_err();
+    assert_eq!(error.to_string(), "line 2");
+    assert!(reverse_text("│00000000│ 61 62 6x 64 65 66 67 68 │abcdefgh│\n").is_err());
+    // Squeezed lines need the position panel.
+    assert!(reverse_text(
+        "  00 00 00 00 00 00 00 00  \n\
+         \x20*                         \n"
+    )
+    .is_err());
+}
+
+#[test]
+fn parse_comments() {
+    use comments::parse_comments;
+
+    let comments = parse_comments(
+        "# offset\tcomment\n\
+         \n\
+         0x10\tentry point\n\
+         4\t width: 2\tbytes \n",
+    )
+    .unwrap();
+    assert_eq!(
+        comments,
+        vec![
+            hexyl::Comment {
+                position: 0x10,
+                text: "entry point".to_owned(),
+            },
+            hexyl::Comment {
+                position: 4,
+                text: "width: 2\tbytes".to_owned(),
+            },
+        ]
+    );
+
+    let error = parse_comments("0\tok\n16 no tab\n").unwrap_err();
+    assert_eq!(error.to_string(), "line 2");
+    assert!(parse_comments("x\tnot a number\n").is_err());
+}
+
+#[test]
+fn parse_csv_ranges() {
+    use csv_ranges::parse_csv_ranges;
+
+    let highlights = parse_csv_ranges(
+        "offset,length,label,color\n\
+         # comment\n\
+         \n\
+         0, 4, \"magic, \"\"v1\"\"\", red\n\
+         0x10,2,e_type,\n\
+         64,8\n",
+    )
+    .unwrap();
+    assert_eq!(
+        highlights,
+        vec![
+            hexyl::Highlight {
+                range: 0..4,
+                color: hexyl::color_from_name("red").unwrap(),
+                label: Some("magic, \"v1\"".to_owned()),
+            },
+            hexyl::Highlight {
+                range: 0x10..0x12,
+                color: hexyl::COLOR_HIGHLIGHT,
+                label: Some("e_type".to_owned()),
+            },
+            hexyl::Highlight {
+                range: 64..72,
+                color: hexyl::COLOR_HIGHLIGHT,
+                label: None,
+            },
+        ]
+    );
+
+    assert!(parse_csv_ranges("0").is_err());
+    assert!(parse_csv_ranges("0,x").is_err());
+    assert!(parse_csv_ranges("0,1,a,purple").is_err());
+    assert!(parse_csv_ranges("0,1,\"a").is_err());
+    assert!(parse_csv_ranges("0,1,a,red,extra").is_err());
+    assert!(parse_csv_ranges("0xffffffffffffffff,2").is_err());
+    assert!(parse_csv_ranges("0,1\noffset,length").is_err());
+}
+
+#[test]
+fn theme_precedence() {
+    use theme::{resolve_theme, Role};
+
+    let env = |name: &str| match name {
+        "HEXYL_COLOR_NULL" => Some("red".to_owned()),
+        "HEXYL_COLOR_OFFSET" => Some("blue".to_owned()),
+        "HEXYL_COLOR_OTHER" => Some(String::new()),
+        _ => None,
+    };
+    let options = |role| (role == Role::Offset).then(|| "green".to_owned());
+    let theme = resolve_theme(options, env).unwrap();
+    assert_eq!(theme.null, hexyl::color_from_name("red").unwrap());
+    assert_eq!(theme.offset, hexyl::color_from_name("green").unwrap());
+    assert_eq!(theme.ascii_other, hexyl::Theme::default().ascii_other);
+    assert_eq!(theme.nonascii, hexyl::Theme::default().nonascii);
+
+    let error = resolve_theme(|_| None, |_| Some("rd".to_owned())).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        r#"unknown color "rd" in HEXYL_COLOR_NULL"#
+    );
+    let error = resolve_theme(|_| Some("rd".to_owned()), |_| None).unwrap_err();
+    assert_eq!(error.to_string(), r#"unknown color "rd" in `--color-null`"#);
+}
+
+#[test]
+fn rules() {
+    use rules::{args_for, parse_rules};
+
+    let rules = parse_rules(
+        "# comment\n\
+         \n\
+         *.wav -> --group-size=2 --endianness=little\n\
+         dumps/*.bin -> --skip=0x100\n\
+         core.? -> --color-command 'color core'\n",
+    )
+    .unwrap();
+    assert_eq!(rules.len(), 3);
+
+    let args = |file: &str| args_for(&rules, Path::new(file));
+    assert_eq!(
+        args("music/a.wav"),
+        ["--group-size=2", "--endianness=little"]
+    );
+    assert_eq!(args("dumps/x.bin"), ["--skip=0x100"]);
+    assert!(args("dumps/x/y.bin").is_empty());
+    assert!(args("x.bin").is_empty());
+    assert_eq!(args("core.1"), ["--color-command", "color core"]);
+    assert!(args("core.12").is_empty());
+    assert!(args("a.wave").is_empty());
+
+    assert!(parse_rules("*.wav --panels=1").is_err());
+    assert!(parse_rules(" -> --panels=1").is_err());
+    assert!(parse_rules("*.wav -> '--panels=1").is_err());
+}
+
+#[test]
+fn until_pattern() {
+    assert_eq!(
+        parse_pattern(r"\x00\x00\xff\xFF").unwrap(),
+        [0x00, 0x00, 0xff, 0xff]
+    );
+    assert_eq!(parse_pattern(r"END\r\n\t\0\\").unwrap(), b"END\r\n\t\0\\");
+    assert_eq!(parse_pattern("ü").unwrap(), [0xc3, 0xbc]);
+    assert!(parse_pattern("").is_err());
+    assert!(parse_pattern(r"\x4").is_err());
+    assert!(parse_pattern(r"\xg0").is_err());
+    assert!(parse_pattern(r"\q").is_err());
+    assert!(parse_pattern("ab\\").is_err());
+}
+
+#[test]
+fn device_alignment() {
+    assert!(!device::is_unaligned(512, 0, None));
+    assert!(!device::is_unaligned(512, 1024, Some(4096)));
+    assert!(device::is_unaligned(512, 100, None));
+    assert!(device::is_unaligned(512, 512, Some(100)));
+
+    let file = File::open("Cargo.toml").unwrap();
+    assert_eq!(device::logical_sector_size(&file), None);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn direct_io() {
+    assert_eq!(device::direct_io_alignment(None), 4096);
+    assert_eq!(device::direct_io_alignment(Some(512)), 4096);
+    assert_eq!(device::direct_io_alignment(Some(8192)), 8192);
+
+    // The file system may not support direct I/O, but the file can be read
+    // either way.
+    let path = Path::new("tests/examples/hello_world_elf64");
+    let file = device::open_direct(path).unwrap();
+    let mut reader = AlignedReader::new(file, device::DIRECT_IO_ALIGNMENT).unwrap();
+    reader.seek(SeekFrom::Start(100)).unwrap();
+    let mut data = vec![];
+    reader.read_to_end(&mut data).unwrap();
+    assert_eq!(data, fs::read(path).unwrap()[100..]);
+}
+
+#[cfg(feature = "decompress")]
+#[test]
+fn decompress_members() {
+    use decompress::{for_each_member, Format, Member};
+
+    /// Returns a single byte per read, so that the start of the next member
+    /// is never in the buffer yet.
+    struct ByteByByte<'a>(&'a [u8]);
+
+    impl Read for ByteByByte<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.0.len().min(buf.len()).min(1);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    let compressed = fs::read("tests/examples/members.zst").unwrap();
+    let mut members = vec![];
+    for_each_member(ByteByByte(&compressed), |member, reader| {
+        let mut first = [0; 1];
+        reader.read_exact(&mut first)?;
+        members.push((member.clone(), first[0]));
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(
+        members,
+        vec![
+            (
+                Member {
+                    number: 1,
+                    format: Format::Zstd,
+                    compressed_offset: 0,
+                    offset: 0,
+                },
+                b'f'
+            ),
+            (
+                Member {
+                    number: 2,
+                    format: Format::Zstd,
+                    compressed_offset: 0x24,
+                    offset: 12,
+                },
+                b's'
+            ),
+        ]
+    );
+
+    let result = for_each_member(&b"\x1f\x8b\x08"[..], |_, reader| {
+        io::copy(reader, &mut io::sink())?;
+        Ok(())
+    });
+    assert!(result.is_err());
+}
+
+#[cfg(feature = "session")]
+#[test]
+fn session_format() {
+    use session::Session;
+
+    let session = Session::new(
+        &["--panels=1".into(), "--skip=4".into(), "file".into()],
+        b"\x00hexyl".to_vec(),
+    )
+    .unwrap();
+    let path = env::temp_dir().join(format!("hexyl-session-{}.hxz", process::id()));
+    session.save(&path).unwrap();
+    let loaded = Session::load(&path);
+    fs::write(&path, &fs::read(&path).unwrap()[..12]).unwrap();
+    let truncated = Session::load(&path);
+    fs::write(&path, b"not zstd").unwrap();
+    let invalid = Session::load(&path);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.unwrap(), session);
+    assert!(truncated.is_err());
+    assert!(invalid.is_err());
+}
+
+#[cfg(feature = "dwarf")]
+#[test]
+fn dwarf_struct_fields() {
+    use dwarf::{struct_fields, Field};
+
+    let path = Path::new("tests/examples/structs.debug");
+    let field = |name: &str, offset, size| Field {
+        name: name.to_owned(),
+        offset,
+        size,
+    };
+    let header = vec![
+        field("magic", 0, 4),
+        field("version", 4, 1),
+        field("length", 8, 4),
+        field("origin.x", 12, 2),
+        field("origin.y", 14, 2),
+        field("next", 16, 8),
+    ];
+    assert_eq!(struct_fields(path, "header").unwrap(), header);
+    assert_eq!(struct_fields(path, "header_t").unwrap(), header);
+    assert_eq!(
+        struct_fields(path, "point").unwrap(),
+        vec![field("x", 0, 2), field("y", 2, 2)]
+    );
+    assert!(struct_fields(path, "int16_t").is_err());
+    assert!(struct_fields(path, "missing").is_err());
+
+    let highlights = dwarf::highlights(&header[..2], 0x100);
+    assert_eq!(highlights[0].range, 0x100..0x104);
+    assert_eq!(highlights[0].label.as_deref(), Some("magic (4 bytes)"));
+    assert_eq!(highlights[1].range, 0x104..0x105);
+    assert_eq!(highlights[1].label.as_deref(), Some("version (1 byte)"));
+    assert_ne!(highlights[0].color, highlights[1].color);
+}
+
+#[test]
+fn annotate_formats() {
+    use annotate::{annotate, FieldKind};
+
+    let fields = |bytes: Vec<u8>| {
+        annotate(&mut io::Cursor::new(bytes))
+            .unwrap()
+            .map(|fields| {
+                fields
+                    .into_iter()
+                    .map(|field| (field.name, field.range, field.kind))
+                    .collect::<Vec<_>>()
+            })
+    };
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    png.extend(b"\0\0\0\x0dIHDR\0\0\0\x01\0\0\0\x01\x08\x02\0\0\0crc!");
+    png.extend(b"\0\0\0\x02IDATxxcrc!\0\0\0\0IENDcrc!trailing");
+    let png = fields(png).unwrap();
+    assert_eq!(png.len(), 1 + 10 + 3 + 3);
+    assert_eq!(png[0], ("PNG signature".to_owned(), 0..8, FieldKind::Magic));
+    assert_eq!(png[1], ("IHDR length".to_owned(), 8..12, FieldKind::Length));
+    assert_eq!(png[3], ("width".to_owned(), 16..20, FieldKind::Integer));
+    assert_eq!(png[10], ("IHDR CRC".to_owned(), 29..33, FieldKind::Integer));
+    assert_eq!(png[13], ("IDAT CRC".to_owned(), 43..47, FieldKind::Integer));
+    assert_eq!(png[16], ("IEND CRC".to_owned(), 55..59, FieldKind::Integer));
+
+    let mut zip = b"PK\x03\x04\x14\0\0\0\0\0\0\0\0\0crc!\x02\0\0\0\x02\0\0\0\x01\0\0\0".to_vec();
+    zip.extend(b"ahi");
+    zip.extend(b"PK\x03\x04\x14\0\x08\0\0\0\0\0\0\0crc!\0\0\0\0\0\0\0\0\x01\0\0\0b");
+    let zip = fields(zip).unwrap();
+    assert_eq!(zip.len(), 2 * 11);
+    assert_eq!(
+        zip[11],
+        (
+            "local file header signature".to_owned(),
+            33..37,
+            FieldKind::Magic
+        )
+    );
+
+    let mut pcap = b"\xd4\xc3\xb2\xa1\x02\0\x04\0".to_vec();
+    pcap.extend([0; 8]);
+    pcap.extend(b"\xff\xff\0\0\x01\0\0\0");
+    pcap.extend(b"sec.usec\x03\0\0\0\x03\0\0\0abc");
+    pcap.extend(b"sec.usec\x01\0\0\0\x01\0\0\0d");
+    let pcap = fields(pcap).unwrap();
+    assert_eq!(pcap.len(), 7 + 2 * 4);
+    assert_eq!(
+        pcap[9],
+        ("captured length".to_owned(), 32..36, FieldKind::Length)
+    );
+    assert_eq!(
+        pcap[11],
+        ("timestamp (seconds)".to_owned(), 43..47, FieldKind::Integer)
+    );
+
+    let mut tar = vec![0; 3 * 512];
+    tar[124..136].copy_from_slice(b"00000000012\0");
+    tar[257..263].copy_from_slice(b"ustar\0");
+    tar[1024 + 257..1024 + 263].copy_from_slice(b"ustar\0");
+    let tar = fields(tar).unwrap();
+    assert_eq!(tar.len(), 2 * 9);
+    assert_eq!(tar[3], ("size".to_owned(), 124..136, FieldKind::Length));
+    assert_eq!(tar[9], ("mode".to_owned(), 1124..1132, FieldKind::Integer));
+
+    assert_eq!(fields(b"0123456789abcdef".to_vec()), None);
+    assert_eq!(fields(vec![]), None);
+}
+
+#[cfg(all(feature = "tui", unix))]
+#[test]
+fn tui_keys() {
+    use tui::{action, parse_keys, Action, Key};
+
+    assert_eq!(parse_keys(b"\x1b[A"), vec![Key::Up]);
+    assert_eq!(parse_keys(b"\x1b[6~"), vec![Key::PageDown]);
+    assert_eq!(parse_keys(b"\x1b"), vec![Key::Escape]);
+    assert_eq!(parse_keys(b"\x1b[99~"), vec![Key::Unknown]);
+    assert_eq!(
+        parse_keys("0xä\r\x7f".as_bytes()),
+        vec![
+            Key::Char('0'),
+            Key::Char('x'),
+            Key::Char('ä'),
+            Key::Enter,
+            Key::Backspace
+        ]
+    );
+    assert_eq!(parse_keys(b"\x03"), vec![Key::Interrupt]);
+
+    assert_eq!(action(&Key::Char('j')), Some(Action::Scroll(1)));
+    assert_eq!(action(&Key::Up), Some(Action::Scroll(-1)));
+    assert_eq!(action(&Key::Char(' ')), Some(Action::Page(1)));
+    assert_eq!(action(&Key::Char('G')), Some(Action::End));
+    assert_eq!(action(&Key::Char('/')), Some(Action::Search));
+    assert_eq!(action(&Key::Interrupt), Some(Action::Quit));
+    assert_eq!(action(&Key::Char('x')), None);
+}
+
+#[cfg(all(feature = "tui", unix))]
+#[test]
+fn tui_view() {
+    use tui::View;
+
+    let mut view = View {
+        position: 0,
+        size: 100,
+        bytes_per_line: 16,
+        lines: 4,
+    };
+    assert_eq!(view.screen_size(), 64);
+    // The last line starts at 96, and is at the bottom of the screen at 48.
+    assert_eq!(view.last_position(), 48);
+    view.scroll(1);
+    assert_eq!(view.position, 16);
+    view.scroll(4);
+    assert_eq!(view.position, 48);
+    view.scroll(-1);
+    assert_eq!(view.position, 32);
+    view.scroll(-10);
+    assert_eq!(view.position, 0);
+
+    // After going to a position, the lines keep their offsets.
+    view.position = 0x42;
+    view.scroll(1);
+    assert_eq!(view.position, 0x42);
+    view.scroll(-1);
+    assert_eq!(view.position, 0x32);
+
+    view.size = 10;
+    assert_eq!(view.last_position(), 0);
+    view.size = 0;
+    assert_eq!(view.last_position(), 0);
+}
+
+#[cfg(unix)]
+#[test]
+fn status_events() {
+    use hexyl::{FindMatch, PrinterEvent, SqueezedRegion};
+    use status::StatusWriter;
+
+    let mut output = vec![];
+    let mut writer = StatusWriter::new(&mut output);
+    writer
+        .event(&PrinterEvent::Match(FindMatch {
+            position: 0x10,
+            pattern: 1,
+        }))
+        .unwrap();
+    writer
+        .event(&PrinterEvent::Squeezed(SqueezedRegion {
+            range: 0x20..0x40,
+            len: 0x20,
+            byte: 0xff,
+        }))
+        .unwrap();
+    writer
+        .event(&PrinterEvent::Progress { bytes_read: 0x100 })
+        .unwrap();
+    writer.summary(0x123).unwrap();
+    writer.summary(0).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "{\"event\":\"match\",\"position\":16,\"pattern\":1}\n\
+         {\"event\":\"squeezed\",\"start\":32,\"end\":64,\"bytes\":32,\"byte\":255}\n\
+         {\"event\":\"progress\",\"bytes_read\":256}\n\
+         {\"event\":\"summary\",\"bytes_read\":291,\"matches\":1,\"squeezed_bytes\":32}\n\
+         {\"event\":\"summary\",\"bytes_read\":0,\"matches\":0,\"squeezed_bytes\":0}\n"
+    );
+}
+
+#[test]
+fn text_detection() {
+    assert!(text::looks_like_text(b"Hello, World!\r\n\tindented\n"));
+    assert!(text::looks_like_text("Grüße, 世界\n".as_bytes()));
+    // A character that is cut off at the end of the sample.
+    assert!(text::looks_like_text(&"Grüße".as_bytes()[..3]));
+
+    assert!(!text::looks_like_text(b""));
+    assert!(!text::looks_like_text(b"text with a \0 byte"));
+    assert!(!text::looks_like_text(b"\x1b[31mred\x1b[0m"));
+    assert!(!text::looks_like_text(b"\x7fELF\x02\x01\x01"));
+    assert!(!text::looks_like_text(b"invalid \xff\xfe UTF-8"));
+}
+
+#[test]
+fn sniff_text_start() {
+    use text::sniff;
+
+    assert_eq!(
+        sniff(b"\xef\xbb\xbfabc").unwrap(),
+        "a UTF-8 byte order mark"
+    );
+    assert_eq!(
+        sniff(b"\xfe\xff\x00a").unwrap(),
+        "a UTF-16BE byte order mark"
+    );
+    assert_eq!(
+        sniff(b"\xff\xfea\x00").unwrap(),
+        "a UTF-16LE byte order mark"
+    );
+    assert_eq!(
+        sniff(b"\x00\x00\xfe\xff").unwrap(),
+        "a UTF-32BE byte order mark"
+    );
+    assert_eq!(
+        sniff(b"\xff\xfe\x00\x00").unwrap(),
+        "a UTF-32LE byte order mark"
+    );
+    assert_eq!(
+        sniff(b"#!/usr/bin/env python3\nprint()\n").unwrap(),
+        "a shebang for `/usr/bin/env python3`"
+    );
+    assert_eq!(sniff(b"#! /bin/sh").unwrap(), "a shebang for `/bin/sh`");
+
+    assert_eq!(sniff(b""), None);
+    assert_eq!(sniff(b"#!\n"), None);
+    assert_eq!(sniff(b"#!\x1b[31m\n"), None);
+    assert_eq!(sniff(b"\x7fELF"), None);
+}
+
+#[test]
+fn verify_diff() {
+    use verify::unified_diff;
+
+    assert_eq!(unified_diff("a\nb\n", "a\nb\n", "old", "new"), None);
+    assert_eq!(
+        unified_diff("a\nb\nc\n", "a\nx\nc\n", "old", "new").unwrap(),
+        "--- old\n+++ new\n@@ -1,3 +1,3 @@\n a\n-b\n+x\n c\n"
+    );
+
+    // Changes that are far apart are shown in separate hunks, with three
+    // lines of context.
+    let old: String = (1..=20).map(|i| format!("{i}\n")).collect();
+    let new = old.replacen("2\n", "two\n", 1).replace("\n18\n", "\n");
+    assert_eq!(
+        unified_diff(&old, &new, "old", "new").unwrap(),
+        "--- old\n+++ new\n\
+         @@ -1,5 +1,5 @@\n 1\n-2\n+two\n 3\n 4\n 5\n\
+         @@ -15,6 +15,5 @@\n 15\n 16\n 17\n-18\n 19\n 20\n"
+    );
+
+    // A missing newline at the end.
+    assert_eq!(
+        unified_diff("a\n", "a", "old", "new").unwrap(),
+        "--- old\n+++ new\n@@ -1,1 +1,1 @@\n-a\n+a\n\\ No newline at end of file\n"
+    );
+    // Lines are only added.
+    assert_eq!(
+        unified_diff("", "a\n", "old", "new").unwrap(),
+        "--- old\n+++ new\n@@ -0,0 +1,1 @@\n+a\n"
+    );
+}
+
+#[test]
+fn highlight_args() {
+    use highlight::{parse_highlight, HighlightArg};
+
+    assert_eq!(
+        parse_highlight("0x40..0x78:yellow:\"program header\"").unwrap(),
+        HighlightArg {
+            start: "0x40",
+            end: "0x78",
+            color: hexyl::color_from_name("yellow").unwrap(),
+            label: Some("program header".to_owned()),
+        }
+    );
+    assert_eq!(
+        parse_highlight("1k..2k::a:b").unwrap(),
+        HighlightArg {
+            start: "1k",
+            end: "2k",
+            color: hexyl::COLOR_HIGHLIGHT,
+            label: Some("a:b".to_owned()),
+        }
+    );
+    assert_eq!(parse_highlight("0..4").unwrap().label, None);
+    assert!(parse_highlight("0-4").is_err());
+    assert!(parse_highlight("0..").is_err());
+    assert!(parse_highlight("0..4:nocolor").is_err());
+}
+
+#[test]
+fn generate_manpage() {
+    let page = manpage::manpage(Cli::command());
+    assert!(page.starts_with(".TH HEXYL 1 "));
+    assert!(page.contains(".SH OPTIONS\n"));
+    let cmd = Cli::command();
+    for arg in cmd.get_arguments().filter(|arg| !arg.is_hide_set()) {
+        if let Some(long) = arg.get_long() {
+            let flag = format!("\\fB\\-\\-{}\\fR", long.replace('-', "\\-"));
+            assert!(page.contains(&flag), "missing {long}");
+        }
+    }
+}
+
+#[test]
+fn jobs_split() {
+    let split = |positions, jobs| jobs::split(positions, NonZeroUsize::new(jobs).unwrap());
+    assert_eq!(split(0..10, 3), [0..4, 4..8, 8..10]);
+    assert_eq!(split(5..7, 4), [5..6, 6..7]);
+    assert_eq!(split(0..0, 2), []);
+    assert_eq!(split(0..8, 1), vec![(0..8)]);
+}
+
+#[test]
+fn jobs_count_ranges() {
+    let path = env::temp_dir().join(format!("hexyl-{}-jobs", process::id()));
+    let contents: Vec<u8> = (0..1000u32).map(|i| (i * 7 % 256) as u8).collect();
+    fs::write(&path, &contents).unwrap();
+    let ranges = [
+        ByteRange {
+            start: 0,
+            end: Some(300),
+        },
+        ByteRange {
+            start: 250,
+            end: None,
+        },
+    ];
+
+    let file = File::open(&path).unwrap();
+    let parallel =
+        jobs::count_ranges_parallel(&file, 0..1000, &ranges, NonZeroUsize::new(4).unwrap())
+            .unwrap();
+    let sequential = count_ranges(&mut &contents[..], 0, &ranges).unwrap();
+    fs::remove_file(&path).unwrap();
+    for (parallel, sequential) in parallel.iter().zip(&sequential) {
+        for category in ByteCategory::ALL {
+            assert_eq!(parallel.count(category), sequential.count(category));
+        }
+    }
+    assert_eq!(parallel[0].total(), 300);
+    assert_eq!(parallel[1].total(), 750);
+}
@@ -0,0 +1,58 @@
+//! Detecting input that looks like text, for `--auto-text` and the hint that
+//! suggests it, and the byte order marks and shebangs that text files start
+//! with.
+
+use std::str;
+
+/// The number of bytes that are read from the input to detect text.
+pub(crate) const SAMPLE_SIZE: u64 = 4 * 1024;
+
+/// The number of bytes that are read from the input to find a byte order mark
+/// or shebang. Longer shebang lines are cut off.
+pub(crate) const SNIFF_SIZE: u64 = 128;
+
+/// The byte order marks, with the UTF-32 ones first since the one of UTF-32LE
+/// starts with the one of UTF-16LE.
+const BYTE_ORDER_MARKS: [(&[u8], &str); 5] = [
+    (b"\x00\x00\xfe\xff", "UTF-32BE"),
+    (b"\xff\xfe\x00\x00", "UTF-32LE"),
+    (b"\xef\xbb\xbf", "UTF-8"),
+    (b"\xfe\xff", "UTF-16BE"),
+    (b"\xff\xfe", "UTF-16LE"),
+];
+
+/// Returns a description of the byte order mark or shebang that `sample`
+/// starts with, if any.
+pub(crate) fn sniff(sample: &[u8]) -> Option<String> {
+    if let Some((_, encoding)) = BYTE_ORDER_MARKS
+        .iter()
+        .find(|(bom, _)| sample.starts_with(bom))
+    {
+        return Some(format!("a {encoding} byte order mark"));
+    }
+    let line = sample.strip_prefix(b"#!")?;
+    let line = line.split(|&b| b == b'\n').next().unwrap_or_default();
+    let interpreter = String::from_utf8_lossy(line);
+    let interpreter = interpreter.trim();
+    if interpreter.is_empty() || interpreter.chars().any(char::is_control) {
+        return None;
+    }
+    Some(format!("a shebang for `{interpreter}`"))
+}
+
+/// Returns whether `sample` is valid UTF-8 without control characters other
+/// than whitespace. A character that is cut off at the end of the sample is
+/// ignored, as the sample might end in the middle of it.
+pub(crate) fn looks_like_text(sample: &[u8]) -> bool {
+    let text = match str::from_utf8(sample) {
+        Ok(text) => text,
+        Err(e) if e.error_len().is_none() => {
+            str::from_utf8(&sample[..e.valid_up_to()]).expect("valid prefix")
+        }
+        Err(_) => return false,
+    };
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r' | '\x0c'))
+}
@@ -0,0 +1,87 @@
+//! Resolving the colors of the byte categories, of the position panel and of
+//! the squeeze marker.
+//! Every color comes from the first of these sources that sets it:
+//!
+//! 1. the `--color-*` options, e.g. `--color-null=red`,
+//! 2. the `HEXYL_COLOR_*` environment variables, e.g. `HEXYL_COLOR_NULL=red`,
+//! 3. the default colors.
+//!
+//! A theme file would go between the environment variables and the defaults.
+
+use anyhow::{anyhow, Result};
+
+use hexyl::{color_from_name, Theme};
+
+/// A color of the theme that can be overridden.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Role {
+    Null,
+    Printable,
+    Whitespace,
+    Other,
+    NonAscii,
+    Offset,
+    Squeeze,
+}
+
+impl Role {
+    pub(crate) const ALL: [Role; 7] = [
+        Role::Null,
+        Role::Printable,
+        Role::Whitespace,
+        Role::Other,
+        Role::NonAscii,
+        Role::Offset,
+        Role::Squeeze,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Role::Null => "null",
+            Role::Printable => "printable",
+            Role::Whitespace => "whitespace",
+            Role::Other => "other",
+            Role::NonAscii => "nonascii",
+            Role::Offset => "offset",
+            Role::Squeeze => "squeeze",
+        }
+    }
+
+    /// The environment variable that overrides the color.
+    pub(crate) fn env_var(self) -> String {
+        format!("HEXYL_COLOR_{}", self.name().to_ascii_uppercase())
+    }
+
+    fn color_mut(self, theme: &mut Theme) -> &mut &'static [u8] {
+        match self {
+            Role::Null => &mut theme.null,
+            Role::Printable => &mut theme.ascii_printable,
+            Role::Whitespace => &mut theme.ascii_whitespace,
+            Role::Other => &mut theme.ascii_other,
+            Role::NonAscii => &mut theme.nonascii,
+            Role::Offset => &mut theme.offset,
+            Role::Squeeze => &mut theme.squeeze,
+        }
+    }
+}
+
+/// Returns the theme with the colors given on the command line (`options`
+/// returns the value of the `--color-*` option of a role) and in the
+/// environment (`env` returns the value of a variable).
+pub(crate) fn resolve_theme(
+    options: impl Fn(Role) -> Option<String>,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<Theme> {
+    let mut theme = Theme::default();
+    for role in Role::ALL {
+        let env_var = role.env_var();
+        let (name, source) = match (options(role), env(&env_var)) {
+            (Some(name), _) => (name, format!("`--color-{}`", role.name())),
+            (None, Some(name)) if !name.is_empty() => (name, env_var),
+            (None, _) => continue,
+        };
+        *role.color_mut(&mut theme) =
+            color_from_name(&name).ok_or_else(|| anyhow!("unknown color {name:?} in {source}"))?;
+    }
+    Ok(theme)
+}
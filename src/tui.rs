@@ -0,0 +1,339 @@
+//! The `--interactive` mode, a pager for a FILE with keys to scroll, to go
+//! to a position and to search. Only the bytes on the screen are read, so
+//! that large files and devices are shown right away.
+
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::mem;
+use std::os::unix::io::AsRawFd;
+use std::str;
+
+use anyhow::Result;
+use terminal_size::terminal_size;
+
+use crate::prompt;
+use crate::terminal::TerminalSession;
+
+const HIDE_CURSOR: &[u8] = b"\x1b[?25l";
+const SHOW_CURSOR: &[u8] = b"\x1b[?25h";
+/// Moves the cursor to the start of the line and clears the line.
+const CLEAR_LINE: &[u8] = b"\r\x1b[K";
+
+/// A key that was pressed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Key {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Enter,
+    Backspace,
+    Escape,
+    /// Ctrl-C, which does not send a signal in raw mode.
+    Interrupt,
+    Char(char),
+    /// An escape sequence that is not known.
+    Unknown,
+}
+
+/// Parses the bytes that were read from the terminal at once. These are the
+/// characters of several keys if they were typed quickly or pasted, or an
+/// escape sequence of a single key.
+pub(crate) fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    if bytes.starts_with(b"\x1b") {
+        return vec![parse_key(bytes)];
+    }
+    String::from_utf8_lossy(bytes)
+        .chars()
+        .map(|c| parse_key(c.encode_utf8(&mut [0; 4]).as_bytes()))
+        .collect()
+}
+
+/// Parses the bytes of a single key press.
+fn parse_key(bytes: &[u8]) -> Key {
+    match bytes {
+        b"\x1b[A" | b"\x1bOA" => Key::Up,
+        b"\x1b[B" | b"\x1bOB" => Key::Down,
+        b"\x1b[5~" => Key::PageUp,
+        b"\x1b[6~" => Key::PageDown,
+        b"\x1b[H" | b"\x1bOH" | b"\x1b[1~" | b"\x1b[7~" => Key::Home,
+        b"\x1b[F" | b"\x1bOF" | b"\x1b[4~" | b"\x1b[8~" => Key::End,
+        b"\r" | b"\n" => Key::Enter,
+        b"\x7f" | b"\x08" => Key::Backspace,
+        b"\x1b" => Key::Escape,
+        b"\x03" => Key::Interrupt,
+        _ => match str::from_utf8(bytes).map(|s| {
+            let mut chars = s.chars();
+            (chars.next(), chars.next())
+        }) {
+            Ok((Some(c), None)) if !c.is_control() => Key::Char(c),
+            _ => Key::Unknown,
+        },
+    }
+}
+
+/// What a key does in the pager.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Action {
+    /// Scroll by the given number of lines.
+    Scroll(i64),
+    /// Scroll by the given number of screens.
+    Page(i64),
+    Start,
+    End,
+    /// Ask for a position and go there.
+    Goto,
+    /// Ask for a text and go to its next occurrence.
+    Search,
+    /// Go to the next occurrence of the last text.
+    SearchNext,
+    Quit,
+}
+
+/// Returns the action of `key`, if any.
+pub(crate) fn action(key: &Key) -> Option<Action> {
+    Some(match key {
+        Key::Down | Key::Enter | Key::Char('j') => Action::Scroll(1),
+        Key::Up | Key::Char('k') => Action::Scroll(-1),
+        Key::PageDown | Key::Char(' ' | 'f') => Action::Page(1),
+        Key::PageUp | Key::Char('b') => Action::Page(-1),
+        Key::Home => Action::Start,
+        Key::End | Key::Char('G') => Action::End,
+        Key::Char('g' | ':') => Action::Goto,
+        Key::Char('/') => Action::Search,
+        Key::Char('n') => Action::SearchNext,
+        Key::Char('q') | Key::Escape | Key::Interrupt => Action::Quit,
+        _ => return None,
+    })
+}
+
+/// The part of the file that is shown on the screen.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct View {
+    /// The position of the first byte on the screen.
+    pub position: u64,
+    /// The size of the file.
+    pub size: u64,
+    pub bytes_per_line: u64,
+    /// The number of lines of bytes on the screen.
+    pub lines: u64,
+}
+
+impl View {
+    /// The number of bytes on the screen.
+    pub(crate) fn screen_size(&self) -> u64 {
+        self.lines.max(1) * self.bytes_per_line
+    }
+
+    /// The position at which the last line of the file is at the bottom of
+    /// the screen.
+    pub(crate) fn last_position(&self) -> u64 {
+        let last_line = self.size.saturating_sub(1) / self.bytes_per_line * self.bytes_per_line;
+        last_line.saturating_sub((self.lines.max(1) - 1) * self.bytes_per_line)
+    }
+
+    /// Scrolls by `lines` lines, without going past the start or the end of
+    /// the file.
+    pub(crate) fn scroll(&mut self, lines: i64) {
+        let delta = lines.saturating_mul(self.bytes_per_line as i64);
+        if delta < 0 {
+            self.position = self.position.saturating_add_signed(delta);
+        } else {
+            // After going to a position near the end, the view does not move
+            // back.
+            let end = self.last_position().max(self.position);
+            self.position = self.position.saturating_add_signed(delta).min(end);
+        }
+    }
+}
+
+/// Keeps the terminal in raw mode, in which the keys are read one by one
+/// and not echoed, until it is dropped. The settings are also restored if
+/// hexyl is ended by a signal while the session is active.
+struct RawMode<'a> {
+    session: &'a TerminalSession,
+    tty: File,
+    original: libc::termios,
+    /// The keys that have been read but not returned yet.
+    keys: VecDeque<Key>,
+}
+
+impl<'a> RawMode<'a> {
+    fn enable(session: &'a TerminalSession) -> io::Result<Self> {
+        let tty = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+        let fd = tty.as_raw_fd();
+        let mut original = unsafe { mem::zeroed::<libc::termios>() };
+        if unsafe { libc::tcgetattr(fd, &mut original) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut raw = original;
+        raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG | libc::IEXTEN);
+        raw.c_iflag &= !(libc::IXON | libc::ICRNL);
+        raw.c_cc[libc::VMIN] = 1;
+        raw.c_cc[libc::VTIME] = 0;
+        session.save_termios(fd, original);
+        if unsafe { libc::tcsetattr(fd, libc::TCSAFLUSH, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(RawMode {
+            session,
+            tty,
+            original,
+            keys: VecDeque::new(),
+        })
+    }
+
+    fn read_key(&mut self) -> io::Result<Key> {
+        let mut buf = [0; 256];
+        while self.keys.is_empty() {
+            match self.tty.read(&mut buf) {
+                Ok(0) => return Ok(Key::Interrupt),
+                Ok(n) => self.keys.extend(parse_keys(&buf[..n])),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(self.keys.pop_front().expect("a key"))
+    }
+
+    /// Reads a line in the status line after `prompt`. Returns `None` if it
+    /// is empty or canceled with Escape or Ctrl-C.
+    fn read_line(&mut self, prompt: &str) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(SHOW_CURSOR)?;
+        loop {
+            stdout.write_all(CLEAR_LINE)?;
+            write!(stdout, "{prompt}{line}")?;
+            stdout.flush()?;
+            match self.read_key()? {
+                Key::Enter => break,
+                Key::Escape | Key::Interrupt => {
+                    line.clear();
+                    break;
+                }
+                Key::Backspace => {
+                    line.pop();
+                }
+                Key::Char(c) => line.push(c),
+                _ => {}
+            }
+        }
+        stdout.write_all(HIDE_CURSOR)?;
+        Ok(Some(line).filter(|line| !line.is_empty()))
+    }
+}
+
+impl Drop for RawMode<'_> {
+    fn drop(&mut self) {
+        self.session.forget_termios();
+        unsafe {
+            libc::tcsetattr(self.tty.as_raw_fd(), libc::TCSAFLUSH, &self.original);
+        }
+    }
+}
+
+/// Shows the file one screen at a time on the alternate screen, and moves
+/// around with the keys until `q`. `dump` prints the bytes of a screen, which
+/// start at the given position. A screen has as many lines as fit into the
+/// terminal besides the `border_lines` of the dump and the status line.
+pub(crate) fn run(
+    mut file: File,
+    bytes_per_line: u64,
+    border_lines: u64,
+    parse_offset: impl Fn(&str) -> Result<i64>,
+    dump: impl Fn(Vec<u8>, u64) -> Result<()>,
+) -> Result<()> {
+    let size = file.metadata()?.len();
+    let session = TerminalSession::start(true)?;
+    let mut raw_mode = RawMode::enable(&session)?;
+    let mut view = View {
+        position: 0,
+        size,
+        bytes_per_line,
+        lines: 1,
+    };
+    let mut pattern: Option<Vec<u8>> = None;
+    let mut message = String::new();
+    loop {
+        let rows = terminal_size().map_or(24, |size| u64::from(size.1 .0));
+        view.lines = rows.saturating_sub(border_lines + 1).max(1);
+
+        let mut chunk = vec![];
+        file.seek(SeekFrom::Start(view.position))?;
+        (&mut file)
+            .take(view.screen_size())
+            .read_to_end(&mut chunk)?;
+        session.clear()?;
+        io::stdout().write_all(HIDE_CURSOR)?;
+        dump(chunk, view.position)?;
+        {
+            let mut stdout = io::stdout().lock();
+            if message.is_empty() {
+                let end = (view.position + view.screen_size()).min(size);
+                let percent = (end * 100).checked_div(size).unwrap_or(100);
+                write!(
+                    stdout,
+                    "{:#x}/{size:#x} ({percent}%)  j/k: line  space/b: page  \
+                     g: go to  /: search  n: next  q: quit",
+                    view.position
+                )?;
+            } else {
+                stdout.write_all(mem::take(&mut message).as_bytes())?;
+            }
+            stdout.flush()?;
+        }
+
+        let Some(action) = action(&raw_mode.read_key()?) else {
+            continue;
+        };
+        match action {
+            Action::Scroll(lines) => view.scroll(lines),
+            Action::Page(screens) => view.scroll(screens.saturating_mul(view.lines as i64)),
+            Action::Start => view.position = 0,
+            Action::End => view.position = view.last_position(),
+            Action::Goto => {
+                let Some(line) = raw_mode.read_line("Go to position: ")? else {
+                    continue;
+                };
+                match parse_offset(&line) {
+                    Ok(offset) => {
+                        let target = if offset < 0 {
+                            size.saturating_add_signed(offset)
+                        } else {
+                            offset as u64
+                        };
+                        if target < size || target == 0 {
+                            view.position = target;
+                        } else {
+                            message = format!(
+                                "Position {target:#x} is beyond the end of the input ({size:#x})"
+                            );
+                        }
+                    }
+                    Err(err) => message = format!("Error: {err}"),
+                }
+            }
+            Action::Search | Action::SearchNext => {
+                if action == Action::Search {
+                    match raw_mode.read_line("Search: ")? {
+                        Some(line) => pattern = Some(line.into_bytes()),
+                        None => continue,
+                    }
+                }
+                let Some(pattern) = &pattern else {
+                    message = "No previous search".to_owned();
+                    continue;
+                };
+                match prompt::search(&mut file, view.position + 1, pattern)? {
+                    Some(found) => view.position = found,
+                    None => message = "Pattern not found".to_owned(),
+                }
+            }
+            Action::Quit => return Ok(()),
+        }
+    }
+}
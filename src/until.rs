@@ -0,0 +1,124 @@
+use std::collections::VecDeque;
+use std::io::{self, Read};
+use std::mem;
+
+/// The maximum number of bytes that are read from the inner reader at once.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A reader that ends at the first occurrence of a pattern in the inner
+/// reader, e.g. at the end marker of a frame in a stream. The pattern itself
+/// is included, unless the reader is created with `inclusive` set to `false`.
+/// Bytes that may be the start of the pattern are held back until it is known
+/// whether the pattern follows, so a read may return fewer bytes than are
+/// available.
+pub struct UntilReader<R: Read> {
+    inner: R,
+    pattern: Vec<u8>,
+    inclusive: bool,
+    /// For every length of a partial match, the length of the longest proper
+    /// prefix of the pattern that is also a suffix of the partial match, to
+    /// continue without going back in the input.
+    fallback: Vec<usize>,
+    /// The length of the partial match at the end of the bytes that have been
+    /// read. These bytes are equal to the start of the pattern, and are held
+    /// back.
+    matched: usize,
+    /// The bytes that can be returned.
+    ready: VecDeque<u8>,
+    found: bool,
+    done: bool,
+    chunk: Vec<u8>,
+}
+
+impl<R: Read> UntilReader<R> {
+    /// # Panics
+    ///
+    /// Panics if `pattern` is empty.
+    pub fn new(inner: R, pattern: Vec<u8>, inclusive: bool) -> Self {
+        assert!(!pattern.is_empty(), "the pattern must not be empty");
+        let mut fallback = vec![0; pattern.len() + 1];
+        for i in 1..pattern.len() {
+            let mut k = fallback[i];
+            while k > 0 && pattern[i] != pattern[k] {
+                k = fallback[k];
+            }
+            fallback[i + 1] = if pattern[i] == pattern[k] { k + 1 } else { 0 };
+        }
+        UntilReader {
+            inner,
+            pattern,
+            inclusive,
+            fallback,
+            matched: 0,
+            ready: VecDeque::new(),
+            found: false,
+            done: false,
+            chunk: vec![],
+        }
+    }
+
+    /// Returns whether the pattern has been found.
+    pub fn found(&self) -> bool {
+        self.found
+    }
+
+    /// Matches the next byte of the input, and moves the bytes that can no
+    /// longer be part of the pattern to `ready`.
+    fn push(&mut self, b: u8) {
+        let held = self.matched;
+        let mut k = self.matched;
+        while k > 0 && self.pattern[k] != b {
+            k = self.fallback[k];
+        }
+        if self.pattern[k] == b {
+            k += 1;
+        }
+        // The held bytes and `b` end with the new partial match.
+        let released = held + 1 - k;
+        let released_held = released.min(held);
+        self.ready.extend(&self.pattern[..released_held]);
+        if released > held {
+            self.ready.push_back(b);
+        }
+        self.matched = k;
+
+        if self.matched == self.pattern.len() {
+            if self.inclusive {
+                self.ready.extend(&self.pattern);
+            }
+            self.matched = 0;
+            self.found = true;
+            self.done = true;
+        }
+    }
+}
+
+impl<R: Read> Read for UntilReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        while self.ready.is_empty() && !self.done {
+            self.chunk.resize(buf.len().min(CHUNK_SIZE), 0);
+            let n = match self.inner.read(&mut self.chunk) {
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if n == 0 {
+                // The input ended within a partial match.
+                self.ready.extend(&self.pattern[..self.matched]);
+                self.done = true;
+            }
+            let chunk = mem::take(&mut self.chunk);
+            for &b in &chunk[..n] {
+                self.push(b);
+                if self.done {
+                    break;
+                }
+            }
+            self.chunk = chunk;
+        }
+        self.ready.read(buf)
+    }
+}
@@ -0,0 +1,172 @@
+//! Comparing the dump with a golden file for `--verify`, with a unified diff of
+//! the lines that differ.
+
+use std::io::{self, Write};
+
+/// The number of unchanged lines that are shown around every change.
+const CONTEXT_LINES: usize = 3;
+
+/// The maximum number of cells of the table that is used to match the lines
+/// in the middle of the two texts, after their common start and end. Above
+/// it, the differing lines are shown as replaced as a whole.
+const MAX_TABLE_SIZE: usize = 1 << 22;
+
+/// Keeps the dump in memory to compare it, and fails once it needs more than
+/// `max_memory` bytes.
+pub(crate) struct DumpBuffer<'a> {
+    pub dump: &'a mut Vec<u8>,
+    pub max_memory: u64,
+}
+
+impl Write for DumpBuffer<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if (self.dump.len() + buf.len()) as u64 > self.max_memory {
+            return Err(io::Error::new(
+                io::ErrorKind::OutOfMemory,
+                format!(
+                    "the dump of `--verify` needs more than the allowed {} bytes of memory",
+                    self.max_memory
+                ),
+            ));
+        }
+        self.dump.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Change {
+    Same,
+    Removed,
+    Added,
+}
+
+/// Returns a unified diff from `expected` to `actual` with the file names in
+/// its header, or `None` if they are equal. Lines without a newline at the end
+/// of the text are marked as such.
+pub(crate) fn unified_diff(
+    expected: &str,
+    actual: &str,
+    expected_name: &str,
+    actual_name: &str,
+) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+    let old: Vec<&str> = expected.split_inclusive('\n').collect();
+    let new: Vec<&str> = actual.split_inclusive('\n').collect();
+    let changes = diff_lines(&old, &new);
+
+    let mut diff = format!("--- {expected_name}\n+++ {actual_name}\n");
+    let changed: Vec<usize> = (0..changes.len())
+        .filter(|&i| changes[i].0 != Change::Same)
+        .collect();
+    let mut hunk_start = 0;
+    while hunk_start < changed.len() {
+        // Changes that are close enough to share their context form a hunk.
+        let mut hunk_end = hunk_start + 1;
+        while hunk_end < changed.len()
+            && changed[hunk_end] - changed[hunk_end - 1] <= 2 * CONTEXT_LINES + 1
+        {
+            hunk_end += 1;
+        }
+        let start = changed[hunk_start].saturating_sub(CONTEXT_LINES);
+        let end = (changed[hunk_end - 1] + 1 + CONTEXT_LINES).min(changes.len());
+
+        let count = |range: &[(Change, &str)], skip: Change| {
+            range.iter().filter(|(change, _)| *change != skip).count()
+        };
+        let old_start = count(&changes[..start], Change::Added);
+        let new_start = count(&changes[..start], Change::Removed);
+        let old_len = count(&changes[start..end], Change::Added);
+        let new_len = count(&changes[start..end], Change::Removed);
+        // Like diff, an empty range starts at the line before it.
+        let first_line = |start: usize, len: usize| if len == 0 { start } else { start + 1 };
+        diff.push_str(&format!(
+            "@@ -{},{old_len} +{},{new_len} @@\n",
+            first_line(old_start, old_len),
+            first_line(new_start, new_len)
+        ));
+        for (change, line) in &changes[start..end] {
+            diff.push(match change {
+                Change::Same => ' ',
+                Change::Removed => '-',
+                Change::Added => '+',
+            });
+            diff.push_str(line);
+            if !line.ends_with('\n') {
+                diff.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+        hunk_start = hunk_end;
+    }
+    Some(diff)
+}
+
+/// Matches the lines of `old` and `new`, and returns every line with whether
+/// it is in both, or only in one of them.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(Change, &'a str)> {
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut changes: Vec<(Change, &str)> = old[..prefix]
+        .iter()
+        .map(|&line| (Change::Same, line))
+        .collect();
+    let table_size = (old_middle.len() + 1).saturating_mul(new_middle.len() + 1);
+    if table_size <= MAX_TABLE_SIZE {
+        // The length of the longest common subsequence of the lines from
+        // `i` and `j` on, in `lcs[i * width + j]`.
+        let width = new_middle.len() + 1;
+        let mut lcs = vec![0u32; table_size];
+        for i in (0..old_middle.len()).rev() {
+            for j in (0..new_middle.len()).rev() {
+                lcs[i * width + j] = if old_middle[i] == new_middle[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < old_middle.len() && j < new_middle.len() {
+            if old_middle[i] == new_middle[j] {
+                changes.push((Change::Same, old_middle[i]));
+                i += 1;
+                j += 1;
+            } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+                changes.push((Change::Removed, old_middle[i]));
+                i += 1;
+            } else {
+                changes.push((Change::Added, new_middle[j]));
+                j += 1;
+            }
+        }
+        changes.extend(old_middle[i..].iter().map(|&line| (Change::Removed, line)));
+        changes.extend(new_middle[j..].iter().map(|&line| (Change::Added, line)));
+    } else {
+        changes.extend(old_middle.iter().map(|&line| (Change::Removed, line)));
+        changes.extend(new_middle.iter().map(|&line| (Change::Added, line)));
+    }
+    changes.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|&line| (Change::Same, line)),
+    );
+    changes
+}
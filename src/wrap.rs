@@ -0,0 +1,121 @@
+use std::io::{self, Write};
+
+/// A writer that wraps lines which are wider than a given number of columns.
+/// Every line except the last one of a wrapped line ends with a continuation
+/// marker. ANSI escape sequences do not count towards the width of a line.
+pub struct WrapWriter<W: Write> {
+    inner: W,
+    width: usize,
+    marker: char,
+    line: Vec<u8>,
+}
+
+impl<W: Write> WrapWriter<W> {
+    /// Creates a new writer, `width` must be at least two columns to leave
+    /// space for the `marker`.
+    pub fn new(inner: W, width: usize, marker: char) -> Self {
+        assert!(width >= 2);
+        WrapWriter {
+            inner,
+            width,
+            marker,
+            line: vec![],
+        }
+    }
+
+    fn write_line(&mut self) -> io::Result<()> {
+        let columns = Columns::new(&self.line);
+        let total = columns.clone().count();
+        if total <= self.width {
+            return self.inner.write_all(&self.line);
+        }
+
+        let mut marker = [0; 4];
+        let marker = self.marker.encode_utf8(&mut marker).as_bytes();
+        let mut start = 0;
+        let mut remaining = total;
+        let mut column = 0;
+        for end in columns {
+            if remaining <= self.width {
+                break;
+            }
+            if column == self.width - 1 {
+                self.inner.write_all(&self.line[start..end])?;
+                self.inner.write_all(marker)?;
+                self.inner.write_all(b"\n")?;
+                start = end;
+                remaining -= column;
+                column = 0;
+            }
+            column += 1;
+        }
+        self.inner.write_all(&self.line[start..])
+    }
+}
+
+impl<W: Write> Write for WrapWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for line in buf.split_inclusive(|&b| b == b'\n') {
+            self.line.extend_from_slice(line);
+            if line.ends_with(b"\n") {
+                self.write_line()?;
+                self.line.clear();
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for WrapWriter<W> {
+    fn drop(&mut self) {
+        if !self.line.is_empty() {
+            let _ = self.write_line();
+        }
+    }
+}
+
+/// An iterator over the byte offsets at which the visible characters of a
+/// line start. Escape sequences and the trailing newline are skipped.
+#[derive(Clone)]
+struct Columns<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Columns<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Columns { bytes, pos: 0 }
+    }
+}
+
+impl<'a> Iterator for Columns<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            let &b = self.bytes.get(self.pos)?;
+            let start = self.pos;
+            self.pos += 1;
+            match b {
+                b'\x1b' => {
+                    // skip a CSI sequence like `ESC [ 3 6 m`
+                    if self.bytes.get(self.pos) == Some(&b'[') {
+                        self.pos += 1;
+                        while let Some(&b) = self.bytes.get(self.pos) {
+                            self.pos += 1;
+                            if (0x40..=0x7e).contains(&b) {
+                                break;
+                            }
+                        }
+                    }
+                }
+                b'\n' | 0x80..=0xbf => {}
+                _ => return Some(start),
+            }
+        }
+    }
+}
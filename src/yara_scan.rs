@@ -0,0 +1,104 @@
+//! Scanning the input with YARA rules for `--yara`, whose string matches are
+//! highlighted in the dump.
+
+use std::ops::Range;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+
+use hexyl::{color_from_name, Highlight};
+
+/// The colors of the matches, one per rule in the order in which the rules
+/// match, repeated if there are more rules.
+const RULE_COLORS: [&str; 6] = [
+    "bright-magenta",
+    "bright-blue",
+    "bright-green",
+    "bright-cyan",
+    "bright-yellow",
+    "bright-red",
+];
+
+/// How long a scan may take, in seconds.
+const TIMEOUT: i32 = 60;
+
+/// A match of a string of a YARA rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct YaraMatch {
+    pub rule: String,
+    /// The identifier of the string, including the `$`.
+    pub string: String,
+    /// The positions of the match in the file.
+    pub range: Range<u64>,
+    /// The index of the rule among the matching rules.
+    pub rule_index: usize,
+}
+
+/// Scans `file` with the rules in the source file `rules` and returns all
+/// string matches, ordered by their position.
+pub(crate) fn scan(rules: &Path, file: &Path) -> Result<Vec<YaraMatch>> {
+    let compiled = yara::Compiler::new()
+        .map_err(yara::Error::from)
+        .and_then(|compiler| compiler.add_rules_file(rules))
+        .and_then(|compiler| Ok(compiler.compile_rules()?))
+        .map_err(flatten)
+        .with_context(|| format!("failed to compile the YARA rules in {rules:?}"))?;
+    let results = compiled
+        .scan_file(file, TIMEOUT)
+        .map_err(flatten)
+        .with_context(|| format!("failed to scan {file:?} with YARA"))?;
+
+    let mut matches = vec![];
+    for (rule_index, rule) in results.iter().enumerate() {
+        for string in &rule.strings {
+            for m in &string.matches {
+                let start = m.offset as u64;
+                matches.push(YaraMatch {
+                    rule: rule.identifier.to_string(),
+                    string: string.identifier.to_string(),
+                    range: start..start + m.length as u64,
+                    rule_index,
+                });
+            }
+        }
+    }
+    matches.sort_by_key(|m| (m.range.start, m.range.end));
+    Ok(matches)
+}
+
+/// Turns a YARA error into a single message, the compile errors of which are
+/// otherwise repeated as its source.
+fn flatten(err: yara::Error) -> anyhow::Error {
+    anyhow!("{}", err.to_string().trim_end())
+}
+
+/// Returns the highlights of the `matches`, at their positions in the file.
+pub(crate) fn highlights(matches: &[YaraMatch]) -> Vec<Highlight> {
+    matches
+        .iter()
+        .map(|m| Highlight {
+            range: m.range.clone(),
+            color: color_from_name(RULE_COLORS[m.rule_index % RULE_COLORS.len()])
+                .expect("known color"),
+            label: Some(format!("{}:{}", m.rule, m.string)),
+        })
+        .collect()
+}
+
+/// Prints a line per match to stderr.
+pub(crate) fn print_summary(matches: &[YaraMatch]) {
+    if matches.is_empty() {
+        eprintln!("No YARA rule matched");
+        return;
+    }
+    for m in matches {
+        eprintln!(
+            "YARA rule {} matched {} at offset {:#x} ({}), length {}",
+            m.rule,
+            m.string,
+            m.range.start,
+            m.range.start,
+            m.range.end - m.range.start
+        );
+    }
+}
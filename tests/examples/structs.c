@@ -0,0 +1,21 @@
+// The source of `structs.debug`, built with `gcc -g -O0 -nostdlib -static
+// -o structs structs.c` and `strip --only-keep-debug -o structs.debug structs`.
+
+#include <stdint.h>
+
+struct point {
+    int16_t x;
+    int16_t y;
+};
+
+typedef struct header {
+    char magic[4];
+    uint8_t version;
+    uint32_t length;
+    struct point origin;
+    const uint64_t *next;
+} header_t;
+
+header_t header;
+
+void _start(void) {}
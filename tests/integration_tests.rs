@@ -3,6 +3,20 @@ use assert_cmd::Command;
 fn hexyl() -> Command {
     let mut cmd = Command::cargo_bin("hexyl").unwrap();
     cmd.current_dir("tests/examples");
+    cmd.env_remove("HEXYL_DEFAULT_ARGS");
+    for category in [
+        "NULL",
+        "PRINTABLE",
+        "WHITESPACE",
+        "OTHER",
+        "NONASCII",
+        "OFFSET",
+        "SQUEEZE",
+    ] {
+        cmd.env_remove(format!("HEXYL_COLOR_{category}"));
+    }
+    // Ignore the rules file of the user.
+    cmd.env("XDG_CONFIG_HOME", "no-config");
     cmd
 }
 trait PrettyAssert<S>
@@ -96,6 +110,43 @@ mod length {
         );
     }
 
+    #[test]
+    fn strict_length_fails_on_short_input() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--length=32")
+            .arg("--strict-length")
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains(
+                "requested 32 bytes with `--length`, but the input ended after 16 bytes",
+            ));
+    }
+
+    #[test]
+    fn strict_length_warns_on_short_input() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--length=32")
+            .arg("--strict-length=warn")
+            .assert()
+            .success()
+            .stderr(predicates::str::starts_with("Warning: requested 32 bytes"));
+    }
+
+    #[test]
+    fn strict_length_accepts_exact_input() {
+        hexyl()
+            .arg("ascii")
+            .arg("--length=16")
+            .arg("--strict-length")
+            .assert()
+            .success()
+            .stderr("");
+    }
+
     #[test]
     fn fail_if_length_and_bytes_options_are_used_simultaneously() {
         hexyl()
@@ -117,6 +168,112 @@ mod length {
     }
 }
 
+mod end {
+    use super::hexyl;
+
+    #[test]
+    fn stops_at_end() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--skip=4")
+            .arg("--end=0xa")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┐\n\
+                 │00000004│ 34 35 36 37 38 39       ┊                         │456789  ┊        │\n\
+                 └────────┴─────────────────────────┴─────────────────────────┴────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn skip_from_end() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--skip=-8")
+            .arg("--end=10")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┐\n\
+                 │00000008│ 38 39                   ┊                         │89      ┊        │\n\
+                 └────────┴─────────────────────────┴─────────────────────────┴────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn fails_before_skip() {
+        hexyl()
+            .arg("ascii")
+            .arg("--skip=8")
+            .arg("--end=4")
+            .assert()
+            .failure()
+            .code(2)
+            .stderr(predicates::str::contains(
+                "`--end` (0x4) is before the position of `--skip` (0x8)",
+            ));
+    }
+
+    #[test]
+    fn conflicts_with_length() {
+        hexyl()
+            .arg("ascii")
+            .arg("--end=4")
+            .arg("--length=2")
+            .assert()
+            .failure()
+            .code(2);
+    }
+}
+
+mod until {
+    use super::hexyl;
+
+    #[test]
+    fn stops_after_pattern() {
+        hexyl()
+            .write_stdin(b"ab\x00\x00\xff\xffcd".as_slice())
+            .arg("--color=never")
+            .arg(r"--until=\x00\x00\xff\xff")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┐\n\
+                 │00000000│ 61 62 00 00 ff ff       ┊                         │ab⋄⋄××  ┊        │\n\
+                 └────────┴─────────────────────────┴─────────────────────────┴────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn stops_before_pattern() {
+        hexyl()
+            .write_stdin(b"ab\x00\x00\x00\xff\xffcd".as_slice())
+            .arg("--color=never")
+            .arg(r"--until=\x00\x00\xff\xff")
+            .arg("--until-exclusive")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┐\n\
+                 │00000000│ 61 62 00                ┊                         │ab⋄     ┊        │\n\
+                 └────────┴─────────────────────────┴─────────────────────────┴────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        hexyl()
+            .arg("ascii")
+            .arg(r"--until=\x4")
+            .assert()
+            .failure()
+            .code(2);
+    }
+}
+
 mod bytes {
     use super::hexyl;
 
@@ -191,6 +348,25 @@ mod skip {
             .failure()
             .stderr(predicates::str::contains("Failed to jump"));
     }
+
+    /// A FILE that is a pipe is skipped by reading, like stdin.
+    #[cfg(unix)]
+    #[test]
+    fn pipe_as_file() {
+        hexyl()
+            .arg("/dev/stdin")
+            .arg("--color=never")
+            .arg("--skip=2")
+            .arg("--length=4")
+            .write_stdin("0123456789")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┐\n\
+                 │00000002│ 32 33 34 35             ┊                         │2345    ┊        │\n\
+                 └────────┴─────────────────────────┴─────────────────────────┴────────┴────────┘\n",
+            );
+    }
 }
 
 mod display_offset {
@@ -211,6 +387,23 @@ mod display_offset {
         );
     }
 
+    #[test]
+    fn positions_above_4_gib() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--display-offset=0xfffffff8")
+            .assert()
+            .success()
+            .stdout(
+                "┌──────────┬─────────────────────────┬────────┐\n\
+                 │00fffffff8│ 30 31 32 33 34 35 36 37 │01234567│\n\
+                 │0100000000│ 38 39 61 62 63 64 65 0a │89abcde_│\n\
+                 └──────────┴─────────────────────────┴────────┘\n",
+            );
+    }
+
     #[test]
     fn display_offset_and_skip() {
         hexyl()
@@ -227,6 +420,66 @@ mod display_offset {
              └────────┴─────────────────────────┴─────────────────────────┴────────┴────────┘\n",
         );
     }
+
+    #[test]
+    fn relative_to_end() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--display-offset=end+0x100")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000110│ 30 31 32 33 34 35 36 37 │01234567│\n\
+                 │00000118│ 38 39 61 62 63 64 65 0a │89abcde_│\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn relative_to_skip_position() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--skip=8")
+            .arg("--display-offset=here-8")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 38 39 61 62 63 64 65 0a │89abcde_│\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn skip_position() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--skip=8")
+            .arg("--display-offset=here")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000008│ 38 39 61 62 63 64 65 0a │89abcde_│\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn relative_to_end_fails_for_stdin() {
+        hexyl()
+            .arg("--display-offset=end")
+            .write_stdin("abc")
+            .assert()
+            .code(2);
+    }
 }
 
 mod blocksize {
@@ -290,158 +543,3063 @@ mod display_settings {
                  └─────────────────────────┴─────────────────────────┴────────┴────────┘\n",
             );
     }
-}
-
-mod group_and_endianness {
-    use super::hexyl;
-    use super::PrettyAssert;
 
     #[test]
-    fn group_2_bytes_be() {
+    fn show_settings() {
         hexyl()
             .arg("ascii")
+            .arg("--show-settings")
+            .arg("--base=octal")
+            .arg("--character-table=ascii")
+            .arg("--panels=1")
             .arg("--color=never")
-            .arg("--group-size=2")
             .assert()
             .success()
             .stdout(
-                "┌────────┬─────────────────────┬─────────────────────┬────────┬────────┐\n\
-                 │00000000│ 3031 3233 3435 3637 ┊ 3839 6162 6364 650a │01234567┊89abcde_│\n\
-                 └────────┴─────────────────────┴─────────────────────┴────────┴────────┘\n",
+                "┌─[oct|1 panel|g1|BE|ascii]────────────────┬────────┐\n\
+                 │00000000│ 060 061 062 063 064 065 066 067 │01234567│\n\
+                 │00000008│ 070 071 141 142 143 144 145 012 │89abcde.│\n\
+                 └────────┴─────────────────────────────────┴────────┘\n",
             );
     }
 
     #[test]
-    fn group_2_bytes_le() {
+    fn columns() {
         hexyl()
             .arg("ascii")
+            .arg("--columns=hex")
             .arg("--color=never")
-            .arg("--group-size=2")
-            .arg("--endianness=little")
             .assert()
             .success()
             .stdout(
-                "┌────────┬─────────────────────┬─────────────────────┬────────┬────────┐\n\
-                 │00000000│ 3130 3332 3534 3736 ┊ 3938 6261 6463 0a65 │01234567┊89abcde_│\n\
-                 └────────┴─────────────────────┴─────────────────────┴────────┴────────┘\n",
+                "┌─────────────────────────┬─────────────────────────┐\n\
+                 │ 30 31 32 33 34 35 36 37 ┊ 38 39 61 62 63 64 65 0a │\n\
+                 └─────────────────────────┴─────────────────────────┘\n",
             );
+        hexyl()
+            .arg("ascii")
+            .arg("--columns=offset,hex")
+            .arg("--plain")
+            .assert()
+            .success()
+            .stdout(" 00000000  30 31 32 33 34 35 36 37   38 39 61 62 63 64 65 0a  \n");
     }
 
     #[test]
-    fn group_4_bytes_be() {
+    fn invalid_columns() {
+        hexyl()
+            .arg("ascii")
+            .arg("--columns=offset,chars")
+            .assert()
+            .code(2);
+        hexyl()
+            .arg("ascii")
+            .arg("--columns=chars,hex")
+            .assert()
+            .code(2);
+        hexyl()
+            .arg("ascii")
+            .arg("--columns=hex,checksum")
+            .assert()
+            .code(2);
+        hexyl()
+            .arg("ascii")
+            .arg("--columns=hex")
+            .arg("--no-position")
+            .assert()
+            .code(2);
+    }
+
+    #[test]
+    fn tick_marks() {
         hexyl()
             .arg("ascii")
+            .arg("--tick-marks")
+            .arg("--border=ascii")
+            .arg("--panels=2")
             .arg("--color=never")
-            .arg("--group-size=4")
             .assert()
             .success()
             .stdout(
-                "┌────────┬───────────────────┬───────────────────┬────────┬────────┐\n\
-                 │00000000│ 30313233 34353637 ┊ 38396162 6364650a │01234567┊89abcde_│\n\
-                 └────────┴───────────────────┴───────────────────┴────────┴────────┘\n",
+                "+--------+-------------------------+-------------------------+---------+---------+\n\
+                 |00000000| 30 31 32 33:34 35 36 37 | 38 39 61 62:63 64 65 0a |0123:4567|89ab:cde_|\n\
+                 +--------+-------------------------+-------------------------+---------+---------+\n",
             );
+        hexyl()
+            .arg("ascii")
+            .arg("--tick-marks")
+            .arg("--group-size=8")
+            .assert()
+            .code(2);
     }
+}
+
+mod terminal_width {
+    use super::hexyl;
 
     #[test]
-    fn group_4_bytes_le() {
+    fn uses_as_many_panels_as_fit() {
         hexyl()
             .arg("ascii")
             .arg("--color=never")
-            .arg("--group-size=4")
-            .arg("--endianness=little")
+            .arg("--terminal-width=80")
             .assert()
             .success()
             .stdout(
-                "┌────────┬───────────────────┬───────────────────┬────────┬────────┐\n\
-                 │00000000│ 33323130 37363534 ┊ 62613938 0a656463 │01234567┊89abcde_│\n\
-                 └────────┴───────────────────┴───────────────────┴────────┴────────┘\n",
+                "┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┐\n\
+                 │00000000│ 30 31 32 33 34 35 36 37 ┊ 38 39 61 62 63 64 65 0a │01234567┊89abcde_│\n\
+                 └────────┴─────────────────────────┴─────────────────────────┴────────┴────────┘\n",
             );
     }
 
     #[test]
-    fn group_8_bytes_be() {
+    fn never_exceeds_the_given_width() {
         hexyl()
             .arg("ascii")
             .arg("--color=never")
-            .arg("--group-size=8")
+            .arg("--terminal-width=79")
             .assert()
             .success()
             .stdout(
-                "┌────────┬──────────────────┬──────────────────┬────────┬────────┐\n\
-                 │00000000│ 3031323334353637 ┊ 383961626364650a │01234567┊89abcde_│\n\
-                 └────────┴──────────────────┴──────────────────┴────────┴────────┘\n",
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 30 31 32 33 34 35 36 37 │01234567│\n\
+                 │00000008│ 38 39 61 62 63 64 65 0a │89abcde_│\n\
+                 └────────┴─────────────────────────┴────────┘\n",
             );
     }
+}
+
+mod fit {
+    use super::hexyl;
 
     #[test]
-    fn group_8_bytes_le() {
+    fn width() {
         hexyl()
             .arg("ascii")
             .arg("--color=never")
-            .arg("--group-size=8")
-            .arg("--endianness=little")
+            .arg("--fit=width:79")
             .assert()
             .success()
             .stdout(
-                "┌────────┬──────────────────┬──────────────────┬────────┬────────┐\n\
-                 │00000000│ 3736353433323130 ┊ 0a65646362613938 │01234567┊89abcde_│\n\
-                 └────────┴──────────────────┴──────────────────┴────────┴────────┘\n",
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 30 31 32 33 34 35 36 37 │01234567│\n\
+                 │00000008│ 38 39 61 62 63 64 65 0a │89abcde_│\n\
+                 └────────┴─────────────────────────┴────────┘\n",
             );
     }
 
     #[test]
-    fn group_size_plain() {
+    fn bytes_per_line() {
         hexyl()
             .arg("ascii")
-            .arg("--color=never")
             .arg("--plain")
-            .arg("--group-size=2")
+            .arg("--fit=bytes:16")
             .assert()
             .success()
-            .stdout("  3031 3233 3435 3637   3839 6162 6364 650a  \n");
+            .stdout("  30 31 32 33 34 35 36 37   38 39 61 62 63 64 65 0a  \n");
     }
 
     #[test]
-    fn group_size_fill_space() {
+    fn fails_for_partial_panels() {
         hexyl()
-            .arg("--color=never")
-            .arg("--group-size=2")
-            .write_stdin("abc")
+            .arg("ascii")
+            .arg("--fit=bytes:12")
             .assert()
-            .success()
-            .stdout(
-                "┌────────┬─────────────────────┬─────────────────────┬────────┬────────┐\n\
-                 │00000000│ 6162 63             ┊                     │abc     ┊        │\n\
-                 └────────┴─────────────────────┴─────────────────────┴────────┴────────┘\n",
-            );
+            .failure();
     }
 
     #[test]
-    fn group_size_invalid() {
+    fn conflicts_with_panels() {
         hexyl()
             .arg("ascii")
-            .arg("--color=never")
-            .arg("--plain")
-            .arg("--group-size=3")
+            .arg("--fit=width")
+            .arg("--panels=1")
             .assert()
             .failure();
     }
+
     #[test]
-    fn squeeze_no_chars() {
+    fn max_panels() {
+        let one_panel = "┌────────┬─────────────────────────┬────────┐\n\
+                         │00000000│ 30 31 32 33 34 35 36 37 │01234567│\n\
+                         │00000008│ 38 39 61 62 63 64 65 0a │89abcde_│\n\
+                         └────────┴─────────────────────────┴────────┘\n";
         hexyl()
-            .arg("hello_world_elf64")
+            .arg("ascii")
             .arg("--color=never")
-            .arg("--skip=1024")
-            .arg("--length=4096")
-            .arg("--no-characters")
+            .arg("--fit=width:300")
+            .arg("--max-panels=1")
             .assert()
             .success()
-            .pretty_stdout(
-                "\
-┌────────┬─────────────────────────┬─────────────────────────┐
-│00000400│ 00 00 00 00 00 00 00 00 ┊ 00 00 00 00 00 00 00 00 │
-│*       │                         ┊                         │
-│00001000│ ba 0e 00 00 00 b9 00 20 ┊ 40 00 bb 01 00 00 00 b8 │
+            .stdout(one_panel);
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--terminal-width=300")
+            .arg("--max-panels=1")
+            .assert()
+            .success()
+            .stdout(one_panel);
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--panels=auto-1")
+            .assert()
+            .success()
+            .stdout(one_panel);
+    }
+
+    #[test]
+    fn max_panels_does_not_limit_fixed_panels() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--fit=bytes:32")
+            .arg("--max-panels=1")
+            .assert()
+            .success()
+            .stdout(predicates::str::contains(
+                "│00000000│ 30 31 32 33 34 35 36 37 ┊ 38 39",
+            ));
+    }
+
+    #[test]
+    fn fails_for_invalid_auto_panels() {
+        for panels in ["auto-0", "auto-", "auto-x"] {
+            hexyl()
+                .arg("ascii")
+                .arg(format!("--panels={panels}"))
+                .assert()
+                .failure()
+                .code(2);
+        }
+    }
+}
+
+mod classify_lines {
+    use super::hexyl;
+
+    #[test]
+    fn adds_tag_column() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--panels=2")
+            .arg("--classify-lines")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┬─┐\n\
+                 │00000000│ 30 31 32 33 34 35 36 37 ┊ 38 39 61 62 63 64 65 0a │01234567┊89abcde_│T│\n\
+                 └────────┴─────────────────────────┴─────────────────────────┴────────┴────────┴─┘\n",
+            );
+    }
+}
+
+mod interleave {
+    use super::hexyl;
+
+    #[test]
+    fn adds_row_per_base() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--interleave=hex,oct")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────────────┬────────┐\n\
+                 │00000000│  30  31  32  33  34  35  36  37 │01234567│\n\
+                 │        │ 060 061 062 063 064 065 066 067 │        │\n\
+                 │00000008│  38  39  61  62  63  64  65  0a │89abcde_│\n\
+                 │        │ 070 071 141 142 143 144 145 012 │        │\n\
+                 └────────┴─────────────────────────────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn conflicts_with_base() {
+        hexyl()
+            .arg("ascii")
+            .arg("--base=dec")
+            .arg("--interleave=hex,oct")
+            .assert()
+            .failure();
+    }
+}
+
+mod step {
+    use super::hexyl;
+
+    #[test]
+    fn shows_every_nth_byte_at_original_positions() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--step=4")
+            .arg("--phase=1")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000001│ 31 35 39 64             │159d    │\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn strict_length_counts_all_bytes() {
+        hexyl()
+            .arg("ascii")
+            .arg("--step=2")
+            .arg("--length=16")
+            .arg("--strict-length")
+            .assert()
+            .success();
+    }
+}
+
+mod filter_byte {
+    use super::hexyl;
+
+    #[test]
+    fn shows_matching_lines_only() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--filter-byte=0x0a,200")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000008│ 38 39 61 62 63 64 65 0a │89abcde_│\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            )
+            .stderr("Found 1 matching bytes in 1 lines\n");
+    }
+
+    #[test]
+    fn fails_for_invalid_byte_values() {
+        hexyl()
+            .arg("ascii")
+            .arg("--filter-byte=0x100")
+            .assert()
+            .failure();
+    }
+}
+
+mod find {
+    use super::hexyl;
+
+    #[test]
+    fn finds_in_parallel_with_jobs() {
+        let find = |jobs: &str| {
+            hexyl()
+                .arg("hello_world_elf64")
+                .arg("--color=never")
+                .arg("--skip=1")
+                .arg("--find=00 40")
+                .arg("--find-text=ELF")
+                .arg(jobs)
+                .assert()
+                .success()
+                .get_output()
+                .clone()
+        };
+        let single = find("--jobs=1");
+        let parallel = find("--jobs=7");
+        assert_eq!(parallel.stdout, single.stdout);
+        assert_eq!(parallel.stderr, single.stderr);
+        assert!(String::from_utf8_lossy(&parallel.stderr).starts_with("Found "));
+    }
+
+    #[test]
+    fn shows_matching_lines_only() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--find=45 ?? 46")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 7f 45 4c 46 02 01 01 00 │•ELF•••⋄│\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            )
+            .stderr("Found 1 match at 0x1\n");
+    }
+
+    #[test]
+    fn finds_text_across_lines() {
+        hexyl()
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--find-text=PNG")
+            .arg("--find-text=\\x00")
+            .write_stdin("0123456789abcdPNG\r\n")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000008│ 38 39 61 62 63 64 50 4e │89abcdPN│\n\
+                 │00000010│ 47 0d 0a                │G__     │\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            )
+            .stderr("Found 1 match at 0xe\n");
+    }
+
+    #[test]
+    fn fails_without_matches() {
+        hexyl()
+            .arg("ascii")
+            .arg("--find=ffff")
+            .assert()
+            .code(5)
+            .stderr("Found no matches\n");
+    }
+
+    #[test]
+    fn fails_for_invalid_hex_strings() {
+        hexyl().arg("ascii").arg("--find=abc").assert().code(2);
+        hexyl().arg("ascii").arg("--find=a?").assert().code(2);
+    }
+}
+
+mod relative_to {
+    use super::hexyl;
+
+    #[test]
+    fn subtracts_the_base_from_the_positions() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--display-offset=0x401000")
+            .arg("--relative-to=0x401008")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │-0000008│ 30 31 32 33 34 35 36 37 │01234567│\n\
+                 │00000000│ 38 39 61 62 63 64 65 0a │89abcde_│\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn fails_for_invalid_offsets() {
+        hexyl()
+            .arg("ascii")
+            .arg("--relative-to=-1")
+            .assert()
+            .code(2);
+    }
+}
+
+mod bytes_per_panel {
+    use super::hexyl;
+
+    #[test]
+    fn shows_panels_of_the_given_width() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--bytes-per-panel=16")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────────────────────────────┬────────────────┐\n\
+                 │00000000│ 30 31 32 33 34 35 36 37 38 39 61 62 63 64 65 0a │0123456789abcde_│\n\
+                 └────────┴─────────────────────────────────────────────────┴────────────────┘\n",
+            );
+    }
+
+    #[test]
+    fn fits_bytes_per_line() {
+        hexyl()
+            .arg("ascii")
+            .arg("--plain")
+            .arg("--width=4")
+            .arg("--fit=bytes:12")
+            .assert()
+            .success()
+            .stdout(format!(
+                "  30 31 32 33   34 35 36 37   38 39 61 62  \n  63 64 65 0a{:30}\n",
+                ""
+            ));
+    }
+
+    #[test]
+    fn fails_for_partial_groups() {
+        hexyl()
+            .arg("ascii")
+            .arg("--bytes-per-panel=6")
+            .arg("--group-size=4")
+            .assert()
+            .code(2);
+        hexyl()
+            .arg("ascii")
+            .arg("--bytes-per-panel=4")
+            .arg("--group-size=4")
+            .arg("--tick-marks")
+            .assert()
+            .code(2);
+    }
+
+    #[test]
+    fn reverses_the_dump() {
+        let dump = hexyl()
+            .arg("hello_world_elf64")
+            .arg("--bytes-per-panel=16")
+            .arg("--panels=3")
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        hexyl()
+            .arg("--reverse")
+            .arg("--bytes-per-panel=16")
+            .write_stdin(dump)
+            .assert()
+            .success()
+            .stdout(std::fs::read("tests/examples/hello_world_elf64").unwrap());
+    }
+}
+
+mod padding {
+    use super::hexyl;
+
+    #[test]
+    fn pads_decimal_values_with_spaces() {
+        hexyl()
+            .arg("--plain")
+            .arg("--base=decimal")
+            .arg("--padding=spaces")
+            .write_stdin("\x00\x07*")
+            .assert()
+            .success()
+            .stdout("    0   7  42                                                        \n");
+    }
+}
+
+mod mnemonics {
+    use super::hexyl;
+
+    #[test]
+    fn shown_below_control_characters() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--mnemonics")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────────────┬────────┐\n\
+                 │00000000│  30  31  32  33  34  35  36  37 │01234567│\n\
+                 │00000008│  38  39  61  62  63  64  65  0a │89abcde_│\n\
+                 │        │                              LF │        │\n\
+                 └────────┴─────────────────────────────────┴────────┘\n",
+            );
+    }
+}
+
+#[cfg(unix)]
+mod color_command {
+    use super::hexyl;
+
+    #[test]
+    fn colors_bytes() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=always")
+            .arg("--color-command=while read o b; do for x in $b; do printf '%s ' red; done; echo; done")
+            .assert()
+            .success()
+            .stdout(predicates::str::contains(
+                "\x1b[31m30 31 32 33 34 35 36 37\x1b[39m",
+            ));
+    }
+
+    #[test]
+    fn fails_on_unknown_color() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=always")
+            .arg("--color-command=while read l; do echo bogus; done")
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains("unknown color \"bogus\""));
+    }
+
+    #[test]
+    fn is_not_run_without_colors() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--color-command=false")
+            .assert()
+            .success();
+    }
+}
+
+mod offset_style {
+    use super::hexyl;
+
+    #[test]
+    fn sector() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--offset-style=sector")
+            .arg("--sector-size=8")
+            .assert()
+            .success()
+            .stdout(
+                "┌───────────────┬─────────────────────────┬────────┐\n\
+                 │LBA 00000000 +0│ 30 31 32 33 34 35 36 37 │01234567│\n\
+                 │LBA 00000001 +0│ 38 39 61 62 63 64 65 0a │89abcde_│\n\
+                 └───────────────┴─────────────────────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn fails_on_zero_sector_size() {
+        hexyl()
+            .arg("ascii")
+            .arg("--offset-style=sector")
+            .arg("--sector-size=0")
+            .assert()
+            .failure();
+    }
+}
+
+mod subcommands {
+    use super::hexyl;
+
+    #[test]
+    fn dump_is_the_default() {
+        let bare = hexyl().arg("ascii").arg("--color=never").output().unwrap();
+        hexyl()
+            .arg("dump")
+            .arg("ascii")
+            .arg("--color=never")
+            .assert()
+            .success()
+            .stdout(bare.stdout);
+    }
+
+    #[test]
+    fn options_before_subcommand_are_rejected() {
+        hexyl()
+            .arg("--color=never")
+            .arg("dump")
+            .arg("ascii")
+            .assert()
+            .failure();
+    }
+}
+
+mod stdin {
+    use super::hexyl;
+
+    #[test]
+    fn no_hint_for_piped_input() {
+        hexyl()
+            .write_stdin("0123")
+            .arg("--color=never")
+            .assert()
+            .success()
+            .stderr("");
+    }
+}
+
+mod summary {
+    use super::hexyl;
+
+    #[test]
+    fn lists_squeezed_regions() {
+        let mut input = b"0123456789abcdef".to_vec();
+        input.extend([0; 0x40]);
+        hexyl()
+            .write_stdin(input)
+            .arg("--color=never")
+            .arg("--summary")
+            .assert()
+            .success()
+            .stdout(predicates::str::ends_with(
+                "└────────┴─────────────────────────┴─────────────────────────┴────────┴────────┘\n\
+                 0x20-0x4f: 48 bytes of 0x00\n",
+            ));
+    }
+
+    #[test]
+    fn conflicts_with_no_squeezing() {
+        hexyl()
+            .arg("ascii")
+            .arg("--summary")
+            .arg("--no-squeezing")
+            .assert()
+            .failure();
+    }
+}
+
+mod io_buffer_size {
+    use super::hexyl;
+
+    #[test]
+    fn does_not_change_the_output() {
+        let expected = hexyl()
+            .arg("hello_world_elf64")
+            .arg("--color=never")
+            .output()
+            .unwrap()
+            .stdout;
+        for size in ["1", "3", "1KiB"] {
+            hexyl()
+                .arg("hello_world_elf64")
+                .arg("--color=never")
+                .arg(format!("--io-buffer-size={size}"))
+                .assert()
+                .success()
+                .stdout(expected.clone());
+        }
+    }
+
+    #[test]
+    fn fails_for_zero() {
+        hexyl()
+            .arg("ascii")
+            .arg("--io-buffer-size=0")
+            .assert()
+            .failure()
+            .code(2);
+    }
+}
+
+mod highlight {
+    use super::hexyl;
+
+    #[test]
+    fn shows_labels_and_legend() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--highlight=2..4:yellow:\"two: three\"")
+            .arg("--highlight=0..1:red:zero")
+            .arg("--highlight=9..0xa")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 30 31 32 33 34 35 36 37 │01234567│ zero, two: three\n\
+                 │00000008│ 38 39 61 62 63 64 65 0a │89abcde_│\n\
+                 └────────┴─────────────────────────┴────────┘\n\
+                 zero        0x0..0x1\n\
+                 two: three  0x2..0x4\n",
+            );
+    }
+
+    #[test]
+    fn strict_sanitize() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--length=8")
+            .arg("--highlight=0..1:red:\x1b]0;title\x07bell\x07")
+            .arg("--strict-sanitize")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 30 31 32 33 34 35 36 37 │01234567│ bell◌\n\
+                 └────────┴─────────────────────────┴────────┘\n\
+                 bell◌  0x0..0x1\n",
+            );
+    }
+
+    #[test]
+    fn fails_for_invalid_ranges() {
+        for arg in ["4..2", "2", "2..4:nocolor", "..4"] {
+            hexyl()
+                .arg("ascii")
+                .arg(format!("--highlight={arg}"))
+                .assert()
+                .failure()
+                .code(2);
+        }
+    }
+}
+
+mod sniff {
+    use super::hexyl;
+
+    #[test]
+    fn reports_shebang() {
+        hexyl()
+            .write_stdin("#!/bin/sh\n")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 23 21 2f 62 69 6e 2f 73 │#!/bin/s│\n\
+                 │00000008│ 68 0a                   │h_      │\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            )
+            .stderr("hexyl: the input starts with a shebang for `/bin/sh`\n");
+    }
+
+    #[test]
+    fn reports_byte_order_mark() {
+        hexyl()
+            .write_stdin(b"\xff\xfea\x00".as_slice())
+            .arg("--color=never")
+            .assert()
+            .success()
+            .stderr("hexyl: the input starts with a UTF-16LE byte order mark\n");
+    }
+
+    #[test]
+    fn no_sniff() {
+        hexyl()
+            .write_stdin("#!/bin/sh\n")
+            .arg("--no-sniff")
+            .assert()
+            .success()
+            .stderr("");
+    }
+
+    #[test]
+    fn only_at_the_start_of_the_input() {
+        hexyl()
+            .write_stdin("x#!/bin/sh\n")
+            .arg("--skip=1")
+            .assert()
+            .success()
+            .stderr("");
+    }
+}
+
+mod footer_info {
+    use super::hexyl;
+
+    #[test]
+    fn file_and_range() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--color=never")
+            .arg("--skip=0x400")
+            .arg("--length=1KiB")
+            .arg("--footer-info")
+            .assert()
+            .success()
+            .stdout(predicates::str::ends_with(
+                "└─[hello_world_elf64 0x400-0x7ff (1 KiB)]────────────────────┴────────┴────────┘\n",
+            ));
+    }
+
+    #[test]
+    fn stdin() {
+        hexyl()
+            .write_stdin("abc")
+            .arg("--color=never")
+            .arg("--footer-info")
+            .assert()
+            .success()
+            .stdout(predicates::str::ends_with(
+                "└─[stdin 0x0-0x2 (3 bytes)]────────┴─────────────────────────┴────────┴────────┘\n",
+            ));
+    }
+}
+
+mod stateless_colors {
+    use super::hexyl;
+
+    #[test]
+    fn resets_after_every_byte() {
+        hexyl()
+            .write_stdin("ab")
+            .arg("--color=always")
+            .arg("--stateless-colors")
+            .arg("--panels=1")
+            .arg("--border=none")
+            .arg("--no-position")
+            .arg("--no-characters")
+            .assert()
+            .success()
+            .stdout("  \x1b[36m61\x1b[39m \x1b[36m62\x1b[39m                    \n");
+    }
+}
+
+mod squeeze_char {
+    use super::hexyl;
+
+    #[test]
+    fn custom_marker() {
+        hexyl()
+            .write_stdin([0u8; 32].as_slice())
+            .arg("--squeeze-char=[snip]")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 00 00 00 00 00 00 00 00 │⋄⋄⋄⋄⋄⋄⋄⋄│\n\
+                 │[snip]  │                         │        │\n\
+                 │00000020│                         │        │\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn marker_color() {
+        hexyl()
+            .write_stdin([0u8; 32].as_slice())
+            .arg("--color-squeeze=red")
+            .arg("--panels=1")
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("│\x1b[31m*\x1b[39m       │"));
+    }
+
+    #[test]
+    fn too_wide() {
+        hexyl()
+            .arg("ascii")
+            .arg("--squeeze-char=[snipped]")
+            .assert()
+            .failure()
+            .code(2);
+    }
+}
+
+mod auto_text {
+    use super::hexyl;
+
+    #[test]
+    fn prints_text_as_it_is() {
+        hexyl()
+            .arg("ascii")
+            .arg("--auto-text")
+            .assert()
+            .success()
+            .stdout("0123456789abcde\n")
+            .stderr("");
+    }
+
+    #[test]
+    fn respects_skip_and_length() {
+        hexyl()
+            .arg("ascii")
+            .arg("--auto-text")
+            .arg("--skip=4")
+            .arg("--length=4")
+            .assert()
+            .success()
+            .stdout("4567");
+    }
+
+    #[test]
+    fn dumps_binary_input() {
+        hexyl()
+            .write_stdin("01\x0023")
+            .arg("--auto-text")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 30 31 00 32 33          │01⋄23   │\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn no_hint_for_piped_output() {
+        hexyl().arg("ascii").assert().success().stderr("");
+    }
+}
+
+#[cfg(feature = "clipboard")]
+mod clipboard {
+    use super::hexyl;
+
+    #[test]
+    fn conflicts_with_file() {
+        hexyl().arg("--clipboard").arg("ascii").assert().failure();
+    }
+}
+
+mod wrap {
+    use super::hexyl;
+
+    #[test]
+    fn wraps_long_lines() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--border=ascii")
+            .arg("--wrap=50")
+            .assert()
+            .success()
+            .stdout(
+                "+--------+-------------------------+-------------\\\n\
+                 ------------+--------+--------+\n\
+                 |00000000| 30 31 32 33 34 35 36 37 | 38 39 61 62 \\\n\
+                 63 64 65 0a |01234567|89abcde_|\n\
+                 +--------+-------------------------+-------------\\\n\
+                 ------------+--------+--------+\n",
+            );
+    }
+
+    #[test]
+    fn conflicts_with_terminal_width() {
+        hexyl()
+            .arg("ascii")
+            .arg("--wrap=40")
+            .arg("--terminal-width=80")
+            .assert()
+            .failure();
+    }
+}
+
+mod mark_eof {
+    use super::hexyl;
+
+    #[test]
+    fn marks_end_of_input() {
+        hexyl()
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--mark-eof")
+            .write_stdin("0123")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 30 31 32 33▏            │0123    │\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            );
+    }
+}
+
+mod count_bytes {
+    use super::hexyl;
+
+    #[test]
+    fn counts_categories_per_range() {
+        hexyl()
+            .arg("ascii")
+            .arg("--count-bytes")
+            .arg("--range=0:4")
+            .arg("--range=0xc:")
+            .assert()
+            .success()
+            .stdout(
+                "Range                  Bytes   Null Printable Whitespace  Other Non-ASCII\n\
+                 00000000-00000004          4   0.0%    100.0%       0.0%   0.0%      0.0%\n\
+                 0000000c-end               4   0.0%     75.0%      25.0%   0.0%      0.0%\n",
+            );
+    }
+
+    #[test]
+    fn counts_in_parallel_with_jobs() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--count-bytes")
+            .arg("--jobs=3")
+            .arg("--range=3:0x1001")
+            .assert()
+            .success()
+            .stdout(
+                "Range                  Bytes   Null Printable Whitespace  Other Non-ASCII\n\
+                 00000003-00001001       4094  98.9%      0.3%       0.1%   0.6%      0.1%\n",
+            );
+    }
+
+    #[test]
+    fn jobs_requires_count_bytes() {
+        hexyl().arg("ascii").arg("--jobs=2").assert().failure();
+    }
+
+    #[test]
+    fn range_requires_count_bytes() {
+        hexyl().arg("ascii").arg("--range=0:4").assert().failure();
+    }
+
+    #[test]
+    fn fails_on_invalid_range() {
+        hexyl()
+            .arg("ascii")
+            .arg("--count-bytes")
+            .arg("--range=4:2")
+            .assert()
+            .failure();
+    }
+}
+
+mod default_args {
+    use super::hexyl;
+
+    #[test]
+    fn are_applied() {
+        hexyl()
+            .env("HEXYL_DEFAULT_ARGS", "--plain --panels=1")
+            .arg("ascii")
+            .assert()
+            .success()
+            .stdout(
+                "  30 31 32 33 34 35 36 37  \n  \
+                   38 39 61 62 63 64 65 0a  \n",
+            );
+    }
+
+    #[test]
+    fn are_overridden_by_command_line() {
+        hexyl()
+            .env("HEXYL_DEFAULT_ARGS", "--plain --panels=1")
+            .arg("dump")
+            .arg("ascii")
+            .arg("--panels=2")
+            .assert()
+            .success()
+            .stdout("  30 31 32 33 34 35 36 37   38 39 61 62 63 64 65 0a  \n");
+    }
+
+    #[test]
+    fn fail_with_invalid_quoting() {
+        hexyl()
+            .env("HEXYL_DEFAULT_ARGS", "'--plain")
+            .arg("ascii")
+            .assert()
+            .failure();
+    }
+}
+
+mod theme {
+    use super::hexyl;
+
+    #[test]
+    fn options_take_precedence_over_env() {
+        hexyl()
+            .env("HEXYL_COLOR_PRINTABLE", "red")
+            .env("HEXYL_COLOR_WHITESPACE", "blue")
+            .arg("ascii")
+            .arg("--color=always")
+            .arg("--color-ascii=bright-blue")
+            .arg("--color-offset=green")
+            .arg("--panels=1")
+            .arg("--border=none")
+            .assert()
+            .success()
+            .stdout(
+                " \x1b[32m00000000\x1b[39m  \x1b[94m30 31 32 33 34 35 36 37\x1b[39m  \
+                 \x1b[94m01234567\x1b[39m \n \
+                 \x1b[32m00000008\x1b[39m  \x1b[94m38 39 61 62 63 64 65 \x1b[34m0a\x1b[39m  \
+                 \x1b[94m89abcde\x1b[34m_\x1b[39m \n",
+            );
+    }
+
+    #[test]
+    fn unknown_color() {
+        hexyl()
+            .env("HEXYL_COLOR_NULL", "rd")
+            .arg("ascii")
+            .assert()
+            .code(2)
+            .stderr(predicates::str::contains(
+                "unknown color \"rd\" in HEXYL_COLOR_NULL",
+            ));
+    }
+}
+
+mod rules {
+    use super::hexyl;
+
+    #[test]
+    fn are_applied_to_matching_files() {
+        hexyl()
+            .env("XDG_CONFIG_HOME", "config")
+            .arg("ascii")
+            .arg("--color=never")
+            .assert()
+            .success()
+            .stdout(
+                " 00000000  30 31 32 33 34 35 36 37  01234567 \n \
+                  00000008  38 39 61 62 63 64 65 0a  89abcde_ \n",
+            );
+    }
+
+    #[test]
+    fn later_rules_and_command_line_take_precedence() {
+        hexyl()
+            .env("XDG_CONFIG_HOME", "config")
+            .env("HEXYL_DEFAULT_ARGS", "--border=none")
+            .arg("ranges.csv")
+            .arg("--length=4")
+            .arg("--color=never")
+            .assert()
+            .success()
+            .stdout(
+                "+--------+-------------------------+--------+\n\
+                 |00000000| 6f 66 66 73             |offs    |\n\
+                 +--------+-------------------------+--------+\n",
+            );
+    }
+
+    #[test]
+    fn are_not_applied_to_other_files() {
+        hexyl()
+            .env("XDG_CONFIG_HOME", "config")
+            .arg("empty")
+            .arg("--color=never")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┐\n\
+                 │        │ No content              │                         │        │        │\n\
+                 └────────┴─────────────────────────┴─────────────────────────┴────────┴────────┘\n",
+            );
+    }
+}
+
+mod cat {
+    use super::hexyl;
+
+    #[test]
+    fn concatenates_files() {
+        hexyl()
+            .arg("--cat")
+            .arg("ascii")
+            .arg("empty")
+            .arg("ascii")
+            .arg("--skip=12")
+            .arg("--length=8")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │0000000c│ 63 64 65 0a 30 31 32 33 │cde_0123│\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn boundaries() {
+        hexyl()
+            .arg("--cat")
+            .arg("ascii")
+            .arg("ascii")
+            .arg("--boundaries")
+            .arg("--display-offset=0x100")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000100│ 30 31 32 33 34 35 36 37 │01234567│ ascii\n\
+                 │00000108│ 38 39 61 62 63 64 65 0a │89abcde_│\n\
+                 │00000110│ 30 31 32 33 34 35 36 37 │01234567│ ascii\n\
+                 │00000118│ 38 39 61 62 63 64 65 0a │89abcde_│\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn conflicts_with_file() {
+        hexyl()
+            .arg("ascii")
+            .arg("--cat")
+            .arg("ascii")
+            .assert()
+            .code(2);
+    }
+}
+
+mod map {
+    use super::hexyl;
+
+    #[test]
+    fn places_files_at_offsets() {
+        hexyl()
+            .arg("--map")
+            .arg("ascii@0x40")
+            .arg("ascii@0")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 30 31 32 33 34 35 36 37 │01234567│ ascii\n\
+                 │00000008│ 38 39 61 62 63 64 65 0a │89abcde_│\n\
+                 │00000010│ __ __ __ __ __ __ __ __ │        │\n\
+                 │*       │                         │        │\n\
+                 │00000040│ 30 31 32 33 34 35 36 37 │01234567│ ascii\n\
+                 │00000048│ 38 39 61 62 63 64 65 0a │89abcde_│\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn colors_positions_per_file() {
+        hexyl()
+            .arg("--map")
+            .arg("ascii@0")
+            .arg("ascii@0x20")
+            .arg("--color=always")
+            .arg("--panels=1")
+            .arg("--no-squeezing")
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("│\x1b[94m00000008\x1b[39m│"))
+            .stdout(predicates::str::contains("│\x1b[90m00000010\x1b[39m│"))
+            .stdout(predicates::str::contains("│\x1b[92m00000020\x1b[39m│"));
+    }
+
+    #[test]
+    fn overlapping_files() {
+        hexyl()
+            .arg("--map")
+            .arg("ascii@0")
+            .arg("ascii@8")
+            .assert()
+            .code(2)
+            .stderr(predicates::str::contains(
+                "ascii at 0x0 overlaps ascii at 0x8",
+            ));
+    }
+
+    #[test]
+    fn invalid_mapping() {
+        hexyl().arg("--map").arg("ascii").assert().code(2);
+    }
+}
+
+mod exit_codes {
+    use super::hexyl;
+
+    #[test]
+    fn usage_error() {
+        hexyl().arg("ascii").arg("--length=x").assert().code(2);
+        hexyl()
+            .arg("ascii")
+            .arg("--unknown-option")
+            .assert()
+            .code(2);
+    }
+
+    #[test]
+    fn file_not_found() {
+        hexyl().arg("non-existing").assert().code(3);
+    }
+
+    #[test]
+    fn io_error() {
+        hexyl().arg(".").assert().code(4);
+    }
+
+    #[test]
+    fn no_match() {
+        hexyl()
+            .arg("ascii")
+            .arg("--filter-byte=0xcc")
+            .assert()
+            .code(5)
+            .stderr("Found 0 matching bytes in 0 lines\n");
+    }
+}
+
+mod recursive {
+    use super::hexyl;
+
+    #[test]
+    #[cfg(unix)]
+    fn dumps_files_in_order() {
+        hexyl()
+            .arg("tree")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--recursive")
+            .arg("--per-file-length=8")
+            .assert()
+            .success()
+            .stdout(
+                "==> tree/a.txt <==\n\
+                 ┌────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 66 69 72 73 74 20 66 69 │first fi│\n\
+                 └────────┴─────────────────────────┴────────┘\n\
+                 \n\
+                 ==> tree/sub/b.bin <==\n\
+                 ┌────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 30 31 32 33 34 35 36 37 │01234567│\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            )
+            .stderr("Dumped 2 files, 0 could not be read\n");
+    }
+
+    #[test]
+    fn per_file_length_requires_recursive() {
+        hexyl()
+            .arg("ascii")
+            .arg("--per-file-length=4")
+            .assert()
+            .code(2);
+    }
+}
+
+mod on_error {
+    use super::hexyl;
+
+    #[test]
+    fn prints_read_statistics() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--retries=3")
+            .arg("--on-error=zero")
+            .assert()
+            .success()
+            .stderr("Read statistics: 0 retries, 0 short reads\n");
+    }
+
+    #[test]
+    fn conflicts_with_step() {
+        hexyl()
+            .arg("ascii")
+            .arg("--on-error=skip")
+            .arg("--step=2")
+            .assert()
+            .code(2);
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod direct_io {
+    use super::hexyl;
+
+    #[test]
+    fn same_output() {
+        let dump = |direct_io: bool| {
+            let mut cmd = hexyl();
+            cmd.arg("hello_world_elf64")
+                .arg("--color=never")
+                .arg("--skip=100")
+                .arg("--length=5000");
+            if direct_io {
+                cmd.arg("--direct-io");
+            }
+            cmd.output().unwrap().stdout
+        };
+        assert_eq!(dump(true), dump(false));
+    }
+
+    #[test]
+    fn requires_file() {
+        hexyl()
+            .arg("--direct-io")
+            .write_stdin("abc")
+            .assert()
+            .code(2);
+    }
+}
+
+mod print_spans {
+    use super::hexyl;
+
+    #[test]
+    fn single_span() {
+        hexyl()
+            .arg("ascii")
+            .arg("--skip=4")
+            .arg("--length=10")
+            .arg("--print-spans")
+            .assert()
+            .success()
+            .stderr("start 0x4 (4), end 0xe (14), length 0xa (10)\n");
+    }
+
+    #[test]
+    fn spans_of_filtered_lines() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--panels=1")
+            .arg("--filter-byte=0x48")
+            .arg("--print-spans")
+            .assert()
+            .success()
+            .stderr(
+                "start 0x2000 (8192), end 0x2008 (8200), length 0x8 (8)\n\
+                 Found 1 matching bytes in 1 lines\n",
+            );
+    }
+}
+
+mod detect_stride {
+    use super::hexyl;
+
+    #[test]
+    fn uses_whole_records_per_line() {
+        let records: Vec<u8> = (0..100u8)
+            .flat_map(|i| {
+                [
+                    i, 0xca, 0xfe, 0, 0, 0, 0x10, 0x20, 0x30, 0x40, 0x50, 0x60, 0x70, i, 0xff, 1,
+                    2, 3, 4, 5, 6, 7, 8, 9,
+                ]
+            })
+            .collect();
+        hexyl()
+            .arg("--detect-stride")
+            .write_stdin(records)
+            .assert()
+            .success()
+            .stderr("Detected a stride of 24 bytes, showing 3 panels\n");
+    }
+
+    #[test]
+    fn no_stride() {
+        hexyl()
+            .arg("ascii")
+            .arg("--detect-stride")
+            .assert()
+            .success()
+            .stderr("No stride detected\n");
+    }
+}
+
+mod prompt {
+    use super::hexyl;
+
+    #[test]
+    fn moves_around() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--prompt")
+            .write_stdin("/Hello\n-8\nq\n")
+            .assert()
+            .success()
+            .stdout(predicates::str::contains(
+                "│00002000│ 48 65 6c 6c 6f 2c 20 77 │Hello, w│",
+            ))
+            .stdout(predicates::str::contains(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00001ff8│ 00 00 00 00 00 00 00 00 │⋄⋄⋄⋄⋄⋄⋄⋄│\n\
+                 │00002000│",
+            ));
+    }
+
+    #[test]
+    fn requires_file() {
+        hexyl().arg("--prompt").write_stdin("q\n").assert().code(2);
+    }
+}
+
+mod hyperlink_offsets {
+    use super::hexyl;
+    use predicates::boolean::PredicateBooleanExt;
+
+    #[test]
+    fn only_with_colors() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--hyperlink-offsets=https://example.com/{offset}")
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("\x1b").not());
+    }
+
+    #[test]
+    fn links_every_offset() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=always")
+            .arg("--panels=1")
+            .arg("--hyperlink-offsets=https://example.com/{offset}")
+            .assert()
+            .success()
+            .stdout(predicates::str::contains(
+                "\x1b]8;;https://example.com/8\x1b\\00000008\x1b]8;;\x1b\\",
+            ));
+    }
+}
+
+mod csv_ranges {
+    use super::hexyl;
+
+    #[test]
+    fn shows_labels() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--color=never")
+            .arg("--length=32")
+            .arg("--csv-ranges=ranges.csv")
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("│•ELF•••⋄┊⋄⋄⋄⋄⋄⋄⋄⋄│ magic, v1\n"))
+            .stdout(predicates::str::contains("┊⋄•@⋄⋄⋄⋄⋄│ e_type\n"));
+    }
+
+    #[test]
+    fn colors_regions() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--color=always")
+            .arg("--length=16")
+            .arg("--csv-ranges=ranges.csv")
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("\x1b[31m7f 45 4c 46 \x1b[32m02"));
+    }
+
+    #[test]
+    fn fails_for_invalid_file() {
+        hexyl()
+            .arg("ascii")
+            .arg("--csv-ranges=ascii")
+            .assert()
+            .failure()
+            .code(2)
+            .stderr(predicates::str::contains("line 1"));
+    }
+}
+
+mod comments {
+    use super::hexyl;
+
+    #[test]
+    fn shows_comments() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--color=never")
+            .arg("--length=32")
+            .arg("--comments=comments.tsv")
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("┊⋄⋄⋄⋄⋄⋄⋄⋄│ magic number\n"))
+            .stdout(predicates::str::contains(
+                "┊⋄•@⋄⋄⋄⋄⋄│ e_machine: x86-64\n│        │                         ┊                         │        ┊        │ entry point\n",
+            ));
+    }
+
+    #[test]
+    fn fails_for_invalid_file() {
+        hexyl()
+            .arg("ascii")
+            .arg("--comments=ascii")
+            .assert()
+            .failure()
+            .code(2)
+            .stderr(predicates::str::contains("line 1"));
+    }
+}
+
+mod output_format {
+    use super::hexyl;
+
+    #[test]
+    fn rle() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--output-format=rle")
+            .arg("--skip=0x1000")
+            .arg("--length=0x1010")
+            .assert()
+            .success()
+            .stdout(
+                "00001000 lit 16 ba 0e 00 00 00 b9 00 20 40 00 bb 01 00 00 00 b8\n\
+                 00001010 lit 13 04 00 00 00 cd 80 b8 01 00 00 00 cd 80\n\
+                 0000101d run 4067 00\n\
+                 00002000 lit 16 48 65 6c 6c 6f 2c 20 77 6f 72 6c 64 21 0a 00 2e\n",
+            );
+    }
+}
+
+mod plain_hex {
+    use super::hexyl;
+
+    #[test]
+    fn wraps_at_60_columns() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--plain-hex")
+            .arg("--length=38")
+            .assert()
+            .success()
+            .stdout(
+                "7f454c4602010100000000000000000002003e0001000000001040000000\n\
+                 0000400000000000\n",
+            );
+    }
+
+    #[test]
+    fn custom_columns() {
+        hexyl()
+            .arg("ascii")
+            .arg("--ps=9")
+            .assert()
+            .success()
+            .stdout("30313233\n34353637\n38396162\n6364650a\n");
+        hexyl()
+            .arg("ascii")
+            .arg("--plain-hex=0")
+            .assert()
+            .success()
+            .stdout("3031323334353637383961626364650a\n");
+    }
+
+    #[test]
+    fn empty_input() {
+        hexyl()
+            .arg("empty")
+            .arg("--plain-hex")
+            .assert()
+            .success()
+            .stdout("");
+    }
+
+    #[test]
+    fn fails_for_one_column() {
+        hexyl()
+            .arg("ascii")
+            .arg("--plain-hex=1")
+            .assert()
+            .failure()
+            .code(2);
+    }
+}
+
+mod convert {
+    use super::hexyl;
+
+    #[test]
+    fn hex_to_include() {
+        hexyl()
+            .arg("convert")
+            .arg("--from=hex")
+            .arg("--to=include")
+            .write_stdin("7f454c46\n02 01\n")
+            .assert()
+            .success()
+            .stdout(
+                "unsigned char buf[] = {\n  \
+                 0x7f, 0x45, 0x4c, 0x46, 0x02, 0x01\n\
+                 };\n\
+                 unsigned int buf_len = 6;\n",
+            );
+    }
+
+    #[test]
+    fn skip_and_length_count_the_bytes() {
+        hexyl()
+            .arg("convert")
+            .arg("--from=hex")
+            .arg("--to=raw")
+            .arg("--skip=1")
+            .arg("--length=2")
+            .write_stdin("30 31 32 33")
+            .assert()
+            .success()
+            .stdout("12");
+        hexyl()
+            .arg("convert")
+            .arg("ascii")
+            .arg("--to=include")
+            .arg("--name=digits")
+            .arg("--skip=4")
+            .arg("-n2")
+            .assert()
+            .success()
+            .stdout("unsigned char digits[] = {\n  0x34, 0x35\n};\nunsigned int digits_len = 2;\n");
+    }
+
+    #[test]
+    fn dump_to_hex() {
+        let dump = hexyl()
+            .arg("ascii")
+            .arg("--color=always")
+            .output()
+            .unwrap()
+            .stdout;
+        hexyl()
+            .arg("convert")
+            .arg("--from=dump")
+            .arg("--to=hex")
+            .write_stdin(dump)
+            .assert()
+            .success()
+            .stdout("3031323334353637383961626364650a\n");
+    }
+
+    #[test]
+    fn fails_for_invalid_hex() {
+        hexyl()
+            .arg("convert")
+            .arg("--from=hex")
+            .arg("--to=raw")
+            .write_stdin("30 3g")
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains(
+                "expected a hex digit at position 0x4, found 'g'",
+            ));
+    }
+
+    #[test]
+    fn fails_for_invalid_name() {
+        hexyl()
+            .arg("convert")
+            .arg("ascii")
+            .arg("--to=include")
+            .arg("--name=1x")
+            .assert()
+            .code(2);
+    }
+}
+
+mod diff {
+    use super::hexyl;
+
+    #[test]
+    fn identical_files() {
+        hexyl()
+            .arg("--diff")
+            .arg("ascii")
+            .arg("ascii")
+            .arg("--color=never")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─[ascii]─────────────────┬────────┬─[ascii]─────────────────┬────────┐\n\
+                 │00000000│ 30 31 32 33 34 35 36 37 │01234567│ 30 31 32 33 34 35 36 37 │01234567│\n\
+                 │00000008│ 38 39 61 62 63 64 65 0a │89abcde_│ 38 39 61 62 63 64 65 0a │89abcde_│\n\
+                 └────────┴─────────────────────────┴────────┴─────────────────────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn different_files() {
+        hexyl()
+            .arg("diff")
+            .arg("ascii")
+            .arg("hello_world_elf64")
+            .arg("--color=never")
+            .arg("--border=none")
+            .arg("--panels=2")
+            .assert()
+            .failure()
+            .code(6)
+            .stderr(predicates::str::contains(
+                "the files differ in 8488 bytes, starting at 0x0",
+            ));
+    }
+
+    #[test]
+    fn highlights_differences() {
+        hexyl()
+            .arg("diff")
+            .arg("ascii")
+            .arg("empty")
+            .arg("--color=always")
+            .assert()
+            .code(6)
+            .stdout(predicates::str::contains("\x1b[7m30\x1b[27m"));
+    }
+
+    #[test]
+    fn fails_for_missing_file() {
+        hexyl()
+            .arg("diff")
+            .arg("ascii")
+            .arg("does-not-exist")
+            .assert()
+            .failure()
+            .code(3);
+    }
+}
+
+mod include {
+    use super::hexyl;
+
+    #[test]
+    fn name_from_file() {
+        hexyl()
+            .arg("ascii")
+            .arg("--include")
+            .assert()
+            .success()
+            .stdout(
+                "unsigned char ascii[] = {\n  \
+                 0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x61, 0x62,\n  \
+                 0x63, 0x64, 0x65, 0x0a\n\
+                 };\n\
+                 unsigned int ascii_len = 16;\n",
+            );
+    }
+
+    #[test]
+    fn custom_name() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--include=elf_header")
+            .arg("--length=4")
+            .assert()
+            .success()
+            .stdout(
+                "unsigned char elf_header[] = {\n  \
+                 0x7f, 0x45, 0x4c, 0x46\n\
+                 };\n\
+                 unsigned int elf_header_len = 4;\n",
+            );
+    }
+
+    #[test]
+    fn stdin() {
+        hexyl()
+            .arg("--include")
+            .write_stdin("hi")
+            .assert()
+            .success()
+            .stdout("unsigned char buf[] = {\n  0x68, 0x69\n};\nunsigned int buf_len = 2;\n");
+    }
+
+    #[test]
+    fn fails_for_invalid_name() {
+        hexyl()
+            .arg("ascii")
+            .arg("--include=my-blob")
+            .assert()
+            .failure()
+            .code(2);
+    }
+}
+
+mod porcelain {
+    use super::hexyl;
+
+    // The output of `--porcelain=v1` is guaranteed to stay the same, so these
+    // tests must never change.
+
+    #[test]
+    fn v1() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--porcelain=v1")
+            .arg("--skip=0x1000")
+            .arg("--length=0x13")
+            .assert()
+            .success()
+            .stdout(
+                "0000000000001000: ba 0e 00 00 00 b9 00 20 40 00 bb 01 00 00 00 b8\n\
+                 0000000000001010: 04 00 00\n",
+            );
+    }
+
+    #[test]
+    fn v1_does_not_squeeze() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--porcelain=v1")
+            .arg("--skip=0x1020")
+            .arg("--length=0x30")
+            .assert()
+            .success()
+            .stdout(
+                "0000000000001020: 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00\n\
+                 0000000000001030: 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00\n\
+                 0000000000001040: 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00\n",
+            );
+    }
+
+    #[test]
+    fn v1_ignores_display_options() {
+        hexyl()
+            .arg("ascii")
+            .arg("--porcelain=v1")
+            .arg("--color=always")
+            .arg("--border=ascii")
+            .arg("--panels=1")
+            .arg("--base=binary")
+            .arg("--group-size=4")
+            .assert()
+            .success()
+            .stdout("0000000000000000: 30 31 32 33 34 35 36 37 38 39 61 62 63 64 65 0a\n");
+    }
+
+    #[test]
+    fn v1_ignores_default_args() {
+        hexyl()
+            .env("HEXYL_DEFAULT_ARGS", "--length=4")
+            .arg("ascii")
+            .arg("--porcelain=v1")
+            .assert()
+            .success()
+            .stdout("0000000000000000: 30 31 32 33 34 35 36 37 38 39 61 62 63 64 65 0a\n");
+    }
+
+    #[test]
+    fn v1_empty_input() {
+        hexyl()
+            .arg("empty")
+            .arg("--porcelain=v1")
+            .assert()
+            .success()
+            .stdout("");
+    }
+}
+
+mod broken_pipe {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::process::{Command, Stdio};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    /// Runs hexyl on a large input from stdin, reads the first line of its
+    /// output and closes the pipe. Checks that hexyl exits successfully soon
+    /// after, without an error message.
+    fn close_output_early(args: &[&str]) {
+        let mut child = Command::new(assert_cmd::cargo::cargo_bin("hexyl"))
+            .args(args)
+            .env_remove("HEXYL_DEFAULT_ARGS")
+            .env("XDG_CONFIG_HOME", "no-config")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        // 64 MiB without repeated lines, so that nothing is squeezed. The
+        // writes fail once hexyl has exited.
+        let mut stdin = child.stdin.take().unwrap();
+        thread::spawn(move || {
+            let buf: Vec<u8> = (0..1024 * 1024).map(|i| (i % 251) as u8).collect();
+            for _ in 0..64 {
+                if stdin.write_all(&buf).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        assert!(!line.is_empty());
+        drop(stdout);
+
+        let start = Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait().unwrap() {
+                break status;
+            }
+            if start.elapsed() > Duration::from_secs(10) {
+                child.kill().unwrap();
+                panic!("hexyl {args:?} did not exit after its output was closed");
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+        let mut stderr = String::new();
+        child
+            .stderr
+            .take()
+            .unwrap()
+            .read_to_string(&mut stderr)
+            .unwrap();
+        assert!(status.success(), "hexyl {args:?} failed: {stderr}");
+        assert_eq!(stderr, "", "hexyl {args:?}");
+    }
+
+    #[test]
+    fn default() {
+        close_output_early(&[]);
+    }
+
+    #[test]
+    fn plain() {
+        close_output_early(&["--plain", "--color=always"]);
+    }
+
+    #[test]
+    fn wrap() {
+        close_output_early(&["--wrap=40"]);
+    }
+
+    #[test]
+    fn porcelain() {
+        close_output_early(&["--porcelain=v1"]);
+    }
+
+    #[test]
+    fn rle() {
+        close_output_early(&["--output-format=rle"]);
+    }
+
+    #[test]
+    fn filter_byte() {
+        close_output_early(&["--filter-byte=0x00", "--print-spans"]);
+    }
+}
+
+#[cfg(feature = "yara")]
+mod yara {
+    use super::hexyl;
+
+    #[test]
+    fn highlights_and_summarizes_matches() {
+        hexyl()
+            .arg("ascii")
+            .arg("--yara=rules.yar")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 30 31 32 33 34 35 36 37 │01234567│\n\
+                 │00000008│ 38 39 61 62 63 64 65 0a │89abcde_│ newline:$nl\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            )
+            .stderr("YARA rule newline matched $nl at offset 0xf (15), length 1\n");
+    }
+
+    #[test]
+    fn matches_follow_skip_and_display_offset() {
+        hexyl()
+            .arg("ascii")
+            .arg("--yara=rules.yar")
+            .arg("--skip=8")
+            .arg("--display-offset=0x100")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000108│ 38 39 61 62 63 64 65 0a │89abcde_│ newline:$nl\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn invalid_rules() {
+        hexyl()
+            .arg("ascii")
+            .arg("--yara=ranges.csv")
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains(
+                "failed to compile the YARA rules",
+            ));
+    }
+}
+
+#[cfg(all(feature = "tui", unix))]
+mod interactive {
+    use super::hexyl;
+
+    #[test]
+    fn needs_a_terminal() {
+        hexyl()
+            .arg("ascii")
+            .arg("--interactive")
+            .assert()
+            .failure()
+            .code(2)
+            .stderr(predicates::str::contains(
+                "`--interactive` needs a terminal",
+            ));
+    }
+
+    #[test]
+    fn requires_a_file() {
+        hexyl().arg("--interactive").assert().failure().code(2);
+    }
+
+    #[test]
+    fn conflicts_with_other_outputs() {
+        for arg in [
+            "--include",
+            "--plain-hex",
+            "--format=json",
+            "--output-format=rle",
+            "--porcelain=v1",
+            "--verify=ascii",
+            "--auto-text",
+            #[cfg(target_os = "linux")]
+            "--direct-io",
+        ] {
+            hexyl()
+                .arg("ascii")
+                .arg("--interactive")
+                .arg(arg)
+                .assert()
+                .failure()
+                .code(2)
+                .stderr(predicates::str::contains("cannot be used with"));
+        }
+    }
+
+    /// hexyl running with the secondary side of a new pseudo terminal as its
+    /// controlling terminal, and with stderr piped.
+    struct Terminal {
+        primary: std::fs::File,
+        secondary: std::fs::File,
+        child: std::process::Child,
+    }
+
+    impl Terminal {
+        fn start(args: &[&str]) -> Terminal {
+            use std::ffi::CStr;
+            use std::fs::{File, OpenOptions};
+            use std::os::unix::io::{AsRawFd, FromRawFd};
+            use std::os::unix::process::CommandExt;
+            use std::process::{Command, Stdio};
+
+            let primary = unsafe {
+                let fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+                assert!(fd >= 0, "failed to open a pseudo terminal");
+                assert_eq!(libc::grantpt(fd), 0);
+                assert_eq!(libc::unlockpt(fd), 0);
+                File::from_raw_fd(fd)
+            };
+            let name = unsafe { CStr::from_ptr(libc::ptsname(primary.as_raw_fd())) }
+                .to_str()
+                .unwrap()
+                .to_owned();
+            let secondary = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(name)
+                .unwrap();
+
+            let mut command = Command::new(assert_cmd::cargo::cargo_bin("hexyl"));
+            command
+                .current_dir("tests/examples")
+                .env_remove("HEXYL_DEFAULT_ARGS")
+                .env("XDG_CONFIG_HOME", "no-config")
+                .args(args)
+                .stdin(secondary.try_clone().unwrap())
+                .stdout(secondary.try_clone().unwrap())
+                .stderr(Stdio::piped());
+            // Makes the pseudo terminal the controlling terminal, for `/dev/tty`.
+            unsafe {
+                command.pre_exec(|| {
+                    libc::setsid();
+                    libc::ioctl(0, libc::TIOCSCTTY as _, 0);
+                    Ok(())
+                });
+            }
+            let child = command.spawn().unwrap();
+            Terminal {
+                primary,
+                secondary,
+                child,
+            }
+        }
+
+        /// The local modes of the terminal, e.g. `ICANON` unless it is in raw
+        /// mode.
+        fn lflag(&self) -> libc::tcflag_t {
+            use std::os::unix::io::AsRawFd;
+
+            let mut termios = unsafe { std::mem::zeroed::<libc::termios>() };
+            assert_eq!(
+                unsafe { libc::tcgetattr(self.secondary.as_raw_fd(), &mut termios) },
+                0
+            );
+            termios.c_lflag
+        }
+
+        fn wait_for_raw_mode(&self) {
+            use std::thread;
+            use std::time::{Duration, Instant};
+
+            let start = Instant::now();
+            while self.lflag() & libc::ICANON != 0 {
+                assert!(start.elapsed() < Duration::from_secs(10), "no raw mode");
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+    }
+
+    #[test]
+    fn restores_the_terminal_on_sigterm() {
+        let mut terminal = Terminal::start(&["--interactive", "ascii"]);
+        assert_ne!(terminal.lflag() & libc::ICANON, 0);
+        terminal.wait_for_raw_mode();
+        unsafe { libc::kill(terminal.child.id() as libc::pid_t, libc::SIGTERM) };
+        let status = terminal.child.wait().unwrap();
+        assert_eq!(status.code(), Some(128 + libc::SIGTERM));
+        assert_ne!(terminal.lflag() & libc::ICANON, 0);
+        assert_ne!(terminal.lflag() & libc::ECHO, 0);
+    }
+
+    #[test]
+    fn writes_no_hints_over_the_screen() {
+        use std::io::Write;
+
+        let path = std::env::temp_dir().join(format!("hexyl-{}-shebang", std::process::id()));
+        std::fs::write(&path, "#!/bin/sh\necho hello world, this is text\n").unwrap();
+        let mut terminal = Terminal::start(&["--interactive", path.to_str().unwrap()]);
+        terminal.wait_for_raw_mode();
+        terminal.primary.write_all(b"jq").unwrap();
+        let output = terminal.child.wait_with_output().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+    }
+}
+
+#[cfg(unix)]
+mod status_fd {
+    use super::hexyl;
+
+    #[test]
+    fn writes_events_to_stderr() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--color=never")
+            .arg("--status-fd=2")
+            .arg("--find-text=Hello")
+            .arg("--skip=0x1f00")
+            .assert()
+            .success()
+            .stderr(
+                "{\"event\":\"match\",\"position\":8192,\"pattern\":0}\n\
+                 {\"event\":\"summary\",\"bytes_read\":552,\"matches\":1,\"squeezed_bytes\":0}\n\
+                 Found 1 match at 0x2000\n",
+            );
+    }
+
+    #[test]
+    fn reports_squeezed_regions() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--status-fd=2")
+            .arg("--length=0x1000")
+            .assert()
+            .success()
+            .stderr(
+                "{\"event\":\"squeezed\",\"start\":256,\"end\":4096,\"bytes\":3840,\"byte\":0}\n\
+                 {\"event\":\"summary\",\"bytes_read\":4096,\"matches\":0,\"squeezed_bytes\":3840}\n",
+            );
+    }
+
+    #[test]
+    fn fails_for_closed_descriptors() {
+        hexyl()
+            .arg("ascii")
+            .arg("--status-fd=99")
+            .assert()
+            .failure()
+            .code(2)
+            .stderr(predicates::str::contains("failed to open `--status-fd`"));
+    }
+}
+
+#[cfg(feature = "dwarf")]
+mod dwarf {
+    use super::hexyl;
+
+    #[test]
+    fn highlights_struct_fields() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--dwarf=structs.debug")
+            .arg("--type=point")
+            .arg("--at=0x12")
+            .arg("--skip=0x10")
+            .arg("--length=16")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000010│ 02 00 3e 00 01 00 00 00 │•⋄>⋄•⋄⋄⋄│ x (2 bytes), y (2 bytes)\n\
+                 │00000018│ 00 10 40 00 00 00 00 00 │⋄•@⋄⋄⋄⋄⋄│\n\
+                 └────────┴─────────────────────────┴────────┘\n\
+                 x (2 bytes)  0x12..0x14\n\
+                 y (2 bytes)  0x14..0x16\n",
+            );
+    }
+
+    #[test]
+    fn unknown_type() {
+        hexyl()
+            .arg("ascii")
+            .arg("--dwarf=structs.debug")
+            .arg("--type=missing")
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains(
+                "no type `missing` in the debug information",
+            ));
+    }
+
+    #[test]
+    fn type_requires_dwarf() {
+        hexyl().arg("ascii").arg("--type=point").assert().failure();
+    }
+}
+
+mod reverse {
+    use super::hexyl;
+
+    #[test]
+    fn restores_the_input() {
+        let dump = hexyl()
+            .arg("hello_world_elf64")
+            .arg("--color=always")
+            .arg("--border=ascii")
+            .arg("--group-size=2")
+            .output()
+            .unwrap()
+            .stdout;
+        hexyl()
+            .arg("--reverse")
+            .arg("--group-size=2")
+            .write_stdin(dump)
+            .assert()
+            .success()
+            .stdout(std::fs::read("tests/examples/hello_world_elf64").unwrap());
+    }
+
+    #[test]
+    fn restores_every_offset_style() {
+        let input = std::fs::read("tests/examples/hello_world_elf64").unwrap();
+        for offset_style in ["byte", "sector", "segment"] {
+            for border in ["unicode", "none"] {
+                let dump = hexyl()
+                    .arg("hello_world_elf64")
+                    .arg("--color=never")
+                    .arg(format!("--border={border}"))
+                    .arg(format!("--offset-style={offset_style}"))
+                    .arg("--sector-size=256")
+                    .output()
+                    .unwrap()
+                    .stdout;
+                hexyl()
+                    .arg("--reverse")
+                    .arg(format!("--offset-style={offset_style}"))
+                    .arg("--sector-size=256")
+                    .write_stdin(dump)
+                    .assert()
+                    .success()
+                    .stdout(input.clone());
+            }
+        }
+    }
+
+    #[test]
+    fn restores_positions_before_relative_to() {
+        let input = std::fs::read("tests/examples/hello_world_elf64").unwrap();
+        for offset_style in ["byte", "sector"] {
+            let dump = hexyl()
+                .arg("hello_world_elf64")
+                .arg("--color=never")
+                .arg("--relative-to=0x10")
+                .arg(format!("--offset-style={offset_style}"))
+                .output()
+                .unwrap()
+                .stdout;
+            assert!(String::from_utf8_lossy(&dump).contains("-00000"));
+            hexyl()
+                .arg("--reverse")
+                .arg(format!("--offset-style={offset_style}"))
+                .write_stdin(dump)
+                .assert()
+                .success()
+                .stdout(input.clone());
+        }
+    }
+
+    #[test]
+    fn fails_for_sector_positions_without_offset_style() {
+        hexyl()
+            .arg("--reverse")
+            .write_stdin("│LBA 00000000 +000│ 30 31 32 33 34 35 36 37 │01234567│\n")
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains("'--offset-style=sector'"));
+    }
+
+    #[test]
+    fn fails_for_unknown_positions() {
+        hexyl()
+            .arg("--reverse")
+            .write_stdin(
+                "│00000000│ 30 31 32 33 34 35 36 37 │01234567│\n\
+                 │0000000g│ 30 31 32 33 34 35 36 37 │01234567│\n",
+            )
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains(
+                "position panel \"0000000g\" not supported by --reverse",
+            ));
+    }
+
+    #[test]
+    fn writes_output_file() {
+        let path = std::env::temp_dir().join(format!("hexyl-{}-reverse", std::process::id()));
+        let assert = hexyl()
+            .arg("--reverse")
+            .arg("--output")
+            .arg(&path)
+            .write_stdin(" 00000000  30 31 32 33  \n")
+            .assert();
+        let output = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert.success().stdout("");
+        assert_eq!(output, b"0123");
+    }
+
+    #[test]
+    fn fails_for_other_base() {
+        hexyl()
+            .arg("--reverse")
+            .arg("--base=octal")
+            .write_stdin("│00000000│ 30 31 32 33 34 35 36 37 │01234567│\n")
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains("found no hex panel"));
+    }
+
+    #[test]
+    fn conflicts_with_debug_style() {
+        hexyl()
+            .arg("--reverse")
+            .arg("--style=debug")
+            .write_stdin("0000:0000  30 31 32 33                                        0123\n")
+            .assert()
+            .failure()
+            .code(2)
+            .stderr(predicates::str::contains("cannot be used with"));
+    }
+
+    /// Runs `hexyl --reverse` with the secondary side of a new pseudo terminal
+    /// as stdout, and returns the output and what arrived at the terminal.
+    #[cfg(unix)]
+    fn reverse_to_terminal(args: &[&str]) -> (std::process::Output, Vec<u8>) {
+        use std::ffi::CStr;
+        use std::fs::{File, OpenOptions};
+        use std::io::{Read, Write};
+        use std::os::unix::io::FromRawFd;
+        use std::process::{Command, Stdio};
+
+        let mut primary = unsafe {
+            let fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+            assert!(fd >= 0, "failed to open a pseudo terminal");
+            assert_eq!(libc::grantpt(fd), 0);
+            assert_eq!(libc::unlockpt(fd), 0);
+            File::from_raw_fd(fd)
+        };
+        let name = unsafe {
+            use std::os::unix::io::AsRawFd;
+            CStr::from_ptr(libc::ptsname(primary.as_raw_fd()))
+                .to_str()
+                .unwrap()
+                .to_owned()
+        };
+        let secondary = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(name)
+            .unwrap();
+
+        let mut child = Command::new(assert_cmd::cargo::cargo_bin("hexyl"))
+            .current_dir("tests/examples")
+            .env_remove("HEXYL_DEFAULT_ARGS")
+            .env("XDG_CONFIG_HOME", "no-config")
+            .arg("--reverse")
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(secondary)
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        // hexyl exits without reading the dump if it refuses the terminal.
+        let _ = child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(b" 00000000  30 31 32 33  \n");
+        let output = child.wait_with_output().unwrap();
+
+        let mut written = Vec::new();
+        if output.status.success() {
+            let mut buf = [0; 64];
+            let n = primary.read(&mut buf).unwrap();
+            written.extend_from_slice(&buf[..n]);
+        }
+        (output, written)
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn refuses_to_write_to_a_terminal() {
+        let (output, _) = reverse_to_terminal(&[]);
+        assert_eq!(output.status.code(), Some(2));
+        assert!(String::from_utf8_lossy(&output.stderr)
+            .contains("refusing to write the binary output of `--reverse` to a terminal"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn force_tty_writes_to_a_terminal() {
+        let (output, written) = reverse_to_terminal(&["--force-tty"]);
+        assert!(output.status.success());
+        assert_eq!(written, b"0123");
+    }
+}
+
+mod verify {
+    use super::hexyl;
+    use std::path::PathBuf;
+
+    fn expected_path(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("hexyl-{}-{name}.txt", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    const EXPECTED: &str = "┌────────┬─────────────────────────┬────────┐\n\
+                            │00000000│ 30 31 32 33 34 35 36 37 │01234567│\n\
+                            │00000008│ 38 39 61 62 63 64 65 0a │89abcde_│\n\
+                            └────────┴─────────────────────────┴────────┘\n";
+
+    #[test]
+    fn matching_dump() {
+        let path = expected_path("matching", EXPECTED);
+        let assert = hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--verify")
+            .arg(&path)
+            .assert();
+        std::fs::remove_file(&path).unwrap();
+        assert.success().stdout("");
+    }
+
+    #[test]
+    fn differing_dump() {
+        let path = expected_path("differing", EXPECTED);
+        let assert = hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--skip=8")
+            .arg("--verify")
+            .arg(&path)
+            .assert();
+        std::fs::remove_file(&path).unwrap();
+        assert
+            .failure()
+            .code(6)
+            .stdout(format!(
+                "--- {}\n\
+                 +++ dump\n\
+                 @@ -1,4 +1,3 @@\n \
+                 ┌────────┬─────────────────────────┬────────┐\n\
+                 -│00000000│ 30 31 32 33 34 35 36 37 │01234567│\n \
+                 │00000008│ 38 39 61 62 63 64 65 0a │89abcde_│\n \
+                 └────────┴─────────────────────────┴────────┘\n",
+                path.display()
+            ))
+            .stderr(predicates::str::contains("the dump differs from"));
+    }
+
+    #[test]
+    fn missing_expected_file() {
+        hexyl()
+            .arg("ascii")
+            .arg("--verify=missing.txt")
+            .assert()
+            .failure()
+            .code(3);
+    }
+}
+
+#[cfg(feature = "session")]
+mod session {
+    use super::hexyl;
+    use std::path::PathBuf;
+
+    fn session_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("hexyl-{}-{name}.hxz", std::process::id()))
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = session_path("save-and-load");
+        let expected = "┌────────┬─────────────────────────┬────────┐\n\
+                        │00000004│ 34 35 36 37             │4567    │\n\
+                        └────────┴─────────────────────────┴────────┘\n";
+        hexyl()
+            .arg("ascii")
+            .arg("--skip=4")
+            .arg("--length=4")
+            .arg("--panels=1")
+            .arg("--color=never")
+            .arg("--save-session")
+            .arg(&path)
+            .assert()
+            .success()
+            .stdout(expected);
+        // The default options of the environment are not used for a session.
+        let loaded = hexyl()
+            .env("HEXYL_DEFAULT_ARGS", "--border=none")
+            .arg("--load-session")
+            .arg(&path)
+            .assert();
+        std::fs::remove_file(&path).unwrap();
+        loaded.success().stdout(expected);
+    }
+
+    #[test]
+    fn save_default_args_and_stdin() {
+        let path = session_path("default-args-and-stdin");
+        hexyl()
+            .env("HEXYL_DEFAULT_ARGS", "--plain")
+            .arg("--save-session")
+            .arg(&path)
+            .write_stdin("hexyl")
+            .assert()
+            .success();
+        let loaded = hexyl().arg("--load-session").arg(&path).assert();
+        std::fs::remove_file(&path).unwrap();
+        loaded
+            .success()
+            .stdout("  68 65 78 79 6c                                     \n");
+    }
+
+    #[test]
+    fn load_is_exclusive() {
+        hexyl()
+            .arg("ascii")
+            .arg("--load-session=session.hxz")
+            .assert()
+            .failure()
+            .code(2);
+    }
+
+    #[test]
+    fn load_invalid_session() {
+        hexyl()
+            .arg("--load-session=ascii")
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains("not a hexyl session file"));
+    }
+}
+
+#[cfg(feature = "decompress")]
+mod decompress {
+    use super::hexyl;
+
+    #[test]
+    fn gzip_members() {
+        hexyl()
+            .arg("members.gz")
+            .arg("--decompress")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .assert()
+            .success()
+            .stdout(
+                "==> gzip member 1 at compressed offset 0x0 <==\n\
+                 ┌────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 66 69 72 73 74 20 6d 65 │first me│\n\
+                 │00000008│ 6d 62 65 72 0a          │mber_   │\n\
+                 └────────┴─────────────────────────┴────────┘\n\
+                 \n\
+                 ==> gzip member 2 at compressed offset 0x21 <==\n\
+                 ┌────────┬─────────────────────────┬────────┐\n\
+                 │0000000d│ 73 65 63 6f 6e 64 0a    │second_ │\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn zstd_frames_with_reset_offsets() {
+        hexyl()
+            .arg("members.zst")
+            .arg("--decompress")
+            .arg("--member-offsets=reset")
+            .arg("--length=4")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .assert()
+            .success()
+            .stdout(
+                "==> zstd frame 1 at compressed offset 0x0 <==\n\
+                 ┌────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 66 69 72 73             │firs    │\n\
+                 └────────┴─────────────────────────┴────────┘\n\
+                 \n\
+                 ==> zstd frame 2 at compressed offset 0x24 <==\n\
+                 ┌────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 73 65 63 6f             │seco    │\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn fails_for_uncompressed_input() {
+        hexyl()
+            .arg("ascii")
+            .arg("--decompress")
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains(
+                "neither gzip nor zstd compressed",
+            ));
+    }
+}
+
+mod max_memory {
+    use super::hexyl;
+
+    #[test]
+    fn limits_spans() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--filter-byte=0x40")
+            .arg("--print-spans")
+            .arg("--max-memory=16")
+            .assert()
+            .failure()
+            .code(4)
+            .stderr(predicates::str::contains(
+                "recording the spans needs more than the allowed 16 bytes of memory",
+            ));
+    }
+
+    #[test]
+    fn allows_spans_within_limit() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--filter-byte=0x40")
+            .arg("--print-spans")
+            .arg("--max-memory=1KiB")
+            .assert()
+            .success();
+    }
+
+    #[test]
+    fn limits_verify_dump() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--verify=missing.txt")
+            .arg("--max-memory=1KiB")
+            .assert()
+            .failure()
+            .code(4)
+            .stderr(predicates::str::contains(
+                "the dump of `--verify` needs more than the allowed 1024 bytes of memory",
+            ));
+    }
+
+    #[test]
+    fn limits_csv_ranges_file() {
+        hexyl()
+            .arg("ascii")
+            .arg("--csv-ranges=ranges.csv")
+            .arg("--max-memory=8")
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains(
+                "the `--csv-ranges` file needs 61 bytes of memory, more than `--max-memory` \
+                 allows (8 bytes)",
+            ));
+    }
+}
+
+mod group_and_endianness {
+    use super::hexyl;
+    use super::PrettyAssert;
+
+    #[test]
+    fn group_2_bytes_be() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--group-size=2")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────┬─────────────────────┬────────┬────────┐\n\
+                 │00000000│ 3031 3233 3435 3637 ┊ 3839 6162 6364 650a │01234567┊89abcde_│\n\
+                 └────────┴─────────────────────┴─────────────────────┴────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn group_2_bytes_both_endianness() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--group-size=2")
+            .arg("--show-both-endianness")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────┬─────────────────────┬────────┬────────┐\n\
+                 │00000000│ 3031 3233 3435 3637 ┊ 3839 6162 6364 650a │01234567┊89abcde_│\n\
+                 │      le│ 3130 3332 3534 3736 ┊ 3938 6261 6463 0a65 │        ┊        │\n\
+                 └────────┴─────────────────────┴─────────────────────┴────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn group_2_bytes_le() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--group-size=2")
+            .arg("--endianness=little")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────┬─────────────────────┬────────┬────────┐\n\
+                 │00000000│ 3130 3332 3534 3736 ┊ 3938 6261 6463 0a65 │01234567┊89abcde_│\n\
+                 └────────┴─────────────────────┴─────────────────────┴────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn group_4_bytes_be() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--group-size=4")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬───────────────────┬───────────────────┬────────┬────────┐\n\
+                 │00000000│ 30313233 34353637 ┊ 38396162 6364650a │01234567┊89abcde_│\n\
+                 └────────┴───────────────────┴───────────────────┴────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn group_4_bytes_le() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--group-size=4")
+            .arg("--endianness=little")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬───────────────────┬───────────────────┬────────┬────────┐\n\
+                 │00000000│ 33323130 37363534 ┊ 62613938 0a656463 │01234567┊89abcde_│\n\
+                 └────────┴───────────────────┴───────────────────┴────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn group_8_bytes_be() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--group-size=8")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬──────────────────┬──────────────────┬────────┬────────┐\n\
+                 │00000000│ 3031323334353637 ┊ 383961626364650a │01234567┊89abcde_│\n\
+                 └────────┴──────────────────┴──────────────────┴────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn group_8_bytes_le() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--group-size=8")
+            .arg("--endianness=little")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬──────────────────┬──────────────────┬────────┬────────┐\n\
+                 │00000000│ 3736353433323130 ┊ 0a65646362613938 │01234567┊89abcde_│\n\
+                 └────────┴──────────────────┴──────────────────┴────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn group_size_plain() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--plain")
+            .arg("--group-size=2")
+            .assert()
+            .success()
+            .stdout("  3031 3233 3435 3637   3839 6162 6364 650a  \n");
+    }
+
+    #[test]
+    fn group_size_fill_space() {
+        hexyl()
+            .arg("--color=never")
+            .arg("--group-size=2")
+            .write_stdin("abc")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────┬─────────────────────┬────────┬────────┐\n\
+                 │00000000│ 6162 63             ┊                     │abc     ┊        │\n\
+                 └────────┴─────────────────────┴─────────────────────┴────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn group_size_invalid() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--plain")
+            .arg("--group-size=3")
+            .assert()
+            .failure();
+    }
+    #[test]
+    fn squeeze_no_chars() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--color=never")
+            .arg("--skip=1024")
+            .arg("--length=4096")
+            .arg("--no-characters")
+            .assert()
+            .success()
+            .pretty_stdout(
+                "\
+┌────────┬─────────────────────────┬─────────────────────────┐
+│00000400│ 00 00 00 00 00 00 00 00 ┊ 00 00 00 00 00 00 00 00 │
+│*       │                         ┊                         │
+│00001000│ ba 0e 00 00 00 b9 00 20 ┊ 40 00 bb 01 00 00 00 b8 │
 │00001010│ 04 00 00 00 cd 80 b8 01 ┊ 00 00 00 cd 80 00 00 00 │
 │00001020│ 00 00 00 00 00 00 00 00 ┊ 00 00 00 00 00 00 00 00 │
 │*       │                         ┊                         │
@@ -613,10 +3771,141 @@ mod base {
             .assert()
             .success()
             .pretty_stdout(
-                "  00110000 00110001 00110010 00110011 00110100 00110101 00110110 00110111  \n  \
-                   00111000 00111001 01100001 01100010 01100011 01100100 01100101 00001010  \n",
+                "  00110000 00110001 00110010 00110011 00110100 00110101 00110110 00110111   \
+                 00111000 00111001 01100001 01100010 01100011 01100100 01100101 00001010  \n",
             );
     }
+
+    /// Dumps 13 bytes with two panels, so that the last group and the last
+    /// panel are incomplete.
+    fn dump_groups(base: &str, group_size: u8) -> String {
+        let output = hexyl()
+            .arg("hello_world_elf64")
+            .arg("--skip=0x3c")
+            .arg("--length=13")
+            .arg("--panels=2")
+            .arg("--color=never")
+            .arg(format!("--base={base}"))
+            .arg(format!("--group-size={group_size}"))
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn groups_binary() {
+        assert_eq!(
+            dump_groups("binary", 1),
+            "┌────────┬─────────────────────────────────────────────────────────────────────────┬─────────────────────────────────────────────────────────────────────────┬────────┬────────┐\n\
+             │0000003c│ 00000100 00000000 00000011 00000000 00000001 00000000 00000000 00000000 ┊ 00000100 00000000 00000000 00000000 00000000                            │•⋄•⋄•⋄⋄⋄┊•⋄⋄⋄⋄   │\n\
+             └────────┴─────────────────────────────────────────────────────────────────────────┴─────────────────────────────────────────────────────────────────────────┴────────┴────────┘\n",
+        );
+        assert_eq!(
+            dump_groups("binary", 2),
+            "┌────────┬─────────────────────────────────────────────────────────────────────┬─────────────────────────────────────────────────────────────────────┬────────┬────────┐\n\
+             │0000003c│ 0000010000000000 0000001100000000 0000000100000000 0000000000000000 ┊ 0000010000000000 0000000000000000 00000000                          │•⋄•⋄•⋄⋄⋄┊•⋄⋄⋄⋄   │\n\
+             └────────┴─────────────────────────────────────────────────────────────────────┴─────────────────────────────────────────────────────────────────────┴────────┴────────┘\n",
+        );
+        assert_eq!(
+            dump_groups("binary", 4),
+            "┌────────┬───────────────────────────────────────────────────────────────────┬───────────────────────────────────────────────────────────────────┬────────┬────────┐\n\
+             │0000003c│ 00000100000000000000001100000000 00000001000000000000000000000000 ┊ 00000100000000000000000000000000 00000000                         │•⋄•⋄•⋄⋄⋄┊•⋄⋄⋄⋄   │\n\
+             └────────┴───────────────────────────────────────────────────────────────────┴───────────────────────────────────────────────────────────────────┴────────┴────────┘\n",
+        );
+        assert_eq!(
+            dump_groups("binary", 8),
+            "┌────────┬──────────────────────────────────────────────────────────────────┬──────────────────────────────────────────────────────────────────┬────────┬────────┐\n\
+             │0000003c│ 0000010000000000000000110000000000000001000000000000000000000000 ┊ 0000010000000000000000000000000000000000                         │•⋄•⋄•⋄⋄⋄┊•⋄⋄⋄⋄   │\n\
+             └────────┴──────────────────────────────────────────────────────────────────┴──────────────────────────────────────────────────────────────────┴────────┴────────┘\n",
+        );
+    }
+
+    #[test]
+    fn groups_octal() {
+        assert_eq!(
+            dump_groups("octal", 1),
+            "┌────────┬─────────────────────────────────┬─────────────────────────────────┬────────┬────────┐\n\
+             │0000003c│ 004 000 003 000 001 000 000 000 ┊ 004 000 000 000 000             │•⋄•⋄•⋄⋄⋄┊•⋄⋄⋄⋄   │\n\
+             └────────┴─────────────────────────────────┴─────────────────────────────────┴────────┴────────┘\n",
+        );
+        assert_eq!(
+            dump_groups("octal", 2),
+            "┌────────┬─────────────────────────────┬─────────────────────────────┬────────┬────────┐\n\
+             │0000003c│ 004000 003000 001000 000000 ┊ 004000 000000 000           │•⋄•⋄•⋄⋄⋄┊•⋄⋄⋄⋄   │\n\
+             └────────┴─────────────────────────────┴─────────────────────────────┴────────┴────────┘\n",
+        );
+        assert_eq!(
+            dump_groups("octal", 4),
+            "┌────────┬───────────────────────────┬───────────────────────────┬────────┬────────┐\n\
+             │0000003c│ 004000003000 001000000000 ┊ 004000000000 000          │•⋄•⋄•⋄⋄⋄┊•⋄⋄⋄⋄   │\n\
+             └────────┴───────────────────────────┴───────────────────────────┴────────┴────────┘\n",
+        );
+        assert_eq!(
+            dump_groups("octal", 8),
+            "┌────────┬──────────────────────────┬──────────────────────────┬────────┬────────┐\n\
+             │0000003c│ 004000003000001000000000 ┊ 004000000000000          │•⋄•⋄•⋄⋄⋄┊•⋄⋄⋄⋄   │\n\
+             └────────┴──────────────────────────┴──────────────────────────┴────────┴────────┘\n",
+        );
+    }
+
+    #[test]
+    fn groups_decimal() {
+        assert_eq!(
+            dump_groups("decimal", 1),
+            "┌────────┬─────────────────────────────────┬─────────────────────────────────┬────────┬────────┐\n\
+             │0000003c│ 004 000 003 000 001 000 000 000 ┊ 004 000 000 000 000             │•⋄•⋄•⋄⋄⋄┊•⋄⋄⋄⋄   │\n\
+             └────────┴─────────────────────────────────┴─────────────────────────────────┴────────┴────────┘\n",
+        );
+        assert_eq!(
+            dump_groups("decimal", 2),
+            "┌────────┬─────────────────────────────┬─────────────────────────────┬────────┬────────┐\n\
+             │0000003c│ 004000 003000 001000 000000 ┊ 004000 000000 000           │•⋄•⋄•⋄⋄⋄┊•⋄⋄⋄⋄   │\n\
+             └────────┴─────────────────────────────┴─────────────────────────────┴────────┴────────┘\n",
+        );
+        assert_eq!(
+            dump_groups("decimal", 4),
+            "┌────────┬───────────────────────────┬───────────────────────────┬────────┬────────┐\n\
+             │0000003c│ 004000003000 001000000000 ┊ 004000000000 000          │•⋄•⋄•⋄⋄⋄┊•⋄⋄⋄⋄   │\n\
+             └────────┴───────────────────────────┴───────────────────────────┴────────┴────────┘\n",
+        );
+        assert_eq!(
+            dump_groups("decimal", 8),
+            "┌────────┬──────────────────────────┬──────────────────────────┬────────┬────────┐\n\
+             │0000003c│ 004000003000001000000000 ┊ 004000000000000          │•⋄•⋄•⋄⋄⋄┊•⋄⋄⋄⋄   │\n\
+             └────────┴──────────────────────────┴──────────────────────────┴────────┴────────┘\n",
+        );
+    }
+
+    #[test]
+    fn groups_hexadecimal() {
+        assert_eq!(
+            dump_groups("hexadecimal", 1),
+            "┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┐\n\
+             │0000003c│ 04 00 03 00 01 00 00 00 ┊ 04 00 00 00 00          │•⋄•⋄•⋄⋄⋄┊•⋄⋄⋄⋄   │\n\
+             └────────┴─────────────────────────┴─────────────────────────┴────────┴────────┘\n",
+        );
+        assert_eq!(
+            dump_groups("hexadecimal", 2),
+            "┌────────┬─────────────────────┬─────────────────────┬────────┬────────┐\n\
+             │0000003c│ 0400 0300 0100 0000 ┊ 0400 0000 00        │•⋄•⋄•⋄⋄⋄┊•⋄⋄⋄⋄   │\n\
+             └────────┴─────────────────────┴─────────────────────┴────────┴────────┘\n",
+        );
+        assert_eq!(
+            dump_groups("hexadecimal", 4),
+            "┌────────┬───────────────────┬───────────────────┬────────┬────────┐\n\
+             │0000003c│ 04000300 01000000 ┊ 04000000 00       │•⋄•⋄•⋄⋄⋄┊•⋄⋄⋄⋄   │\n\
+             └────────┴───────────────────┴───────────────────┴────────┴────────┘\n",
+        );
+        assert_eq!(
+            dump_groups("hexadecimal", 8),
+            "┌────────┬──────────────────┬──────────────────┬────────┬────────┐\n\
+             │0000003c│ 0400030001000000 ┊ 0400000000       │•⋄•⋄•⋄⋄⋄┊•⋄⋄⋄⋄   │\n\
+             └────────┴──────────────────┴──────────────────┴────────┴────────┘\n",
+        );
+    }
 }
 
 mod character_table {
@@ -677,6 +3966,23 @@ mod character_table {
             );
     }
 
+    #[test]
+    fn latin1() {
+        hexyl()
+            .write_stdin(b"caf\xe9 \x85\xa0\xad\xff\x00\t\x0b".as_slice())
+            .arg("--color=never")
+            .arg("--character-table=latin1")
+            .arg("--panels=1")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 63 61 66 e9 20 85 a0 ad │café •_-│\n\
+                 │00000008│ ff 00 09 0b             │ÿ⋄_•    │\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            );
+    }
+
     #[test]
     fn codepage_437() {
         hexyl()
@@ -731,6 +4037,33 @@ mod character_table {
             );
     }
 
+    #[test]
+    fn codepage_437_blank_controls() {
+        hexyl()
+            .write_stdin("A\x00\x01\x1f\x7f\u{e8}")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--character-table=codepage-437")
+            .arg("--cp437-blank-controls")
+            .assert()
+            .success()
+            .pretty_stdout(
+                "┌────────┬─────────────────────────┬────────┐
+│00000000│ 41 00 01 1f 7f c3 a8    │A   ⌂├¿ │
+└────────┴─────────────────────────┴────────┘
+",
+            );
+    }
+
+    #[test]
+    fn blank_controls_requires_codepage_437() {
+        hexyl()
+            .write_stdin("A")
+            .arg("--cp437-blank-controls")
+            .assert()
+            .code(2);
+    }
+
     #[test]
     fn codepage_1047() {
         hexyl()
@@ -785,3 +4118,384 @@ mod character_table {
             );
     }
 }
+
+mod completion {
+    use super::hexyl;
+
+    #[test]
+    fn generates_bash_completion() {
+        hexyl()
+            .arg("--completion=bash")
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("complete -F _hexyl"));
+    }
+
+    #[test]
+    fn fails_on_unknown_shell() {
+        hexyl().arg("--completion=tcsh").assert().failure();
+    }
+}
+
+mod manpage {
+    use super::hexyl;
+
+    #[test]
+    fn generates_manpage() {
+        hexyl()
+            .arg("--generate-manpage")
+            .assert()
+            .success()
+            .stdout(predicates::str::starts_with(".TH HEXYL 1"))
+            .stdout(predicates::str::contains(
+                "\\fB\\-\\-generate\\-manpage\\fR",
+            ));
+    }
+}
+
+mod style {
+    use super::hexyl;
+
+    #[test]
+    fn debug_looks_like_dos_debug() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--style=debug")
+            .arg("--display-offset=0x100")
+            .assert()
+            .success()
+            .stdout(
+                " 0000:0100  30 31 32 33 34 35 36 37-38 39 61 62 63 64 65 0A  0123456789abcde. \n",
+            );
+    }
+
+    #[test]
+    fn debug_can_be_combined_with_other_options() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--style=debug")
+            .arg("--border=ascii")
+            .arg("--character-table=codepage-437")
+            .assert()
+            .success()
+            .stdout(
+                "+---------+-------------------------------------------------+----------------+\n\
+                 |0000:0000| 30 31 32 33 34 35 36 37-38 39 61 62 63 64 65 0A |0123456789abcde◙|\n\
+                 +---------+-------------------------------------------------+----------------+\n",
+            );
+    }
+}
+
+mod annotate {
+    use super::hexyl;
+
+    #[test]
+    fn labels_the_fields_of_the_elf_header() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--color=never")
+            .arg("--annotate")
+            .arg("--length=64")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬─────────────────────────┬────────┬────────┐\n\
+                 │00000000│ 7f 45 4c 46 02 01 01 00 ┊ 00 00 00 00 00 00 00 00 │•ELF•••⋄┊⋄⋄⋄⋄⋄⋄⋄⋄│ ELF magic, EI_CLASS, EI_DATA, EI_VERSION, EI_OSABI, EI_ABIVERSION\n\
+                 │00000010│ 02 00 3e 00 01 00 00 00 ┊ 00 10 40 00 00 00 00 00 │•⋄>⋄•⋄⋄⋄┊⋄•@⋄⋄⋄⋄⋄│ e_type, e_machine, e_version, e_entry\n\
+                 │00000020│ 40 00 00 00 00 00 00 00 ┊ 28 20 00 00 00 00 00 00 │@⋄⋄⋄⋄⋄⋄⋄┊( ⋄⋄⋄⋄⋄⋄│ e_phoff, e_shoff\n\
+                 │00000030│ 00 00 00 00 40 00 38 00 ┊ 03 00 40 00 04 00 03 00 │⋄⋄⋄⋄@⋄8⋄┊•⋄@⋄•⋄•⋄│ e_flags, e_ehsize, e_phentsize, e_phnum, e_shentsize, e_shnum, e_shstrndx\n\
+                 └────────┴─────────────────────────┴─────────────────────────┴────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn warns_about_unknown_formats() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--annotate")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 30 31 32 33 34 35 36 37 │01234567│\n\
+                 │00000008│ 38 39 61 62 63 64 65 0a │89abcde_│\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            )
+            .stderr("hexyl: `--annotate` does not know the format of \"ascii\"\n");
+    }
+
+    #[test]
+    fn requires_a_file() {
+        hexyl()
+            .arg("--annotate")
+            .write_stdin("\x7fELF")
+            .assert()
+            .failure();
+    }
+}
+
+mod compact {
+    use super::hexyl;
+
+    #[test]
+    fn prints_the_layout_of_hexdump() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--color=never")
+            .arg("--compact")
+            .arg("--length=32")
+            .assert()
+            .success()
+            .stdout(
+                "00000000: 7f 45 4c 46 02 01 01 00  00 00 00 00 00 00 00 00  |.ELF............|\n\
+                 00000010: 02 00 3e 00 01 00 00 00  00 10 40 00 00 00 00 00  |..>.......@.....|\n",
+            );
+    }
+
+    #[test]
+    fn keeps_other_options() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--compact")
+            .arg("--character-table=default")
+            .arg("--panels=1")
+            .assert()
+            .success()
+            .stdout(
+                "00000000: 30 31 32 33 34 35 36 37  |01234567|\n\
+                 00000008: 38 39 61 62 63 64 65 0a  |89abcde_|\n",
+            );
+    }
+
+    #[test]
+    fn prints_nothing_for_empty_input() {
+        hexyl()
+            .arg("empty")
+            .arg("--color=never")
+            .arg("--compact")
+            .assert()
+            .success()
+            .stdout("");
+    }
+
+    #[test]
+    fn conflicts_with_reverse() {
+        hexyl()
+            .arg("--reverse")
+            .arg("--compact")
+            .write_stdin("00000000: 30 31 32 33  |0123|\n")
+            .assert()
+            .failure()
+            .code(2)
+            .stderr(predicates::str::contains("cannot be used with"));
+    }
+}
+
+mod line_hash {
+    use super::hexyl;
+
+    #[test]
+    fn shows_a_checksum_per_line() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--line-hash=crc16")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┬────┐\n\
+                 │00000000│ 30 31 32 33 34 35 36 37 │01234567│f134│\n\
+                 │00000008│ 38 39 61 62 63 64 65 0a │89abcde_│8cae│\n\
+                 └────────┴─────────────────────────┴────────┴────┘\n",
+            );
+    }
+}
+
+mod json_format {
+    use super::hexyl;
+
+    #[test]
+    fn prints_one_object_per_line() {
+        hexyl()
+            .arg("ascii")
+            .arg("--format=json")
+            .assert()
+            .success()
+            .stdout(
+                "{\"offset\":0,\"bytes\":[48,49,50,51,52,53,54,55,56,57,97,98,99,100,101,10],\"ascii\":\"0123456789abcde.\",\"squeezed\":false}\n",
+            );
+    }
+
+    #[test]
+    fn marks_squeezed_lines() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--output-format=json")
+            .arg("--skip=0xf0")
+            .arg("--length=0x30")
+            .assert()
+            .success()
+            .stdout(
+                "{\"offset\":240,\"bytes\":[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0],\"ascii\":\"................\",\"squeezed\":false}\n\
+                 {\"offset\":256,\"bytes\":[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0],\"ascii\":\"................\",\"squeezed\":true,\"length\":32}\n",
+            );
+    }
+
+    #[test]
+    fn writes_the_length_of_squeezed_regions_before_the_next_line() {
+        let mut input = vec![0; 64];
+        input.extend_from_slice(b"ab");
+        hexyl()
+            .arg("--format=json")
+            .write_stdin(input)
+            .assert()
+            .success()
+            .stdout(
+                "{\"offset\":0,\"bytes\":[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0],\"ascii\":\"................\",\"squeezed\":false}\n\
+                 {\"offset\":16,\"bytes\":[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0],\"ascii\":\"................\",\"squeezed\":true,\"length\":48}\n\
+                 {\"offset\":64,\"bytes\":[97,98],\"ascii\":\"ab\",\"squeezed\":false}\n",
+            );
+    }
+}
+
+mod char_panels {
+    use super::hexyl;
+
+    #[test]
+    fn shows_only_the_last_character_panel() {
+        hexyl()
+            .arg("hello_world_elf64")
+            .arg("--color=never")
+            .arg("--panels=4")
+            .arg("--char-panels=last")
+            .arg("--length=64")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬─────────────────────────┬─────────────────────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 7f 45 4c 46 02 01 01 00 ┊ 00 00 00 00 00 00 00 00 ┊ 02 00 3e 00 01 00 00 00 ┊ 00 10 40 00 00 00 00 00 │⋄•@⋄⋄⋄⋄⋄│\n\
+                 │00000020│ 40 00 00 00 00 00 00 00 ┊ 28 20 00 00 00 00 00 00 ┊ 00 00 00 00 40 00 38 00 ┊ 03 00 40 00 04 00 03 00 │•⋄@⋄•⋄•⋄│\n\
+                 └────────┴─────────────────────────┴─────────────────────────┴─────────────────────────┴─────────────────────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn shows_no_character_panel() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .arg("--char-panels=none")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┐\n\
+                 │00000000│ 30 31 32 33 34 35 36 37 │\n\
+                 │00000008│ 38 39 61 62 63 64 65 0a │\n\
+                 └────────┴─────────────────────────┘\n",
+            );
+    }
+}
+
+mod ruler {
+    use super::hexyl;
+
+    #[test]
+    fn shows_the_columns_below_the_header() {
+        hexyl()
+            .arg("ascii")
+            .arg("--color=never")
+            .arg("--ruler")
+            .arg("--panels=1")
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │        │ 00 01 02 03 04 05 06 07 │01234567│\n\
+                 │00000000│ 30 31 32 33 34 35 36 37 │01234567│\n\
+                 │00000008│ 38 39 61 62 63 64 65 0a │89abcde_│\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            );
+    }
+
+    #[test]
+    fn is_printed_without_border() {
+        hexyl()
+            .arg("ascii")
+            .arg("--plain")
+            .arg("--ruler")
+            .arg("--panels=1")
+            .assert()
+            .success()
+            .stdout(
+                "  00 01 02 03 04 05 06 07  \n  \
+                   30 31 32 33 34 35 36 37  \n  \
+                   38 39 61 62 63 64 65 0a  \n",
+            );
+    }
+}
+
+mod follow {
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::hexyl;
+
+    #[test]
+    fn prints_the_appended_bytes() {
+        let path = std::env::temp_dir().join(format!("hexyl-{}-follow", std::process::id()));
+        fs::write(&path, b"0123456789").unwrap();
+        let appender = {
+            let path = path.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(300));
+                let mut file = OpenOptions::new().append(true).open(path).unwrap();
+                file.write_all(b"abcdef").unwrap();
+            })
+        };
+
+        hexyl()
+            .arg(&path)
+            .arg("--follow")
+            .arg("--length=16")
+            .arg("--color=never")
+            .arg("--panels=1")
+            .timeout(Duration::from_secs(10))
+            .assert()
+            .success()
+            .stdout(
+                "┌────────┬─────────────────────────┬────────┐\n\
+                 │00000000│ 30 31 32 33 34 35 36 37 │01234567│\n\
+                 │00000008│ 38 39 61 62 63 64 65 66 │89abcdef│\n\
+                 └────────┴─────────────────────────┴────────┘\n",
+            );
+        appender.join().unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn fails_without_file() {
+        hexyl()
+            .arg("--follow")
+            .write_stdin("abc")
+            .assert()
+            .failure();
+    }
+}
+
+mod watch {
+    use super::hexyl;
+
+    #[test]
+    fn fails_without_file() {
+        hexyl().arg("--watch").write_stdin("abc").assert().failure();
+    }
+}
@@ -0,0 +1,53 @@
+use hexyl::squeezer::{SqueezeAction, Squeezer, LSIZE};
+
+use SqueezeAction::*;
+
+fn actions(squeezer: &mut Squeezer, lines: &[&[u8]]) -> Vec<SqueezeAction> {
+    lines.iter().map(|line| squeezer.process(line)).collect()
+}
+
+#[test]
+fn squeezes_repeated_lines() {
+    let zeros = [0u8; LSIZE];
+    let text = [b'a'; LSIZE];
+    let mut squeezer = Squeezer::new(true, LSIZE);
+    assert_eq!(
+        actions(
+            &mut squeezer,
+            &[&zeros, &zeros, &zeros, &zeros, &text, &text]
+        ),
+        vec![Ignore, Print, Delete, Delete, Ignore, Print]
+    );
+    assert!(squeezer.is_squeezing());
+}
+
+#[test]
+fn only_squeezes_lines_of_a_single_byte() {
+    let mut line = [0u8; LSIZE];
+    line[LSIZE - 1] = 1;
+    let mut squeezer = Squeezer::new(true, LSIZE);
+    assert_eq!(
+        actions(&mut squeezer, &[&line, &line, &line]),
+        vec![Ignore, Ignore, Ignore]
+    );
+    assert!(!squeezer.is_squeezing());
+}
+
+#[test]
+fn never_squeezes_incomplete_lines() {
+    let mut squeezer = Squeezer::new(true, 8);
+    assert_eq!(squeezer.line_size(), 8);
+    assert_eq!(
+        actions(&mut squeezer, &[&[0; 8], &[0; 4]]),
+        vec![Ignore, Ignore]
+    );
+}
+
+#[test]
+fn can_be_disabled() {
+    let mut squeezer = Squeezer::new(false, LSIZE);
+    assert_eq!(
+        actions(&mut squeezer, &[&[0; LSIZE], &[0; LSIZE]]),
+        vec![Ignore, Ignore]
+    );
+}
@@ -0,0 +1,110 @@
+//! Checks that hexyl streams its input in constant memory. These tests read
+//! several GiB and are slow in debug builds, so they are ignored by default:
+//!
+//! ```text
+//! cargo test --release --test stress_tests -- --ignored
+//! ```
+#![cfg(target_os = "linux")]
+
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+
+const GIB: u64 = 1024 * 1024 * 1024;
+
+/// The largest resident set size that hexyl may reach while streaming.
+const MAX_RSS: u64 = 32 * 1024 * 1024;
+
+/// Runs hexyl with `len` bytes from `input` on stdin, and returns its stdout
+/// and stderr. Fails if hexyl is not successful.
+fn run_hexyl(args: &[&str], len: u64, input: fn(u64) -> u8) -> (String, String) {
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin("hexyl"))
+        .args(args)
+        .env_remove("HEXYL_DEFAULT_ARGS")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    let writer = thread::spawn(move || -> io::Result<()> {
+        let mut buf = vec![0; 1024 * 1024];
+        let mut position = 0;
+        while position < len {
+            let n = buf.len().min((len - position) as usize);
+            for (i, b) in buf[..n].iter_mut().enumerate() {
+                *b = input(position + i as u64);
+            }
+            stdin.write_all(&buf[..n])?;
+            position += n as u64;
+        }
+        Ok(())
+    });
+
+    let mut stderr = child.stderr.take().unwrap();
+    let stderr = thread::spawn(move || {
+        let mut output = String::new();
+        stderr.read_to_string(&mut output).map(|_| output)
+    });
+    let mut stdout = String::new();
+    child
+        .stdout
+        .take()
+        .unwrap()
+        .read_to_string(&mut stdout)
+        .unwrap();
+    let status = child.wait().unwrap();
+    writer.join().unwrap().unwrap();
+    let stderr = stderr.join().unwrap().unwrap();
+    assert!(status.success(), "hexyl failed: {stderr}");
+    (stdout, stderr)
+}
+
+/// Returns the largest resident set size of all children that have exited.
+fn max_rss_of_children() -> u64 {
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    assert_eq!(
+        unsafe { libc::getrusage(libc::RUSAGE_CHILDREN, &mut usage) },
+        0
+    );
+    // In KiB on Linux.
+    usage.ru_maxrss as u64 * 1024
+}
+
+#[test]
+#[ignore]
+fn squeezes_gigabytes_in_constant_memory() {
+    let (stdout, _) = run_hexyl(&["--color=never"], 4 * GIB, |_| 0);
+
+    assert!(stdout.contains("│*       │"));
+    assert!(stdout.contains("│0100000000│"));
+    assert!(max_rss_of_children() < MAX_RSS);
+}
+
+#[test]
+#[ignore]
+fn filters_gigabytes_in_constant_memory() {
+    // A breakpoint at the start of every 256 MiB.
+    let input = |position| {
+        if position % (256 * 1024 * 1024) == 0 {
+            0xcc
+        } else {
+            (position % 0xcb) as u8
+        }
+    };
+    let (stdout, stderr) = run_hexyl(
+        &[
+            "--color=never",
+            "--filter-byte=0xcc",
+            "--print-spans",
+            "--max-memory=1KiB",
+        ],
+        4 * GIB,
+        input,
+    );
+
+    assert_eq!(stdout.lines().count(), 16 + 2);
+    assert!(stderr.contains("Found 16 matching bytes in 16 lines"));
+    assert!(max_rss_of_children() < MAX_RSS);
+}